@@ -1,37 +1,116 @@
 //! Actix Web adapter for Glory SSR.
 
 use std::convert::Infallible;
+use std::sync::Arc;
 
-use actix_web::{HttpResponse, http::header::ContentType};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{self, ContentType, HeaderName, HeaderValue};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder, Route, web};
 use bytes::Bytes;
-pub use glory_core::web::holders::{HtmlChunk, ServerHolder};
+use futures::StreamExt;
+pub use glory_core::web::holders::{HtmlChunk, RenderPool, RenderStream, ServerHolder};
+pub use glory_core::web::{CSP_HEADER, ContentSecurityPolicy, CspNonce, ResponseHead, ServerResponseOptions};
+pub use glory_serverfn::actix_mount::request_context;
+pub use glory_serverfn::{IsrCache, IsrConfig, IsrRenderer, IsrResponse, IsrRoute, IsrStatus, RequestContext, revalidate_path, revalidate_tag};
 
 pub fn render_to_string(holder: &ServerHolder) -> String {
     holder.render_string()
 }
 
+/// Renders the holder into a `text/html` response carrying the status,
+/// headers and redirect set through its [`ServerResponseOptions`].
 pub fn into_response(holder: ServerHolder) -> HttpResponse {
-    HttpResponse::Ok().content_type(ContentType::html()).body(holder.render_string())
+    let html = holder.render_string();
+    response_builder(holder.response_options().head())
+        .content_type(ContentType::html())
+        .body(html)
 }
 
-/// Streams the holder's [`HtmlChunk`] sequence as an Actix chunked body via
+/// Streams a holder's [`HtmlChunk`] sequence as an Actix chunked body via
 /// [`HttpResponse::streaming`], with a `text/html` content type.
 ///
-/// `ServerHolder` is `!Send` (the reactive runtime holds `Rc`s) while Actix's
-/// `streaming` body requires a `Stream + 'static`. We drive the holder's render
-/// to completion on *this* thread up front — collapsing the whole [`HtmlChunk`]
-/// sequence into an owned `Vec<Bytes>` — and only then feed it to
-/// [`futures::stream::iter`]. The resulting stream owns plain `Bytes`, so it
-/// satisfies the body API while every `!Send` step stays on the current thread.
+/// `ServerHolder` is `!Send`, so the holder is built by `factory` and
+/// rendered on the global [`RenderPool`]; the response body only carries the
+/// owned chunks the worker sends back. The document head and shell reach the
+/// client before any Suspense resource resolves, each boundary's patch follows
+/// as soon as it is ready, and a client disconnect cancels the render. The
+/// response status and headers are whatever the tree set before the shell
+/// was flushed.
+pub async fn into_streaming_response<F>(factory: F) -> HttpResponse
+where
+    F: FnOnce() -> ServerHolder + Send + 'static,
+{
+    into_render_stream_response(RenderPool::global().render(factory)).await
+}
+
+/// Wraps an already started [`RenderStream`] (for example from a dedicated
+/// [`RenderPool`]) into a streaming `text/html` Actix response.
+pub async fn into_render_stream_response(mut stream: RenderStream) -> HttpResponse {
+    let head = stream.head().await;
+    let body = stream.html().map(|html| Result::<Bytes, Infallible>::Ok(Bytes::from(html)));
+    response_builder(head).content_type(ContentType::html()).streaming(body)
+}
+
+/// Starts an Actix response from a [`ResponseHead`]; header values replace,
+/// `set-cookie` values accumulate.
+pub fn response_builder(head: ResponseHead) -> HttpResponseBuilder {
+    let mut builder = HttpResponse::build(StatusCode::from_u16(head.status).unwrap_or(StatusCode::OK));
+    for (name, value) in head.headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            (Ok(name), Ok(value)) if name == header::SET_COOKIE => {
+                builder.append_header((name, value));
+            }
+            (Ok(name), Ok(value)) => {
+                builder.insert_header((name, value));
+            }
+            _ => glory_core::warn!("dropping invalid response header {name:?}"),
+        }
+    }
+    builder
+}
+
+/// Converts an [`IsrResponse`] into a `text/html` Actix response carrying the
+/// page's status and headers plus its `Cache-Control`, `Age` and cache-status
+/// headers.
+pub fn into_isr_response(isr: IsrResponse) -> HttpResponse {
+    let mut builder = response_builder(isr.head);
+    builder.content_type(ContentType::html());
+    for (name, value) in isr.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            builder.insert_header((name, value));
+        }
+    }
+    builder.body(isr.body)
+}
+
+/// GET route serving pages through incremental static regeneration.
+///
+/// `holder_factory` builds a fresh holder per render; it runs on a blocking
+/// thread for misses and on a background thread for stale regenerations.
 ///
-/// This wires up the streaming-body transport (the prerequisite pipeline for
-/// first-byte flush); true incremental parse-and-flush over the wire is bounded
-/// by the `!Send` runtime and is tracked separately as FS4.
-pub fn into_streaming_response(holder: ServerHolder) -> HttpResponse {
-    let chunks: Vec<Bytes> = futures::executor::block_on(async {
-        use futures::StreamExt;
-        holder.render_stream().map(|chunk| Bytes::from(chunk.into_string())).collect().await
+/// ```ignore
+/// let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/blog/{slug}").ttl_secs(60)));
+/// App::new().route("/blog/{slug}", isr_route(cache, |ctx| {
+///     ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(App)
+/// }));
+/// ```
+pub fn isr_route<H>(cache: IsrCache, holder_factory: H) -> Route
+where
+    H: Fn(&RequestContext) -> ServerHolder + Send + Sync + 'static,
+{
+    let render: IsrRenderer = Arc::new(move |context| {
+        let holder = holder_factory(context);
+        let html = holder.render_string();
+        (holder.response_options().head(), html)
     });
-    let stream = futures::stream::iter(chunks.into_iter().map(Result::<Bytes, Infallible>::Ok));
-    HttpResponse::Ok().content_type(ContentType::html()).streaming(stream)
+    web::get().to(move |request: HttpRequest| {
+        let cache = cache.clone();
+        let render = render.clone();
+        async move {
+            match cache.serve(&request_context(&request), &render).await {
+                Ok(isr) => into_isr_response(isr),
+                Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+            }
+        }
+    })
 }
@@ -36,10 +36,32 @@ fn into_response_sets_ok_status_and_html_content_type() {
     assert!(content_type.contains("text/html"), "content-type was {content_type:?}");
 }
 
+#[derive(Debug)]
+struct NotFound;
+
+impl Widget for NotFound {
+    fn build(&mut self, ctx: &mut Scope) {
+        let response = ctx.response_options();
+        response.set_status(404).unwrap();
+        response.insert_header("x-robots-tag", "noindex").unwrap();
+        div().text("missing").show_in(ctx);
+    }
+}
+
+#[test]
+fn responses_honor_response_options() {
+    let response = into_response(ServerHolder::new(GloryConfig::default(), "/").mount(NotFound));
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers().get("x-robots-tag").unwrap(), "noindex");
+
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(NotFound)));
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers().get("x-robots-tag").unwrap(), "noindex");
+}
+
 #[test]
 fn into_streaming_response_sets_ok_status_and_html_content_type() {
-    let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let response = into_streaming_response(holder);
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(Hello)));
     assert_eq!(response.status(), StatusCode::OK);
     let content_type = response
         .headers()
@@ -51,9 +73,55 @@ fn into_streaming_response_sets_ok_status_and_html_content_type() {
 
 #[test]
 fn into_streaming_response_body_contains_widget_html() {
-    let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let response = into_streaming_response(holder);
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(Hello)));
     let body = futures::executor::block_on(async { actix_web::body::to_bytes(response.into_body()).await.unwrap() });
     let body = String::from_utf8(body.to_vec()).unwrap();
     assert!(body.contains("hello-actix"), "{body}");
 }
+
+#[actix_web::test]
+async fn isr_route_caches_rendered_page_with_cache_headers() {
+    use actix_web::{App, test};
+    use glory_actix::{IsrCache, IsrConfig, IsrRoute, isr_route};
+
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/actix-isr").ttl_secs(60)));
+    let app = test::init_service(App::new().route(
+        "/actix-isr",
+        isr_route(cache, |ctx| ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(Hello)),
+    ))
+    .await;
+
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let response = test::call_service(&app, test::TestRequest::get().uri("/actix-isr").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let cache_control = response.headers().get("cache-control").unwrap().to_str().unwrap().to_owned();
+        assert!(cache_control.contains("s-maxage=60"), "{cache_control}");
+        assert!(response.headers().contains_key("age"));
+        statuses.push(response.headers().get("x-glory-cache").unwrap().to_str().unwrap().to_owned());
+        let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+        assert!(body.contains("hello-actix"), "{body}");
+    }
+    assert_eq!(statuses, ["MISS", "HIT"]);
+}
+
+#[actix_web::test]
+async fn isr_route_passes_not_found_pages_through_uncached() {
+    use actix_web::{App, test};
+    use glory_actix::{IsrCache, IsrConfig, IsrRoute, isr_route};
+
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/actix-isr-missing")));
+    let app = test::init_service(App::new().route(
+        "/actix-isr-missing",
+        isr_route(cache, |ctx| ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(NotFound)),
+    ))
+    .await;
+
+    for _ in 0..2 {
+        let response = test::call_service(&app, test::TestRequest::get().uri("/actix-isr-missing").to_request()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("x-robots-tag").unwrap(), "noindex");
+        assert_eq!(response.headers().get("x-glory-cache").unwrap(), "BYPASS");
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-cache");
+    }
+}
@@ -1,45 +1,119 @@
 //! Axum adapter for Glory SSR.
 
 use std::convert::Infallible;
+use std::sync::Arc;
 
 use axum::body::Body;
-use axum::http::{StatusCode, header};
+use axum::http::header::{HeaderName, HeaderValue};
+use axum::http::{Request, StatusCode, header};
 use axum::response::{Html, IntoResponse, Response};
+use axum::routing::MethodRouter;
 use bytes::Bytes;
-pub use glory_core::web::holders::{HtmlChunk, ServerHolder};
+use futures::StreamExt;
+pub use glory_core::web::holders::{HtmlChunk, RenderPool, RenderStream, ServerHolder};
+pub use glory_core::web::{CSP_HEADER, ContentSecurityPolicy, CspNonce, ResponseHead, ServerResponseOptions};
+pub use glory_serverfn::axum_mount::request_context;
+pub use glory_serverfn::{IsrCache, IsrConfig, IsrRenderer, IsrResponse, IsrRoute, IsrStatus, RequestContext, revalidate_path, revalidate_tag};
 
 pub fn render_to_string(holder: &ServerHolder) -> String {
     holder.render_string()
 }
 
-pub fn into_response(holder: ServerHolder) -> Html<String> {
-    Html(holder.render_string())
+/// Renders the holder into a `text/html` response carrying the status,
+/// headers and redirect set through its [`ServerResponseOptions`].
+pub fn into_response(holder: ServerHolder) -> Response {
+    let html = holder.render_string();
+    let mut response = Html(html).into_response();
+    apply_response_head(&mut response, holder.response_options().head());
+    response
 }
 
-/// Streams the holder's [`HtmlChunk`] sequence as an Axum chunked body
+/// Streams a holder's [`HtmlChunk`] sequence as an Axum chunked body
 /// (`Transfer-Encoding: chunked`) with a `text/html` content type.
 ///
-/// `ServerHolder` itself is `!Send` (the reactive runtime holds `Rc`s) and
-/// [`Body::from_stream`] requires a `Stream + Send`. We sidestep that by driving
-/// the holder's render to completion on *this* thread up front — collapsing the
-/// whole [`HtmlChunk`] sequence into an owned `Vec<Bytes>` — and only then
-/// handing it to [`futures::stream::iter`]. The resulting stream is `Send`
-/// because `Bytes` is `Send`, so it satisfies the body API while every `!Send`
-/// step stays on the current thread.
+/// `ServerHolder` is `!Send` while [`Body::from_stream`] requires a
+/// `Stream + Send`, so the holder is built by `factory` and rendered on the
+/// global [`RenderPool`]. The document head and shell reach the client before
+/// any Suspense resource resolves, each boundary's patch follows as soon as it
+/// is ready, and a client disconnect cancels the render. The response status
+/// and headers are whatever the tree set before the shell was flushed.
+pub async fn into_streaming_response<F>(factory: F) -> Response
+where
+    F: FnOnce() -> ServerHolder + Send + 'static,
+{
+    into_render_stream_response(RenderPool::global().render(factory)).await
+}
+
+/// Wraps an already started [`RenderStream`] (for example from a dedicated
+/// [`RenderPool`]) into a streaming `text/html` Axum response.
+pub async fn into_render_stream_response(mut stream: RenderStream) -> Response {
+    let head = stream.head().await;
+    let body = stream.html().map(|html| Result::<Bytes, Infallible>::Ok(Bytes::from(html)));
+    let mut response = ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], Body::from_stream(body)).into_response();
+    apply_response_head(&mut response, head);
+    response
+}
+
+/// Writes a [`ResponseHead`] onto an Axum response; header values replace,
+/// `set-cookie` values accumulate.
+pub fn apply_response_head(response: &mut Response, head: ResponseHead) {
+    *response.status_mut() = StatusCode::from_u16(head.status).unwrap_or(StatusCode::OK);
+    for (name, value) in head.headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            (Ok(name), Ok(value)) if name == header::SET_COOKIE => {
+                response.headers_mut().append(name, value);
+            }
+            (Ok(name), Ok(value)) => {
+                response.headers_mut().insert(name, value);
+            }
+            _ => glory_core::warn!("dropping invalid response header {name:?}"),
+        }
+    }
+}
+
+/// Converts an [`IsrResponse`] into a `text/html` Axum response carrying the
+/// page's status and headers plus its `Cache-Control`, `Age` and cache-status
+/// headers.
+pub fn into_isr_response(isr: IsrResponse) -> Response {
+    let mut response = ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], isr.body).into_response();
+    apply_response_head(&mut response, isr.head);
+    for (name, value) in isr.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
+
+/// GET route serving pages through incremental static regeneration.
+///
+/// `holder_factory` builds a fresh holder per render; it runs on a blocking
+/// thread for misses and on a background thread for stale regenerations.
 ///
-/// This wires up the streaming-body transport (the prerequisite pipeline for
-/// first-byte flush); true incremental parse-and-flush over the wire is bounded
-/// by the `!Send` runtime and is tracked separately as FS4.
-pub fn into_streaming_response(holder: ServerHolder) -> Response {
-    let chunks: Vec<Bytes> = futures::executor::block_on(async {
-        use futures::StreamExt;
-        holder.render_stream().map(|chunk| Bytes::from(chunk.into_string())).collect().await
+/// ```ignore
+/// let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/blog/{slug}").ttl_secs(60)));
+/// let app = Router::new().route("/blog/{slug}", isr_route(cache, |ctx| {
+///     ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(App)
+/// }));
+/// ```
+pub fn isr_route<S, H>(cache: IsrCache, holder_factory: H) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+    H: Fn(&RequestContext) -> ServerHolder + Send + Sync + 'static,
+{
+    let render: IsrRenderer = Arc::new(move |context| {
+        let holder = holder_factory(context);
+        let html = holder.render_string();
+        (holder.response_options().head(), html)
     });
-    let stream = futures::stream::iter(chunks.into_iter().map(Result::<Bytes, Infallible>::Ok));
-    (
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
-        Body::from_stream(stream),
-    )
-        .into_response()
+    axum::routing::get(move |request: Request<Body>| {
+        let cache = cache.clone();
+        let render = render.clone();
+        async move {
+            match cache.serve(&request_context(&request), &render).await {
+                Ok(isr) => into_isr_response(isr),
+                Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            }
+        }
+    })
 }
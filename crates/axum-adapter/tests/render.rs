@@ -1,5 +1,5 @@
 //! Behaviour coverage for the Axum SSR adapter: a mounted widget renders to
-//! HTML and `into_response` wraps it as `Html<String>`.
+//! HTML and `into_response` wraps it in a `text/html` response.
 
 use axum::http::StatusCode;
 use glory_axum::{ServerHolder, into_response, into_streaming_response, render_to_string};
@@ -26,14 +26,44 @@ fn render_to_string_emits_widget_html() {
 #[test]
 fn into_response_wraps_rendered_html() {
     let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let html = into_response(holder);
-    assert!(html.0.contains("hello-axum"), "{}", html.0);
+    let response = into_response(holder);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-type").unwrap().to_str().unwrap().contains("text/html"));
+    let body = futures::executor::block_on(axum::body::to_bytes(response.into_body(), usize::MAX)).unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("hello-axum"), "{body:?}");
+}
+
+#[derive(Debug)]
+struct Moved;
+
+impl Widget for Moved {
+    fn build(&mut self, ctx: &mut Scope) {
+        let response = ctx.response_options();
+        response.redirect("/login").unwrap();
+        response.set_cookie_header("a=1").unwrap();
+        response.set_cookie_header("b=2").unwrap();
+        div().text("moved").show_in(ctx);
+    }
+}
+
+#[test]
+fn responses_honor_response_options() {
+    let response = into_response(ServerHolder::new(GloryConfig::default(), "/").mount(Moved));
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(response.headers().get("location").unwrap(), "/login");
+    assert_eq!(response.headers().get_all("set-cookie").iter().count(), 2);
+
+    let response = futures::executor::block_on(into_streaming_response(|| {
+        ServerHolder::new_streaming(GloryConfig::default(), "/").mount(Moved)
+    }));
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(response.headers().get("location").unwrap(), "/login");
+    assert!(response.headers().get("content-type").unwrap().to_str().unwrap().contains("text/html"));
 }
 
 #[test]
 fn into_streaming_response_sets_ok_status_and_html_content_type() {
-    let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let response = into_streaming_response(holder);
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(Hello)));
     assert_eq!(response.status(), StatusCode::OK);
     let content_type = response
         .headers()
@@ -45,9 +75,38 @@ fn into_streaming_response_sets_ok_status_and_html_content_type() {
 
 #[test]
 fn into_streaming_response_body_contains_widget_html() {
-    let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let response = into_streaming_response(holder);
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(Hello)));
     let body = futures::executor::block_on(async { axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap() });
     let body = String::from_utf8(body.to_vec()).unwrap();
     assert!(body.contains("hello-axum"), "{body}");
 }
+
+#[test]
+fn isr_route_caches_rendered_page_with_cache_headers() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use glory_axum::{IsrCache, IsrConfig, IsrRoute, isr_route};
+    use tower::ServiceExt;
+
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/axum-isr").ttl_secs(60)));
+    let app: axum::Router = axum::Router::new().route(
+        "/axum-isr",
+        isr_route(cache, |ctx| ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(Hello)),
+    );
+
+    // Misses render on tokio's blocking pool.
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let request = Request::builder().uri("/axum-isr").body(Body::empty()).unwrap();
+        let response = runtime.block_on(app.clone().oneshot(request)).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let cache_control = response.headers().get("cache-control").unwrap().to_str().unwrap().to_owned();
+        assert!(cache_control.contains("s-maxage=60"), "{cache_control}");
+        assert!(response.headers().contains_key("age"));
+        statuses.push(response.headers().get("x-glory-cache").unwrap().to_str().unwrap().to_owned());
+        let body = futures::executor::block_on(axum::body::to_bytes(response.into_body(), usize::MAX)).unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("hello-axum"));
+    }
+    assert_eq!(statuses, ["MISS", "HIT"]);
+}
@@ -54,6 +54,7 @@ pub async fn bundle_proj(proj: &Arc<Project>, optimize_images: bool) -> Result<(
     optimize_static_assets(&dist).await?;
     if proj.target == BuildTarget::Desktop {
         bundle_desktop_installers(proj, &dist).await?;
+        write_update_manifest(proj, &dist).await?;
     }
     write_manifest(proj, &dist, &asset_map).await?;
 
@@ -115,10 +116,21 @@ async fn write_windows_msi_artifacts(proj: &Project, dist: &Utf8Path) -> Result<
     fs::create_dir_all(&obj_dir).await.dot()?;
     copy_bundle_payload(dist, &staging).await?;
 
+    let exe_name = proj
+        .bin
+        .exe_file
+        .file_name()
+        .ok_or_else(|| anyhow!("desktop executable path has no file name: {}", proj.bin.exe_file))?;
     let product_name = installer_product_name(&proj.name);
     let version = msi_version(&proj.bin.version);
     let manufacturer = xml_escape(&installer_publisher());
     let upgrade_code = deterministic_guid(&format!("glory:{}:{}", proj.name, proj.bin.name));
+    let (scheme_refs, scheme_components) = wix_url_scheme_components(
+        &format!("glory:{}:{}", proj.name, proj.bin.name),
+        &product_name,
+        exe_name,
+        &proj.deep_link_schemes,
+    );
     let product_wxs = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <Wix xmlns="http://schemas.microsoft.com/wix/2006/wi">
@@ -127,13 +139,13 @@ async fn write_windows_msi_artifacts(proj: &Project, dist: &Utf8Path) -> Result<
     <MajorUpgrade DowngradeErrorMessage="A newer version of {product_name} is already installed." />
     <MediaTemplate EmbedCab="yes" />
     <Feature Id="DefaultFeature" Title="{product_name}" Level="1">
-      <ComponentGroupRef Id="AppFiles" />
+      <ComponentGroupRef Id="AppFiles" />{scheme_refs}
     </Feature>
     <Directory Id="TARGETDIR" Name="SourceDir">
       <Directory Id="ProgramFilesFolder">
         <Directory Id="INSTALLFOLDER" Name="{product_name}" />
       </Directory>
-    </Directory>
+    </Directory>{scheme_components}
   </Product>
 </Wix>
 "#
@@ -276,6 +288,37 @@ async fn ensure_wix_tools() -> Option<(std::path::PathBuf, std::path::PathBuf, s
     }
 }
 
+/// WiX `ComponentRef`s and components registering each deep-link scheme
+/// under `HKCR\<scheme>`, so `scheme://...` links launch the installed
+/// executable with the URL. Pure function for unit testing.
+fn wix_url_scheme_components(seed: &str, product: &str, exe_name: &str, schemes: &[String]) -> (String, String) {
+    let mut refs = String::new();
+    let mut components = String::new();
+    for scheme in schemes {
+        let id = format!("UrlScheme_{}", scheme.replace(['+', '-'], "_"));
+        let guid = deterministic_guid(&format!("{seed}:scheme:{scheme}"));
+        refs.push_str(&format!("\n      <ComponentRef Id=\"{id}\" />"));
+        components.push_str(&format!(
+            r#"
+    <DirectoryRef Id="INSTALLFOLDER">
+      <Component Id="{id}" Guid="{guid}">
+        <RegistryKey Root="HKCR" Key="{key}">
+          <RegistryValue Type="string" Value="URL:{product} Protocol" KeyPath="yes" />
+          <RegistryValue Type="string" Name="URL Protocol" Value="" />
+          <RegistryKey Key="shell\open\command">
+            <RegistryValue Type="string" Value="&quot;[INSTALLFOLDER]{exe}&quot; &quot;%1&quot;" />
+          </RegistryKey>
+        </RegistryKey>
+      </Component>
+    </DirectoryRef>"#,
+            key = xml_escape(scheme),
+            product = xml_escape(product),
+            exe = xml_escape(exe_name),
+        ));
+    }
+    (refs, components)
+}
+
 /// Resolve the WiX trio inside an already-populated directory.
 fn wix_tools_in(dir: &std::path::Path) -> Option<(std::path::PathBuf, std::path::PathBuf, std::path::PathBuf)> {
     let heat = dir.join("heat.exe");
@@ -324,7 +367,7 @@ async fn write_macos_app_bundle(proj: &Project, dist: &Utf8Path) -> Result<()> {
     fs::create_dir_all(app_dir.join(&layout.resources_dir)).await.dot()?;
 
     let bundle_id = macos_bundle_id(&proj.name);
-    let plist = macos_info_plist(&product, exe_name, &bundle_id, &proj.bin.version);
+    let plist = macos_info_plist(&product, exe_name, &bundle_id, &proj.bin.version, &proj.deep_link_schemes);
     fs::write(app_dir.join(&layout.info_plist), plist).await.dot()?;
 
     // Copy the whole bundle payload (server exe + site assets) into MacOS/.
@@ -386,9 +429,9 @@ fn macos_bundle_id_with_prefix(prefix: &str, name: &str) -> String {
     format!("{prefix}.{}", debian_package_name(name))
 }
 
-/// Generate a minimal but valid `Info.plist` for the desktop `.app`. Pure
-/// function (no IO) for unit testing.
-fn macos_info_plist(product: &str, exe_name: &str, bundle_id: &str, version: &str) -> String {
+/// Generate a minimal but valid `Info.plist` for the desktop `.app`, claiming
+/// the project's deep-link schemes. Pure function (no IO) for unit testing.
+fn macos_info_plist(product: &str, exe_name: &str, bundle_id: &str, version: &str, schemes: &[String]) -> String {
     let short_version = msi_version(version);
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -415,13 +458,29 @@ fn macos_info_plist(product: &str, exe_name: &str, bundle_id: &str, version: &st
 	<string>10.13</string>
 	<key>NSHighResolutionCapable</key>
 	<true/>
-</dict>
+{url_types}</dict>
 </plist>
 "#,
         name = plist_escape(product),
         exe = plist_escape(exe_name),
         id = plist_escape(bundle_id),
         version = plist_escape(version),
+        url_types = macos_url_types(bundle_id, schemes),
+    )
+}
+
+/// `CFBundleURLTypes` entry for the deep-link schemes; empty without any.
+fn macos_url_types(bundle_id: &str, schemes: &[String]) -> String {
+    if schemes.is_empty() {
+        return String::new();
+    }
+    let schemes: String = schemes
+        .iter()
+        .map(|scheme| format!("\t\t\t\t<string>{}</string>\n", plist_escape(scheme)))
+        .collect();
+    format!(
+        "\t<key>CFBundleURLTypes</key>\n\t<array>\n\t\t<dict>\n\t\t\t<key>CFBundleURLName</key>\n\t\t\t<string>{}</string>\n\t\t\t<key>CFBundleURLSchemes</key>\n\t\t\t<array>\n{schemes}\t\t\t</array>\n\t\t</dict>\n\t</array>\n",
+        plist_escape(bundle_id),
     )
 }
 
@@ -581,7 +640,7 @@ async fn write_linux_appimage(proj: &Project, dist: &Utf8Path) -> Result<()> {
     fs::write(&apprun_path, &apprun).await.dot()?;
     set_executable(&apprun_path).await?;
 
-    let desktop = appimage_desktop_entry(&product, &package);
+    let desktop = appimage_desktop_entry(&product, &package, &proj.deep_link_schemes);
     fs::write(app_dir.join(format!("{package}.desktop")), &desktop).await.dot()?;
     fs::write(app_dir.join("usr/share/applications").join(format!("{package}.desktop")), &desktop)
         .await
@@ -626,10 +685,12 @@ exec "$HERE/usr/bin/{exe_name}" "$@"
 }
 
 /// `.desktop` entry for the AppImage (top-level + usr/share/applications).
-fn appimage_desktop_entry(product: &str, package: &str) -> String {
+fn appimage_desktop_entry(product: &str, package: &str, schemes: &[String]) -> String {
     format!(
-        "[Desktop Entry]\nType=Application\nName={name}\nExec={package}\nIcon={package}\nTerminal=false\nCategories=Utility;\n",
+        "[Desktop Entry]\nType=Application\nName={name}\nExec={package}{args}\nIcon={package}\nTerminal=false\nCategories=Utility;\n{mime}",
         name = desktop_value(product),
+        args = desktop_exec_args(schemes),
+        mime = desktop_mime_types(schemes),
     )
 }
 
@@ -679,6 +740,159 @@ async fn write_linux_deb(proj: &Project, dist: &Utf8Path) -> Result<()> {
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// Auto-update manifest + installer signatures.
+// ---------------------------------------------------------------------------
+
+/// Sign this platform's installer and describe it in
+/// `installers/update-manifest.json` for `glory_desktop::DesktopUpdater`.
+/// Skipped (with a warning) without a signing key, and quietly when no
+/// self-updating installer was built.
+async fn write_update_manifest(proj: &Project, dist: &Utf8Path) -> Result<()> {
+    let Some(installer) = update_installer(proj, dist) else {
+        return Ok(());
+    };
+    let Some(key) = UpdateSigningKey::from_env()? else {
+        log::warn!("Update manifest skipped: set GLORY_UPDATE_PRIVATE_KEY or GLORY_UPDATE_PRIVATE_KEY_PATH to sign installers for auto-update");
+        return Ok(());
+    };
+    let installers = dist.join("installers");
+    let target = update_target();
+    let version = &proj.bin.version;
+    let bytes = fs::read(&installer).await?;
+    let signature = key.sign(&target, version, &bytes);
+    fs::write(format!("{installer}.sig"), &signature).await.dot()?;
+
+    let relative = installer.strip_prefix(&installers)?;
+    let url = match non_empty_env("GLORY_UPDATE_BASE_URL") {
+        Some(base) => format!("{}/{relative}", base.trim_end_matches('/')),
+        None => relative.to_string(),
+    };
+    let manifest = update_manifest(
+        version,
+        non_empty_env("GLORY_UPDATE_NOTES").as_deref(),
+        &target,
+        &url,
+        &signature,
+        bytes.len() as u64,
+    );
+    let manifest_path = installers.join("update-manifest.json");
+    fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?).await.dot()?;
+    log::info!(
+        "Update manifest written to {} (public key {})",
+        GRAY.paint(manifest_path.as_str()),
+        key.public_key()
+    );
+    Ok(())
+}
+
+/// The installer the desktop updater can apply on this platform: the MSI,
+/// the DMG, or the AppImage (a `.deb` can't replace itself).
+fn update_installer(proj: &Project, dist: &Utf8Path) -> Option<Utf8PathBuf> {
+    let installer = if cfg!(target_os = "windows") {
+        dist.join("installers/windows")
+            .join(format!("{}_{}_x64.msi", package_file_stem(&proj.name), msi_version(&proj.bin.version)))
+    } else if cfg!(target_os = "macos") {
+        dist.join("installers/macos")
+            .join(format!("{}_{}.dmg", package_file_stem(&proj.name), proj.bin.version))
+    } else if cfg!(target_os = "linux") {
+        dist.join("installers/linux").join(format!(
+            "{}_{}_{}.AppImage",
+            debian_package_name(&proj.name),
+            proj.bin.version,
+            appimage_arch()
+        ))
+    } else {
+        return None;
+    };
+    installer.exists().then_some(installer)
+}
+
+/// The target key the desktop updater looks up, `{os}-{arch}`; matches
+/// `glory_desktop::current_target` for an app built on this host.
+fn update_target() -> String {
+    format!("{}-{}", env::consts::OS, env::consts::ARCH)
+}
+
+/// The bytes an installer's signature covers. Must match
+/// `glory_desktop::signed_message`.
+fn update_signed_message(target: &str, version: &str, sha256: &[u8]) -> Vec<u8> {
+    let mut message = format!("glory-update-v1\n{target}\n{version}\n").into_bytes();
+    message.extend(sha256.iter().flat_map(|byte| format!("{byte:02x}").into_bytes()));
+    message
+}
+
+/// The update manifest for one target. Pure function for unit testing;
+/// manifests from several build machines merge by their `platforms` maps.
+fn update_manifest(version: &str, notes: Option<&str>, target: &str, url: &str, signature: &str, size: u64) -> serde_json::Value {
+    let mut manifest = serde_json::json!({
+        "version": version,
+        "platforms": {
+            target: {
+                "url": url,
+                "signature": signature,
+                "size": size,
+            },
+        },
+    });
+    if let Some(notes) = notes {
+        manifest["notes"] = notes.into();
+    }
+    manifest
+}
+
+/// Ed25519 key signing installers for the desktop updater.
+struct UpdateSigningKey {
+    pair: ring::signature::Ed25519KeyPair,
+}
+
+impl UpdateSigningKey {
+    /// Resolve from `GLORY_UPDATE_PRIVATE_KEY` (preferred) or the file named
+    /// by `GLORY_UPDATE_PRIVATE_KEY_PATH`.
+    fn from_env() -> Result<Option<Self>> {
+        let encoded = if let Some(key) = non_empty_env("GLORY_UPDATE_PRIVATE_KEY") {
+            key
+        } else if let Some(path) = non_empty_env("GLORY_UPDATE_PRIVATE_KEY_PATH") {
+            std::fs::read_to_string(&path).context(format!("Reading update signing key {path}"))?
+        } else {
+            return Ok(None);
+        };
+        Self::from_base64(&encoded).map(Some)
+    }
+
+    /// A key from its base64 32-byte seed.
+    fn from_base64(encoded: &str) -> Result<Self> {
+        use base64::Engine;
+
+        let seed = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .context("Update signing key is not base64")?;
+        if seed.len() != 32 {
+            bail!("Update signing key must be a 32-byte ed25519 seed, got {} bytes", seed.len());
+        }
+        let pair =
+            ring::signature::Ed25519KeyPair::from_seed_unchecked(&seed).map_err(|_| anyhow!("Update signing key is not a valid ed25519 seed"))?;
+        Ok(Self { pair })
+    }
+
+    /// The base64 public key to embed in the app's `DesktopUpdater`.
+    fn public_key(&self) -> String {
+        use base64::Engine;
+        use ring::signature::KeyPair;
+
+        base64::engine::general_purpose::STANDARD.encode(self.pair.public_key())
+    }
+
+    /// Base64 signature of `installer` as `version` for `target`.
+    fn sign(&self, target: &str, version: &str, installer: &[u8]) -> String {
+        use base64::Engine;
+
+        let digest = ring::digest::digest(&ring::digest::SHA256, installer);
+        let signature = self.pair.sign(&update_signed_message(target, version, digest.as_ref()));
+        base64::engine::general_purpose::STANDARD.encode(signature.as_ref())
+    }
+}
+
 async fn bundle_android(proj: &Project, dist: &Utf8Path) -> Result<()> {
     let jni_libs = proj.site.root_dir.join("android").join("jniLibs");
     if !jni_libs.exists() {
@@ -1247,11 +1461,27 @@ fn write_ar_member(writer: &mut impl Write, name: &str, data: &[u8]) -> Result<(
 
 fn desktop_entry(proj: &Project, package: &str, exe_name: &str) -> String {
     format!(
-        "[Desktop Entry]\nType=Application\nName={}\nExec=/usr/lib/{package}/{exe_name}\nTerminal=false\nCategories=Utility;\n",
+        "[Desktop Entry]\nType=Application\nName={}\nExec=/usr/lib/{package}/{exe_name}{}\nTerminal=false\nCategories=Utility;\n{}",
         desktop_value(&installer_product_name(&proj.name)),
+        desktop_exec_args(&proj.deep_link_schemes),
+        desktop_mime_types(&proj.deep_link_schemes),
     )
 }
 
+/// `%u` passes the opened deep link to the executable.
+fn desktop_exec_args(schemes: &[String]) -> &'static str {
+    if schemes.is_empty() { "" } else { " %u" }
+}
+
+/// `MimeType=` line claiming each deep-link scheme as an `x-scheme-handler`.
+fn desktop_mime_types(schemes: &[String]) -> String {
+    if schemes.is_empty() {
+        return String::new();
+    }
+    let types: String = schemes.iter().map(|scheme| format!("x-scheme-handler/{scheme};")).collect();
+    format!("MimeType={types}\n")
+}
+
 fn msi_version(version: &str) -> String {
     semver::Version::parse(version)
         .map(|version| format!("{}.{}.{}", version.major, version.minor, version.patch))
@@ -2108,7 +2338,7 @@ options:
 
     #[test]
     fn macos_info_plist_carries_identifiers_and_versions() {
-        let plist = macos_info_plist("My App", "server", "com.glory.my-app", "1.2.3-beta.1");
+        let plist = macos_info_plist("My App", "server", "com.glory.my-app", "1.2.3-beta.1", &[]);
         assert!(plist.contains("<key>CFBundleExecutable</key>\n\t<string>server</string>"), "{plist}");
         assert!(
             plist.contains("<key>CFBundleIdentifier</key>\n\t<string>com.glory.my-app</string>"),
@@ -2121,6 +2351,101 @@ options:
             "{plist}"
         );
         assert!(plist.contains("<key>CFBundlePackageType</key>\n\t<string>APPL</string>"), "{plist}");
+        assert!(!plist.contains("CFBundleURLTypes"), "{plist}");
+    }
+
+    #[test]
+    fn update_installers_are_signed_over_target_version_and_digest() {
+        use base64::Engine;
+        use ring::signature::{ED25519, UnparsedPublicKey};
+
+        let message = update_signed_message("linux-x86_64", "1.2.0", &[0xab; 32]);
+        assert_eq!(message, format!("glory-update-v1\nlinux-x86_64\n1.2.0\n{}", "ab".repeat(32)).into_bytes());
+
+        let key = UpdateSigningKey::from_base64(&base64::engine::general_purpose::STANDARD.encode([7u8; 32])).unwrap();
+        let signature = key.sign("linux-x86_64", "1.2.0", b"installer");
+        let decode = |value: &str| base64::engine::general_purpose::STANDARD.decode(value).unwrap();
+        let public_key = UnparsedPublicKey::new(&ED25519, decode(&key.public_key()));
+        let digest = ring::digest::digest(&ring::digest::SHA256, b"installer");
+        assert!(
+            public_key
+                .verify(&update_signed_message("linux-x86_64", "1.2.0", digest.as_ref()), &decode(&signature))
+                .is_ok()
+        );
+        assert!(
+            public_key
+                .verify(&update_signed_message("linux-x86_64", "1.1.0", digest.as_ref()), &decode(&signature))
+                .is_err()
+        );
+
+        assert!(UpdateSigningKey::from_base64("not base64!").is_err());
+        assert!(UpdateSigningKey::from_base64(&base64::engine::general_purpose::STANDARD.encode([7u8; 16])).is_err());
+    }
+
+    #[test]
+    fn update_manifest_describes_this_targets_installer() {
+        let manifest = update_manifest(
+            "1.2.0",
+            Some("Faster startup"),
+            "windows-x86_64",
+            "windows/MyApp_1.2.0_x64.msi",
+            "c2ln",
+            42,
+        );
+        assert_eq!(
+            manifest,
+            serde_json::json!({
+                "version": "1.2.0",
+                "notes": "Faster startup",
+                "platforms": {
+                    "windows-x86_64": {
+                        "url": "windows/MyApp_1.2.0_x64.msi",
+                        "signature": "c2ln",
+                        "size": 42,
+                    },
+                },
+            })
+        );
+        assert!(update_manifest("1.2.0", None, "linux-x86_64", "u", "s", 1).get("notes").is_none());
+    }
+
+    #[test]
+    fn deep_link_schemes_are_registered_in_every_installer() {
+        let schemes = vec!["myapp".to_owned(), "my-app".to_owned()];
+
+        let plist = macos_info_plist("My App", "server", "com.glory.my-app", "1.0.0", &schemes);
+        assert!(
+            plist.contains("<key>CFBundleURLName</key>\n\t\t\t<string>com.glory.my-app</string>"),
+            "{plist}"
+        );
+        assert!(
+            plist.contains(
+                "<key>CFBundleURLSchemes</key>\n\t\t\t<array>\n\t\t\t\t<string>myapp</string>\n\t\t\t\t<string>my-app</string>\n\t\t\t</array>"
+            ),
+            "{plist}"
+        );
+        assert!(plist.ends_with("\t</array>\n</dict>\n</plist>\n"), "{plist}");
+
+        let entry = appimage_desktop_entry("My App", "my-app", &schemes);
+        assert!(entry.contains("Exec=my-app %u\n"), "{entry}");
+        assert!(entry.contains("MimeType=x-scheme-handler/myapp;x-scheme-handler/my-app;\n"), "{entry}");
+
+        let (refs, components) = wix_url_scheme_components("glory:app:server", "MyApp", "server.exe", &schemes);
+        assert!(refs.contains(r#"<ComponentRef Id="UrlScheme_myapp" />"#), "{refs}");
+        assert!(refs.contains(r#"<ComponentRef Id="UrlScheme_my_app" />"#), "{refs}");
+        assert!(components.contains(r#"<RegistryKey Root="HKCR" Key="myapp">"#), "{components}");
+        assert!(
+            components.contains(r#"<RegistryValue Type="string" Name="URL Protocol" Value="" />"#),
+            "{components}"
+        );
+        assert!(
+            components.contains(r#"Value="&quot;[INSTALLFOLDER]server.exe&quot; &quot;%1&quot;""#),
+            "{components}"
+        );
+        assert_eq!(
+            wix_url_scheme_components("glory:app:server", "MyApp", "server.exe", &[]),
+            (String::new(), String::new())
+        );
     }
 
     #[test]
@@ -2196,11 +2521,12 @@ options:
 
     #[test]
     fn appimage_desktop_entry_references_package() {
-        let entry = appimage_desktop_entry("My App", "my-app");
+        let entry = appimage_desktop_entry("My App", "my-app", &[]);
         assert!(entry.contains("Name=My App"), "{entry}");
         assert!(entry.contains("Exec=my-app"), "{entry}");
         assert!(entry.contains("Icon=my-app"), "{entry}");
         assert!(entry.contains("Type=Application"), "{entry}");
+        assert!(!entry.contains("MimeType"), "{entry}");
     }
 
     #[test]
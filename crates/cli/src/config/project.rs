@@ -37,6 +37,8 @@ pub struct Project {
     pub assets: Option<AssetsConfig>,
     pub js_dir: Utf8PathBuf,
     pub target: BuildTarget,
+    /// URL schemes `glory bundle` registers for desktop deep links.
+    pub deep_link_schemes: Vec<String>,
 }
 
 impl Debug for Project {
@@ -99,6 +101,7 @@ impl Project {
                 assets: AssetsConfig::resolve(&config),
                 js_dir,
                 target: cli.target,
+                deep_link_schemes: config.deep_link_schemes.clone(),
             };
             resolved.push(Arc::new(proj));
         }
@@ -221,6 +224,10 @@ pub struct ProjectConfig {
     pub bin_features: Vec<String>,
     #[serde(default)]
     pub bin_default_features: bool,
+    /// URL schemes (e.g. `myapp` for `myapp://...` links) the desktop
+    /// bundle registers with the OS.
+    #[serde(default)]
+    pub deep_link_schemes: Vec<String>,
 
     #[serde(skip)]
     pub config_dir: Utf8PathBuf,
@@ -258,6 +265,9 @@ impl ProjectConfig {
         for proxy in &self.proxy {
             validate_proxy_rule(proxy)?;
         }
+        for scheme in &self.deep_link_schemes {
+            validate_url_scheme(scheme)?;
+        }
         Ok(())
     }
 }
@@ -278,6 +288,21 @@ pub fn validate_proxy_rule(rule: &str) -> Result<()> {
     Ok(())
 }
 
+/// A deep-link scheme must be a bare RFC 3986 scheme (`myapp`, not
+/// `myapp://`) and must not take over web or file links.
+fn validate_url_scheme(scheme: &str) -> Result<()> {
+    let mut chars = scheme.chars();
+    let valid =
+        chars.next().is_some_and(|first| first.is_ascii_alphabetic()) && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid {
+        bail!("deep link scheme must be a bare URL scheme like 'myapp': {scheme}");
+    }
+    if ["http", "https", "file"].iter().any(|reserved| scheme.eq_ignore_ascii_case(reserved)) {
+        bail!("deep link scheme cannot be '{scheme}'");
+    }
+    Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ProjectDefinition {
@@ -419,4 +444,19 @@ mod tests {
         }));
         assert!(with_key_only.validate().is_err());
     }
+
+    #[test]
+    fn validates_deep_link_schemes() {
+        let valid = project_config(serde_json::json!({
+            "deep_link_schemes": ["myapp", "my-app+v2.beta"]
+        }));
+        assert!(valid.validate().is_ok());
+
+        for scheme in ["myapp://", "2app", "", "my app", "https"] {
+            let invalid = project_config(serde_json::json!({
+                "deep_link_schemes": [scheme]
+            }));
+            assert!(invalid.validate().is_err(), "{scheme:?} should be rejected");
+        }
+    }
 }
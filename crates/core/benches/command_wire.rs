@@ -6,9 +6,14 @@
 //! compare these numbers against a frame budget (16.6ms) and typical
 //! batch sizes (a large initial mount ≈ 1000 rows × 4 commands).
 //!
+//! The `command_wire_binary` group measures the interning postcard codec
+//! LiveView negotiates (`renderer::command_wire`) on the same batches; the
+//! byte counts in the benchmark names compare the payload sizes.
+//!
 //! Run: `cargo bench -p glory-core --bench command_wire`
 
 use criterion::{Criterion, criterion_group, criterion_main};
+use glory_core::renderer::command_wire::{CommandDecoder, CommandEncoder};
 use glory_core::renderer::{Command, CommandInsertPosition};
 
 /// A realistic mount batch: each row = Create + SetAttribute + SetText + Insert.
@@ -72,5 +77,36 @@ fn bench_wire(criterion: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_wire);
+fn bench_binary_wire(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("command_wire_binary");
+    group.sample_size(40);
+
+    for rows in [100u64, 1000] {
+        let batch = typical_batch(rows);
+        let bytes = CommandEncoder::new().encode(&batch);
+        // A fresh encoder per iteration: a mount pays for interning its names.
+        group.bench_function(format!("serialize_{rows}_rows_{}_cmds", batch.len()), |bencher| {
+            bencher.iter(|| CommandEncoder::new().encode(std::hint::black_box(&batch)))
+        });
+        group.bench_function(format!("deserialize_{rows}_rows_{}_bytes", bytes.len()), |bencher| {
+            bencher.iter(|| CommandDecoder::new().decode(std::hint::black_box(&bytes)).unwrap())
+        });
+    }
+
+    // Steady state: the connection has already interned the patch's names.
+    let patch = patch_batch();
+    let mut encoder = CommandEncoder::new();
+    let mut decoder = CommandDecoder::new();
+    decoder.decode(&encoder.encode(&patch)).unwrap();
+    let patch_bytes = encoder.encode(&patch);
+    group.bench_function(format!("serialize_single_patch_{}_bytes", patch_bytes.len()), |bencher| {
+        bencher.iter(|| encoder.encode(std::hint::black_box(&patch)))
+    });
+    group.bench_function("deserialize_single_patch", |bencher| {
+        bencher.iter(|| decoder.decode(std::hint::black_box(&patch_bytes)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_wire, bench_binary_wire);
 criterion_main!(benches);
@@ -8,7 +8,8 @@ use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use futures::StreamExt;
 use glory_core::config::GloryConfig;
 use glory_core::web::holders::{HtmlChunk, ServerHolder};
-use glory_core::web::widgets::{article, div, h1, li, p, ul};
+use glory_core::web::widgets::{a, article, div, footer, h1, li, nav, p, ul};
+use glory_core::widgets::StaticTemplate;
 use glory_core::{Holder, Scope, Widget};
 
 #[derive(Debug)]
@@ -31,6 +32,41 @@ impl Widget for SsrRows {
     }
 }
 
+/// Links in the benchmark's page chrome. Fixed, because a template's build
+/// function takes no inputs.
+const CHROME_LINKS: usize = 500;
+
+/// Page chrome with no reactive parts, rendered either as plain elements or
+/// through a [`StaticTemplate`].
+#[derive(Debug)]
+struct SsrChrome {
+    template: bool,
+}
+
+fn chrome() -> impl Widget {
+    let items: Vec<_> = (0..CHROME_LINKS)
+        .map(|index| {
+            li().class("nav-item")
+                .fill(a().attr("href", format!("/section/{index}")).text(format!("Section {index}")))
+        })
+        .collect();
+    div()
+        .attr("id", "chrome")
+        .fill(nav().fill(ul().fill(items)))
+        .fill(footer().fill(p().text("static footer")))
+}
+
+impl Widget for SsrChrome {
+    fn build(&mut self, ctx: &mut Scope) {
+        let page = article().fill(h1().text("SSR benchmark"));
+        if self.template {
+            page.fill(StaticTemplate::new(chrome)).show_in(ctx);
+        } else {
+            page.fill(chrome()).show_in(ctx);
+        }
+    }
+}
+
 fn mount_rows(rows: usize) -> ServerHolder {
     ServerHolder::new(GloryConfig::default(), "/bench").mount(SsrRows { rows })
 }
@@ -56,5 +92,22 @@ fn bench_ssr_render(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_ssr_render);
+fn bench_ssr_static_template(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ssr_static_template");
+    group.sample_size(20);
+
+    for template in [false, true] {
+        let name = if template { "template" } else { "elements" };
+        group.bench_function(format!("mount_render_{CHROME_LINKS}_links_{name}"), |b| {
+            b.iter(|| {
+                let holder = ServerHolder::new(GloryConfig::default(), "/bench").mount(SsrChrome { template });
+                black_box(holder.render_string())
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_ssr_render, bench_ssr_static_template);
 criterion_main!(benches);
@@ -142,13 +142,17 @@ where
     let generation = Rc::new(Cell::new(0_u64));
     let active_suspense_generation = Rc::new(Cell::new(0_u64));
     let suspense_boundary = parent.suspense_boundary;
+    let origin = ResourceOrigin::of(parent);
     let future_fn = Rc::new(future_fn);
     effect_in(parent, move || {
         let future = (future_fn)();
         let cell = cell_for_effect;
         let run = ResourceRun::start(&generation, suspense_boundary, &active_suspense_generation);
+        let origin = origin.clone();
         crate::spawn::spawn_local(async move {
-            let val = future.await;
+            let Some(val) = origin.settle(future, &run).await else {
+                return;
+            };
             run.commit(cell, val);
         });
     });
@@ -192,6 +196,7 @@ where
     let generation = Rc::new(Cell::new(0_u64));
     let active_suspense_generation = Rc::new(Cell::new(0_u64));
     let suspense_boundary = parent.suspense_boundary;
+    let origin = ResourceOrigin::of(parent);
     let future_fn = Rc::new(future_fn);
     let token = Rc::new(token);
     effect_in(parent, move || {
@@ -202,8 +207,11 @@ where
         #[cfg(not(all(feature = "web-ssr", not(feature = "single-app"), not(target_arch = "wasm32"))))]
         let _ = &token;
         let run = ResourceRun::start(&generation, suspense_boundary, &active_suspense_generation);
+        let origin = origin.clone();
         crate::spawn::spawn_local(async move {
-            let val = future.await;
+            let Some(val) = origin.settle(future, &run).await else {
+                return;
+            };
             // Capture for hydration before the value is consumed by `commit`,
             // but only persist it when this run is the one that wins the cell.
             #[cfg(all(feature = "web-ssr", not(feature = "single-app"), not(target_arch = "wasm32")))]
@@ -312,6 +320,56 @@ where
     Coroutine { tx }
 }
 
+/// Where a resource was created. On the streaming server a panicking future
+/// is routed to the enclosing `ErrorBoundary` instead of tearing down the
+/// whole response, mirroring how the scheduler treats a panicking patch.
+#[derive(Clone, Debug)]
+struct ResourceOrigin {
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    view_id: ViewId,
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    error_boundary: Option<ViewId>,
+}
+
+impl ResourceOrigin {
+    fn of(parent: &Scope) -> Self {
+        #[cfg(not(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32"))))]
+        let _ = parent;
+        Self {
+            #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+            view_id: parent.view_id.clone(),
+            #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+            error_boundary: parent.error_boundary.clone(),
+        }
+    }
+
+    /// Awaits `future`; `None` means it panicked and the error was handed to
+    /// the boundary.
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    async fn settle<Fut: std::future::Future>(&self, future: Fut, run: &ResourceRun) -> Option<Fut::Output> {
+        use futures::FutureExt;
+
+        let payload = match std::panic::AssertUnwindSafe(future).catch_unwind().await {
+            Ok(value) => return Some(value),
+            Err(payload) => payload,
+        };
+        let Some(boundary_id) = &self.error_boundary else {
+            std::panic::resume_unwind(payload);
+        };
+        let error = crate::BoundaryError::from_panic(payload, Some(self.view_id.clone()));
+        if !super::scheduler::capture_error_in(boundary_id, error) {
+            panic!("error boundary `{boundary_id}` rejected captured panic");
+        }
+        run.finish_suspense();
+        None
+    }
+
+    #[cfg(not(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32"))))]
+    async fn settle<Fut: std::future::Future>(&self, future: Fut, _run: &ResourceRun) -> Option<Fut::Output> {
+        Some(future.await)
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ResourceRun {
     generation: Rc<Cell<u64>>,
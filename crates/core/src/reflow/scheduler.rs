@@ -93,6 +93,27 @@ where
     })
 }
 
+/// Hands `error` to the `ErrorBoundary` mounted at `boundary_id`, outside
+/// the patch loop — used when a resource future panics while a streaming
+/// render is being driven. Returns false when the boundary is gone or refused.
+#[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+pub(crate) fn capture_error_in(boundary_id: &ViewId, error: crate::BoundaryError) -> bool {
+    let holder_id = boundary_id.holder_id();
+    // The fallback's nodes belong on the holder's command stream.
+    let _queue_guard = crate::renderer::command::make_holder_queue_current(holder_id);
+    batch(holder_id, || {
+        ROOT_VIEWS.with(|root_views| {
+            let Ok(mut root_views) = root_views.try_borrow_mut() else {
+                return false;
+            };
+            let Some(boundary) = root_views.entry(holder_id).or_default().get_mut(boundary_id) else {
+                return false;
+            };
+            boundary.widget.capture_error(&mut boundary.scope, error)
+        })
+    })
+}
+
 #[cfg(feature = "single-app")]
 pub fn schedule() {
     if !is_running() && !is_batching() {
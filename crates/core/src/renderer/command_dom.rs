@@ -40,7 +40,7 @@ pub struct DomNode {
 
 /// See the module docs. Panics on malformed batches (unknown node ids):
 /// a malformed batch is a framework bug, and tests should fail loudly.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommandDom {
     nodes: HashMap<u64, DomNode>,
     /// child id -> parent id, for the move-on-insert rule.
@@ -205,6 +205,100 @@ impl CommandDom {
         }
     }
 
+    /// Commands that turn this tree into `target`, for a consumer that already
+    /// shows this tree (e.g. server-rendered HTML) and must end up showing
+    /// `target`. Ids name the same node in both trees; a node whose tag
+    /// changed is created anew. Listeners are not compared.
+    ///
+    /// The batch removes nodes first, then creates nodes, then updates
+    /// content, and moves children last. A node whose text or markup is
+    /// rewritten gets all of its children inserted again, because `SetText`
+    /// and `SetHtml` clear them, and so does one with a child created anew.
+    pub fn diff(&self, target: &CommandDom) -> Vec<Command> {
+        let kept = |id: u64| match (self.nodes.get(&id), target.nodes.get(&id)) {
+            (Some(old), Some(new)) => old.name == new.name && old.is_void == new.is_void,
+            _ => false,
+        };
+        let mut ids: Vec<u64> = target.nodes.keys().copied().filter(|id| *id != ROOT_ID).collect();
+        ids.sort_unstable();
+
+        let mut removals: Vec<(u64, u64)> = self
+            .parents
+            .iter()
+            .filter(|(child, _)| !kept(**child) || !target.parents.contains_key(*child))
+            .map(|(child, parent)| (*parent, *child))
+            .collect();
+        removals.sort_unstable();
+        let mut batch: Vec<Command> = removals.into_iter().map(|(parent, child)| Command::Remove { parent, child }).collect();
+
+        for id in &ids {
+            if !kept(*id) {
+                let node = &target.nodes[id];
+                batch.push(Command::Create {
+                    id: *id,
+                    name: node.name.clone(),
+                    is_void: node.is_void,
+                });
+            }
+        }
+
+        let empty = DomNode::default();
+        let mut rewritten = BTreeSet::new();
+        for id in std::iter::once(ROOT_ID).chain(ids.iter().copied()) {
+            let new = &target.nodes[&id];
+            let old = if kept(id) { &self.nodes[&id] } else { &empty };
+            for name in old.attributes.keys().filter(|name| !new.attributes.contains_key(*name)) {
+                batch.push(Command::RemoveAttribute { id, name: name.clone() });
+            }
+            for (name, value) in new.attributes.iter().filter(|(name, value)| old.attributes.get(*name) != Some(*value)) {
+                batch.push(Command::SetAttribute {
+                    id,
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+            for name in old.properties.keys().filter(|name| !new.properties.contains_key(*name)) {
+                batch.push(Command::RemoveProperty { id, name: name.clone() });
+            }
+            for (name, value) in new.properties.iter().filter(|(name, value)| old.properties.get(*name) != Some(*value)) {
+                batch.push(Command::SetProperty {
+                    id,
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+            for value in old.classes.difference(&new.classes) {
+                batch.push(Command::RemoveClass { id, value: value.clone() });
+            }
+            for value in new.classes.difference(&old.classes) {
+                batch.push(Command::AddClass { id, value: value.clone() });
+            }
+            if new.text != old.text || new.raw_html != old.raw_html {
+                batch.push(match &new.raw_html {
+                    Some(value) => Command::SetHtml { id, value: value.clone() },
+                    None => Command::SetText {
+                        id,
+                        value: new.text.clone().unwrap_or_default(),
+                    },
+                });
+                rewritten.insert(id);
+            }
+        }
+
+        for id in std::iter::once(ROOT_ID).chain(ids) {
+            let new = &target.nodes[&id];
+            let replaced_child = new.children.iter().any(|child| !kept(*child));
+            if !kept(id) || rewritten.contains(&id) || replaced_child || self.nodes[&id].children != new.children {
+                batch.extend(new.children.iter().map(|child| Command::Insert {
+                    parent: id,
+                    child: *child,
+                    position: CommandInsertPosition::Tail,
+                }));
+            }
+        }
+        batch
+    }
+
     /// Depth-first text contents of all `tag` elements, in document order.
     /// The conformance counterpart of parsing `<li>` sequences out of SSR
     /// snapshots.
@@ -305,6 +399,84 @@ mod tests {
         assert_eq!(parent_node.text.as_deref(), Some("flat"));
     }
 
+    fn create(id: u64, name: &str) -> Command {
+        Command::Create {
+            id,
+            name: name.into(),
+            is_void: false,
+        }
+    }
+
+    fn tail(parent: u64, child: u64) -> Command {
+        Command::Insert {
+            parent,
+            child,
+            position: CommandInsertPosition::Tail,
+        }
+    }
+
+    #[test]
+    fn diff_turns_one_tree_into_another() {
+        let mut before = CommandDom::new();
+        before.apply_batch(&[
+            create(1, "ul"),
+            create(2, "li"),
+            create(3, "li"),
+            create(4, "p"),
+            Command::SetText { id: 2, value: "A".into() },
+            Command::SetText { id: 3, value: "B".into() },
+            Command::SetAttribute {
+                id: 1,
+                name: "title".into(),
+                value: "old".into(),
+            },
+            Command::AddClass {
+                id: 2,
+                value: "stale".into(),
+            },
+            tail(ROOT_ID, 1),
+            tail(1, 2),
+            tail(1, 3),
+            tail(ROOT_ID, 4),
+        ]);
+        let mut after = CommandDom::new();
+        after.apply_batch(&[
+            create(1, "ul"),
+            create(2, "li"),
+            create(3, "li"),
+            create(4, "span"),
+            create(5, "li"),
+            Command::SetText { id: 2, value: "A".into() },
+            Command::SetText { id: 3, value: "B!".into() },
+            Command::SetText { id: 5, value: "C".into() },
+            Command::AddClass {
+                id: 2,
+                value: "fresh".into(),
+            },
+            tail(ROOT_ID, 1),
+            tail(1, 3),
+            tail(1, 2),
+            tail(1, 5),
+            tail(ROOT_ID, 4),
+        ]);
+
+        let diff = before.diff(&after);
+        let mut patched = before.clone();
+        patched.apply_batch(&diff);
+        assert_eq!(patched.inner_html(ROOT_ID), after.inner_html(ROOT_ID));
+        assert_eq!(
+            patched.inner_html(ROOT_ID),
+            r#"<ul><li>B!</li><li class="fresh">A</li><li>C</li></ul><span></span>"#
+        );
+        assert!(!diff.iter().any(|command| matches!(command, Command::SetText { id: 2, .. })));
+        assert!(
+            diff.contains(&Command::Remove { parent: ROOT_ID, child: 4 }),
+            "the retagged node is replaced"
+        );
+
+        assert!(after.diff(&after).is_empty());
+    }
+
     #[test]
     fn remove_ignores_stale_parent() {
         let mut dom = CommandDom::new();
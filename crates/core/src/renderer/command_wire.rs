@@ -0,0 +1,361 @@
+//! Compact binary encoding of [`Command`] batches.
+//!
+//! The JSON wire format repeats every tag, attribute and event name and
+//! spells node ids out as decimal text; on table-heavy pages that is most of
+//! the payload. [`CommandEncoder`] writes a batch with postcard instead: ids
+//! become varints, and names (tags, attributes, properties, events) and class
+//! strings are interned. The first batch using a string carries it once, and
+//! every later command refers to it by index.
+//!
+//! The interning table lives in the encoder, so a [`CommandDecoder`] must see
+//! the same batches in the same order. Start both afresh whenever a batch may
+//! have been lost, e.g. on every new connection.
+//!
+//! # Format
+//!
+//! A batch is the postcard encoding of the strings it newly interns (appended
+//! to the table in order) followed by its commands. Commands keep the variant
+//! and field order of [`Command`]; interned fields hold a table index instead
+//! of the string. The LiveView client in `crates/liveview` decodes exactly this
+//! shape, so changing it is a wire-protocol change.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Command, CommandInsertPosition, NodeQuery};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandWireError {
+    #[error("malformed command batch: {0}")]
+    Malformed(#[from] postcard::Error),
+    #[error("command batch refers to unknown string {0}")]
+    UnknownString(u32),
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireBatch<'a> {
+    #[serde(borrow)]
+    strings: Vec<Cow<'a, str>>,
+    #[serde(borrow)]
+    commands: Vec<WireCommand<'a>>,
+}
+
+/// [`Command`] with interned fields as table indices. Variant order must
+/// match [`Command`].
+#[derive(Serialize, Deserialize)]
+enum WireCommand<'a> {
+    Create {
+        id: u64,
+        name: u32,
+        is_void: bool,
+    },
+    SetAttribute {
+        id: u64,
+        name: u32,
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    },
+    RemoveAttribute {
+        id: u64,
+        name: u32,
+    },
+    SetProperty {
+        id: u64,
+        name: u32,
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    },
+    RemoveProperty {
+        id: u64,
+        name: u32,
+    },
+    AddClass {
+        id: u64,
+        value: u32,
+    },
+    RemoveClass {
+        id: u64,
+        value: u32,
+    },
+    SetText {
+        id: u64,
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    },
+    SetHtml {
+        id: u64,
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    },
+    Insert {
+        parent: u64,
+        child: u64,
+        position: CommandInsertPosition,
+    },
+    Remove {
+        parent: u64,
+        child: u64,
+    },
+    AttachEvent {
+        id: u64,
+        name: u32,
+        bubbles: bool,
+    },
+    DetachEvent {
+        id: u64,
+        name: u32,
+    },
+    Query {
+        id: u64,
+        token: u64,
+        kind: NodeQuery,
+    },
+}
+
+/// Encodes batches for one [`CommandDecoder`]. See the module docs.
+#[derive(Debug, Default)]
+pub struct CommandEncoder {
+    interned: HashMap<String, u32>,
+}
+
+impl CommandEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of strings interned so far.
+    pub fn interned(&self) -> usize {
+        self.interned.len()
+    }
+
+    pub fn encode(&mut self, commands: &[Command]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.encode_into(commands, &mut bytes);
+        bytes
+    }
+
+    /// Appends the encoded batch to `bytes`.
+    pub fn encode_into(&mut self, commands: &[Command], bytes: &mut Vec<u8>) {
+        let mut strings = Vec::new();
+        let mut index_of = |value| intern(&mut self.interned, &mut strings, value);
+        let commands = commands
+            .iter()
+            .map(|command| match command {
+                Command::Create { id, name, is_void } => WireCommand::Create {
+                    id: *id,
+                    name: index_of(name),
+                    is_void: *is_void,
+                },
+                Command::SetAttribute { id, name, value } => WireCommand::SetAttribute {
+                    id: *id,
+                    name: index_of(name),
+                    value: Cow::Borrowed(value),
+                },
+                Command::RemoveAttribute { id, name } => WireCommand::RemoveAttribute {
+                    id: *id,
+                    name: index_of(name),
+                },
+                Command::SetProperty { id, name, value } => WireCommand::SetProperty {
+                    id: *id,
+                    name: index_of(name),
+                    value: Cow::Borrowed(value),
+                },
+                Command::RemoveProperty { id, name } => WireCommand::RemoveProperty {
+                    id: *id,
+                    name: index_of(name),
+                },
+                Command::AddClass { id, value } => WireCommand::AddClass {
+                    id: *id,
+                    value: index_of(value),
+                },
+                Command::RemoveClass { id, value } => WireCommand::RemoveClass {
+                    id: *id,
+                    value: index_of(value),
+                },
+                Command::SetText { id, value } => WireCommand::SetText {
+                    id: *id,
+                    value: Cow::Borrowed(value),
+                },
+                Command::SetHtml { id, value } => WireCommand::SetHtml {
+                    id: *id,
+                    value: Cow::Borrowed(value),
+                },
+                Command::Insert { parent, child, position } => WireCommand::Insert {
+                    parent: *parent,
+                    child: *child,
+                    position: *position,
+                },
+                Command::Remove { parent, child } => WireCommand::Remove {
+                    parent: *parent,
+                    child: *child,
+                },
+                Command::AttachEvent { id, name, bubbles } => WireCommand::AttachEvent {
+                    id: *id,
+                    name: index_of(name),
+                    bubbles: *bubbles,
+                },
+                Command::DetachEvent { id, name } => WireCommand::DetachEvent {
+                    id: *id,
+                    name: index_of(name),
+                },
+                Command::Query { id, token, kind } => WireCommand::Query {
+                    id: *id,
+                    token: *token,
+                    kind: *kind,
+                },
+            })
+            .collect();
+        let batch = WireBatch { strings, commands };
+        let encoded = postcard::to_extend(&batch, std::mem::take(bytes)).expect("command batches serialize");
+        *bytes = encoded;
+    }
+}
+
+fn intern<'a>(interned: &mut HashMap<String, u32>, fresh: &mut Vec<Cow<'a, str>>, value: &'a str) -> u32 {
+    if let Some(index) = interned.get(value) {
+        return *index;
+    }
+    let index = interned.len() as u32;
+    interned.insert(value.to_owned(), index);
+    fresh.push(Cow::Borrowed(value));
+    index
+}
+
+/// Decodes what one [`CommandEncoder`] produced, in order.
+#[derive(Debug, Default)]
+pub struct CommandDecoder {
+    strings: Vec<String>,
+}
+
+impl CommandDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Vec<Command>, CommandWireError> {
+        let batch: WireBatch<'_> = postcard::from_bytes(bytes)?;
+        self.strings.extend(batch.strings.into_iter().map(Cow::into_owned));
+        let string = |index: u32| self.strings.get(index as usize).cloned().ok_or(CommandWireError::UnknownString(index));
+        batch
+            .commands
+            .into_iter()
+            .map(|command| {
+                Ok(match command {
+                    WireCommand::Create { id, name, is_void } => Command::Create {
+                        id,
+                        name: string(name)?,
+                        is_void,
+                    },
+                    WireCommand::SetAttribute { id, name, value } => Command::SetAttribute {
+                        id,
+                        name: string(name)?,
+                        value: value.into_owned(),
+                    },
+                    WireCommand::RemoveAttribute { id, name } => Command::RemoveAttribute { id, name: string(name)? },
+                    WireCommand::SetProperty { id, name, value } => Command::SetProperty {
+                        id,
+                        name: string(name)?,
+                        value: value.into_owned(),
+                    },
+                    WireCommand::RemoveProperty { id, name } => Command::RemoveProperty { id, name: string(name)? },
+                    WireCommand::AddClass { id, value } => Command::AddClass { id, value: string(value)? },
+                    WireCommand::RemoveClass { id, value } => Command::RemoveClass { id, value: string(value)? },
+                    WireCommand::SetText { id, value } => Command::SetText {
+                        id,
+                        value: value.into_owned(),
+                    },
+                    WireCommand::SetHtml { id, value } => Command::SetHtml {
+                        id,
+                        value: value.into_owned(),
+                    },
+                    WireCommand::Insert { parent, child, position } => Command::Insert { parent, child, position },
+                    WireCommand::Remove { parent, child } => Command::Remove { parent, child },
+                    WireCommand::AttachEvent { id, name, bubbles } => Command::AttachEvent {
+                        id,
+                        name: string(name)?,
+                        bubbles,
+                    },
+                    WireCommand::DetachEvent { id, name } => Command::DetachEvent { id, name: string(name)? },
+                    WireCommand::Query { id, token, kind } => Command::Query { id, token, kind },
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(from: u64, to: u64) -> Vec<Command> {
+        (from..to)
+            .flat_map(|id| {
+                [
+                    Command::Create {
+                        id,
+                        name: "tr".into(),
+                        is_void: false,
+                    },
+                    Command::SetAttribute {
+                        id,
+                        name: "data-id".into(),
+                        value: id.to_string(),
+                    },
+                    Command::AddClass { id, value: "row".into() },
+                    Command::Insert {
+                        parent: 0,
+                        child: id,
+                        position: CommandInsertPosition::Before(1),
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batches_round_trip_and_names_are_sent_once() {
+        let mut encoder = CommandEncoder::new();
+        let mut decoder = CommandDecoder::new();
+
+        let mount = rows(1, 50);
+        let first = encoder.encode(&mount);
+        assert_eq!(decoder.decode(&first).unwrap(), mount);
+        assert_eq!(encoder.interned(), 3);
+
+        let mut patch = rows(50, 51);
+        patch.extend([
+            Command::AttachEvent {
+                id: 50,
+                name: "click".into(),
+                bubbles: true,
+            },
+            Command::Query {
+                id: 50,
+                token: 7,
+                kind: NodeQuery::Value,
+            },
+            Command::SetText {
+                id: 50,
+                value: "ünïcode".into(),
+            },
+        ]);
+        let second = encoder.encode(&patch);
+        assert_eq!(decoder.decode(&second).unwrap(), patch);
+        assert_eq!(encoder.interned(), 4, "only `click` is new");
+        assert!(!second.windows(2).any(|window| window == b"tr"), "known names are not repeated");
+
+        let json = serde_json::to_vec(&mount).unwrap();
+        assert!(first.len() * 4 < json.len(), "binary {} vs json {}", first.len(), json.len());
+    }
+
+    #[test]
+    fn a_decoder_that_missed_a_batch_reports_unknown_strings() {
+        let mut encoder = CommandEncoder::new();
+        encoder.encode(&rows(1, 2));
+        let bytes = encoder.encode(&rows(2, 3));
+        assert!(matches!(CommandDecoder::new().decode(&bytes), Err(CommandWireError::UnknownString(0))));
+        assert!(matches!(CommandDecoder::new().decode(&[0xff]), Err(CommandWireError::Malformed(_))));
+    }
+}
@@ -13,6 +13,7 @@ use std::sync::Arc;
 
 pub mod command;
 pub mod command_dom;
+pub mod command_wire;
 pub mod ssr_dom;
 pub use command::{
     BoundingRect, ClipboardData, Command, CommandInsertPosition, CommandNode, CommandQueue, CommandRenderer, CurrentQueueGuard, EventData,
@@ -25,6 +25,10 @@ use std::rc::Rc;
 
 use super::command::{Command, CommandInsertPosition};
 
+/// Attribute carrying an element's command-stream node id in HTML rendered
+/// by [`ServerHolder::node_ids`](crate::web::holders::ServerHolder::node_ids).
+pub const NODE_ID_ATTRIBUTE: &str = "data-glory-id";
+
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct SsrNode {
     name: Rc<RefCell<Cow<'static, str>>>,
@@ -274,6 +278,34 @@ impl SsrNode {
     }
 }
 
+/// Read access for [`StaticTemplate`](crate::widgets::StaticTemplate), which
+/// copies a replayed root element into its compiled form.
+#[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+impl SsrNode {
+    pub(crate) fn name(&self) -> String {
+        self.name.borrow().to_string()
+    }
+    pub(crate) fn is_void(&self) -> bool {
+        *self.is_void.borrow()
+    }
+    pub(crate) fn attributes(&self) -> Vec<(String, String)> {
+        self.attributes.borrow().iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+    pub(crate) fn properties(&self) -> Vec<(String, Option<String>)> {
+        self.properties
+            .borrow()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.as_ref().map(|v| v.to_string())))
+            .collect()
+    }
+    pub(crate) fn has_classes(&self) -> bool {
+        !self.classes.borrow().is_empty()
+    }
+    pub(crate) fn children(&self) -> Vec<SsrNode> {
+        self.children.borrow().clone()
+    }
+}
+
 fn escape_html_text(value: &str) -> String {
     value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
 }
@@ -94,6 +94,9 @@ pub struct Scope {
     pub(crate) suspense_boundary: Option<SuspenseBoundary>,
     #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
     compact_fill_allowed: bool,
+    /// Type name of the widget this scope belongs to, for hydration reports.
+    #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+    pub(crate) widget_type: &'static str,
 
     next_child_view_id: AtomicU64,
     next_resource_id: AtomicU64,
@@ -125,6 +128,8 @@ impl Scope {
             suspense_boundary: None,
             #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
             compact_fill_allowed: false,
+            #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+            widget_type: "",
 
             next_child_view_id: AtomicU64::new(0),
             next_resource_id: AtomicU64::new(0),
@@ -155,6 +160,8 @@ impl Scope {
             suspense_boundary: None,
             #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
             compact_fill_allowed: false,
+            #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+            widget_type: "",
 
             next_child_view_id: AtomicU64::new(0),
             next_resource_id: AtomicU64::new(0),
@@ -240,6 +247,65 @@ impl Scope {
         self.truck.borrow_mut()
     }
 
+    /// The HTTP response options of the current server render, for setting
+    /// the status, headers, cookies or a redirect from inside the tree.
+    /// Detached (writes are never read) when not rendering on a server.
+    pub fn response_options(&self) -> crate::web::ServerResponseOptions {
+        crate::web::ServerResponseOptions::from_truck(&self.truck.borrow()).unwrap_or_default()
+    }
+
+    /// The CSP nonce of the current server render, if it has one.
+    pub fn csp_nonce(&self) -> Option<crate::web::CspNonce> {
+        crate::web::CspNonce::from_truck(&self.truck.borrow())
+    }
+
+    /// A [`Cage`] whose initial value is computed once, on the server, and
+    /// adopted by the client while hydrating instead of running `init` again.
+    ///
+    /// Use it for state derived from the request, such as the signed-in user
+    /// or feature flags. The server serializes the value into the page's
+    /// hydration payload under `key`. A hydrating client deserializes it from
+    /// there. Cages sharing a key share the value. Outside hydration, and
+    /// when the payload has no entry for `key`, `init` runs as usual. A value
+    /// that does not deserialize as `T` is reported (as an error in debug
+    /// builds) and `init` runs instead.
+    pub fn hydratable_cage<T, F>(&self, key: &str, init: F) -> Cage<T>
+    where
+        T: std::fmt::Debug + serde::Serialize + serde::de::DeserializeOwned + 'static,
+        F: FnOnce() -> T,
+    {
+        let token = format!("cage:{key}");
+
+        #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+        if crate::web::is_hydrating() {
+            match crate::web::hydrated_value::<T>(&token) {
+                Some(Ok(value)) => return Cage::new(value),
+                Some(Err(e)) => {
+                    let message = format!(
+                        "[hydratable_cage]: server value for `{key}` is not a `{}`: {e}; running `init` instead",
+                        std::any::type_name::<T>()
+                    );
+                    if cfg!(debug_assertions) {
+                        crate::console::error(&message);
+                    } else {
+                        crate::console::debug_warn(&message);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        let value = init();
+        #[cfg(all(feature = "web-ssr", not(feature = "single-app"), not(target_arch = "wasm32")))]
+        match serde_json::to_string(&value) {
+            Ok(json) => crate::stream_ssr::record_resource_json(&token, json),
+            Err(e) => crate::error!("[hydratable_cage]: `{key}` failed to serialize: {e}"),
+        }
+        #[cfg(not(all(feature = "web-ssr", not(feature = "single-app"), not(target_arch = "wasm32"))))]
+        let _ = &token;
+        Cage::new(value)
+    }
+
     pub fn child_views(&self) -> &IndexMap<ViewId, View> {
         &self.child_views
     }
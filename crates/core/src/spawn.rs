@@ -80,7 +80,7 @@ cfg_feature! {
 
     /// Takes the futures deferred so far, leaving the queue armed-but-empty so
     /// futures spawned while draining (suspense waterfalls) keep deferring.
-    fn take_deferred() -> Vec<Pin<Box<dyn Future<Output = ()>>>> {
+    pub(crate) fn take_deferred() -> Vec<Pin<Box<dyn Future<Output = ()>>>> {
         DEFERRED.with(|slot| {
             let mut slot = slot.borrow_mut();
             match slot.as_mut() {
@@ -90,12 +90,23 @@ cfg_feature! {
         })
     }
 
+    /// True when futures were deferred since the last [`take_deferred`].
+    pub(crate) fn has_deferred() -> bool {
+        DEFERRED.with(|slot| slot.borrow().as_ref().is_some_and(|queue| !queue.is_empty()))
+    }
+
     /// Disarms the deferral queue, restoring `previous` (typically the value
     /// returned by [`begin_deferred`]).
     pub(crate) fn end_deferred(previous: Option<Vec<Pin<Box<dyn Future<Output = ()>>>>>) {
         DEFERRED.with(|slot| *slot.borrow_mut() = previous);
     }
 
+    /// Puts `queue` in place of the thread's deferral queue and returns the
+    /// queue it replaces, so a render worker can switch between renders.
+    pub(crate) fn swap_deferred(queue: Option<Vec<Pin<Box<dyn Future<Output = ()>>>>>) -> Option<Vec<Pin<Box<dyn Future<Output = ()>>>>> {
+        DEFERRED.with(|slot| std::mem::replace(&mut *slot.borrow_mut(), queue))
+    }
+
     /// Drives every deferred future to completion, re-collecting any futures
     /// that later deferral (suspense waterfalls) produces, until the queue
     /// stays empty.
@@ -1,7 +1,9 @@
 //! Streaming-SSR coordination shared between the server holder (the
-//! orchestrator) and [`Suspense`](crate::widgets::Suspense), which tags its
+//! orchestrator), [`Suspense`](crate::widgets::Suspense), which tags its
 //! rendered region so a resolved body can be streamed in as an out-of-order
-//! patch.
+//! patch, and [`ErrorBoundary`](crate::widgets::ErrorBoundary), whose
+//! fallback is streamed in place of a boundary that failed after the shell
+//! was sent.
 //!
 //! This API only exists for server-side streaming SSR (non-wasm, `web-ssr`,
 //! multi-holder); the module is empty everywhere else, so non-server builds
@@ -12,8 +14,15 @@
 mod imp {
     use std::cell::{Cell, RefCell};
     use std::collections::BTreeMap;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::rc::Rc;
 
+    use crate::node::Node;
+    use crate::renderer::{BackendRenderer, InsertPosition, Renderer};
     use crate::scope::SuspenseBoundary;
+    use crate::view::ViewPlacement;
+    use crate::{BoundaryError, Scope, ViewId};
 
     /// A Suspense region registered during a streaming mount.
     #[derive(Clone)]
@@ -26,11 +35,34 @@ mod imp {
         pub wrapper_id: u64,
         /// Boundary handle, used to read pending state at flush time.
         pub boundary: SuspenseBoundary,
+        /// Nearest enclosing `ErrorBoundary`, whose fallback replaces this
+        /// region if it fails after the shell was flushed.
+        pub error_boundary: Option<ViewId>,
+    }
+
+    /// An `ErrorBoundary` registered during a streaming mount.
+    #[derive(Clone)]
+    pub(crate) struct ErrorBoundaryRegistration {
+        pub view_id: ViewId,
+        /// Enclosing `ErrorBoundary`, if any.
+        pub parent: Option<ViewId>,
+        /// SSR node id of the wrapper whose children are the boundary region.
+        pub wrapper_id: u64,
+        /// Set by the boundary when it switches to its fallback.
+        pub error: Rc<RefCell<Option<BoundaryError>>>,
+    }
+
+    /// Everything registered during one streaming mount.
+    #[derive(Default)]
+    pub(crate) struct StreamRegistrations {
+        pub boundaries: Vec<BoundaryRegistration>,
+        pub error_boundaries: Vec<ErrorBoundaryRegistration>,
     }
 
     thread_local! {
         static STREAMING: Cell<bool> = const { Cell::new(false) };
         static BOUNDARIES: RefCell<Vec<BoundaryRegistration>> = const { RefCell::new(Vec::new()) };
+        static ERROR_BOUNDARIES: RefCell<Vec<ErrorBoundaryRegistration>> = const { RefCell::new(Vec::new()) };
         static NEXT_ID: Cell<u64> = const { Cell::new(0) };
         /// Resolved `resource_hydratable_in` values (token → JSON) captured
         /// during a server render so the client can adopt them and skip the
@@ -38,6 +70,46 @@ mod imp {
         static RESOURCE_DATA: RefCell<Option<BTreeMap<String, String>>> = const { RefCell::new(None) };
     }
 
+    /// The thread-local state of one server render, kept aside while other
+    /// renders run on the thread. A render pool worker installs it around
+    /// every poll of its render.
+    #[derive(Default)]
+    pub(crate) struct RenderLocals {
+        streaming: bool,
+        boundaries: Vec<BoundaryRegistration>,
+        error_boundaries: Vec<ErrorBoundaryRegistration>,
+        next_id: u64,
+        resource_data: Option<BTreeMap<String, String>>,
+        deferred: Option<Vec<Pin<Box<dyn Future<Output = ()>>>>>,
+    }
+
+    impl RenderLocals {
+        /// Makes this the thread's render state until the guard drops, which
+        /// puts the previous state back.
+        pub(crate) fn install(&mut self) -> InstalledLocals<'_> {
+            self.swap();
+            InstalledLocals(self)
+        }
+
+        fn swap(&mut self) {
+            self.streaming = STREAMING.with(|flag| flag.replace(self.streaming));
+            BOUNDARIES.with_borrow_mut(|boundaries| std::mem::swap(boundaries, &mut self.boundaries));
+            ERROR_BOUNDARIES.with_borrow_mut(|boundaries| std::mem::swap(boundaries, &mut self.error_boundaries));
+            self.next_id = NEXT_ID.with(|next| next.replace(self.next_id));
+            RESOURCE_DATA.with_borrow_mut(|data| std::mem::swap(data, &mut self.resource_data));
+            self.deferred = crate::spawn::swap_deferred(self.deferred.take());
+        }
+    }
+
+    /// Restores the render state [`RenderLocals::install`] replaced.
+    pub(crate) struct InstalledLocals<'a>(&'a mut RenderLocals);
+
+    impl Drop for InstalledLocals<'_> {
+        fn drop(&mut self) {
+            self.0.swap();
+        }
+    }
+
     /// True while a streaming mount is in progress on this thread.
     pub(crate) fn is_streaming() -> bool {
         STREAMING.with(Cell::get)
@@ -48,13 +120,17 @@ mod imp {
     pub(crate) fn begin() {
         STREAMING.with(|flag| flag.set(true));
         BOUNDARIES.with(|b| b.borrow_mut().clear());
+        ERROR_BOUNDARIES.with(|b| b.borrow_mut().clear());
         NEXT_ID.with(|n| n.set(0));
     }
 
     /// Disarms streaming mode and hands back the registered boundaries.
-    pub(crate) fn finish() -> Vec<BoundaryRegistration> {
+    pub(crate) fn finish() -> StreamRegistrations {
         STREAMING.with(|flag| flag.set(false));
-        BOUNDARIES.with(|b| std::mem::take(&mut *b.borrow_mut()))
+        StreamRegistrations {
+            boundaries: BOUNDARIES.with(|b| std::mem::take(&mut *b.borrow_mut())),
+            error_boundaries: ERROR_BOUNDARIES.with(|b| std::mem::take(&mut *b.borrow_mut())),
+        }
     }
 
     /// Allocates the next deterministic placeholder id for a boundary.
@@ -71,6 +147,39 @@ mod imp {
         BOUNDARIES.with(|b| b.borrow_mut().push(registration));
     }
 
+    /// Records an error boundary so the holder can stream its fallback.
+    pub(crate) fn register_error_boundary(registration: ErrorBoundaryRegistration) {
+        ERROR_BOUNDARIES.with(|b| b.borrow_mut().push(registration));
+    }
+
+    /// Creates a wrapper element tagged `attr="value"` and routes `ctx`'s
+    /// children under it. The holder unwraps it when serializing, so it never
+    /// reaches the page.
+    pub(crate) fn create_wrapper(ctx: &mut Scope, tag: &'static str, attr: &'static str, value: String) -> Node {
+        let renderer = BackendRenderer::default();
+        let wrapper = renderer.create_element(tag.into(), false);
+        renderer.set_attribute(&wrapper, attr.into(), value.into());
+        ctx.render_node = Some(wrapper.clone());
+        ctx.first_child_node = Some(wrapper.clone());
+        ctx.last_child_node = Some(wrapper.clone());
+        wrapper
+    }
+
+    /// Places a wrapper under the view's parent, honouring its sibling
+    /// placement (mirrors `Element::flood`).
+    pub(crate) fn insert_wrapper(ctx: &Scope, wrapper: &Node) {
+        let Some(parent_node) = ctx.parent_node.as_ref() else {
+            return;
+        };
+        let renderer = BackendRenderer::default();
+        match &ctx.placement {
+            ViewPlacement::Head => renderer.insert_child(parent_node, wrapper, InsertPosition::Head),
+            ViewPlacement::Before(next_node) => renderer.insert_child(parent_node, wrapper, InsertPosition::Before(next_node)),
+            ViewPlacement::After(prev_node) => renderer.insert_child(parent_node, wrapper, InsertPosition::After(prev_node)),
+            ViewPlacement::Tail | ViewPlacement::Unset => renderer.insert_child(parent_node, wrapper, InsertPosition::Tail),
+        }
+    }
+
     /// Arms resolved-resource-value capture for a server render. Cleared and
     /// reset so a previous (un-rendered) holder never leaks data into this one.
     pub(crate) fn arm_resource_capture() {
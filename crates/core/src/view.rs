@@ -113,7 +113,13 @@ pub struct View {
 }
 
 impl View {
-    pub fn new(scope: Scope, widget: impl Widget) -> Self {
+    pub fn new<W: Widget>(scope: Scope, widget: W) -> Self {
+        #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+        let scope = {
+            let mut scope = scope;
+            scope.widget_type = std::any::type_name::<W>();
+            scope
+        };
         Self {
             id: scope.view_id.clone(),
             widget: Box::new(widget),
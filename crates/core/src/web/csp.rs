@@ -0,0 +1,236 @@
+//! Content Security Policy nonces for the inline code Glory emits.
+//!
+//! A server render that should run under a strict CSP gets a fresh
+//! [`CspNonce`] per request, usually through
+//! [`ServerHolder::csp`](crate::web::holders::ServerHolder::csp), which also
+//! writes the matching `Content-Security-Policy` header built by
+//! [`ContentSecurityPolicy`]. The nonce lives in the holder's [`Truck`], and
+//! everything inline the render produces carries it: the document bootstrap,
+//! the streaming patch scripts, the resource hydration payload, the
+//! hot-reload client and every `<script>`/`<style>` element in the tree.
+//!
+//! In the browser, `<script>` and `<style>` elements created after load reuse
+//! the nonce of the page's bootstrap script.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::Truck;
+
+/// Name of the header [`ContentSecurityPolicy::header_value`] is meant for.
+pub const CSP_HEADER: &str = "content-security-policy";
+
+/// Per-request nonce allowing Glory's inline scripts and styles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CspNonce(Arc<str>);
+
+impl CspNonce {
+    /// Wraps a nonce generated elsewhere, e.g. by a proxy that also sets the
+    /// header.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into().into())
+    }
+
+    /// A fresh nonce: 128 random bits, base64 encoded.
+    #[cfg(all(feature = "web-ssr", not(target_arch = "wasm32")))]
+    pub fn generate() -> Self {
+        use base64::Engine;
+
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes).expect("the operating system provides randomness");
+        Self::new(base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    /// The nonce injected into `truck` by its holder, if any.
+    pub fn from_truck(truck: &Truck) -> Option<Self> {
+        truck.obtain::<Self>().ok().cloned()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The policy source allowing this nonce: `'nonce-…'`.
+    pub fn source(&self) -> String {
+        format!("'nonce-{}'", self.0)
+    }
+
+    /// ` nonce="…"`, ready to splice into an inline tag.
+    pub fn html_attr(&self) -> String {
+        let value = self
+            .0
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        format!(r#" nonce="{value}""#)
+    }
+
+    /// [`html_attr`](Self::html_attr), or nothing without a nonce.
+    #[cfg(feature = "web-ssr")]
+    pub(crate) fn attr(nonce: Option<&Self>) -> String {
+        nonce.map(Self::html_attr).unwrap_or_default()
+    }
+
+    /// True for the elements a nonce is written onto.
+    pub(crate) fn applies_to(tag: &str) -> bool {
+        tag.eq_ignore_ascii_case("script") || tag.eq_ignore_ascii_case("style")
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Builder for a `Content-Security-Policy` header matching what Glory emits.
+///
+/// The default policy allows same-origin resources, scripts and styles
+/// carrying the request's nonce, and WebAssembly compilation for the
+/// hydration bundle. With [`strict_dynamic`](Self::strict_dynamic) (the
+/// default) scripts are trusted only through the nonce, and whatever they
+/// load — the wasm-bindgen module the bootstrap imports — inherits that
+/// trust. Style attributes stay allowed since widgets commonly set them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    strict_dynamic: bool,
+    directives: Vec<(String, Vec<String>)>,
+}
+
+impl Default for ContentSecurityPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentSecurityPolicy {
+    pub fn new() -> Self {
+        let directive = |name: &str, sources: &[&str]| (name.to_owned(), sources.iter().map(|source| (*source).to_owned()).collect());
+        Self {
+            strict_dynamic: true,
+            directives: vec![
+                directive("default-src", &["'self'"]),
+                directive("script-src", &[]),
+                directive("style-src", &["'self'"]),
+                directive("style-src-attr", &["'unsafe-inline'"]),
+                directive("img-src", &["'self'", "data:"]),
+                directive("connect-src", &["'self'"]),
+                directive("object-src", &["'none'"]),
+                directive("base-uri", &["'none'"]),
+            ],
+        }
+    }
+
+    /// Trusts scripts through the nonce alone (`'strict-dynamic'`) instead of
+    /// by origin. On by default.
+    pub fn strict_dynamic(mut self, enabled: bool) -> Self {
+        self.strict_dynamic = enabled;
+        self
+    }
+
+    /// Adds `source` to `directive`, creating the directive if needed.
+    pub fn source(mut self, directive: impl AsRef<str>, source: impl Into<String>) -> Self {
+        let directive = directive.as_ref().to_ascii_lowercase();
+        let source = source.into();
+        match self.directives.iter_mut().find(|(name, _)| *name == directive) {
+            Some((_, sources)) if sources.contains(&source) => {}
+            Some((_, sources)) => sources.push(source),
+            None => self.directives.push((directive, vec![source])),
+        }
+        self
+    }
+
+    /// The header value for a response rendered with `nonce`.
+    pub fn header_value(&self, nonce: &CspNonce) -> String {
+        self.directives
+            .iter()
+            .map(|(name, sources)| {
+                let mut all = Vec::with_capacity(sources.len() + 3);
+                match name.as_str() {
+                    "script-src" if self.strict_dynamic => {
+                        all.extend([nonce.source(), "'strict-dynamic'".into(), "'wasm-unsafe-eval'".into()]);
+                    }
+                    "script-src" => all.extend(["'self'".into(), nonce.source(), "'wasm-unsafe-eval'".into()]),
+                    "style-src" => all.push(nonce.source()),
+                    _ => {}
+                }
+                for source in sources {
+                    if !all.contains(source) {
+                        all.push(source.clone());
+                    }
+                }
+                format!("{name} {}", all.join(" "))
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// The nonce the page was served with, read once from its bootstrap script.
+/// Browsers hide the `nonce` attribute after load, so this goes through the
+/// `nonce` property.
+#[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+pub(crate) fn document_nonce() -> Option<String> {
+    thread_local! {
+        static NONCE: std::cell::OnceCell<Option<String>> = const { std::cell::OnceCell::new() };
+    }
+    NONCE.with(|nonce| {
+        nonce
+            .get_or_init(|| {
+                let script = crate::web::document().query_selector("script[nonce]").ok().flatten()?;
+                js_sys::Reflect::get(&script, &wasm_bindgen::JsValue::from_str("nonce"))
+                    .ok()?
+                    .as_string()
+                    .filter(|value| !value.is_empty())
+            })
+            .clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_dynamic_policy_trusts_scripts_through_the_nonce() {
+        let nonce = CspNonce::new("abc");
+        let header = ContentSecurityPolicy::new().header_value(&nonce);
+        assert!(header.contains("script-src 'nonce-abc' 'strict-dynamic' 'wasm-unsafe-eval'"), "{header}");
+        assert!(header.contains("style-src 'nonce-abc' 'self'"), "{header}");
+        assert!(header.contains("object-src 'none'"));
+        assert!(header.contains("base-uri 'none'"));
+    }
+
+    #[test]
+    fn origin_policy_and_extra_sources() {
+        let nonce = CspNonce::new("abc");
+        let header = ContentSecurityPolicy::new()
+            .strict_dynamic(false)
+            .source("script-src", "https://cdn.example")
+            .source("Connect-Src", "wss://live.example")
+            .source("frame-ancestors", "'none'")
+            .header_value(&nonce);
+        assert!(
+            header.contains("script-src 'self' 'nonce-abc' 'wasm-unsafe-eval' https://cdn.example"),
+            "{header}"
+        );
+        assert!(header.contains("connect-src 'self' wss://live.example"), "{header}");
+        assert!(header.ends_with("frame-ancestors 'none'"), "{header}");
+    }
+
+    #[cfg(feature = "web-ssr")]
+    #[test]
+    fn nonce_attr_is_escaped() {
+        assert_eq!(CspNonce::attr(Some(&CspNonce::new(r#"a"b"#))), r#" nonce="a&quot;b""#);
+        assert_eq!(CspNonce::attr(None), "");
+    }
+
+    #[cfg(all(feature = "web-ssr", not(target_arch = "wasm32")))]
+    #[test]
+    fn generated_nonces_are_unique() {
+        let first = CspNonce::generate();
+        assert_eq!(first.as_str().len(), 24);
+        assert_ne!(first, CspNonce::generate());
+    }
+}
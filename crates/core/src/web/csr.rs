@@ -38,6 +38,20 @@ pub fn take_hydrated_resource<T>(token: &str) -> Option<T>
 where
     T: serde::de::DeserializeOwned,
 {
+    hydrated_json(token, true).and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// Reads a server-recorded value for `token` without consuming it, so every
+/// [`Scope::hydratable_cage`](crate::Scope::hydratable_cage) sharing a key
+/// adopts it. `Some(Err(_))` when the payload does not deserialize as `T`.
+pub fn hydrated_value<T>(token: &str) -> Option<Result<T, serde_json::Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    hydrated_json(token, false).map(|json| serde_json::from_str(&json))
+}
+
+fn hydrated_json(token: &str, take: bool) -> Option<String> {
     let window = window();
     let store = js_sys::Reflect::get(&window, &JsValue::from_str("__gloryResource")).ok()?;
     let store: &js_sys::Object = store.dyn_ref()?;
@@ -46,7 +60,8 @@ where
     if value.is_undefined() {
         return None;
     }
-    let _ = js_sys::Reflect::delete_property(store, &key);
-    let json = js_sys::JSON::stringify(&value).ok()?.as_string()?;
-    serde_json::from_str(&json).ok()
+    if take {
+        let _ = js_sys::Reflect::delete_property(store, &key);
+    }
+    js_sys::JSON::stringify(&value).ok()?.as_string()
 }
@@ -57,6 +57,12 @@ impl CommandHolder {
         }
     }
 
+    /// The id every view of this holder's tree carries, see
+    /// [`Scope::holder_id`].
+    pub fn id(&self) -> HolderId {
+        self.id
+    }
+
     pub fn renderer(&self) -> &CommandRenderer {
         &self.renderer
     }
@@ -0,0 +1,204 @@
+//! Client entry for pages built from [`Island`](crate::widgets::Island)s.
+//!
+//! Instead of mounting the whole app like [`BrowserHolder`](super::BrowserHolder),
+//! [`Islands`] looks for the `<glory-island>` wrappers the server rendered,
+//! deserializes each island's widget from its props and hydrates only that
+//! subtree, on the schedule the island asked for. All islands booted by one
+//! registry share its [`Truck`], which is how they share context.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+
+use crate::view::VIEW_ID_DELIMITER;
+use crate::widgets::island::{ISLAND_LOAD_ATTR, ISLAND_NAME_ATTR, ISLAND_PROPS_ATTR, IslandLoad, IslandWidget};
+use crate::{Scope, Truck, ViewId};
+
+/// Set on a wrapper once its island has been scheduled, so booting twice is
+/// harmless.
+const ISLAND_BOOTED_ATTR: &str = "data-glory-booted";
+
+type IslandMount = Box<dyn Fn(Scope, &web_sys::Element, &str) -> Result<(), serde_json::Error>>;
+
+struct Registry {
+    truck: Rc<RefCell<Truck>>,
+    mounts: HashMap<&'static str, IslandMount>,
+}
+
+/// Registry of the island widgets a client bundle can boot.
+///
+/// ```ignore
+/// Islands::new().register::<Counter>().register::<SearchBox>().hydrate();
+/// ```
+pub struct Islands {
+    registry: Registry,
+}
+
+impl Default for Islands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Islands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Islands")
+            .field("islands", &self.registry.mounts.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Islands {
+    pub fn new() -> Self {
+        Self::with_truck(Default::default())
+    }
+
+    /// Boots islands against an existing truck, e.g. one shared with a
+    /// [`BrowserHolder`](super::BrowserHolder).
+    pub fn with_truck(truck: Rc<RefCell<Truck>>) -> Self {
+        Self {
+            registry: Registry {
+                truck,
+                mounts: HashMap::new(),
+            },
+        }
+    }
+
+    /// The truck every island is mounted with. Inject shared context here
+    /// before calling [`hydrate`](Self::hydrate).
+    pub fn truck(&self) -> Rc<RefCell<Truck>> {
+        self.registry.truck.clone()
+    }
+
+    /// Makes `W` bootable under its [`IslandWidget::NAME`].
+    pub fn register<W: IslandWidget>(mut self) -> Self {
+        self.registry.mounts.insert(
+            W::NAME,
+            Box::new(|scope, host, props| {
+                let widget = serde_json::from_str::<W>(props)?;
+                widget.mount_to(scope, host);
+                Ok(())
+            }),
+        );
+        self
+    }
+
+    /// Schedules every top-level island in the document.
+    pub fn hydrate(self) {
+        if let Some(root) = crate::web::document().document_element() {
+            self.hydrate_in(&root);
+        }
+    }
+
+    /// Schedules every top-level island below `root`. Islands nested inside
+    /// another island are hydrated as part of their parent.
+    pub fn hydrate_in(self, root: &web_sys::Element) {
+        let registry = Rc::new(self.registry);
+        let Ok(list) = root.query_selector_all(&format!("[{ISLAND_NAME_ATTR}]")) else {
+            return;
+        };
+        for i in 0..list.length() {
+            let island = list.item(i).unwrap_throw().unchecked_into::<web_sys::Element>();
+            let nested = island
+                .parent_element()
+                .and_then(|parent| parent.closest(&format!("[{ISLAND_NAME_ATTR}]")).ok().flatten())
+                .is_some();
+            if nested || island.has_attribute(ISLAND_BOOTED_ATTR) {
+                continue;
+            }
+            island.set_attribute(ISLAND_BOOTED_ATTR, "").unwrap_throw();
+            schedule(registry.clone(), island);
+        }
+    }
+}
+
+fn schedule(registry: Rc<Registry>, island: web_sys::Element) {
+    let load = island
+        .get_attribute(ISLAND_LOAD_ATTR)
+        .map(|value| IslandLoad::parse(&value))
+        .unwrap_or_default();
+    match load {
+        IslandLoad::Eager => boot(&registry, &island),
+        IslandLoad::Idle => {
+            let booted = Rc::new(std::cell::Cell::new(false));
+            crate::web::request_idle_callback(move || {
+                if !booted.replace(true) {
+                    boot(&registry, &island);
+                }
+            });
+        }
+        IslandLoad::Visible => {
+            let target = island.clone();
+            let callback = Closure::wrap(Box::new(move |entries: js_sys::Array, observer: web_sys::IntersectionObserver| {
+                let visible = entries
+                    .iter()
+                    .any(|entry| entry.unchecked_into::<web_sys::IntersectionObserverEntry>().is_intersecting());
+                if visible {
+                    observer.disconnect();
+                    boot(&registry, &island);
+                }
+            }) as Box<dyn FnMut(js_sys::Array, web_sys::IntersectionObserver)>);
+            let observer = web_sys::IntersectionObserver::new(callback.as_ref().unchecked_ref()).unwrap_throw();
+            observer.observe(&target);
+            let _ = callback.into_js_value();
+        }
+    }
+}
+
+/// Hydrates one island: only the `gly-id` nodes inside its wrapper are
+/// claimed, and whatever the island did not re-create is dropped.
+fn boot(registry: &Registry, island: &web_sys::Element) {
+    let name = island.get_attribute(ISLAND_NAME_ATTR).unwrap_or_default();
+    let Some(mount) = registry.mounts.get(name.as_str()) else {
+        crate::warn!("[islands]: `{name}` is not registered; it stays static");
+        return;
+    };
+    let Some(wrapper_id) = island.get_attribute("gly-id") else {
+        crate::warn!("[islands]: `{name}` was not server rendered");
+        return;
+    };
+    let props = island.get_attribute(ISLAND_PROPS_ATTR).unwrap_or_else(|| "null".into());
+
+    let hydrating = mark_hydrating(island);
+    crate::web::HYDRATING.store(hydrating, Ordering::Relaxed);
+    let view_id = ViewId::new(format!("{wrapper_id}{VIEW_ID_DELIMITER}0"));
+    let result = mount(Scope::new_root(view_id, registry.truck.clone()), island, &props);
+    crate::web::HYDRATING.store(false, Ordering::Relaxed);
+    if let Err(e) = result {
+        crate::error!("[islands]: `{name}` props failed to deserialize: {e}");
+    }
+    if hydrating {
+        finish_hydrating(island);
+    }
+}
+
+fn mark_hydrating(island: &web_sys::Element) -> bool {
+    let Ok(list) = island.query_selector_all("[gly-id]") else {
+        return false;
+    };
+    for i in 0..list.length() {
+        let ele = list.item(i).unwrap_throw().unchecked_into::<web_sys::Element>();
+        ele.set_attribute("gly-hydrating", "1").unwrap_throw();
+    }
+    list.length() > 0
+}
+
+fn finish_hydrating(island: &web_sys::Element) {
+    let Ok(list) = island.query_selector_all("[gly-id]") else {
+        return;
+    };
+    for i in 0..list.length() {
+        let ele = list.item(i).unwrap_throw().unchecked_into::<web_sys::Element>();
+        if ele.has_attribute("gly-hydrating") {
+            crate::debug_warn!("[hydrating]: remove element: {}", ele.outer_html());
+            ele.remove();
+        } else {
+            ele.remove_attribute("gly-id").ok();
+        }
+    }
+}
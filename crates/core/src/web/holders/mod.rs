@@ -2,6 +2,8 @@ cfg_feature! {
     #![all(target_arch = "wasm32", feature = "web-csr")]
     mod browser;
     pub use browser::BrowserHolder;
+    mod islands;
+    pub use islands::Islands;
 }
 
 cfg_feature! {
@@ -10,6 +12,12 @@ cfg_feature! {
     pub use server::*;
 }
 
+cfg_feature! {
+    #![all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32"))]
+    mod render_pool;
+    pub use render_pool::{RenderPool, RenderStream};
+}
+
 cfg_feature! {
     #![all(feature = "backend-command", not(feature = "single-app"))]
     mod command;
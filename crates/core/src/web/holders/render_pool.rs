@@ -0,0 +1,232 @@
+//! Per-request render workers for incremental streaming SSR.
+//!
+//! A [`ServerHolder`] is `!Send` (the reactive runtime holds `Rc`s), so it
+//! cannot ride inside the `Send` body stream a web framework wants. Instead a
+//! [`RenderPool`] builds and renders each holder as a local task on one of its
+//! own threads and hands the adapter a [`RenderStream`] — a `Send` receiver of [`HtmlChunk`]s.
+//! The shell is sent as soon as the holder has mounted; every Suspense patch
+//! follows the moment its boundary resolves (see
+//! [`ServerHolder::new_streaming`]).
+//!
+//! The response head ([`RenderStream::head`]) is frozen when the shell is
+//! flushed, so status codes, headers and redirects set while mounting reach
+//! the adapter before the body starts.
+//!
+//! The channel between worker and response is bounded, so a slow client
+//! applies backpressure to the render, and dropping the [`RenderStream`]
+//! (the client went away) cancels it: the worker drops the holder and every
+//! pending resource future.
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::{FutureExt, Stream, StreamExt};
+
+use super::{HtmlChunk, ServerHolder};
+use crate::stream_ssr::RenderLocals;
+use crate::web::ResponseHead;
+
+/// Chunks a render may run ahead of its client before it waits.
+const DEFAULT_STREAM_BUFFER: usize = 16;
+
+type RenderJob = Box<dyn FnOnce() -> LocalRender + Send + 'static>;
+
+type LocalRender = Pin<Box<dyn Future<Output = ()>>>;
+
+/// A fixed set of threads that each run many [`ServerHolder`] renders as
+/// local tasks.
+///
+/// Render state (the deferral queue, Suspense registrations, captured
+/// resource data) is thread-local, so every render keeps its own and the
+/// worker installs it around each poll. A render waiting on resources
+/// leaves its thread to the others; size the pool for CPU count.
+pub struct RenderPool {
+    workers: Vec<UnboundedSender<RenderJob>>,
+    next: AtomicUsize,
+    buffer: usize,
+}
+
+impl RenderPool {
+    /// Spawns `workers` render threads (at least one).
+    pub fn new(workers: usize) -> Self {
+        let workers = (0..workers.max(1))
+            .map(|index| {
+                let (jobs, receiver) = futures::channel::mpsc::unbounded::<RenderJob>();
+                std::thread::Builder::new()
+                    .name(format!("glory-render-{index}"))
+                    .spawn(move || run_render_worker(receiver))
+                    .expect("glory: failed to spawn render worker");
+                jobs
+            })
+            .collect();
+        Self {
+            workers,
+            next: AtomicUsize::new(0),
+            buffer: DEFAULT_STREAM_BUFFER,
+        }
+    }
+
+    /// Number of chunks a render may produce ahead of the client before it
+    /// is paused (defaults to 16).
+    pub fn buffer(mut self, chunks: usize) -> Self {
+        self.buffer = chunks.max(1);
+        self
+    }
+
+    /// Number of render threads in this pool.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// The process-wide pool the framework adapters render on. Sized by the
+    /// `GLORY_RENDER_WORKERS` environment variable, defaulting to the
+    /// available parallelism (at least four).
+    pub fn global() -> &'static RenderPool {
+        static POOL: OnceLock<RenderPool> = OnceLock::new();
+        POOL.get_or_init(|| RenderPool::new(render_worker_count()))
+    }
+
+    /// Builds a holder with `factory` on a worker thread and streams its
+    /// render back.
+    ///
+    /// When called inside a Tokio runtime, the render runs in that runtime's
+    /// context so resources can use Tokio timers and IO.
+    pub fn render<F>(&self, factory: F) -> RenderStream
+    where
+        F: FnOnce() -> ServerHolder + Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::mpsc::channel(self.buffer);
+        let (head_sender, head) = tokio::sync::oneshot::channel();
+        let runtime = tokio::runtime::Handle::try_current().ok();
+        let job: RenderJob = Box::new(move || {
+            Box::pin(IsolatedRender {
+                runtime,
+                locals: RenderLocals::default(),
+                render: Box::pin(async move {
+                    let holder = factory();
+                    holder.stream_into(head_sender, &sender).await;
+                }),
+            })
+        });
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.workers.len();
+        if self.workers[index].unbounded_send(job).is_err() {
+            tracing::error!("glory render pool has no running workers");
+        }
+        RenderStream {
+            head: Some(head),
+            receiver,
+        }
+    }
+}
+
+impl std::fmt::Debug for RenderPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderPool")
+            .field("workers", &self.workers.len())
+            .field("buffer", &self.buffer)
+            .finish()
+    }
+}
+
+/// The `Send` side of a worker render: yields each [`HtmlChunk`] as the
+/// worker produces it. Dropping it cancels the render.
+#[derive(Debug)]
+pub struct RenderStream {
+    head: Option<tokio::sync::oneshot::Receiver<ResponseHead>>,
+    receiver: tokio::sync::mpsc::Receiver<HtmlChunk>,
+}
+
+impl RenderStream {
+    /// The status and headers the tree set before its shell was flushed.
+    /// Await this before building the response; a render that fails before
+    /// flushing answers `500`. Later calls return the default head.
+    pub async fn head(&mut self) -> ResponseHead {
+        match self.head.take() {
+            Some(head) => head.await.unwrap_or(ResponseHead {
+                status: 500,
+                headers: Vec::new(),
+            }),
+            None => ResponseHead::default(),
+        }
+    }
+
+    /// The same stream serialized to HTML strings, ready for a response body.
+    pub fn html(self) -> impl Stream<Item = String> + Send + 'static {
+        futures::StreamExt::map(self, HtmlChunk::into_string)
+    }
+}
+
+impl Stream for RenderStream {
+    type Item = HtmlChunk;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+fn render_worker_count() -> usize {
+    std::env::var("GLORY_RENDER_WORKERS")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|value| value.get()).unwrap_or(1).max(4))
+}
+
+fn run_render_worker(mut jobs: UnboundedReceiver<RenderJob>) {
+    use futures::task::LocalSpawnExt;
+
+    let mut pool = futures::executor::LocalPool::new();
+    let spawner = pool.spawner();
+    let renders = spawner.clone();
+    spawner
+        .spawn_local(async move {
+            while let Some(job) = jobs.next().await {
+                // A panicking widget ends its own response (the stream just
+                // closes); it must not take the worker down with it.
+                let render = AssertUnwindSafe(job()).catch_unwind().map(|result| {
+                    if result.is_err() {
+                        tracing::error!("glory render worker recovered from a panicking render");
+                    }
+                });
+                if renders.spawn_local(render).is_err() {
+                    break;
+                }
+            }
+        })
+        .expect("glory: failed to spawn render dispatcher");
+    pool.run();
+}
+
+/// A render polled with its own thread-local render state and inside the
+/// runtime it was started from.
+struct IsolatedRender {
+    runtime: Option<tokio::runtime::Handle>,
+    locals: RenderLocals,
+    render: LocalRender,
+}
+
+impl Future for IsolatedRender {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let _runtime = this.runtime.as_ref().map(tokio::runtime::Handle::enter);
+        let _locals = this.locals.install();
+        this.render.as_mut().poll(cx)
+    }
+}
+
+impl Drop for IsolatedRender {
+    fn drop(&mut self) {
+        // A render dropped unfinished cleans up its own state, not the
+        // thread's.
+        let _runtime = self.runtime.as_ref().map(tokio::runtime::Handle::enter);
+        let _locals = self.locals.install();
+        self.render = Box::pin(async {});
+    }
+}
@@ -1,15 +1,29 @@
 use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
+#[cfg(not(target_arch = "wasm32"))]
+use indexmap::IndexSet;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::BoundaryError;
 use crate::config::GloryConfig;
 use crate::reflow::scheduler::{BATCHING, RUNNING};
 use crate::reflow::{PENDING_ITEMS, REVISING_ITEMS};
-use crate::renderer::CommandQueue;
-use crate::renderer::ssr_dom::SsrDocument;
+use crate::renderer::ssr_dom::{NODE_ID_ATTRIBUTE, SsrDocument};
+use crate::renderer::{Command, CommandInsertPosition, CommandQueue};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::ssr_dom::SsrNode;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::web::ResponseHead;
 use crate::web::widgets::*;
+use crate::web::{CSP_HEADER, ContentSecurityPolicy, CspNonce, ServerResponseOptions};
 use crate::{Holder, HolderId, ROOT_VIEWS, Scope, Truck, ViewId, Widget};
 
 const DEPOT_URL_KEY: &str = "glory::url";
@@ -27,6 +41,16 @@ pub struct ServerHolder {
     /// resources defer instead of blocking, so the shell flushes with
     /// fallbacks, then resolved bodies stream in as out-of-order patches.
     streaming: bool,
+    /// Status, headers and redirect written by the tree; also injected into
+    /// the truck so widgets can reach it.
+    response: ServerResponseOptions,
+    /// How long an incremental stream waits for deferred resources before
+    /// flushing the remaining fallbacks; `None` waits indefinitely.
+    #[cfg(not(target_arch = "wasm32"))]
+    stream_timeout: Option<Duration>,
+    /// Whether rendered elements carry their command-stream id, see
+    /// [`node_ids`](Self::node_ids).
+    node_ids: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -34,8 +58,34 @@ pub enum HtmlChunk {
     DocumentStart(String),
     BodyOpen(String),
     App(String),
-    Placeholder { id: String, fallback_html: String },
-    PlaceholderPatch { id: String, html: String },
+    Placeholder {
+        id: String,
+        fallback_html: String,
+    },
+    PlaceholderPatch {
+        id: String,
+        html: String,
+        /// Nonce for the inline script that swaps the patch in.
+        nonce: Option<CspNonce>,
+    },
+    /// A boundary whose enclosing `ErrorBoundary` failed after the shell was
+    /// sent: `html` is that boundary's fallback (empty when another
+    /// placeholder already carried it) and `error` is handed to the client so
+    /// hydration keeps the fallback.
+    PlaceholderError {
+        id: String,
+        html: String,
+        boundary: String,
+        error: BoundaryError,
+        nonce: Option<CspNonce>,
+    },
+    /// A boundary still pending when the stream timeout elapsed; `html` is its
+    /// fallback.
+    PlaceholderTimeout {
+        id: String,
+        html: String,
+        nonce: Option<CspNonce>,
+    },
     DocumentEnd(&'static str),
 }
 
@@ -46,18 +96,58 @@ impl HtmlChunk {
             HtmlChunk::Placeholder { id, fallback_html } => {
                 format!(r#"<template data-glory-placeholder="{}">{}</template>"#, escape_html_attr(&id), fallback_html)
             }
-            HtmlChunk::PlaceholderPatch { id, html } => {
-                let id_attr = escape_html_attr(&id);
-                let id_json = serde_json::to_string(&id).expect("placeholder id can always be encoded as JSON");
-                format!(
-                    r#"<template data-glory-placeholder-patch="{id_attr}">{html}</template><script>window.__gloryStreamHydrate&&window.__gloryStreamHydrate.patchFromTemplate({id_json});</script>"#
-                )
+            HtmlChunk::PlaceholderPatch { id, html, nonce } => placeholder_patch(&id, "", &html, nonce.as_ref()),
+            HtmlChunk::PlaceholderError {
+                id,
+                html,
+                boundary,
+                error,
+                nonce,
+            } => {
+                let error = serde_json::to_string(&error).expect("boundary error serializes");
+                let attrs = format!(
+                    r#" data-glory-state="error" data-glory-error-boundary="{}" data-glory-error="{}""#,
+                    escape_html_attr(&boundary),
+                    escape_html_attr(&error)
+                );
+                placeholder_patch(&id, &attrs, &html, nonce.as_ref())
             }
+            HtmlChunk::PlaceholderTimeout { id, html, nonce } => placeholder_patch(&id, r#" data-glory-state="timeout""#, &html, nonce.as_ref()),
             HtmlChunk::DocumentEnd(value) => value.to_owned(),
         }
     }
 }
 
+/// `<template data-glory-placeholder-patch>` plus the script asking the
+/// stream-hydrate runtime to swap it in; `attrs` describe non-resolved states.
+fn placeholder_patch(id: &str, attrs: &str, html: &str, nonce: Option<&CspNonce>) -> String {
+    let id_attr = escape_html_attr(id);
+    let id_json = serde_json::to_string(id).expect("placeholder id can always be encoded as JSON");
+    let nonce = CspNonce::attr(nonce);
+    format!(
+        r#"<template data-glory-placeholder-patch="{id_attr}"{attrs}>{html}</template><script{nonce}>window.__gloryStreamHydrate&&window.__gloryStreamHydrate.patchFromTemplate({id_json});</script>"#
+    )
+}
+
+/// The node id field of a command addressing a single node.
+fn command_target(command: &mut Command) -> Option<&mut u64> {
+    match command {
+        Command::Create { id, .. }
+        | Command::SetAttribute { id, .. }
+        | Command::RemoveAttribute { id, .. }
+        | Command::SetProperty { id, .. }
+        | Command::RemoveProperty { id, .. }
+        | Command::AddClass { id, .. }
+        | Command::RemoveClass { id, .. }
+        | Command::SetText { id, .. }
+        | Command::SetHtml { id, .. }
+        | Command::AttachEvent { id, .. }
+        | Command::DetachEvent { id, .. }
+        | Command::Query { id, .. } => Some(id),
+        Command::Insert { .. } | Command::Remove { .. } => None,
+    }
+}
+
 fn escape_html_attr(value: &str) -> String {
     value
         .replace('&', "&amp;")
@@ -83,8 +173,14 @@ impl ServerHolder {
     }
 
     fn with_streaming(config: impl Into<Arc<GloryConfig>>, url: impl Into<String>, streaming: bool) -> Self {
+        let config = config.into();
         let mut truck = Truck::new();
         truck.insert(DEPOT_URL_KEY, url.into());
+        let response = ServerResponseOptions::default();
+        truck.inject(response.clone());
+        if let Some(nonce) = &config.csp_nonce {
+            truck.inject(CspNonce::new(nonce.clone()));
+        }
         let queue = CommandQueue::new();
         let host_node = {
             let _guard = queue.make_current();
@@ -94,19 +190,129 @@ impl ServerHolder {
         crate::renderer::command::register_holder_queue(id, queue.clone());
         Self {
             id,
-            config: config.into(),
+            config,
             truck: Rc::new(RefCell::new(truck)),
             host_node,
             queue,
             next_root_view_id: AtomicU64::new(0),
             streaming,
+            response,
+            #[cfg(not(target_arch = "wasm32"))]
+            stream_timeout: None,
+            node_ids: false,
+        }
+    }
+
+    /// Caps how long an incremental stream (see
+    /// [`RenderPool`](super::RenderPool)) waits on deferred resources. When it
+    /// elapses, every boundary still pending is patched with its fallback as a
+    /// [`HtmlChunk::PlaceholderTimeout`] and the document is closed, so the
+    /// client finishes loading and fetches the rest itself.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stream_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_timeout = Some(timeout);
+        self
+    }
+
+    /// Renders under a Content Security Policy: gives this request a fresh
+    /// [`CspNonce`] (unless one was configured), puts it on every inline
+    /// script and style the render emits, and sets the matching
+    /// `Content-Security-Policy` response header. Call before mounting.
+    ///
+    /// While `glory serve` is watching, the hot-reload websocket is allowed
+    /// too.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn csp(self, mut policy: ContentSecurityPolicy) -> Self {
+        let nonce = self.csp_nonce().unwrap_or_else(|| {
+            let nonce = CspNonce::generate();
+            self.truck.borrow_mut().inject(nonce.clone());
+            nonce
+        });
+        if std::env::var("GLORY_WATCH").is_ok() {
+            policy = policy.source("connect-src", "ws:").source("connect-src", "wss:");
+        }
+        if let Err(e) = self.response.insert_header(CSP_HEADER, policy.header_value(&nonce)) {
+            crate::warn!("[csp]: {e}");
         }
+        self
+    }
+
+    /// Renders every element with a `data-glory-id` attribute holding its
+    /// node id, numbered as in [`host_relative_commands`]. A command-stream
+    /// client (such as a LiveView page upgrading from this render) adopts the
+    /// elements by that id instead of building them again.
+    ///
+    /// [`host_relative_commands`]: Self::host_relative_commands
+    pub fn node_ids(mut self) -> Self {
+        self.node_ids = true;
+        self
+    }
+
+    /// The recorded command stream, renumbered so the host node is the
+    /// reserved root `0`. That is how a [`CommandHolder`] numbers the same
+    /// tree, so for a widget that renders the same way in both holders the
+    /// ids match the ones a live session assigns.
+    ///
+    /// [`CommandHolder`]: super::CommandHolder
+    pub fn host_relative_commands(&self) -> Vec<Command> {
+        let host = self.host_node.node().id();
+        let rebase = |id: u64| id.checked_sub(host);
+        self.queue
+            .commands()
+            .into_iter()
+            .filter_map(|command| {
+                Some(match command {
+                    Command::Insert { parent, child, position } => Command::Insert {
+                        parent: rebase(parent)?,
+                        child: rebase(child).filter(|child| *child != 0)?,
+                        position: match position {
+                            CommandInsertPosition::Before(anchor) => CommandInsertPosition::Before(rebase(anchor)?),
+                            CommandInsertPosition::After(anchor) => CommandInsertPosition::After(rebase(anchor)?),
+                            position => position,
+                        },
+                    },
+                    Command::Remove { parent, child } => Command::Remove {
+                        parent: rebase(parent)?,
+                        child: rebase(child).filter(|child| *child != 0)?,
+                    },
+                    mut command => {
+                        let id = command_target(&mut command)?;
+                        *id = rebase(*id).filter(|id| *id != 0)?;
+                        command
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The nonce inline scripts and styles of this render carry, if any.
+    pub fn csp_nonce(&self) -> Option<CspNonce> {
+        CspNonce::from_truck(&self.truck.borrow())
+    }
+
+    /// The response options widgets wrote during mount; adapters answer with
+    /// its [`head`](ServerResponseOptions::head).
+    pub fn response_options(&self) -> ServerResponseOptions {
+        self.response.clone()
     }
 
     /// Replays the recorded command stream into the legacy-exact SSR tree.
     /// Non-draining: rendering is repeatable.
     pub fn replay(&self) -> SsrDocument {
-        SsrDocument::replay(&self.queue.commands())
+        let commands = self.queue.commands();
+        let document = SsrDocument::replay(&commands);
+        if self.node_ids {
+            let host = self.host_node.node().id();
+            for command in &commands {
+                if let Command::Create { id, .. } = command
+                    && *id > host
+                    && let Some(node) = document.node(*id)
+                {
+                    node.set_attribute(NODE_ID_ATTRIBUTE, (id - host).to_string());
+                }
+            }
+        }
+        document
     }
 
     /// Rendered HTML of the mounted app subtree (what becomes the
@@ -121,7 +327,7 @@ impl ServerHolder {
         let mut chunks = vec![HtmlChunk::DocumentStart(head), HtmlChunk::BodyOpen(mid)];
         chunks.extend(document.inner_html_chunks(self.host_node.node().id()).into_iter().map(HtmlChunk::App));
         #[cfg(not(target_arch = "wasm32"))]
-        chunks.extend(resource_hydration_chunk());
+        chunks.extend(resource_hydration_chunk(self.csp_nonce().as_ref()));
         chunks.push(HtmlChunk::DocumentEnd(tail));
         chunks
     }
@@ -139,77 +345,171 @@ impl ServerHolder {
 
     /// Streaming SSR pipeline: serialize the shell with pending Suspense
     /// boundaries collapsed to `<template data-glory-placeholder>` markers,
-    /// drive the deferred async resources to completion, then emit a
-    /// `PlaceholderPatch` carrying each resolved body.
+    /// drive the deferred async resources to completion, then emit a patch
+    /// for each marker, parents before the boundaries nested inside them.
     #[cfg(not(target_arch = "wasm32"))]
     fn streaming_chunks(&self) -> Vec<HtmlChunk> {
-        use std::collections::HashSet;
-
-        use crate::renderer::ssr_dom::SsrNode;
-
-        // Disarm streaming and collect the boundaries registered during mount.
-        let boundaries = crate::stream_ssr::finish();
-        // Pending *before* draining = the boundaries that need a placeholder
-        // now and a streamed patch later.
-        let pending: HashSet<String> = boundaries
-            .iter()
-            .filter(|registration| registration.boundary.pending_count() > 0)
-            .map(|registration| registration.placeholder_id.clone())
-            .collect();
-
-        let host_id = self.host_node.node().id();
-        let initial_doc = self.replay();
-
-        let pending_for_initial = pending.clone();
-        let initial_replace = move |node: &SsrNode| -> Option<String> {
-            let id = node.get_attribute("data-glory-suspense")?;
-            let inner = node.inner_html_with(&unwrap_suspense);
-            if pending_for_initial.contains(&id) {
-                Some(format!(
-                    r#"<template data-glory-placeholder="{}">{}</template>"#,
-                    escape_html_attr(&id),
-                    inner
-                ))
-            } else {
-                // Resolved synchronously (no async work) — unwrap inline.
-                Some(inner)
-            }
-        };
-
-        let (head, mid, tail) = crate::web::utils::html_parts_separated(&self.config, &self.truck.borrow(), &initial_doc);
-        let mut chunks = vec![HtmlChunk::DocumentStart(head), HtmlChunk::BodyOpen(mid)];
-        chunks.extend(initial_doc.inner_html_chunks_with(host_id, &initial_replace).into_iter().map(HtmlChunk::App));
+        let mut state = StreamState::take(self.csp_nonce());
+        let (mut chunks, tail) = self.shell_chunks(&mut state);
 
         // Resolve the deferred async resources; Suspense boundaries flip to
         // their bodies as each resource commits.
         crate::spawn::drive_deferred();
         crate::spawn::end_deferred(None);
 
-        if !pending.is_empty() {
-            let resolved_doc = self.replay();
-            for registration in &boundaries {
-                if !pending.contains(&registration.placeholder_id) {
-                    continue;
-                }
-                let html = resolved_doc
-                    .node(registration.wrapper_id)
-                    .map(|node| node.inner_html_with(&unwrap_suspense))
-                    .unwrap_or_default();
-                chunks.push(HtmlChunk::PlaceholderPatch {
-                    id: registration.placeholder_id.clone(),
-                    html,
-                });
-            }
+        if state.has_open() {
+            let document = self.replay();
+            chunks.extend(state.settle(&document));
+            chunks.extend(state.finish(&document));
         }
 
         // Emit the resolved-resource payload after draining so streamed
         // hydratable resources are included.
-        chunks.extend(resource_hydration_chunk());
+        chunks.extend(resource_hydration_chunk(self.csp_nonce().as_ref()));
 
         chunks.push(HtmlChunk::DocumentEnd(tail));
         chunks
     }
 
+    /// Head, body-open and app shell chunks, with every pending boundary
+    /// collapsed to its placeholder marker. Returns the document tail too.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn shell_chunks(&self, state: &mut StreamState) -> (Vec<HtmlChunk>, &'static str) {
+        let document = self.replay();
+        let markers = RefCell::new(Vec::new());
+        let replace = |node: &SsrNode| state.render_node(node, Some(&markers));
+        let (head, mid, tail) = crate::web::utils::html_parts_separated(&self.config, &self.truck.borrow(), &document);
+        let mut chunks = vec![HtmlChunk::DocumentStart(head), HtmlChunk::BodyOpen(mid)];
+        chunks.extend(
+            document
+                .inner_html_chunks_with(self.host_node.node().id(), &replace)
+                .into_iter()
+                .map(HtmlChunk::App),
+        );
+        state.open.extend(markers.into_inner());
+        (chunks, tail)
+    }
+
+    /// Incremental counterpart of [`render_stream`](Self::render_stream) used
+    /// by the [`RenderPool`](super::RenderPool) workers: the shell goes out
+    /// first, then deferred resources are polled *concurrently* and each
+    /// Suspense boundary's patch is sent the moment its last resource
+    /// commits, rather than after every boundary has resolved.
+    ///
+    /// The response head is frozen and sent on `head` right before the first
+    /// chunk. `sender` is bounded, so a slow client backs the render up
+    /// instead of buffering it. Returns `false` as soon as the receiving side
+    /// is gone; pending resource futures are dropped with it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) async fn stream_into(&self, head: tokio::sync::oneshot::Sender<ResponseHead>, sender: &tokio::sync::mpsc::Sender<HtmlChunk>) -> bool {
+        use std::future::Future;
+        use std::task::Poll;
+
+        use futures::StreamExt;
+        use futures::stream::FuturesUnordered;
+
+        if !self.streaming {
+            let chunks = self.html_chunks();
+            let _ = head.send(self.response.flush_head());
+            for chunk in chunks {
+                if sender.send(chunk).await.is_err() {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        // Disarms deferral even when the client disconnects mid-render.
+        struct DisarmDeferred;
+        impl Drop for DisarmDeferred {
+            fn drop(&mut self) {
+                crate::spawn::end_deferred(None);
+            }
+        }
+        let _disarm = DisarmDeferred;
+
+        let mut state = StreamState::take(self.csp_nonce());
+        let (shell, tail) = self.shell_chunks(&mut state);
+        let _ = head.send(self.response.flush_head());
+        for chunk in shell {
+            if sender.send(chunk).await.is_err() {
+                return false;
+            }
+        }
+
+        let closed = sender.closed();
+        futures::pin_mut!(closed);
+        let deadline = stream_deadline(self.stream_timeout);
+        futures::pin_mut!(deadline);
+        let mut tasks = FuturesUnordered::new();
+        let mut timed_out = false;
+        loop {
+            tasks.extend(crate::spawn::take_deferred());
+
+            if state.has_open() {
+                let document = self.replay();
+                for chunk in state.settle(&document) {
+                    if sender.send(chunk).await.is_err() {
+                        return false;
+                    }
+                }
+            }
+
+            if tasks.is_empty() {
+                break;
+            }
+            // Wake on a finished resource, on freshly deferred work (a
+            // resource spawning a waterfall mid-flight), on disconnect or when
+            // the stream timeout elapses.
+            let wake = futures::future::poll_fn(|cx| {
+                if closed.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(Wake::Closed);
+                }
+                if tasks.poll_next_unpin(cx).is_ready() || crate::spawn::has_deferred() {
+                    return Poll::Ready(Wake::Progress);
+                }
+                if deadline.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(Wake::TimedOut);
+                }
+                Poll::Pending
+            })
+            .await;
+            match wake {
+                Wake::Closed => return false,
+                Wake::Progress => {}
+                Wake::TimedOut => {
+                    timed_out = true;
+                    break;
+                }
+            }
+        }
+        // Out of time: abandon the outstanding resources and let the client
+        // take over from the fallbacks.
+        drop(tasks);
+
+        // Boundaries still open get their final markup so the client is not
+        // left waiting on a marker.
+        if state.has_open() {
+            let document = self.replay();
+            let chunks = if timed_out { state.time_out(&document) } else { state.finish(&document) };
+            for chunk in chunks {
+                if sender.send(chunk).await.is_err() {
+                    return false;
+                }
+            }
+        }
+
+        for chunk in resource_hydration_chunk(self.csp_nonce().as_ref())
+            .into_iter()
+            .chain([HtmlChunk::DocumentEnd(tail)])
+        {
+            if sender.send(chunk).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
     pub fn render_stream(&self) -> futures::stream::Iter<std::vec::IntoIter<HtmlChunk>> {
         futures::stream::iter(self.rendered_chunks())
     }
@@ -219,20 +519,219 @@ impl ServerHolder {
     }
 }
 
-/// Recursively unwraps streaming Suspense wrappers (`data-glory-suspense`
-/// nodes) into their children, so serialized output never leaks the internal
-/// `<glory-suspense>` element.
+/// Why the incremental stream loop woke up.
+#[cfg(not(target_arch = "wasm32"))]
+enum Wake {
+    Closed,
+    Progress,
+    TimedOut,
+}
+
+/// Resolves once `timeout` has elapsed, never when it is `None`. Render
+/// workers without a tokio runtime fall back to a timer thread.
+#[cfg(not(target_arch = "wasm32"))]
+fn stream_deadline(timeout: Option<Duration>) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    let Some(timeout) = timeout else {
+        return Box::pin(futures::future::pending());
+    };
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Box::pin(tokio::time::sleep(timeout));
+    }
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        let _ = sender.send(());
+    });
+    Box::pin(async move {
+        let _ = receiver.await;
+    })
+}
+
+/// Streaming bookkeeping for one render: the boundaries registered during
+/// mount and the placeholder markers the client holds but has not had patched
+/// yet. A nested boundary only gets a marker once the patch of the boundary
+/// around it is rendered, so patches always reach the client parent first.
 #[cfg(not(target_arch = "wasm32"))]
-fn unwrap_suspense(node: &crate::renderer::ssr_dom::SsrNode) -> Option<String> {
-    node.get_attribute("data-glory-suspense")?;
-    Some(node.inner_html_with(&unwrap_suspense))
+struct StreamState {
+    boundaries: Vec<crate::stream_ssr::BoundaryRegistration>,
+    error_boundaries: Vec<crate::stream_ssr::ErrorBoundaryRegistration>,
+    /// Markers sent and not yet patched, in the order they were sent.
+    open: IndexSet<String>,
+    /// Error boundaries whose fallback has already been streamed.
+    streamed_errors: HashSet<ViewId>,
+    nonce: Option<CspNonce>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StreamState {
+    /// Disarms streaming and collects what was registered during mount.
+    fn take(nonce: Option<CspNonce>) -> Self {
+        let registrations = crate::stream_ssr::finish();
+        Self {
+            boundaries: registrations.boundaries,
+            error_boundaries: registrations.error_boundaries,
+            open: IndexSet::new(),
+            streamed_errors: HashSet::new(),
+            nonce,
+        }
+    }
+
+    fn has_open(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    /// Serializes a streaming wrapper node so the internal elements never
+    /// reach the page. With `markers`, a still-pending Suspense collapses to
+    /// its placeholder marker and its id is recorded; without, boundaries
+    /// render inline as they currently stand.
+    fn render_node(&self, node: &SsrNode, markers: Option<&RefCell<Vec<String>>>) -> Option<String> {
+        let inner = || node.inner_html_with(&|child| self.render_node(child, markers));
+        if node.get_attribute("data-glory-error-boundary").is_some() {
+            return Some(inner());
+        }
+        let id = node.get_attribute("data-glory-suspense")?;
+        let html = inner();
+        let pending = self.boundary(&id).is_some_and(|registration| registration.boundary.pending_count() > 0);
+        match markers {
+            Some(markers) if pending => {
+                markers.borrow_mut().push(id.clone());
+                Some(format!(
+                    r#"<template data-glory-placeholder="{}">{html}</template>"#,
+                    escape_html_attr(&id)
+                ))
+            }
+            _ => Some(html),
+        }
+    }
+
+    /// Children of the wrapper `wrapper_id`, recording nested markers as open
+    /// when `live`.
+    fn render_wrapper(&mut self, document: &SsrDocument, wrapper_id: u64, live: bool) -> String {
+        let markers = RefCell::new(Vec::new());
+        let html = document
+            .node(wrapper_id)
+            .map(|node| node.inner_html_with(&|child| self.render_node(child, live.then_some(&markers))))
+            .unwrap_or_default();
+        self.open.extend(markers.into_inner());
+        html
+    }
+
+    fn boundary(&self, id: &str) -> Option<&crate::stream_ssr::BoundaryRegistration> {
+        self.boundaries.iter().find(|registration| registration.placeholder_id == id)
+    }
+
+    fn error_boundary(&self, view_id: &ViewId) -> Option<&crate::stream_ssr::ErrorBoundaryRegistration> {
+        self.error_boundaries.iter().find(|registration| &registration.view_id == view_id)
+    }
+
+    /// The outermost failed `ErrorBoundary` around the placeholder `id`: its
+    /// fallback is what the server tree now shows in the placeholder's place.
+    fn failed_boundary(&self, id: &str) -> Option<(ViewId, BoundaryError)> {
+        let mut next = self.boundary(id)?.error_boundary.clone();
+        let mut failed = None;
+        while let Some(view_id) = next {
+            let registration = self.error_boundary(&view_id)?;
+            if let Some(error) = registration.error.borrow().clone() {
+                failed = Some((view_id, error));
+            }
+            next = registration.parent.clone();
+        }
+        failed
+    }
+
+    fn error_patch(&mut self, document: &SsrDocument, id: String, boundary: ViewId, error: BoundaryError) -> HtmlChunk {
+        let html = match self.error_boundary(&boundary).map(|registration| registration.wrapper_id) {
+            Some(wrapper_id) if self.streamed_errors.insert(boundary.clone()) => self.render_wrapper(document, wrapper_id, true),
+            _ => String::new(),
+        };
+        HtmlChunk::PlaceholderError {
+            id,
+            html,
+            boundary: boundary.to_string(),
+            error,
+            nonce: self.nonce.clone(),
+        }
+    }
+
+    /// Patches every open marker that can be settled now: failed boundaries
+    /// stream their `ErrorBoundary` fallback, resolved ones their body. The
+    /// markers a patch introduces are considered in the same pass.
+    fn settle(&mut self, document: &SsrDocument) -> Vec<HtmlChunk> {
+        let mut chunks = Vec::new();
+        loop {
+            let next = self.open.iter().find_map(|id| {
+                if let Some((boundary, error)) = self.failed_boundary(id) {
+                    return Some((id.clone(), Some((boundary, error))));
+                }
+                let resolved = self.boundary(id).is_none_or(|registration| registration.boundary.pending_count() == 0);
+                resolved.then(|| (id.clone(), None))
+            });
+            let Some((id, failure)) = next else {
+                break;
+            };
+            self.open.shift_remove(&id);
+            let chunk = match failure {
+                Some((boundary, error)) => self.error_patch(document, id, boundary, error),
+                None => {
+                    let wrapper_id = self.boundary(&id).map(|registration| registration.wrapper_id);
+                    let html = wrapper_id
+                        .map(|wrapper_id| self.render_wrapper(document, wrapper_id, true))
+                        .unwrap_or_default();
+                    HtmlChunk::PlaceholderPatch {
+                        id,
+                        html,
+                        nonce: self.nonce.clone(),
+                    }
+                }
+            };
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    /// Final markup for every open marker once no work is left: boundaries
+    /// whose resources never committed render inline as they stand.
+    fn finish(&mut self, document: &SsrDocument) -> Vec<HtmlChunk> {
+        let nonce = self.nonce.clone();
+        self.drain(document, |id, html| HtmlChunk::PlaceholderPatch {
+            id,
+            html,
+            nonce: nonce.clone(),
+        })
+    }
+
+    /// Fallbacks for every open marker once the stream timeout elapsed.
+    fn time_out(&mut self, document: &SsrDocument) -> Vec<HtmlChunk> {
+        let nonce = self.nonce.clone();
+        self.drain(document, |id, html| HtmlChunk::PlaceholderTimeout {
+            id,
+            html,
+            nonce: nonce.clone(),
+        })
+    }
+
+    fn drain(&mut self, document: &SsrDocument, patch: impl Fn(String, String) -> HtmlChunk) -> Vec<HtmlChunk> {
+        let mut chunks = Vec::new();
+        while let Some(id) = self.open.shift_remove_index(0) {
+            if let Some((boundary, error)) = self.failed_boundary(&id) {
+                chunks.push(self.error_patch(document, id, boundary, error));
+                continue;
+            }
+            let wrapper_id = self.boundary(&id).map(|registration| registration.wrapper_id);
+            let html = wrapper_id
+                .map(|wrapper_id| self.render_wrapper(document, wrapper_id, false))
+                .unwrap_or_default();
+            chunks.push(patch(id, html));
+        }
+        chunks
+    }
 }
 
 /// Builds the `<script>window.__gloryResource=…</script>` payload chunk from
 /// the resolved [`resource_hydratable_in`](crate::reflow::resource_hydratable_in)
 /// values captured during the render, or `None` when none were recorded.
 #[cfg(not(target_arch = "wasm32"))]
-fn resource_hydration_chunk() -> Option<HtmlChunk> {
+fn resource_hydration_chunk(nonce: Option<&CspNonce>) -> Option<HtmlChunk> {
     let data = crate::stream_ssr::take_resource_data();
     if data.is_empty() {
         return None;
@@ -253,8 +752,9 @@ fn resource_hydration_chunk() -> Option<HtmlChunk> {
 
     // Never let an embedded `</script>` terminate the inline script early.
     let safe = object.replace("</", "<\\/");
+    let nonce = CspNonce::attr(nonce);
     Some(HtmlChunk::App(format!(
-        r#"<script>window.__gloryResource=Object.assign(window.__gloryResource||{{}},{safe});</script>"#
+        r#"<script{nonce}>window.__gloryResource=Object.assign(window.__gloryResource||{{}},{safe});</script>"#
     )))
 }
 
@@ -330,8 +830,22 @@ cfg_feature! {
     impl Scribe for ServerHolder {
         fn render(self, res: &mut Response) {
             res.add_header("content-type", "text/html", true).ok();
-            res.status_code(StatusCode::OK);
-            res.stream(self.render_stream().map(|chunk| Result::<_, Infallible>::Ok(chunk.into_string())));
+            let chunks = self.render_stream();
+            apply_response_head(res, self.response.head());
+            res.stream(chunks.map(|chunk| Result::<_, Infallible>::Ok(chunk.into_string())));
+        }
+    }
+
+    /// Writes a [`ResponseHead`](crate::web::ResponseHead) onto a Salvo
+    /// response; header values replace, `set-cookie` values accumulate.
+    pub fn apply_response_head(res: &mut Response, head: crate::web::ResponseHead) {
+        res.status_code(StatusCode::from_u16(head.status).unwrap_or(StatusCode::OK));
+        for (name, value) in head.headers {
+            let overwrite = name != "set-cookie";
+            let added = salvo::http::HeaderName::from_bytes(name.as_bytes()).map(|header| res.add_header(header, value, overwrite));
+            if !matches!(added, Ok(Ok(_))) {
+                crate::warn!("dropping invalid response header {name:?}");
+            }
         }
     }
 
@@ -356,8 +870,15 @@ cfg_feature! {
     #[async_trait]
     impl salvo::Handler for SalvoHandler {
         async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
-            let holder = (self.holder_factory)(self.config.clone(), req.uri().to_string());
-            res.render(holder);
+            // The holder is built and rendered on a render worker so the shell
+            // flushes before any Suspense resource resolves.
+            let factory = self.holder_factory.clone();
+            let config = self.config.clone();
+            let url = req.uri().to_string();
+            let mut stream = super::RenderPool::global().render(move || factory(config, url));
+            res.add_header("content-type", "text/html", true).ok();
+            apply_response_head(res, stream.head().await);
+            res.stream(stream.html().map(Result::<_, Infallible>::Ok));
         }
     }
 }
@@ -367,6 +888,7 @@ mod tests {
     use futures::StreamExt;
 
     use crate::web::widgets::{div, li, ul};
+    use crate::web::holders::{RenderPool, RenderStream};
     use crate::{Holder, Scope, Widget};
 
     use super::*;
@@ -408,6 +930,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn node_ids_number_elements_from_the_host() {
+        let holder = ServerHolder::new(GloryConfig::default(), "/").node_ids().mount(NestedStreamWidget);
+        assert_eq!(
+            holder.app_html(),
+            concat!(
+                r#"<ul data-glory-id="1" gly-id="0-0"><li data-glory-id="2" gly-id="0-0-0">a</li>"#,
+                r#"<li data-glory-id="3" gly-id="0-0-1">b</li><li data-glory-id="4" gly-id="0-0-2">c</li></ul>"#
+            )
+        );
+
+        let commands = holder.host_relative_commands();
+        assert!(matches!(&commands[0], Command::Create { id: 1, name, .. } if name == "ul"));
+        assert!(commands.contains(&Command::Insert {
+            parent: 0,
+            child: 1,
+            position: CommandInsertPosition::Tail,
+        }));
+        let plain = ServerHolder::new(GloryConfig::default(), "/").mount(NestedStreamWidget);
+        assert_eq!(SsrDocument::replay(&commands).inner_html(0), plain.app_html());
+    }
+
     #[test]
     fn render_stream_yields_dom_boundary_chunks() {
         let holder = ServerHolder::new(GloryConfig::default(), "/").mount(NestedStreamWidget);
@@ -434,6 +978,7 @@ mod tests {
         let patch = HtmlChunk::PlaceholderPatch {
             id: "user:1".to_string(),
             html: "<strong>Chris</strong>".to_string(),
+            nonce: None,
         }
         .into_string();
 
@@ -441,5 +986,457 @@ mod tests {
         assert!(patch.contains(r#"data-glory-placeholder-patch="user:1""#));
         assert!(patch.contains(r#"patchFromTemplate("user:1")"#));
         assert!(patch.contains("<strong>Chris</strong>"));
+        assert!(patch.contains("</template><script>"));
+
+        let failed = HtmlChunk::PlaceholderError {
+            id: "user:1".to_string(),
+            html: "<p>oops</p>".to_string(),
+            boundary: "0-0".to_string(),
+            error: BoundaryError::new("bad \"id\"", None),
+            nonce: Some(CspNonce::new("n0nce")),
+        }
+        .into_string();
+        assert!(failed.contains(r#"data-glory-placeholder-patch="user:1" data-glory-state="error" data-glory-error-boundary="0-0""#));
+        assert!(failed.contains("&quot;message&quot;:&quot;bad \\&quot;id\\&quot;&quot;"), "{failed}");
+        assert!(failed.contains(r#"patchFromTemplate("user:1")"#));
+        assert!(failed.contains(r#"</template><script nonce="n0nce">"#));
+
+        let timed_out = HtmlChunk::PlaceholderTimeout {
+            id: "user:1".to_string(),
+            html: "<span>Loading</span>".to_string(),
+            nonce: None,
+        }
+        .into_string();
+        assert!(timed_out.contains(r#"data-glory-state="timeout">"#));
+    }
+
+    #[derive(Debug)]
+    struct GatedBody {
+        label: &'static str,
+        gate: Option<futures::channel::oneshot::Receiver<()>>,
+        dropped: Option<Arc<std::sync::atomic::AtomicBool>>,
+        panics: bool,
+    }
+
+    /// Flags when the resource future is dropped without completing.
+    struct DropFlag(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl Widget for GatedBody {
+        fn build(&mut self, ctx: &mut Scope) {
+            let gate = Rc::new(RefCell::new(self.gate.take()));
+            let dropped = self.dropped.take();
+            let panics = self.panics;
+            let _res = crate::reflow::resource_in(ctx, move || {
+                let gate = gate.borrow_mut().take();
+                let flag = dropped.clone().map(DropFlag);
+                async move {
+                    if let Some(gate) = gate {
+                        let _ = gate.await;
+                    }
+                    if let Some(flag) = flag {
+                        std::mem::forget(flag);
+                    }
+                    if panics {
+                        panic!("resource failed");
+                    }
+                }
+            });
+            div().text(self.label).show_in(ctx);
+        }
+    }
+
+    #[derive(Debug)]
+    struct TwoBoundaries {
+        fast: Option<GatedBody>,
+        slow: Option<GatedBody>,
+    }
+
+    impl Widget for TwoBoundaries {
+        fn build(&mut self, ctx: &mut Scope) {
+            for body in [self.fast.take(), self.slow.take()].into_iter().flatten() {
+                crate::widgets::Suspense::new(body, |ctx| {
+                    div().text("loading").show_in(ctx);
+                })
+                .show_in(ctx);
+            }
+        }
+    }
+
+    fn gated(label: &'static str) -> (futures::channel::oneshot::Sender<()>, GatedBody) {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let body = GatedBody {
+            label,
+            gate: Some(receiver),
+            dropped: None,
+            panics: false,
+        };
+        (sender, body)
+    }
+
+    fn loading(ctx: &mut Scope) {
+        div().text("loading").show_in(ctx);
+    }
+
+    /// Reads chunks until the shell holds a marker for every id in `ids`.
+    fn read_shell(stream: &mut RenderStream, ids: &[&str]) -> String {
+        let mut shell = String::new();
+        while !ids.iter().all(|id| shell.contains(&format!(r#"data-glory-placeholder="{id}""#))) {
+            let chunk = futures::executor::block_on(stream.next()).expect("shell chunk");
+            assert!(!matches!(chunk, HtmlChunk::PlaceholderPatch { .. }), "patch before its resource resolved: {chunk:?}");
+            shell.push_str(&chunk.into_string());
+        }
+        shell
+    }
+
+    fn next_patch(stream: &mut RenderStream) -> (String, String) {
+        loop {
+            match futures::executor::block_on(stream.next()).expect("patch chunk") {
+                HtmlChunk::PlaceholderPatch { id, html, .. } => return (id, html),
+                HtmlChunk::DocumentEnd(_) => panic!("document ended before the patch"),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn render_pool_flushes_shell_then_patches_boundaries_as_they_resolve() {
+        let pool = RenderPool::new(1);
+        let (release_fast, fast) = gated("fast-body");
+        let (release_slow, slow) = gated("slow-body");
+        let mut stream = pool.render(move || {
+            ServerHolder::new_streaming(GloryConfig::default(), "/").mount(TwoBoundaries {
+                fast: Some(fast),
+                slow: Some(slow),
+            })
+        });
+
+        let shell = read_shell(&mut stream, &["gly-suspense-0", "gly-suspense-1"]);
+        assert!(shell.contains("loading"), "{shell}");
+        assert!(!shell.contains("fast-body") && !shell.contains("slow-body"), "{shell}");
+
+        // The second boundary resolves first and is patched without waiting
+        // for the first.
+        release_slow.send(()).unwrap();
+        let (id, html) = next_patch(&mut stream);
+        assert_eq!(id, "gly-suspense-1");
+        assert!(html.contains("slow-body"), "{html}");
+
+        release_fast.send(()).unwrap();
+        let (id, html) = next_patch(&mut stream);
+        assert_eq!(id, "gly-suspense-0");
+        assert!(html.contains("fast-body"), "{html}");
+
+        let rest: Vec<HtmlChunk> = futures::executor::block_on(stream.collect());
+        assert!(matches!(rest.last(), Some(HtmlChunk::DocumentEnd(_))), "{rest:?}");
+    }
+
+    #[test]
+    fn render_pool_worker_serves_other_renders_while_one_waits() {
+        let pool = RenderPool::new(1);
+        let (release_first, first) = gated("first-body");
+        let (release_second, second) = gated("second-body");
+        let mut first = pool.render(move || ServerHolder::new_streaming(GloryConfig::default(), "/").mount(crate::widgets::Suspense::new(first, loading)));
+        read_shell(&mut first, &["gly-suspense-0"]);
+
+        // The only worker still waits on the first render's resource.
+        let mut second = pool.render(move || ServerHolder::new_streaming(GloryConfig::default(), "/").mount(crate::widgets::Suspense::new(second, loading)));
+        read_shell(&mut second, &["gly-suspense-0"]);
+        release_second.send(()).unwrap();
+        let (id, html) = next_patch(&mut second);
+        assert_eq!(id, "gly-suspense-0");
+        assert!(html.contains("second-body") && !html.contains("first-body"), "{html}");
+        let rest: Vec<HtmlChunk> = futures::executor::block_on(second.collect());
+        assert!(matches!(rest.last(), Some(HtmlChunk::DocumentEnd(_))), "{rest:?}");
+
+        release_first.send(()).unwrap();
+        let (_, html) = next_patch(&mut first);
+        assert!(html.contains("first-body"), "{html}");
+    }
+
+    #[derive(Debug)]
+    struct NestedBoundaries {
+        outer: Option<GatedBody>,
+        inner: Option<GatedBody>,
+    }
+
+    impl Widget for NestedBoundaries {
+        fn build(&mut self, ctx: &mut Scope) {
+            let outer = self.outer.take().unwrap();
+            let inner = self.inner.take().unwrap();
+            crate::widgets::Suspense::new(div().fill(outer).fill(crate::widgets::Suspense::new(inner, loading)), loading).show_in(ctx);
+        }
+    }
+
+    #[test]
+    fn render_pool_streams_nested_boundaries_parent_first() {
+        let pool = RenderPool::new(1);
+        let (release_outer, outer) = gated("outer-body");
+        let (release_inner, inner) = gated("inner-body");
+        let mut stream = pool.render(move || {
+            ServerHolder::new_streaming(GloryConfig::default(), "/").mount(NestedBoundaries {
+                outer: Some(outer),
+                inner: Some(inner),
+            })
+        });
+
+        // Only the outer boundary is on the page; the inner one lives inside
+        // the outer body that has not been sent yet.
+        let shell = read_shell(&mut stream, &["gly-suspense-0"]);
+        assert!(!shell.contains(r#"data-glory-placeholder="gly-suspense-1""#), "{shell}");
+
+        release_outer.send(()).unwrap();
+        let (id, html) = next_patch(&mut stream);
+        assert_eq!(id, "gly-suspense-0");
+        assert!(html.contains("outer-body"), "{html}");
+        assert!(html.contains(r#"data-glory-placeholder="gly-suspense-1""#), "{html}");
+
+        release_inner.send(()).unwrap();
+        let (id, html) = next_patch(&mut stream);
+        assert_eq!(id, "gly-suspense-1");
+        assert!(html.contains("inner-body"), "{html}");
+    }
+
+    #[test]
+    fn nested_boundary_resolved_first_is_inlined_into_its_parent_patch() {
+        let (release_outer, outer) = gated("outer-body");
+        let (release_inner, inner) = gated("inner-body");
+        release_inner.send(()).unwrap();
+        release_outer.send(()).unwrap();
+        let chunks = ServerHolder::new_streaming(GloryConfig::default(), "/")
+            .mount(NestedBoundaries {
+                outer: Some(outer),
+                inner: Some(inner),
+            })
+            .rendered_chunks();
+        let patches: Vec<_> = chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                HtmlChunk::PlaceholderPatch { id, html, .. } => Some((id.as_str(), html.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(patches.len(), 1, "{chunks:?}");
+        assert_eq!(patches[0].0, "gly-suspense-0");
+        assert!(patches[0].1.contains("outer-body") && patches[0].1.contains("inner-body"), "{chunks:?}");
+    }
+
+    #[derive(Debug)]
+    struct GuardedBoundary {
+        body: Option<GatedBody>,
+    }
+
+    impl Widget for GuardedBoundary {
+        fn build(&mut self, ctx: &mut Scope) {
+            let body = self.body.take().unwrap();
+            crate::widgets::ErrorBoundary::new(crate::widgets::Suspense::new(body, loading), |error, ctx| {
+                div().text(format!("failed: {}", error.message())).show_in(ctx);
+            })
+            .show_in(ctx);
+        }
+    }
+
+    #[test]
+    fn failing_resource_streams_its_error_boundary_fallback() {
+        let pool = RenderPool::new(1);
+        let (release, mut body) = gated("never-shown");
+        body.panics = true;
+        let mut stream = pool.render(move || ServerHolder::new_streaming(GloryConfig::default(), "/").mount(GuardedBoundary { body: Some(body) }));
+        let shell = read_shell(&mut stream, &["gly-suspense-0"]);
+        assert!(!shell.contains("<glory-error-boundary"), "{shell}");
+
+        release.send(()).unwrap();
+        let rest: Vec<HtmlChunk> = futures::executor::block_on(stream.collect());
+        let failure = rest
+            .iter()
+            .find_map(|chunk| match chunk {
+                HtmlChunk::PlaceholderError {
+                    id, html, boundary, error, ..
+                } => Some((id, html, boundary, error)),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("no error patch: {rest:?}"));
+        assert_eq!(failure.0, "gly-suspense-0");
+        assert!(failure.1.contains("failed: resource failed"), "{}", failure.1);
+        assert!(!failure.1.contains("never-shown"), "{}", failure.1);
+        assert_eq!(failure.2, "0-0");
+        assert_eq!(failure.3.message(), "resource failed");
+        assert!(matches!(rest.last(), Some(HtmlChunk::DocumentEnd(_))), "{rest:?}");
+    }
+
+    #[test]
+    fn stream_timeout_flushes_remaining_fallbacks() {
+        let pool = RenderPool::new(1);
+        let (release, body) = gated("too-slow");
+        let stream = pool.render(move || {
+            ServerHolder::new_streaming(GloryConfig::default(), "/")
+                .stream_timeout(Duration::from_millis(20))
+                .mount(crate::widgets::Suspense::new(body, loading))
+        });
+        let chunks: Vec<HtmlChunk> = futures::executor::block_on(stream.collect());
+        let timed_out = chunks
+            .iter()
+            .find_map(|chunk| match chunk {
+                HtmlChunk::PlaceholderTimeout { id, html, .. } => Some((id, html)),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("no timeout patch: {chunks:?}"));
+        assert_eq!(timed_out.0, "gly-suspense-0");
+        assert!(timed_out.1.contains("loading") && !timed_out.1.contains("too-slow"), "{}", timed_out.1);
+        assert!(matches!(chunks.last(), Some(HtmlChunk::DocumentEnd(_))), "{chunks:?}");
+        drop(release);
+    }
+
+    #[derive(Debug)]
+    struct CspPage;
+
+    impl Widget for CspPage {
+        fn build(&mut self, ctx: &mut Scope) {
+            crate::web::widgets::style().text("p { color: red; }").show_in(ctx);
+            crate::widgets::Suspense::new(
+                GatedBody {
+                    label: "body",
+                    gate: None,
+                    dropped: None,
+                    panics: false,
+                },
+                loading,
+            )
+            .show_in(ctx);
+        }
+    }
+
+    #[test]
+    fn csp_nonce_reaches_every_inline_script_and_the_header() {
+        let holder = ServerHolder::new_streaming(GloryConfig::default(), "/")
+            .csp(ContentSecurityPolicy::new())
+            .mount(CspPage);
+        let nonce = holder.csp_nonce().expect("csp generates a nonce");
+        let html = holder.render_string();
+
+        let attr = format!(r#" nonce="{nonce}""#);
+        assert!(html.contains(&format!("{attr}>p {{ color: red; }}</style>")), "{html}");
+        assert!(html.contains("data-glory-placeholder-patch"), "{html}");
+        for tag in html.split("<script").skip(1) {
+            let open = &tag[..tag.find('>').expect("script tag closes")];
+            assert!(open.contains(&attr), "<script{open}> has no nonce");
+        }
+
+        let header = holder.response_options().header(CSP_HEADER).expect("csp header");
+        assert!(header.contains(&nonce.source()));
+
+        let other = ServerHolder::new(GloryConfig::default(), "/").csp(ContentSecurityPolicy::new());
+        assert_ne!(other.csp_nonce(), Some(nonce));
+    }
+
+    #[test]
+    fn dropping_render_stream_cancels_pending_resources() {
+        let pool = RenderPool::new(1);
+        let dropped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (_release_fast, fast) = gated("fast-body");
+        let (_release_slow, mut slow) = gated("slow-body");
+        slow.dropped = Some(dropped.clone());
+        let mut stream = pool.render(move || {
+            ServerHolder::new_streaming(GloryConfig::default(), "/").mount(TwoBoundaries {
+                fast: Some(fast),
+                slow: Some(slow),
+            })
+        });
+        read_shell(&mut stream, &["gly-suspense-0", "gly-suspense-1"]);
+        drop(stream);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !dropped.load(Ordering::SeqCst) {
+            assert!(std::time::Instant::now() < deadline, "render was not cancelled");
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        // The worker is free for the next request.
+        let html: String = futures::executor::block_on(
+            pool.render(|| ServerHolder::new(GloryConfig::default(), "/").mount(StreamWidget))
+                .html()
+                .collect::<Vec<_>>(),
+        )
+        .concat();
+        assert!(html.contains("streamed"), "{html}");
+    }
+
+    #[derive(Debug)]
+    struct NotFoundPage;
+
+    impl Widget for NotFoundPage {
+        fn build(&mut self, ctx: &mut Scope) {
+            let response = ctx.response_options();
+            response.set_status(404).unwrap();
+            response.set_cookie_header("seen=1; Path=/").unwrap();
+            div().text("missing").show_in(ctx);
+        }
+    }
+
+    #[test]
+    fn widgets_write_response_options_through_the_truck() {
+        let holder = ServerHolder::new(GloryConfig::default(), "/").mount(NotFoundPage);
+        let head = holder.response_options().head();
+        assert_eq!(head.status, 404);
+        assert_eq!(head.header("set-cookie"), Some("seen=1; Path=/"));
+        assert!(!holder.response_options().is_head_flushed());
+    }
+
+    /// Tries to change the status once its (streamed) resource resolves.
+    #[derive(Debug)]
+    struct LateStatus {
+        result: Arc<std::sync::Mutex<Option<Result<(), crate::web::ResponseOptionsError>>>>,
+    }
+
+    impl Widget for LateStatus {
+        fn build(&mut self, ctx: &mut Scope) {
+            let response = ctx.response_options();
+            response.insert_header("x-shell", "1").unwrap();
+            let result = self.result.clone();
+            let _res = crate::reflow::resource_in(ctx, move || {
+                let response = response.clone();
+                let result = result.clone();
+                async move {
+                    *result.lock().unwrap() = Some(response.set_status(500));
+                }
+            });
+            div().text("late").show_in(ctx);
+        }
+    }
+
+    #[test]
+    fn render_stream_head_is_frozen_when_the_shell_flushes() {
+        let pool = RenderPool::new(1);
+        let result = Arc::new(std::sync::Mutex::new(None));
+        let widget_result = result.clone();
+        let mut stream = pool.render(move || {
+            ServerHolder::new_streaming(GloryConfig::default(), "/").mount(crate::widgets::Suspense::new(
+                LateStatus { result: widget_result },
+                |ctx| {
+                    div().text("loading").show_in(ctx);
+                },
+            ))
+        });
+        let head = futures::executor::block_on(stream.head());
+        assert_eq!(head.status, 200);
+        assert_eq!(head.header("x-shell"), Some("1"));
+        let html = futures::executor::block_on(stream.html().collect::<Vec<_>>()).concat();
+        assert!(html.contains("late"), "{html}");
+        assert_eq!(
+            *result.lock().unwrap(),
+            Some(Err(crate::web::ResponseOptionsError::HeadFlushed("status")))
+        );
+    }
+
+    #[test]
+    fn render_stream_head_reports_status_set_during_mount() {
+        let pool = RenderPool::new(1);
+        let mut stream = pool.render(|| ServerHolder::new(GloryConfig::default(), "/").mount(NotFoundPage));
+        assert_eq!(futures::executor::block_on(stream.head()).status, 404);
     }
 }
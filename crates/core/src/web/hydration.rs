@@ -0,0 +1,140 @@
+//! Hydration mismatch detection and recovery.
+//!
+//! While hydrating, every [`Element`](crate::web::widgets::Element) looks up
+//! the server node carrying its `gly-id` and checks it against what the client
+//! is about to render. In debug builds the tag, static attributes and text are
+//! compared and every difference is reported — to the console, to
+//! [`take_mismatches`], and as a [`MISMATCH_EVENT`] on `window`, which the
+//! hot-reload client shows in its overlay.
+//!
+//! Structural mismatches (a missing node or a different tag) are recovered in
+//! every build: the stale server subtree is discarded and just that element is
+//! client-rendered in its place, so the rest of the page keeps hydrating
+//! against the right nodes. Attribute and text differences heal on their own
+//! because the first build writes every attribute.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+
+use crate::ViewId;
+
+/// `CustomEvent` dispatched on `window` for every mismatch; `detail` carries
+/// `viewId`, `widget`, `kind`, `expected`, `found` and `message`.
+pub const MISMATCH_EVENT: &str = "glory:hydration-mismatch";
+
+/// Set on an element the client rendered instead of adopting; nothing below
+/// it is expected to exist on the server side.
+pub(crate) const CLIENT_RENDERED_ATTR: &str = "gly-client-rendered";
+
+thread_local! {
+    static MISMATCHES: RefCell<Vec<HydrationMismatch>> = const { RefCell::new(Vec::new()) };
+}
+
+/// What differed between the server node and the client widget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// No server node carries the view's `gly-id`.
+    Missing,
+    /// The server node is a different element.
+    Tag,
+    /// A static attribute differs; `None` means absent.
+    Attribute(String),
+    /// The element's text differs.
+    Text,
+}
+
+impl MismatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MismatchKind::Missing => "missing",
+            MismatchKind::Tag => "tag",
+            MismatchKind::Attribute(_) => "attribute",
+            MismatchKind::Text => "text",
+        }
+    }
+}
+
+/// One difference found while hydrating.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HydrationMismatch {
+    pub view_id: ViewId,
+    /// Type name of the widget that owns the element.
+    pub widget: &'static str,
+    pub kind: MismatchKind,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+    /// True when the server subtree was discarded and client-rendered.
+    pub recovered: bool,
+}
+
+impl fmt::Display for HydrationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = self.expected.as_deref().unwrap_or("<none>");
+        let found = self.found.as_deref().unwrap_or("<none>");
+        match &self.kind {
+            MismatchKind::Missing => write!(f, "view {} ({}): server rendered no <{expected}>", self.view_id, self.widget)?,
+            MismatchKind::Tag => write!(
+                f,
+                "view {} ({}): expected <{expected}>, server rendered <{found}>",
+                self.view_id, self.widget
+            )?,
+            MismatchKind::Attribute(name) => write!(
+                f,
+                "view {} ({}): attribute `{name}` expected {expected:?}, found {found:?}",
+                self.view_id, self.widget
+            )?,
+            MismatchKind::Text => write!(f, "view {} ({}): text expected {expected:?}, found {found:?}", self.view_id, self.widget)?,
+        }
+        if self.recovered {
+            f.write_str("; subtree client-rendered")?;
+        }
+        Ok(())
+    }
+}
+
+/// Mismatches reported since the last call, oldest first.
+pub fn take_mismatches() -> Vec<HydrationMismatch> {
+    MISMATCHES.with(|mismatches| std::mem::take(&mut *mismatches.borrow_mut()))
+}
+
+/// True when attribute and text differences are checked (debug builds).
+pub(crate) fn verifying() -> bool {
+    cfg!(debug_assertions)
+}
+
+pub(crate) fn report(mismatch: HydrationMismatch) {
+    let message = format!("[hydration] mismatch at {mismatch}");
+    if verifying() {
+        crate::console::error(&message);
+    } else {
+        crate::console::debug_warn(&message);
+    }
+    dispatch(&mismatch, &message);
+    MISMATCHES.with(|mismatches| mismatches.borrow_mut().push(mismatch));
+}
+
+fn dispatch(mismatch: &HydrationMismatch, message: &str) {
+    let detail = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&detail, &JsValue::from_str(key), &value).unwrap_throw();
+    };
+    let optional = |value: &Option<String>| value.as_deref().map(JsValue::from_str).unwrap_or(JsValue::NULL);
+    set("viewId", JsValue::from_str(&mismatch.view_id));
+    set("widget", JsValue::from_str(mismatch.widget));
+    set("kind", JsValue::from_str(mismatch.kind.as_str()));
+    if let MismatchKind::Attribute(name) = &mismatch.kind {
+        set("attribute", JsValue::from_str(name));
+    }
+    set("expected", optional(&mismatch.expected));
+    set("found", optional(&mismatch.found));
+    set("recovered", JsValue::from_bool(mismatch.recovered));
+    set("message", JsValue::from_str(message));
+
+    let init = web_sys::CustomEventInit::new();
+    init.set_detail(&detail);
+    if let Ok(event) = web_sys::CustomEvent::new_with_event_init_dict(MISMATCH_EVENT, &init) {
+        crate::web::window().dispatch_event(&event).ok();
+    }
+}
@@ -5,14 +5,18 @@ pub mod widgets;
 
 mod attr;
 mod class;
+pub mod csp;
 mod prop;
+pub mod response;
 mod style;
 pub mod utils;
 
 pub use attr::AttrValue;
 pub use class::{ClassPart, Classes};
+pub use csp::{CSP_HEADER, ContentSecurityPolicy, CspNonce};
 pub use helpers::*;
 pub use prop::PropValue;
+pub use response::{ResponseHead, ResponseOptionsError, ServerResponseOptions};
 pub use style::{ScopedStyle, scoped_css};
 pub use widgets::Element;
 
@@ -20,6 +24,8 @@ pub use widgets::Element;
 mod csr;
 #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
 pub use csr::*;
+#[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+pub mod hydration;
 
 #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
 pub fn escape(s: &str) -> String {
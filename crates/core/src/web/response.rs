@@ -0,0 +1,231 @@
+//! HTTP response options written from inside the widget tree during SSR.
+//!
+//! A [`ServerHolder`](crate::web::holders::ServerHolder) injects one
+//! [`ServerResponseOptions`] into its [`Truck`] before mounting. Widgets,
+//! router hoops and `ErrorBoundary` fallbacks reach it through
+//! [`Scope::response_options`](crate::Scope::response_options) (or
+//! [`ServerResponseOptions::from_truck`]) to set the status code, headers,
+//! cookies or a redirect; the framework adapters read it back as a
+//! [`ResponseHead`] when they build the response.
+//!
+//! In streaming SSR the head is sent together with the shell, so anything
+//! changed afterwards — typically from a Suspense resource that resolves
+//! later — is rejected with [`ResponseOptionsError::HeadFlushed`].
+//!
+//! Outside a server render (CSR, LiveView, tests without a holder) the handle
+//! is detached: writes succeed and are simply never read.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::Truck;
+
+/// Error returned when [`ServerResponseOptions`] rejects a change.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ResponseOptionsError {
+    #[error("response {0} changed after the document head was flushed; set it before the shell renders, not from a streamed Suspense body")]
+    HeadFlushed(&'static str),
+    #[error("invalid HTTP status code {0}")]
+    InvalidStatus(u16),
+    #[error("invalid response header {0:?}")]
+    InvalidHeader(String),
+}
+
+/// Status line and headers an adapter should answer with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseHead {
+    pub status: u16,
+    /// Lower-cased header names in insertion order; `set-cookie` may repeat.
+    pub headers: Vec<(String, String)>,
+}
+
+impl Default for ResponseHead {
+    fn default() -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl ResponseHead {
+    /// The last value set for `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().rev().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// The redirect target when the status is a 3xx with a `location`.
+    pub fn location(&self) -> Option<&str> {
+        (300..400).contains(&self.status).then(|| self.header("location")).flatten()
+    }
+}
+
+#[derive(Debug, Default)]
+struct ResponseState {
+    head: ResponseHead,
+    head_flushed: bool,
+}
+
+/// Shared handle to the response being rendered. Cloning shares the state.
+#[derive(Clone, Debug, Default)]
+pub struct ServerResponseOptions {
+    state: Rc<RefCell<ResponseState>>,
+}
+
+impl ServerResponseOptions {
+    /// The options injected into `truck` by its holder, if any.
+    pub fn from_truck(truck: &Truck) -> Option<Self> {
+        truck.obtain::<Self>().ok().cloned()
+    }
+
+    /// The status code that will be sent (200 unless changed).
+    pub fn status(&self) -> u16 {
+        self.state.borrow().head.status
+    }
+
+    pub fn set_status(&self, status: u16) -> Result<(), ResponseOptionsError> {
+        if !(100..=999).contains(&status) {
+            return Err(ResponseOptionsError::InvalidStatus(status));
+        }
+        self.update("status", |head| head.status = status)
+    }
+
+    /// The last value set for `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.state.borrow().head.header(name).map(str::to_owned)
+    }
+
+    /// Sets `name`, replacing any earlier value.
+    pub fn insert_header(&self, name: impl AsRef<str>, value: impl Into<String>) -> Result<(), ResponseOptionsError> {
+        let (name, value) = validate_header(name.as_ref(), value.into())?;
+        self.update("headers", |head| {
+            head.headers.retain(|(key, _)| *key != name);
+            head.headers.push((name, value));
+        })
+    }
+
+    /// Adds a value for `name`, keeping earlier ones.
+    pub fn append_header(&self, name: impl AsRef<str>, value: impl Into<String>) -> Result<(), ResponseOptionsError> {
+        let (name, value) = validate_header(name.as_ref(), value.into())?;
+        self.update("headers", |head| head.headers.push((name, value)))
+    }
+
+    /// Appends a complete `Set-Cookie` header value. glory-serverfn's
+    /// `ResponseOptionsExt::set_cookie` builds one from `CookieOptions`.
+    pub fn set_cookie_header(&self, value: impl Into<String>) -> Result<(), ResponseOptionsError> {
+        self.append_header("set-cookie", value)
+    }
+
+    /// Answers with `302 Found` and `location`. A 3xx status set earlier
+    /// (for example `308`) is kept.
+    pub fn redirect(&self, location: impl Into<String>) -> Result<(), ResponseOptionsError> {
+        let status = self.status();
+        self.redirect_with(if (300..400).contains(&status) { status } else { 302 }, location)
+    }
+
+    /// Answers with the given 3xx `status` and `location`.
+    pub fn redirect_with(&self, status: u16, location: impl Into<String>) -> Result<(), ResponseOptionsError> {
+        if !(300..400).contains(&status) {
+            return Err(ResponseOptionsError::InvalidStatus(status));
+        }
+        let (name, location) = validate_header("location", location.into())?;
+        self.update("redirect", |head| {
+            head.status = status;
+            head.headers.retain(|(key, _)| *key != name);
+            head.headers.push((name, location));
+        })
+    }
+
+    /// The redirect target, when a redirect has been set.
+    pub fn redirect_location(&self) -> Option<String> {
+        self.state.borrow().head.location().map(str::to_owned)
+    }
+
+    /// True once the head has been handed to the adapter; later changes fail.
+    pub fn is_head_flushed(&self) -> bool {
+        self.state.borrow().head_flushed
+    }
+
+    /// Snapshot of the status and headers set so far.
+    pub fn head(&self) -> ResponseHead {
+        self.state.borrow().head.clone()
+    }
+
+    /// Snapshots the head for sending and freezes it. Called by the holder
+    /// right before the first streamed chunk.
+    pub fn flush_head(&self) -> ResponseHead {
+        let mut state = self.state.borrow_mut();
+        state.head_flushed = true;
+        state.head.clone()
+    }
+
+    fn update(&self, what: &'static str, change: impl FnOnce(&mut ResponseHead)) -> Result<(), ResponseOptionsError> {
+        let mut state = self.state.borrow_mut();
+        if state.head_flushed {
+            let error = ResponseOptionsError::HeadFlushed(what);
+            crate::warn!("{error}");
+            return Err(error);
+        }
+        change(&mut state.head);
+        Ok(())
+    }
+}
+
+fn validate_header(name: &str, value: String) -> Result<(String, String), ResponseOptionsError> {
+    let valid_name = !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte));
+    if !valid_name {
+        return Err(ResponseOptionsError::InvalidHeader(name.to_owned()));
+    }
+    if value.bytes().any(|byte| matches!(byte, b'\r' | b'\n' | 0)) {
+        return Err(ResponseOptionsError::InvalidHeader(name.to_owned()));
+    }
+    Ok((name.to_ascii_lowercase(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_sets_found_status_and_location() {
+        let options = ServerResponseOptions::default();
+        options.redirect("/login?next=%2F").unwrap();
+        let head = options.head();
+        assert_eq!(head.status, 302);
+        assert_eq!(head.location(), Some("/login?next=%2F"));
+
+        options.set_status(308).unwrap();
+        options.redirect("/moved").unwrap();
+        assert_eq!(options.status(), 308);
+        assert_eq!(options.redirect_location().as_deref(), Some("/moved"));
+    }
+
+    #[test]
+    fn headers_insert_replace_and_cookies_append() {
+        let options = ServerResponseOptions::default();
+        options.insert_header("X-Frame-Options", "DENY").unwrap();
+        options.insert_header("x-frame-options", "SAMEORIGIN").unwrap();
+        options.set_cookie_header("a=1").unwrap();
+        options.set_cookie_header("b=2").unwrap();
+        let head = options.head();
+        assert_eq!(head.header("X-Frame-Options"), Some("SAMEORIGIN"));
+        assert_eq!(head.headers.iter().filter(|(name, _)| name == "set-cookie").count(), 2);
+        assert!(options.insert_header("bad name", "x").is_err());
+        assert!(options.insert_header("x-split", "a\r\nb: c").is_err());
+        assert_eq!(options.set_status(42), Err(ResponseOptionsError::InvalidStatus(42)));
+    }
+
+    #[test]
+    fn changes_after_flush_are_rejected() {
+        let options = ServerResponseOptions::default();
+        options.set_status(404).unwrap();
+        assert_eq!(options.flush_head().status, 404);
+        assert_eq!(options.set_status(500), Err(ResponseOptionsError::HeadFlushed("status")));
+        assert_eq!(options.redirect("/"), Err(ResponseOptionsError::HeadFlushed("redirect")));
+        assert_eq!(options.status(), 404);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::{GloryConfig, ReloadWebSocketProtocol};
+use crate::web::CspNonce;
 use crate::web::widgets::{DEPOT_BODY_META_KEY, DEPOT_HEAD_MIXIN_KEY, DEPOT_HTML_META_KEY};
 use crate::{Node, Truck};
 
@@ -45,20 +46,11 @@ fn auto_reload(nonce_str: &str, options: &GloryConfig) -> String {
     }
 }
 
-fn nonce_attr(config: &GloryConfig) -> String {
-    config
-        .csp_nonce
-        .as_deref()
-        .map(|nonce| format!(r#" nonce="{}""#, escape_html_attr(nonce)))
-        .unwrap_or_default()
-}
-
-fn escape_html_attr(value: &str) -> String {
-    value
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
+/// The render's nonce attribute; the configured static nonce when the holder
+/// was not given one.
+fn nonce_attr(config: &GloryConfig, truck: &Truck) -> String {
+    let nonce = CspNonce::from_truck(truck).or_else(|| config.csp_nonce.clone().map(CspNonce::new));
+    CspNonce::attr(nonce.as_ref())
 }
 
 const STREAM_HYDRATE_JS: &str = r#"(function () {
@@ -77,13 +69,26 @@ const STREAM_HYDRATE_JS: &str = r#"(function () {
         return 'template[data-glory-placeholder-patch="' + cssEscape(id) + '"]';
     }
 
+    // Errors of `ErrorBoundary`s that failed after the shell was sent, by
+    // view id; the boundary reads them back while hydrating.
+    window.__gloryBoundaryErrors = window.__gloryBoundaryErrors || {};
+
     const api = {
+        // Settled placeholders: "resolved", "error" or "timeout".
+        states: {},
         patchFromTemplate(id) {
             const patch = document.querySelector(patchSelector(id));
             const marker = document.querySelector(markerSelector(id));
             if (!patch || !marker) return false;
+            const state = patch.getAttribute("data-glory-state") || "resolved";
+            const boundary = patch.getAttribute("data-glory-error-boundary");
+            if (state === "error" && boundary) {
+                window.__gloryBoundaryErrors[boundary] = patch.getAttribute("data-glory-error");
+            }
             marker.replaceWith(patch.content.cloneNode(true));
             patch.remove();
+            api.states[id] = state;
+            window.dispatchEvent(new CustomEvent("glory:stream-patch", { detail: { id: id, state: state } }));
             return true;
         },
         flush() {
@@ -126,7 +131,7 @@ pub fn html_parts_separated(
         wasm_output_name.push_str("_bg");
     }
 
-    let nonce = nonce_attr(config);
+    let nonce = nonce_attr(config, truck);
     let glory_auto_reload = auto_reload(&nonce, config);
 
     // The truck stores command-stream node handles; their rendered content
@@ -189,6 +194,8 @@ mod tests {
         assert!(head.contains("window.__gloryStreamHydrate"));
         assert!(head.contains("patchFromTemplate"));
         assert!(head.contains("template[data-glory-placeholder-patch]"));
+        assert!(head.contains("window.__gloryBoundaryErrors[boundary]"));
+        assert!(head.contains(r#"new CustomEvent("glory:stream-patch""#));
     }
 
     #[test]
@@ -203,4 +210,19 @@ mod tests {
 
         assert!(head.contains(r#"nonce="&quot;&lt;&amp;&gt;&quot;""#));
     }
+
+    #[test]
+    fn ssr_head_prefers_the_request_nonce() {
+        let config = GloryConfig {
+            csp_nonce: Some("static".to_string()),
+            ..GloryConfig::default()
+        };
+        let mut truck = Truck::new();
+        truck.inject(CspNonce::new("per-request"));
+        let document = crate::renderer::ssr_dom::SsrDocument::new();
+        let (head, _, _) = html_parts_separated(&config, &truck, &document);
+
+        assert!(head.contains(r#"<script nonce="per-request">"#));
+        assert!(!head.contains("static"));
+    }
 }
@@ -13,7 +13,8 @@ use crate::reflow::{Bond, Lotus};
 use crate::renderer::{InsertPosition, Renderer, WebRenderer};
 use crate::view::{ViewId, ViewPlacement};
 use crate::web::events::EventDescriptor;
-use crate::web::{AttrValue, ClassPart, Classes, PropValue};
+use crate::web::hydration::{self, HydrationMismatch, MismatchKind};
+use crate::web::{AttrValue, ClassPart, Classes, CspNonce, PropValue};
 use crate::widget::{Filler, IntoFiller};
 use crate::{NodeRef, Scope, Widget};
 
@@ -37,6 +38,8 @@ where
     listeners: Vec<Box<dyn FnOnce(&T)>>,
 
     pub(crate) node: T,
+    /// Server node that failed hydration and is swapped for `node` on flood.
+    stale: Option<web_sys::Element>,
     #[educe(Debug(ignore))]
     pub(crate) renderer: WebRenderer,
 }
@@ -50,6 +53,8 @@ where
         let node = <T as AsRef<web_sys::Element>>::as_ref(&self.node);
         if crate::web::is_hydrating() && node.has_attribute("gly-hydrating") {
             node.remove_attribute("gly-hydrating").unwrap_throw();
+        } else if let Some(stale) = self.stale.take() {
+            stale.replace_with_with_node_1(node).unwrap_throw();
         } else {
             match &ctx.placement {
                 ViewPlacement::Head => self.renderer.insert_child(parent_node, node, InsertPosition::Head),
@@ -67,27 +72,41 @@ where
         for id in ids {
             ctx.attach_child(&id);
         }
+        if crate::web::is_hydrating() {
+            node.remove_attribute(hydration::CLIENT_RENDERED_ATTR).ok();
+        }
     }
-    #[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
     fn hydrate(&mut self, ctx: &mut Scope) {
-        let selector = format!("{}[gly-id='{}']", self.name, ctx.view_id);
-        let exist_node = if let Some(pnode) = &ctx.parent_node {
-            let node = pnode.query_selector(&selector).unwrap_throw();
-            if node.is_none() {
-                crate::debug_warn!("[hydrating]: node not found: {} {}", selector, pnode.outer_html());
-            }
-            node
-        } else {
-            let node = crate::web::document().query_selector(&selector).unwrap_throw();
-            if node.is_none() {
-                crate::debug_warn!("[hydrating]: node not found2: {}", selector);
+        let selector = format!("[gly-id='{}']", ctx.view_id);
+        let exist_node = match &ctx.parent_node {
+            Some(pnode) => pnode.query_selector(&selector).unwrap_throw(),
+            None => crate::web::document().query_selector(&selector).unwrap_throw(),
+        };
+        let node = <T as AsRef<web_sys::Element>>::as_ref(&self.node);
+        let Some(exist_node) = exist_node else {
+            // Below a client-rendered element the server has nothing to offer.
+            let client_rendered = ctx
+                .parent_node
+                .as_ref()
+                .and_then(|pnode| pnode.closest(&format!("[{}]", hydration::CLIENT_RENDERED_ATTR)).ok().flatten())
+                .is_some();
+            if !client_rendered {
+                hydration::report(self.mismatch(ctx, MismatchKind::Missing, Some(self.name.to_string()), None, true));
+                node.set_attribute(hydration::CLIENT_RENDERED_ATTR, "").unwrap_throw();
             }
-            node
+            return;
         };
-        if let Some(exist_node) = exist_node {
-            self.node = wasm_bindgen::JsCast::unchecked_into(exist_node);
-            crate::debug_warn!("[hydrating]: node exist: {}", selector);
+        if !exist_node.tag_name().eq_ignore_ascii_case(&self.name) {
+            let found = exist_node.tag_name().to_ascii_lowercase();
+            hydration::report(self.mismatch(ctx, MismatchKind::Tag, Some(self.name.to_string()), Some(found), true));
+            node.set_attribute(hydration::CLIENT_RENDERED_ATTR, "").unwrap_throw();
+            self.stale = Some(exist_node);
+            return;
+        }
+        if hydration::verifying() {
+            self.verify_hydration(ctx, &exist_node);
         }
+        self.node = wasm_bindgen::JsCast::unchecked_into(exist_node);
     }
     fn build(&mut self, ctx: &mut Scope) {
         let node = <T as AsRef<web_sys::Element>>::as_ref(&self.node);
@@ -113,6 +132,7 @@ where
             value.inject_to(&ctx.view_id, &mut node.clone(), name, true);
         }
         self.classes.inject_to(&ctx.view_id, &mut node.clone(), "class", true);
+        self.apply_csp_nonce(node);
 
         for listener in std::mem::take(&mut self.listeners) {
             (listener)(&self.node);
@@ -315,6 +335,7 @@ where
             props: BTreeMap::new(),
             fillers: vec![],
             node,
+            stale: None,
             listeners: vec![],
             renderer: WebRenderer,
         }
@@ -324,6 +345,40 @@ where
         &self.node
     }
 
+    fn mismatch(&self, ctx: &Scope, kind: MismatchKind, expected: Option<String>, found: Option<String>, recovered: bool) -> HydrationMismatch {
+        HydrationMismatch {
+            view_id: ctx.view_id.clone(),
+            widget: ctx.widget_type,
+            kind,
+            expected,
+            found,
+            recovered,
+        }
+    }
+
+    /// Reports static attributes and text that differ from the server node.
+    /// They are not recovered: the first build writes them anyway.
+    fn verify_hydration(&self, ctx: &Scope, exist_node: &web_sys::Element) {
+        for (name, value) in &self.attrs {
+            let expected = value.to_string();
+            match name.as_ref() {
+                "inner_html" => {}
+                "inner_text" => {
+                    let found = exist_node.text_content();
+                    if self.fillers.is_empty() && expected.is_some() && found != expected {
+                        hydration::report(self.mismatch(ctx, MismatchKind::Text, expected, found, false));
+                    }
+                }
+                _ => {
+                    let found = exist_node.get_attribute(name);
+                    if found != expected {
+                        hydration::report(self.mismatch(ctx, MismatchKind::Attribute(name.to_string()), expected, found, false));
+                    }
+                }
+            }
+        }
+    }
+
     fn can_compact_fill(&self) -> bool {
         !crate::web::is_hydrating()
             && self.listeners.is_empty()
@@ -351,10 +406,22 @@ where
             value.inject_to(&ctx.view_id, &mut node.clone(), name, true);
         }
         self.classes.inject_to(&ctx.view_id, &mut node.clone(), "class", true);
+        self.apply_csp_nonce(&node);
 
         parent_node.append_child(&node).unwrap_throw();
     }
 
+    /// Gives a client-created `<script>`/`<style>` the page's nonce so a
+    /// strict CSP still applies it.
+    fn apply_csp_nonce(&self, node: &web_sys::Element) {
+        if CspNonce::applies_to(&self.name)
+            && !self.attrs.contains_key("nonce")
+            && let Some(nonce) = crate::web::csp::document_nonce()
+        {
+            node.set_attribute("nonce", &nonce).unwrap_throw();
+        }
+    }
+
     pub fn add_filler(&mut self, filler: impl IntoFiller) {
         self.fillers.push(filler.into_filler());
     }
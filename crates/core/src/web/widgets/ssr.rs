@@ -11,7 +11,7 @@ use crate::reflow::{Bond, Lotus};
 use crate::renderer::{BackendRenderer, InsertPosition, Renderer};
 use crate::view::{ViewId, ViewPlacement};
 use crate::web::events::EventDescriptor;
-use crate::web::{AttrValue, ClassPart, Classes, PropValue};
+use crate::web::{AttrValue, ClassPart, Classes, CspNonce, PropValue};
 use crate::widget::{Filler, IntoFiller};
 use crate::{Scope, Widget};
 
@@ -67,6 +67,12 @@ impl Widget for Element {
         }
 
         self.attrs.insert("gly-id".into(), Box::new(ctx.view_id.clone()));
+        if CspNonce::applies_to(&self.name)
+            && !self.attrs.contains_key("nonce")
+            && let Some(nonce) = ctx.csp_nonce()
+        {
+            self.attrs.insert("nonce".into(), Box::new(nonce.to_string()));
+        }
         for (name, value) in &self.attrs {
             value.inject_to(&ctx.view_id, &mut self.node, name, true);
         }
@@ -107,6 +113,20 @@ impl Widget for Element {
         }
         ctx.mark_descendants_dom_detached();
     }
+    /// True when nothing about this element can change after it is built, so
+    /// its markup may be produced once and reused (see
+    /// [`StaticTemplate`](crate::widgets::StaticTemplate)).
+    fn can_fill_compact(&self) -> bool {
+        // Scripts and styles carry the nonce of the request they render in.
+        if CspNonce::applies_to(&self.name) {
+            return false;
+        }
+        #[cfg(feature = "backend-command")]
+        if !self.listener_names.borrow().is_empty() {
+            return false;
+        }
+        self.attrs.values().all(|value| value.is_static()) && self.props.values().all(|value| value.is_static()) && self.classes.is_static()
+    }
     fn patch(&mut self, ctx: &mut Scope) {
         for (name, value) in &self.props {
             value.inject_to(&ctx.view_id, &mut self.node, name, false);
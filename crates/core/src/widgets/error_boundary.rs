@@ -6,10 +6,21 @@ use wasm_bindgen::UnwrapThrowExt;
 
 use crate::{BoundaryError, Filler, IntoFiller, Scope, ViewId, Widget};
 
+#[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+use crate::node::Node;
+
 pub struct ErrorBoundary {
     body: Option<Filler>,
     fallback: Box<dyn Fn(&BoundaryError, &mut Scope)>,
     error: Option<BoundaryError>,
+    /// Streaming-SSR wrapper tagged `data-glory-error-boundary` and the error
+    /// slot shared with the holder, so a fallback rendered after the shell
+    /// was flushed can still be streamed.
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    stream: Option<(Node, Rc<RefCell<Option<BoundaryError>>>)>,
 }
 
 impl fmt::Debug for ErrorBoundary {
@@ -27,6 +38,8 @@ impl ErrorBoundary {
             body: Some(body.into_filler()),
             fallback: Box::new(fallback),
             error: None,
+            #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+            stream: None,
         }
     }
 
@@ -61,8 +74,45 @@ impl ErrorBoundary {
         self.render_fallback(ctx);
         #[cfg(feature = "web-ssr")]
         save_state(ctx, self.error.as_ref().unwrap());
+        #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+        if let Some((_, slot)) = &self.stream {
+            *slot.borrow_mut() = self.error.clone();
+        }
+    }
+
+    /// Streaming SSR only: route the boundary's children under a wrapper and
+    /// register it so the holder can stream the fallback if a suspended
+    /// descendant fails after the shell was sent.
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    fn stream_setup(&mut self, ctx: &mut Scope) {
+        if !crate::stream_ssr::is_streaming() {
+            return;
+        }
+        let view_id = ctx.view_id().to_string();
+        let wrapper = crate::stream_ssr::create_wrapper(ctx, "glory-error-boundary", "data-glory-error-boundary", view_id);
+        let slot = Rc::new(RefCell::new(None));
+        crate::stream_ssr::register_error_boundary(crate::stream_ssr::ErrorBoundaryRegistration {
+            view_id: ctx.view_id.clone(),
+            parent: ctx.error_boundary.clone(),
+            wrapper_id: wrapper.id(),
+            error: slot.clone(),
+        });
+        self.stream = Some((wrapper, slot));
+    }
+
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    fn stream_insert_wrapper(&self, ctx: &mut Scope) {
+        if let Some((wrapper, _)) = &self.stream {
+            crate::stream_ssr::insert_wrapper(ctx, wrapper);
+        }
     }
 
+    #[cfg(not(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32"))))]
+    fn stream_setup(&mut self, _ctx: &mut Scope) {}
+
+    #[cfg(not(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32"))))]
+    fn stream_insert_wrapper(&self, _ctx: &mut Scope) {}
+
     fn attach_body_children(&mut self, ctx: &mut Scope) {
         let ids = ctx.child_views.keys().cloned().collect::<Vec<_>>();
         for id in ids {
@@ -90,12 +140,14 @@ impl Widget for ErrorBoundary {
             return;
         }
 
+        self.stream_setup(ctx);
         if let Err(error) = self.fill_body(ctx) {
             self.replace_with_fallback(ctx, error);
         }
     }
 
     fn flood(&mut self, ctx: &mut Scope) {
+        self.stream_insert_wrapper(ctx);
         if self.error.is_some() {
             let ids = ctx.child_views.keys().cloned().collect::<Vec<_>>();
             for id in ids {
@@ -141,5 +193,9 @@ fn load_state(ctx: &Scope) -> Option<BoundaryError> {
         let data = crate::web::window().atob(&data).unwrap_throw();
         return serde_json::from_str(&data).ok();
     }
-    None
+    // Errors raised after the shell was streamed arrive with the patch that
+    // swapped the fallback in, recorded by the stream-hydrate runtime.
+    let errors = js_sys::Reflect::get(&crate::web::window(), &"__gloryBoundaryErrors".into()).ok()?;
+    let data = js_sys::Reflect::get(&errors, &ctx.view_id().to_string().into()).ok()?.as_string()?;
+    serde_json::from_str(&data).ok()
 }
@@ -0,0 +1,130 @@
+//! Islands: interactive widgets hydrated on their own inside an otherwise
+//! static server-rendered page.
+//!
+//! Wrapping a widget in [`Island`] renders it inside a `<glory-island>`
+//! element that records the island's registered name, its load strategy and
+//! the widget itself serialized as JSON props. On the client,
+//! `glory_core::web::holders::Islands` finds those wrappers, rebuilds each
+//! widget from its props and hydrates just that subtree — the rest of the
+//! page is never walked, so static content costs nothing to boot.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::{Scope, Widget};
+
+#[cfg(all(target_arch = "wasm32", feature = "web-csr"))]
+type IslandElement = crate::web::widgets::Element<web_sys::HtmlElement>;
+#[cfg(not(all(target_arch = "wasm32", feature = "web-csr")))]
+type IslandElement = crate::web::widgets::Element;
+
+/// Tag name of the wrapper element every island renders.
+pub const ISLAND_TAG: &str = "glory-island";
+/// Attribute holding the island's [`IslandWidget::NAME`].
+pub const ISLAND_NAME_ATTR: &str = "data-glory-island";
+/// Attribute holding the island widget serialized as JSON.
+pub const ISLAND_PROPS_ATTR: &str = "data-glory-props";
+/// Attribute holding the island's [`IslandLoad`] strategy.
+pub const ISLAND_LOAD_ATTR: &str = "data-glory-load";
+
+/// A widget that can be booted on its own from serialized props.
+///
+/// The widget's fields are its props: they are serialized into the page
+/// during SSR and deserialized on the client, so keep them to plain data and
+/// create signals and resources in `build`.
+pub trait IslandWidget: Widget + Serialize + DeserializeOwned {
+    /// Name the client registry looks the island up by; unique per app.
+    const NAME: &'static str;
+}
+
+/// When the client hydrates an island.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IslandLoad {
+    /// As soon as the islands are booted.
+    #[default]
+    Eager,
+    /// During the first idle period after boot (`requestIdleCallback`).
+    Idle,
+    /// The first time the island scrolls into the viewport.
+    Visible,
+}
+
+impl IslandLoad {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IslandLoad::Eager => "eager",
+            IslandLoad::Idle => "idle",
+            IslandLoad::Visible => "visible",
+        }
+    }
+
+    /// Parses the wrapper's load attribute; unknown values load eagerly.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "idle" => IslandLoad::Idle,
+            "visible" => IslandLoad::Visible,
+            _ => IslandLoad::Eager,
+        }
+    }
+}
+
+/// Marks `widget` as an island. See the [module docs](self).
+pub struct Island<W: IslandWidget> {
+    widget: Option<W>,
+    load: IslandLoad,
+}
+
+impl<W: IslandWidget> fmt::Debug for Island<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Island").field("name", &W::NAME).field("load", &self.load).finish()
+    }
+}
+
+impl<W: IslandWidget> Island<W> {
+    pub fn new(widget: W) -> Self {
+        Self {
+            widget: Some(widget),
+            load: IslandLoad::Eager,
+        }
+    }
+
+    /// Sets when the client hydrates this island.
+    pub fn load(mut self, load: IslandLoad) -> Self {
+        self.load = load;
+        self
+    }
+
+    /// Shorthand for `load(IslandLoad::Idle)`.
+    pub fn on_idle(self) -> Self {
+        self.load(IslandLoad::Idle)
+    }
+
+    /// Shorthand for `load(IslandLoad::Visible)`.
+    pub fn on_visible(self) -> Self {
+        self.load(IslandLoad::Visible)
+    }
+}
+
+impl<W: IslandWidget> Widget for Island<W> {
+    fn build(&mut self, ctx: &mut Scope) {
+        let Some(widget) = self.widget.take() else {
+            return;
+        };
+        let wrapper = IslandElement::new(ISLAND_TAG, false)
+            .attr(ISLAND_NAME_ATTR, W::NAME)
+            .attr(ISLAND_LOAD_ATTR, self.load.as_str());
+        // A client-rendered island is already live; props only matter when
+        // the page is rendered on the server and booted later.
+        #[cfg(not(all(target_arch = "wasm32", feature = "web-csr")))]
+        let wrapper = match serde_json::to_string(&widget) {
+            Ok(props) => wrapper.attr(ISLAND_PROPS_ATTR, props),
+            Err(e) => {
+                crate::error!("island `{}` props failed to serialize: {e}", W::NAME);
+                wrapper
+            }
+        };
+        wrapper.fill(widget).show_in(ctx);
+    }
+}
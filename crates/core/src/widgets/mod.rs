@@ -1,10 +1,14 @@
 mod each;
 mod error_boundary;
+pub mod island;
+pub mod static_template;
 mod suspense;
 pub mod switch;
 
 pub use each::Each;
 pub use error_boundary::ErrorBoundary;
+pub use island::{Island, IslandLoad, IslandWidget};
+pub use static_template::StaticTemplate;
 pub use suspense::Suspense;
 pub use switch::Switch;
 
@@ -14,7 +14,7 @@ use crate::web::holders::ServerHolder;
 use crate::web::widgets::{
     button, div, form, head_mixin, input, label, li, link, math as math_widgets, meta, option, select, style, svg as svg_widgets, textarea, title, ul,
 };
-use crate::widgets::{Each, ErrorBoundary, Suspense, Switch};
+use crate::widgets::{Each, ErrorBoundary, Island, IslandWidget, StaticTemplate, Suspense, Switch};
 use crate::{Holder, Scope, Widget};
 
 fn render_html(holder: &ServerHolder) -> String {
@@ -158,6 +158,36 @@ fn blocking_hydratable_resource_embeds_value_payload() {
     assert!(html.contains("ready"), "{html}");
 }
 
+#[derive(Debug)]
+struct HydratableCageWidget {
+    inits: Rc<Cell<usize>>,
+}
+
+impl Widget for HydratableCageWidget {
+    fn build(&mut self, ctx: &mut Scope) {
+        let inits = self.inits.clone();
+        let flags = ctx.hydratable_cage("flags", move || {
+            inits.set(inits.get() + 1);
+            vec!["beta".to_string()]
+        });
+        div().text(flags.get().join(",")).show_in(ctx);
+    }
+}
+
+#[test]
+fn hydratable_cage_records_its_value_in_the_payload() {
+    let inits = Rc::new(Cell::new(0));
+    let holder = make_holder().mount(HydratableCageWidget { inits: inits.clone() });
+    let html = holder.render_string();
+
+    assert_eq!(inits.get(), 1);
+    assert!(html.contains(">beta</div>"), "{html}");
+    assert!(
+        html.contains(r#"window.__gloryResource=Object.assign(window.__gloryResource||{},{"cage:flags":["beta"]})"#),
+        "{html}"
+    );
+}
+
 // ----------------------------------------------------------------------------
 // Error boundaries
 // ----------------------------------------------------------------------------
@@ -233,6 +263,74 @@ fn error_boundary_catches_child_patch_panics() {
     assert!(!html.contains("ready"), "{html}");
 }
 
+// ----------------------------------------------------------------------------
+// Islands
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CounterIsland {
+    label: String,
+    start: i32,
+}
+
+impl Widget for CounterIsland {
+    fn build(&mut self, ctx: &mut Scope) {
+        button().text(format!("{}: {}", self.label, self.start)).show_in(ctx);
+    }
+}
+
+impl IslandWidget for CounterIsland {
+    const NAME: &'static str = "counter";
+}
+
+#[derive(Debug)]
+struct IslandPage;
+
+impl Widget for IslandPage {
+    fn build(&mut self, ctx: &mut Scope) {
+        div()
+            .fill(div().text("static copy"))
+            .fill(Island::new(CounterIsland {
+                label: "Likes \"<3\"".into(),
+                start: 2,
+            }))
+            .fill(
+                Island::new(CounterIsland {
+                    label: "Later".into(),
+                    start: 0,
+                })
+                .on_visible(),
+            )
+            .show_in(ctx);
+    }
+}
+
+#[test]
+fn island_renders_wrapper_with_props_and_load_strategy() {
+    let holder = make_holder().mount(IslandPage);
+    let html = render_html(&holder);
+
+    assert!(html.contains("static copy"), "{html}");
+    assert_eq!(html.matches("<glory-island ").count(), 2, "{html}");
+    assert!(html.contains(r#"data-glory-island="counter""#), "{html}");
+    assert!(html.contains(r#"data-glory-load="eager""#), "{html}");
+    assert!(html.contains(r#"data-glory-load="visible""#), "{html}");
+    // Props round-trip through an escaped attribute.
+    assert!(
+        html.contains(r#"data-glory-props="{&quot;label&quot;:&quot;Likes \&quot;&lt;3\&quot;&quot;,&quot;start&quot;:2}""#),
+        "{html}"
+    );
+    // The client boots each island from `{wrapper gly-id}-0`, so the
+    // widget's own nodes must sit directly below that path.
+    let wrapper_id = html
+        .split("<glory-island ")
+        .nth(1)
+        .and_then(|tail| tail.split(r#"gly-id=""#).nth(1))
+        .and_then(|tail| tail.split('"').next())
+        .unwrap();
+    assert!(html.contains(&format!(r#"<button gly-id="{wrapper_id}-0-0">Likes"#)), "{html}");
+}
+
 // ----------------------------------------------------------------------------
 // Document head
 // ----------------------------------------------------------------------------
@@ -878,3 +976,99 @@ fn switch_toggles_and_restores_cached_view() {
     assert!(after_restore.contains("LEFT"));
     assert!(!after_restore.contains("RIGHT"));
 }
+
+// ----------------------------------------------------------------------------
+// Static template
+// ----------------------------------------------------------------------------
+
+thread_local! {
+    static CHROME_BUILDS: Cell<usize> = const { Cell::new(0) };
+    static CHROME_LABEL: Cell<Option<Cage<String>>> = const { Cell::new(None) };
+}
+
+fn static_chrome() -> impl Widget {
+    CHROME_BUILDS.set(CHROME_BUILDS.get() + 1);
+    ul().attr("class", "nav")
+        .fill(li().text("Home"))
+        .fill(li().fill(label().attr("for", "q").text("Search")))
+}
+
+#[derive(Debug)]
+struct StaticChromeWidget;
+
+impl Widget for StaticChromeWidget {
+    fn build(&mut self, ctx: &mut Scope) {
+        div().fill(StaticTemplate::new(static_chrome)).show_in(ctx);
+    }
+}
+
+#[test]
+fn static_template_matches_a_normal_render_and_builds_once() {
+    let expected = "<div gly-id=\"0-0\"><ul class=\"nav\" gly-id=\"0-0-0-0\"><li gly-id=\"0-0-0-0-0\">Home</li>\
+                    <li gly-id=\"0-0-0-0-1\"><label for=\"q\" gly-id=\"0-0-0-0-1-0\">Search</label></li></ul></div>";
+
+    let first = make_holder().mount(StaticChromeWidget);
+    assert_eq!(render_html(&first), expected);
+    let compiled = CHROME_BUILDS.get();
+
+    let second = make_holder().mount(StaticChromeWidget);
+    assert_eq!(render_html(&second), expected);
+    assert_eq!(CHROME_BUILDS.get(), compiled, "later renders reuse the compiled markup");
+}
+
+/// One `StaticTemplate` call site shared by every instance.
+#[derive(Debug)]
+struct ItemTemplate<W: Widget>(fn() -> W);
+
+impl<W: Widget> Widget for ItemTemplate<W> {
+    fn build(&mut self, ctx: &mut Scope) {
+        StaticTemplate::new(self.0).show_in(ctx);
+    }
+}
+
+#[derive(Debug)]
+struct ItemListWidget;
+
+impl Widget for ItemListWidget {
+    fn build(&mut self, ctx: &mut Scope) {
+        ul().fill(ItemTemplate(|| li().text("alpha")))
+            .fill(ItemTemplate(|| li().text("beta")))
+            .show_in(ctx);
+    }
+}
+
+#[test]
+fn static_template_instances_at_one_call_site_keep_their_own_markup() {
+    for _ in 0..2 {
+        let holder = make_holder().mount(ItemListWidget);
+        let html = render_html(&holder);
+        assert!(html.contains(">alpha</li>"), "{html}");
+        assert!(html.contains(">beta</li>"), "{html}");
+    }
+}
+
+fn reactive_chrome() -> impl Widget {
+    let label = CHROME_LABEL.get().expect("label set by the test");
+    div().fill(li().text(label))
+}
+
+#[derive(Debug)]
+struct ReactiveChromeWidget;
+
+impl Widget for ReactiveChromeWidget {
+    fn build(&mut self, ctx: &mut Scope) {
+        StaticTemplate::new(reactive_chrome).show_in(ctx);
+    }
+}
+
+#[test]
+fn static_template_with_reactive_parts_renders_normally() {
+    let label = Cage::new("first".to_string());
+    CHROME_LABEL.set(Some(label));
+    let holder = make_holder().mount(ReactiveChromeWidget);
+    assert!(render_html(&holder).contains(">first</li>"));
+
+    label.revise(|mut v| *v = "second".into());
+    let html = render_html(&holder);
+    assert!(html.contains(">second</li>"), "{html}");
+}
@@ -0,0 +1,239 @@
+//! Static templates: element chains compiled to HTML once per process.
+//!
+//! On the server, the first render of a [`StaticTemplate`] builds its element
+//! chain into a scratch command queue, checks that every view in it is a
+//! plain element with no reactive attributes, and caches the resulting markup
+//! keyed by the chain's build function. The function captures nothing, so
+//! every call builds the same markup. Every later render emits just the root element
+//! with the cached markup spliced in as its inner HTML, so the command stream
+//! carries a handful of commands instead of the whole subtree. The `gly-id`s
+//! inside the cached markup are rewritten to the view path of the current
+//! mount, so hydration finds exactly the nodes a normal render would produce.
+//!
+//! On the client, and for chains that turn out to be dynamic, the element is
+//! built normally.
+
+use std::fmt;
+use std::panic::Location;
+use std::rc::Rc;
+
+use crate::{Scope, Widget};
+
+pub struct StaticTemplate {
+    location: &'static Location<'static>,
+    /// Address of the build function, which keys the compiled markup.
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    key: usize,
+    show: Rc<dyn Fn(&mut Scope)>,
+    /// Rendered from the compiled markup rather than built.
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    compiled: bool,
+}
+
+impl fmt::Debug for StaticTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticTemplate").field("location", &self.location).finish()
+    }
+}
+
+impl StaticTemplate {
+    /// Wraps the element chain `build` returns. `build` is a plain function
+    /// or a non-capturing closure, so it cannot carry per-instance or
+    /// per-request data into the shared markup. Chains that use reactive
+    /// values are detected and rendered normally.
+    #[track_caller]
+    pub fn new<W>(build: fn() -> W) -> Self
+    where
+        W: Widget,
+    {
+        Self {
+            location: Location::caller(),
+            #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+            key: build as usize,
+            show: Rc::new(move |ctx| {
+                build().show_in(ctx);
+            }),
+            #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+            compiled: false,
+        }
+    }
+}
+
+impl Widget for StaticTemplate {
+    fn build(&mut self, ctx: &mut Scope) {
+        #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+        if ctx.truck().contains::<crate::web::ServerResponseOptions>()
+            && let Some(template) = server::compiled(self.key, self.location, ctx.view_id(), &self.show)
+        {
+            template.render(ctx);
+            self.compiled = true;
+            return;
+        }
+        (self.show)(ctx);
+    }
+
+    #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+    fn can_fill_compact(&self) -> bool {
+        self.compiled
+    }
+}
+
+#[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
+mod server {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::panic::Location;
+    use std::rc::Rc;
+    use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+    use crate::renderer::ssr_dom::SsrDocument;
+    use crate::renderer::{BackendRenderer, CommandQueue, Renderer};
+    use crate::view::View;
+    use crate::web::ServerResponseOptions;
+    use crate::web::widgets::Element;
+    use crate::{Scope, Truck, ViewId, Widget};
+
+    type Templates = HashMap<usize, Option<Arc<Template>>>;
+
+    /// Compiled templates by build function; `None` marks a dynamic chain.
+    static TEMPLATES: LazyLock<Mutex<Templates>> = LazyLock::new(Default::default);
+
+    /// The compiled template for the build function `key`, compiling it on
+    /// first use.
+    pub(super) fn compiled(key: usize, location: &'static Location<'static>, at: &ViewId, show: &Rc<dyn Fn(&mut Scope)>) -> Option<Arc<Template>> {
+        if let Some(template) = TEMPLATES.lock().unwrap_or_else(PoisonError::into_inner).get(&key) {
+            return template.clone();
+        }
+        // Compile without holding the lock: templates may nest.
+        let template = Template::compile(at, show).map(Arc::new);
+        if template.is_none() {
+            crate::debug_warn!("[static_template]: {location} has reactive parts; it renders normally");
+        }
+        TEMPLATES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_insert(template)
+            .clone()
+    }
+
+    /// Markup split at every `gly-id` so it can be re-rooted at a view path.
+    #[derive(Debug)]
+    struct Spliced {
+        parts: Vec<String>,
+    }
+
+    impl Spliced {
+        fn new(html: &str, root: &ViewId) -> Self {
+            Self {
+                parts: html.split(&Self::marker(root)).map(str::to_owned).collect(),
+            }
+        }
+
+        fn marker(root: &str) -> String {
+            format!(" gly-id=\"{root}")
+        }
+
+        fn splice(&self, root: &ViewId) -> String {
+            self.parts.join(&Self::marker(root))
+        }
+    }
+
+    /// A compiled element chain: the root element's own attributes plus its
+    /// children's markup.
+    #[derive(Debug)]
+    pub(super) struct Template {
+        name: String,
+        is_void: bool,
+        attributes: Vec<(String, String)>,
+        properties: Vec<(String, Option<String>)>,
+        inner: Option<Spliced>,
+    }
+
+    impl Template {
+        /// Renders the chain into a scratch command queue, outside the view
+        /// tree; `None` when any view in it is not a static element.
+        ///
+        /// The scratch views take the ids the real render at `at` will use,
+        /// so a reactive value bound while compiling subscribes the same
+        /// view, in the same holder, that ends up rendering it.
+        fn compile(at: &ViewId, show: &Rc<dyn Fn(&mut Scope)>) -> Option<Self> {
+            let queue = CommandQueue::new();
+            let _guard = queue.make_current();
+            let host = BackendRenderer::default().create_element("div".into(), false);
+            let mut truck = Truck::new();
+            // Lets templates nested in this one compile too.
+            truck.inject(ServerResponseOptions::default());
+            let root_id = at.clone();
+            let mut root = View::new(Scope::new_root(root_id.clone(), Rc::new(RefCell::new(truck))), Capture(show.clone()));
+            root.scope.parent_node = Some(host.clone());
+            root.scope.render_node = Some(host.clone());
+            root.attach();
+            if root.scope.child_views.len() != 1 || !root.scope.child_views.values().all(is_static) {
+                return None;
+            }
+
+            let document = SsrDocument::replay(&queue.commands());
+            let [element] = &document.node(host.id())?.children()[..] else {
+                return None;
+            };
+            if element.has_classes() {
+                return None;
+            }
+            let has_children = !element.children().is_empty();
+            let attributes = element
+                .attributes()
+                .into_iter()
+                .filter(|(name, _)| name != "gly-id" && !(has_children && (name == "inner_html" || name == "inner_text")))
+                .collect();
+            let properties = element
+                .properties()
+                .into_iter()
+                .filter(|(name, _)| !(has_children && name == "text"))
+                .collect();
+            Some(Self {
+                name: element.name(),
+                is_void: element.is_void(),
+                attributes,
+                properties,
+                inner: has_children.then(|| Spliced::new(&element.inner_html(), &root_id)),
+            })
+        }
+
+        /// Shows the root element under `ctx`, where the chain itself would
+        /// have gone; the element adds its own `gly-id`.
+        pub(super) fn render(&self, ctx: &mut Scope) {
+            let mut element = Element::new(self.name.clone(), self.is_void);
+            for (name, value) in &self.attributes {
+                element.add_attr(name.clone(), value.clone());
+            }
+            for (name, value) in &self.properties {
+                element.add_prop(name.clone(), value.clone());
+            }
+            if let Some(inner) = &self.inner {
+                element.set_html(inner.splice(ctx.view_id()));
+            }
+            element.show_in(ctx);
+        }
+    }
+
+    fn is_static(view: &View) -> bool {
+        view.widget.can_fill_compact() && view.scope.child_views.values().all(is_static)
+    }
+
+    /// Root of the scratch render, standing in for the `StaticTemplate` view
+    /// so the chain gets the same relative view paths.
+    struct Capture(Rc<dyn Fn(&mut Scope)>);
+
+    impl std::fmt::Debug for Capture {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Capture").finish()
+        }
+    }
+
+    impl Widget for Capture {
+        fn build(&mut self, ctx: &mut Scope) {
+            (self.0)(ctx);
+        }
+    }
+}
@@ -87,50 +87,26 @@ impl Suspense {
     /// resolved body. No-op for every other render path.
     #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
     fn stream_setup(&mut self, ctx: &mut Scope) {
-        use crate::renderer::{BackendRenderer, Renderer};
-
         if !crate::stream_ssr::is_streaming() {
             return;
         }
 
-        let renderer = BackendRenderer::default();
-        let wrapper = renderer.create_element("glory-suspense".into(), false);
         let placeholder_id = crate::stream_ssr::next_placeholder_id();
-        renderer.set_attribute(&wrapper, "data-glory-suspense".into(), placeholder_id.clone().into());
-        let wrapper_id = wrapper.id();
-
-        // The boundary region lives under the wrapper; children attach here.
-        ctx.render_node = Some(wrapper.clone());
-        ctx.first_child_node = Some(wrapper.clone());
-        ctx.last_child_node = Some(wrapper.clone());
-
+        let wrapper = crate::stream_ssr::create_wrapper(ctx, "glory-suspense", "data-glory-suspense", placeholder_id.clone());
         crate::stream_ssr::register_boundary(crate::stream_ssr::BoundaryRegistration {
             placeholder_id,
-            wrapper_id,
+            wrapper_id: wrapper.id(),
             boundary: self.boundary,
+            error_boundary: ctx.error_boundary.clone(),
         });
         self.wrapper = Some(wrapper);
     }
 
-    /// Streaming SSR only: place the wrapper under the boundary's parent,
-    /// honouring this view's sibling placement (mirrors `Element::flood`).
+    /// Streaming SSR only: place the wrapper under the boundary's parent.
     #[cfg(all(not(feature = "single-app"), feature = "web-ssr", not(target_arch = "wasm32")))]
     fn stream_insert_wrapper(&self, ctx: &mut Scope) {
-        use crate::renderer::{BackendRenderer, InsertPosition, Renderer};
-        use crate::view::ViewPlacement;
-
-        let Some(wrapper) = self.wrapper.as_ref() else {
-            return;
-        };
-        let Some(parent_node) = ctx.parent_node.as_ref() else {
-            return;
-        };
-        let renderer = BackendRenderer::default();
-        match &ctx.placement {
-            ViewPlacement::Head => renderer.insert_child(parent_node, wrapper, InsertPosition::Head),
-            ViewPlacement::Before(next_node) => renderer.insert_child(parent_node, wrapper, InsertPosition::Before(next_node)),
-            ViewPlacement::After(prev_node) => renderer.insert_child(parent_node, wrapper, InsertPosition::After(prev_node)),
-            ViewPlacement::Tail | ViewPlacement::Unset => renderer.insert_child(parent_node, wrapper, InsertPosition::Tail),
+        if let Some(wrapper) = self.wrapper.as_ref() {
+            crate::stream_ssr::insert_wrapper(ctx, wrapper);
         }
     }
 
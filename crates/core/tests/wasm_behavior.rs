@@ -34,7 +34,8 @@
 use glory_core::reflow::{Bond, Cage};
 use glory_core::web::events;
 use glory_core::web::holders::BrowserHolder;
-use glory_core::web::widgets::{button, div, li, span, ul};
+use glory_core::web::hydration::MismatchKind;
+use glory_core::web::widgets::{button, div, li, p, span, ul};
 use glory_core::widgets::switch::Case;
 use glory_core::widgets::{Each, Switch};
 use glory_core::{Holder, Scope, Widget};
@@ -538,3 +539,61 @@ fn event_driven_switch_swaps_subtree() {
 
     teardown(&host);
 }
+
+// ===========================================================================
+// 16. Hydration: mismatched server markup is reported and recovered
+// ===========================================================================
+
+#[derive(Debug)]
+struct HydratedClockWidget;
+impl Widget for HydratedClockWidget {
+    fn build(&mut self, ctx: &mut Scope) {
+        div()
+            .attr("data-zone", "client")
+            .fill(span().id("hc-time").text("client time"))
+            .fill(p().id("hc-note").text("note"))
+            .show_in(ctx);
+    }
+}
+
+#[wasm_bindgen_test]
+fn hydration_mismatch_client_renders_only_the_diverging_subtree() {
+    let document = glory_core::web::document();
+    let host = document.create_element("div").unwrap_throw();
+    host.set_inner_html(r#"<div gly-id="0-0" data-zone="server"><em gly-id="0-0-0">server time</em><p gly-id="0-0-1" id="hc-note">note</p></div>"#);
+    document.body().unwrap_throw().append_child(&host).unwrap_throw();
+    let server_note = host.query_selector("#hc-note").unwrap_throw().unwrap_throw();
+    glory_core::web::hydration::take_mismatches();
+
+    let _holder = BrowserHolder::with_host_node(&host).mount(HydratedClockWidget);
+
+    let mismatches = glory_core::web::hydration::take_mismatches();
+    let tag = mismatches.iter().find(|m| m.kind == MismatchKind::Tag).expect("tag mismatch reported");
+    assert_eq!(&*tag.view_id, "0-0-0");
+    assert_eq!(tag.expected.as_deref(), Some("span"));
+    assert_eq!(tag.found.as_deref(), Some("em"));
+    assert!(tag.recovered);
+    assert!(tag.widget.ends_with("Span"), "{}", tag.widget);
+    assert!(
+        mismatches
+            .iter()
+            .any(|m| m.kind == MismatchKind::Attribute("data-zone".into()) && !m.recovered)
+    );
+
+    // The stale <em> is replaced in place; its sibling is still the adopted
+    // server node, and attributes heal on the first build.
+    assert!(host.query_selector("em").unwrap_throw().is_none());
+    assert_eq!(text_of(&host, "#hc-time").as_deref(), Some("client time"));
+    let time = require(&host, "#hc-time");
+    assert_eq!(time.next_element_sibling().map(|el| el.id()).as_deref(), Some("hc-note"));
+    assert!(
+        host.query_selector("#hc-note")
+            .unwrap_throw()
+            .unwrap_throw()
+            .is_same_node(Some(&server_note))
+    );
+    assert_eq!(require(&host, "div").get_attribute("data-zone").as_deref(), Some("client"));
+    assert!(host.query_selector("[gly-id], [gly-client-rendered]").unwrap_throw().is_none());
+
+    teardown(&host);
+}
@@ -0,0 +1,473 @@
+//! Native open/save/message dialogs.
+//!
+//! [`DesktopWindowHandle`](crate::DesktopWindowHandle) queues a dialog onto
+//! the event loop, which shows it parented to the handle's window through the
+//! window's [`DesktopDialogProvider`] (the platform dialogs unless
+//! [`DesktopConfig::with_dialog_provider`](crate::DesktopConfig::with_dialog_provider)
+//! installs another). A worker thread waits for the answer and sends it back
+//! to the event-loop thread, where the future returned by the handle resolves.
+//!
+//! Providers are plain trait objects, so tests script answers without a
+//! display:
+//!
+//! ```ignore
+//! struct Scripted;
+//! impl DesktopDialogProvider for Scripted {
+//!     fn open_file(&self, _: DesktopDialogParent<'_>, _: DesktopFileDialog) -> DesktopDialogFuture<Vec<PathBuf>> {
+//!         Box::pin(std::future::ready(vec![PathBuf::from("/tmp/report.csv")]))
+//!     }
+//!     // ...
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use futures_channel::oneshot;
+use raw_window_handle::{DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle};
+
+/// Answer of a [`DesktopDialogProvider`]. Must not borrow the parent: the
+/// host waits for it on a worker thread.
+pub type DesktopDialogFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Shows native dialogs for a window.
+///
+/// Called on the event-loop thread. A provider should only create the dialog
+/// there and do its waiting inside the returned future.
+pub trait DesktopDialogProvider {
+    /// Resolves with the picked files; empty when cancelled.
+    fn open_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Vec<PathBuf>>;
+    /// Resolves with the chosen path; `None` when cancelled.
+    fn save_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Option<PathBuf>>;
+    fn message(&self, parent: DesktopDialogParent<'_>, dialog: DesktopMessageDialog) -> DesktopDialogFuture<DesktopMessageResult>;
+}
+
+/// The window a dialog belongs to, as raw window and display handles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DesktopDialogParent<'a> {
+    window: Option<WindowHandle<'a>>,
+    display: Option<DisplayHandle<'a>>,
+}
+
+impl<'a> DesktopDialogParent<'a> {
+    pub fn new<W: HasWindowHandle + HasDisplayHandle>(window: &'a W) -> Self {
+        Self {
+            window: window.window_handle().ok(),
+            display: window.display_handle().ok(),
+        }
+    }
+
+    /// A parent without a window; the dialog is shown unparented.
+    pub fn detached() -> Self {
+        Self::default()
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.window.is_some()
+    }
+}
+
+impl HasWindowHandle for DesktopDialogParent<'_> {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.window.ok_or(HandleError::Unavailable)
+    }
+}
+
+impl HasDisplayHandle for DesktopDialogParent<'_> {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        self.display.ok_or(HandleError::Unavailable)
+    }
+}
+
+/// Options of an open or save file dialog.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DesktopFileDialog {
+    pub title: Option<String>,
+    /// Directory the dialog starts in.
+    pub directory: Option<PathBuf>,
+    /// Suggested file name of a save dialog.
+    pub file_name: Option<String>,
+    pub filters: Vec<DesktopFileFilter>,
+    /// Lets an open dialog pick several files.
+    pub multiple: bool,
+}
+
+impl DesktopFileDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Adds a filter named `name` matching `extensions` (without the dot).
+    pub fn with_filter(mut self, name: impl Into<String>, extensions: &[&str]) -> Self {
+        self.filters.push(DesktopFileFilter {
+            name: name.into(),
+            extensions: extensions.iter().map(|extension| (*extension).to_owned()).collect(),
+        });
+        self
+    }
+
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopFileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+/// Options of a message dialog.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DesktopMessageDialog {
+    pub title: String,
+    pub description: String,
+    pub level: DesktopMessageLevel,
+    pub buttons: DesktopMessageButtons,
+}
+
+impl DesktopMessageDialog {
+    pub fn new(title: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_level(mut self, level: DesktopMessageLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_buttons(mut self, buttons: DesktopMessageButtons) -> Self {
+        self.buttons = buttons;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DesktopMessageLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DesktopMessageButtons {
+    #[default]
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+}
+
+/// The button that closed a message dialog. Closing it otherwise, or losing
+/// the event loop, gives `Cancel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DesktopMessageResult {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+/// A dialog queued from a window handle to the event loop.
+#[derive(Clone, Debug)]
+pub(crate) enum DialogRequest {
+    Open(DesktopFileDialog),
+    Save(DesktopFileDialog),
+    Message(DesktopMessageDialog),
+}
+
+impl DialogRequest {
+    pub(crate) fn show(self, provider: &dyn DesktopDialogProvider, parent: DesktopDialogParent<'_>) -> DesktopDialogFuture<DialogAnswer> {
+        match self {
+            DialogRequest::Open(dialog) => {
+                let files = provider.open_file(parent, dialog);
+                Box::pin(async move { DialogAnswer::Files(files.await) })
+            }
+            DialogRequest::Save(dialog) => {
+                let file = provider.save_file(parent, dialog);
+                Box::pin(async move { DialogAnswer::File(file.await) })
+            }
+            DialogRequest::Message(dialog) => {
+                let result = provider.message(parent, dialog);
+                Box::pin(async move { DialogAnswer::Message(result.await) })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DialogAnswer {
+    Files(Vec<PathBuf>),
+    File(Option<PathBuf>),
+    Message(DesktopMessageResult),
+}
+
+/// What each kind of dialog resolves to. A missing answer (the dialog never
+/// ran) reads as cancelled.
+impl DialogAnswer {
+    pub(crate) fn into_files(answer: Option<Self>) -> Vec<PathBuf> {
+        match answer {
+            Some(DialogAnswer::Files(files)) => files,
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_file(answer: Option<Self>) -> Option<PathBuf> {
+        match answer {
+            Some(DialogAnswer::File(file)) => file,
+            _ => None,
+        }
+    }
+
+    pub(crate) fn into_message(answer: Option<Self>) -> DesktopMessageResult {
+        match answer {
+            Some(DialogAnswer::Message(result)) => result,
+            _ => DesktopMessageResult::Cancel,
+        }
+    }
+}
+
+/// Dialogs waiting for an answer, owned by the event-loop thread. Same shape
+/// as the `eval` registry: the handle registers a sender under a fresh id and
+/// the answer coming back through the event loop resolves it.
+#[derive(Default)]
+pub(crate) struct PendingDialogs {
+    next_id: u64,
+    pending: HashMap<u64, oneshot::Sender<DialogAnswer>>,
+}
+
+impl PendingDialogs {
+    pub(crate) fn register(&mut self) -> (u64, impl Future<Output = Option<DialogAnswer>> + 'static) {
+        let id = self.next_id;
+        self.next_id = id.wrapping_add(1);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.insert(id, sender);
+        (id, async move { receiver.await.ok() })
+    }
+
+    /// Resolves `id`. A caller that stopped waiting is tolerated.
+    pub(crate) fn resolve(&mut self, id: u64, answer: DialogAnswer) {
+        if let Some(sender) = self.pending.remove(&id) {
+            let _ = sender.send(answer);
+        }
+    }
+
+    /// Drops `id` unanswered, which resolves it as cancelled.
+    pub(crate) fn cancel(&mut self, id: u64) {
+        self.pending.remove(&id);
+    }
+}
+
+/// The platform dialogs, through `rfd`.
+#[cfg(feature = "runtime")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DesktopNativeDialogs;
+
+#[cfg(feature = "runtime")]
+impl DesktopNativeDialogs {
+    fn file_dialog(parent: DesktopDialogParent<'_>, dialog: &DesktopFileDialog) -> rfd::AsyncFileDialog {
+        let mut builder = rfd::AsyncFileDialog::new().set_parent(&parent);
+        if let Some(title) = &dialog.title {
+            builder = builder.set_title(title);
+        }
+        if let Some(directory) = &dialog.directory {
+            builder = builder.set_directory(directory);
+        }
+        if let Some(file_name) = &dialog.file_name {
+            builder = builder.set_file_name(file_name);
+        }
+        for filter in &dialog.filters {
+            builder = builder.add_filter(&filter.name, filter.extensions.as_slice());
+        }
+        builder
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl DesktopDialogProvider for DesktopNativeDialogs {
+    fn open_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Vec<PathBuf>> {
+        let builder = Self::file_dialog(parent, &dialog);
+        if dialog.multiple {
+            let files = builder.pick_files();
+            Box::pin(async move { files.await.unwrap_or_default().iter().map(|file| file.path().to_owned()).collect() })
+        } else {
+            let file = builder.pick_file();
+            Box::pin(async move { file.await.map(|file| file.path().to_owned()).into_iter().collect() })
+        }
+    }
+
+    fn save_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Option<PathBuf>> {
+        let file = Self::file_dialog(parent, &dialog).save_file();
+        Box::pin(async move { file.await.map(|file| file.path().to_owned()) })
+    }
+
+    fn message(&self, parent: DesktopDialogParent<'_>, dialog: DesktopMessageDialog) -> DesktopDialogFuture<DesktopMessageResult> {
+        let level = match dialog.level {
+            DesktopMessageLevel::Info => rfd::MessageLevel::Info,
+            DesktopMessageLevel::Warning => rfd::MessageLevel::Warning,
+            DesktopMessageLevel::Error => rfd::MessageLevel::Error,
+        };
+        let buttons = match dialog.buttons {
+            DesktopMessageButtons::Ok => rfd::MessageButtons::Ok,
+            DesktopMessageButtons::OkCancel => rfd::MessageButtons::OkCancel,
+            DesktopMessageButtons::YesNo => rfd::MessageButtons::YesNo,
+            DesktopMessageButtons::YesNoCancel => rfd::MessageButtons::YesNoCancel,
+        };
+        let result = rfd::AsyncMessageDialog::new()
+            .set_parent(&parent)
+            .set_title(dialog.title)
+            .set_description(dialog.description)
+            .set_level(level)
+            .set_buttons(buttons)
+            .show();
+        Box::pin(async move {
+            match result.await {
+                rfd::MessageDialogResult::Ok | rfd::MessageDialogResult::Custom(_) => DesktopMessageResult::Ok,
+                rfd::MessageDialogResult::Cancel => DesktopMessageResult::Cancel,
+                rfd::MessageDialogResult::Yes => DesktopMessageResult::Yes,
+                rfd::MessageDialogResult::No => DesktopMessageResult::No,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// Answers from a script and records what it was asked.
+    #[derive(Default)]
+    struct Scripted {
+        files: RefCell<Vec<Vec<PathBuf>>>,
+        asked: RefCell<Vec<(bool, String)>>,
+    }
+
+    impl DesktopDialogProvider for Scripted {
+        fn open_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Vec<PathBuf>> {
+            self.asked.borrow_mut().push((parent.is_attached(), format!("open {:?}", dialog.title)));
+            let files = self.files.borrow_mut().remove(0);
+            Box::pin(std::future::ready(files))
+        }
+
+        fn save_file(&self, parent: DesktopDialogParent<'_>, dialog: DesktopFileDialog) -> DesktopDialogFuture<Option<PathBuf>> {
+            self.asked
+                .borrow_mut()
+                .push((parent.is_attached(), format!("save {:?}", dialog.file_name)));
+            let file = dialog.directory.zip(dialog.file_name).map(|(directory, name)| directory.join(name));
+            Box::pin(std::future::ready(file))
+        }
+
+        fn message(&self, parent: DesktopDialogParent<'_>, dialog: DesktopMessageDialog) -> DesktopDialogFuture<DesktopMessageResult> {
+            self.asked.borrow_mut().push((parent.is_attached(), format!("message {}", dialog.title)));
+            let result = match dialog.buttons {
+                DesktopMessageButtons::YesNo | DesktopMessageButtons::YesNoCancel => DesktopMessageResult::Yes,
+                _ => DesktopMessageResult::Ok,
+            };
+            Box::pin(std::future::ready(result))
+        }
+    }
+
+    #[test]
+    fn file_dialog_builders_collect_options() {
+        let dialog = DesktopFileDialog::new()
+            .with_title("Import")
+            .with_directory("/home/me")
+            .with_file_name("data.csv")
+            .with_filter("Tables", &["csv", "tsv"])
+            .with_multiple(true);
+        assert_eq!(dialog.title.as_deref(), Some("Import"));
+        assert_eq!(dialog.directory, Some(PathBuf::from("/home/me")));
+        assert_eq!(dialog.file_name.as_deref(), Some("data.csv"));
+        assert_eq!(
+            dialog.filters,
+            vec![DesktopFileFilter {
+                name: "Tables".to_owned(),
+                extensions: vec!["csv".to_owned(), "tsv".to_owned()],
+            }]
+        );
+        assert!(dialog.multiple);
+
+        let message = DesktopMessageDialog::new("Delete", "Delete 3 files?").with_buttons(DesktopMessageButtons::YesNo);
+        assert_eq!(message.level, DesktopMessageLevel::Info);
+        assert_eq!(message.buttons, DesktopMessageButtons::YesNo);
+    }
+
+    #[test]
+    fn scripted_providers_answer_requests_without_a_display() {
+        let provider = Scripted::default();
+        provider.files.borrow_mut().push(vec![PathBuf::from("/a.txt"), PathBuf::from("/b.txt")]);
+
+        let open = DialogRequest::Open(DesktopFileDialog::new().with_title("Pick")).show(&provider, DesktopDialogParent::detached());
+        let save = DialogRequest::Save(DesktopFileDialog::new().with_directory("/out").with_file_name("report.pdf"))
+            .show(&provider, DesktopDialogParent::detached());
+        let message = DialogRequest::Message(DesktopMessageDialog::new("Quit", "Quit now?").with_buttons(DesktopMessageButtons::YesNo))
+            .show(&provider, DesktopDialogParent::detached());
+
+        assert_eq!(
+            futures::executor::block_on(open),
+            DialogAnswer::Files(vec![PathBuf::from("/a.txt"), PathBuf::from("/b.txt")])
+        );
+        assert_eq!(
+            futures::executor::block_on(save),
+            DialogAnswer::File(Some(PathBuf::from("/out/report.pdf")))
+        );
+        assert_eq!(futures::executor::block_on(message), DialogAnswer::Message(DesktopMessageResult::Yes));
+        assert_eq!(
+            *provider.asked.borrow(),
+            vec![
+                (false, "open Some(\"Pick\")".to_owned()),
+                (false, "save Some(\"report.pdf\")".to_owned()),
+                (false, "message Quit".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_dialogs_resolve_once_and_read_as_cancelled_when_dropped() {
+        let mut pending = PendingDialogs::default();
+        let (open, answered) = pending.register();
+        let (save, cancelled) = pending.register();
+        assert_ne!(open, save);
+
+        pending.resolve(open, DialogAnswer::Files(vec![PathBuf::from("/a.txt")]));
+        pending.resolve(open, DialogAnswer::Files(Vec::new()));
+        pending.cancel(save);
+
+        let files = DialogAnswer::into_files(futures::executor::block_on(answered));
+        assert_eq!(files, vec![PathBuf::from("/a.txt")]);
+        let answer = futures::executor::block_on(cancelled);
+        assert_eq!(answer, None);
+        assert_eq!(DialogAnswer::into_file(answer.clone()), None);
+        assert_eq!(DialogAnswer::into_message(answer), DesktopMessageResult::Cancel);
+
+        let (late, receiver) = pending.register();
+        drop(receiver);
+        pending.resolve(late, DialogAnswer::Message(DesktopMessageResult::Ok));
+        assert!(pending.pending.is_empty());
+    }
+}
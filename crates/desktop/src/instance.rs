@@ -0,0 +1,427 @@
+//! Single-instance enforcement and deep links.
+//!
+//! The first launch of an app takes a [`DesktopInstanceLock`]: an OS file
+//! lock (`flock` / `LockFileEx`) on a lock file that records a local socket
+//! it listens on, plus a random token. Later launches find the file locked,
+//! send their arguments over the socket as a [`DesktopLaunchEvent`] and exit.
+//! The OS releases the lock when the holder exits or crashes, so there is no
+//! stale lock to clean up.
+//!
+//! The socket is a Unix domain socket beside the lock file on Unix, and a
+//! loopback TCP port on Windows. The lock file and the socket are readable by
+//! the current user only, so other users can't read the token.
+//!
+//! Arguments that are URLs with a registered scheme, like
+//! `myapp://project/42`, become [`DesktopDeepLink`]s. Their
+//! [`path`](DesktopDeepLink::path) feeds a glory-routing `Aviator`.
+
+use std::fs::{File, TryLockError};
+use std::io::{BufRead, BufReader, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How long a launch waits for the running instance to answer.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often a launch retries a running instance that hasn't started
+/// listening yet.
+const FORWARD_ATTEMPTS: u32 = 10;
+
+/// A launch of the app, as seen by the instance that was already running.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopLaunchEvent {
+    /// The launch's arguments, without the executable.
+    pub args: Vec<String>,
+    /// The launch's working directory, to resolve relative paths in `args`.
+    pub cwd: Option<PathBuf>,
+}
+
+impl DesktopLaunchEvent {
+    /// This process's arguments and working directory.
+    pub fn current() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            cwd: std::env::current_dir().ok(),
+        }
+    }
+}
+
+/// A URL with one of the app's schemes, e.g. `myapp://project/42?tab=files`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopDeepLink {
+    pub url: String,
+    /// The scheme, lowercased.
+    pub scheme: String,
+}
+
+impl DesktopDeepLink {
+    /// `url` as a deep link when its scheme is one of `schemes`, compared
+    /// case-insensitively.
+    pub fn parse(url: &str, schemes: &[String]) -> Option<Self> {
+        let (scheme, _) = url.split_once(':')?;
+        schemes.iter().find(|candidate| candidate.eq_ignore_ascii_case(scheme)).map(|_| Self {
+            url: url.to_owned(),
+            scheme: scheme.to_ascii_lowercase(),
+        })
+    }
+
+    /// The link as an app path for an `Aviator`: `myapp://project/42?tab=1`
+    /// and `myapp:///project/42?tab=1` both give `/project/42?tab=1`.
+    pub fn path(&self) -> String {
+        let rest = &self.url[self.scheme.len() + 1..];
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+        if rest.starts_with('/') { rest.to_owned() } else { format!("/{rest}") }
+    }
+}
+
+/// The deep links among `args`, in order.
+pub(crate) fn deep_links(args: &[String], schemes: &[String]) -> Vec<DesktopDeepLink> {
+    args.iter().filter_map(|arg| DesktopDeepLink::parse(arg, schemes)).collect()
+}
+
+/// The result of [`DesktopInstanceLock::acquire`].
+#[derive(Debug)]
+pub enum DesktopInstance {
+    /// No other instance runs; this process holds the lock.
+    Primary(DesktopInstanceLock),
+    /// Another instance runs and received this launch.
+    Secondary,
+}
+
+/// What the lock file records.
+#[derive(Debug, Serialize, Deserialize)]
+struct LockRecord {
+    /// The socket path on Unix, the loopback address elsewhere.
+    endpoint: String,
+    token: String,
+}
+
+/// What a later launch sends to the running instance.
+#[derive(Debug, Serialize, Deserialize)]
+struct Forward {
+    token: String,
+    launch: DesktopLaunchEvent,
+}
+
+/// The single-instance lock held by the running instance.
+#[derive(Debug)]
+pub struct DesktopInstanceLock {
+    path: PathBuf,
+    /// Holds the OS lock until dropped.
+    file: File,
+    listener: transport::Listener,
+    token: String,
+}
+
+impl DesktopInstanceLock {
+    /// Takes the lock at `path`, or hands `launch` to the instance holding it.
+    pub fn acquire(path: impl Into<PathBuf>, launch: &DesktopLaunchEvent) -> std::io::Result<DesktopInstance> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = open_private(&path)?;
+        match file.try_lock() {
+            Ok(()) => {
+                let (listener, endpoint) = transport::bind(&path)?;
+                let token = random_token();
+                let record = LockRecord {
+                    endpoint,
+                    token: token.clone(),
+                };
+                file.set_len(0)?;
+                file.rewind()?;
+                file.write_all(&serde_json::to_vec(&record).map_err(std::io::Error::other)?)?;
+                file.sync_data()?;
+                Ok(DesktopInstance::Primary(Self { path, file, listener, token }))
+            }
+            Err(TryLockError::WouldBlock) => {
+                // The holder writes its record right after taking the lock;
+                // give a launch racing it a moment to start listening.
+                let mut last_error = None;
+                for attempt in 0..FORWARD_ATTEMPTS {
+                    if attempt > 0 {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    match read_record(&mut file).and_then(|record| forward(&record, launch)) {
+                        Ok(()) => return Ok(DesktopInstance::Secondary),
+                        Err(err) => last_error = Some(err),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| std::io::Error::other("the running instance did not answer")))
+            }
+            Err(TryLockError::Error(err)) => Err(err),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Answers later launches on a background thread, calling `on_launch`
+    /// with each one. The lock is held until the process exits.
+    pub fn listen(self, on_launch: impl Fn(DesktopLaunchEvent) + Send + 'static) {
+        std::thread::spawn(move || {
+            for stream in self.listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                match receive(stream, &self.token) {
+                    Ok(launch) => on_launch(launch),
+                    Err(err) => tracing::warn!(%err, "glory-desktop: rejected a launch forwarded to this instance"),
+                }
+            }
+        });
+    }
+}
+
+impl Drop for DesktopInstanceLock {
+    fn drop(&mut self) {
+        // The lock file stays: removing it while locked would let a later
+        // launch lock a fresh file while another waits on the old one.
+        transport::unbind(&self.path);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Opens (or creates) `path` for reading and writing, readable by the
+/// current user only where the platform allows.
+fn open_private(path: &Path) -> std::io::Result<File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(path)?;
+    // The mode only applies to new files; tighten one left by an older build.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    Ok(file)
+}
+
+fn read_record(file: &mut File) -> std::io::Result<LockRecord> {
+    let mut contents = Vec::new();
+    file.rewind()?;
+    file.read_to_end(&mut contents)?;
+    serde_json::from_slice(&contents).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+fn forward(record: &LockRecord, launch: &DesktopLaunchEvent) -> std::io::Result<()> {
+    let mut stream = transport::connect(&record.endpoint)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.set_write_timeout(Some(FORWARD_TIMEOUT))?;
+    let message = Forward {
+        token: record.token.clone(),
+        launch: launch.clone(),
+    };
+    let mut line = serde_json::to_vec(&message).map_err(std::io::Error::other)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer)?;
+    if answer.trim_end() == "ok" {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the running instance refused the launch",
+        ))
+    }
+}
+
+fn receive(mut stream: transport::Stream, token: &str) -> std::io::Result<DesktopLaunchEvent> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let message: Forward = serde_json::from_str(&line).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    if message.token != token {
+        return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "wrong instance token"));
+    }
+    stream.write_all(b"ok\n")?;
+    Ok(message.launch)
+}
+
+/// 128 random bits from the operating system, hex encoded.
+fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("the operating system provides randomness");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// A Unix domain socket beside the lock file.
+#[cfg(unix)]
+mod transport {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+
+    pub(super) type Listener = UnixListener;
+    pub(super) type Stream = UnixStream;
+
+    fn socket_path(lock: &Path) -> PathBuf {
+        lock.with_extension("sock")
+    }
+
+    /// Called with the lock held, so a leftover socket file is stale.
+    pub(super) fn bind(lock: &Path) -> std::io::Result<(Listener, String)> {
+        let path = socket_path(lock);
+        match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        let listener = UnixListener::bind(&path)?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        let endpoint = path
+            .into_os_string()
+            .into_string()
+            .map_err(|_| std::io::Error::other("socket path is not UTF-8"))?;
+        Ok((listener, endpoint))
+    }
+
+    pub(super) fn unbind(lock: &Path) {
+        let _ = std::fs::remove_file(socket_path(lock));
+    }
+
+    pub(super) fn connect(endpoint: &str) -> std::io::Result<Stream> {
+        UnixStream::connect(endpoint)
+    }
+}
+
+/// A loopback TCP port; the lock file's per-user location keeps the
+/// token private.
+#[cfg(not(unix))]
+mod transport {
+    use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+    use std::path::Path;
+
+    pub(super) type Listener = TcpListener;
+    pub(super) type Stream = TcpStream;
+
+    pub(super) fn bind(_lock: &Path) -> std::io::Result<(Listener, String)> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let endpoint = listener.local_addr()?.to_string();
+        Ok((listener, endpoint))
+    }
+
+    pub(super) fn unbind(_lock: &Path) {}
+
+    pub(super) fn connect(endpoint: &str) -> std::io::Result<Stream> {
+        let address: SocketAddr = endpoint.parse().map_err(std::io::Error::other)?;
+        if !address.ip().is_loopback() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "instance endpoint is not local"));
+        }
+        TcpStream::connect_timeout(&address, super::FORWARD_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn schemes() -> Vec<String> {
+        vec!["myapp".to_owned()]
+    }
+
+    #[test]
+    fn deep_links_map_to_app_paths() {
+        let link = DesktopDeepLink::parse("myapp://project/42?tab=files", &schemes()).unwrap();
+        assert_eq!(link.scheme, "myapp");
+        assert_eq!(link.path(), "/project/42?tab=files");
+        assert_eq!(DesktopDeepLink::parse("MyApp:///project/42", &schemes()).unwrap().path(), "/project/42");
+        assert_eq!(DesktopDeepLink::parse("myapp:settings", &schemes()).unwrap().path(), "/settings");
+        assert_eq!(DesktopDeepLink::parse("myapp://", &schemes()).unwrap().path(), "/");
+
+        let args = ["--verbose", "https://glory.rs", "myapp://a", "notes.txt"].map(String::from);
+        let links = deep_links(&args, &schemes());
+        assert_eq!(links.iter().map(|link| link.url.as_str()).collect::<Vec<_>>(), vec!["myapp://a"]);
+    }
+
+    #[test]
+    fn later_launches_reach_the_running_instance() {
+        let dir = std::env::temp_dir().join(format!("glory-desktop-instance-{}", std::process::id()));
+        let path = dir.join("instance.lock");
+        let first = DesktopLaunchEvent { args: vec![], cwd: None };
+        let DesktopInstance::Primary(lock) = DesktopInstanceLock::acquire(&path, &first).unwrap() else {
+            panic!("the first launch must take the lock");
+        };
+        let (sender, receiver) = mpsc::channel();
+        lock.listen(move |launch| sender.send(launch).unwrap());
+
+        let second = DesktopLaunchEvent {
+            args: vec!["myapp://project/42".to_owned()],
+            cwd: Some(PathBuf::from("/home/me")),
+        };
+        assert!(matches!(
+            DesktopInstanceLock::acquire(&path, &second).unwrap(),
+            DesktopInstance::Secondary
+        ));
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), second);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn locks_left_by_exited_instances_are_taken_over() {
+        let dir = std::env::temp_dir().join(format!("glory-desktop-stale-instance-{}", std::process::id()));
+        let path = dir.join("instance.lock");
+        std::fs::create_dir_all(&dir).unwrap();
+        // An exited instance's record: the file is there but nobody locks it.
+        std::fs::write(&path, r#"{"endpoint":"gone","token":"gone"}"#).unwrap();
+
+        let DesktopInstance::Primary(lock) = DesktopInstanceLock::acquire(&path, &DesktopLaunchEvent::default()).unwrap() else {
+            panic!("an unlocked lock file must be taken over");
+        };
+        assert!(std::fs::read_to_string(&path).unwrap().contains(&lock.token));
+        assert_eq!(lock.token.len(), 32);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+            assert_eq!(
+                std::fs::metadata(path.with_extension("sock")).unwrap().permissions().mode() & 0o777,
+                0o600
+            );
+        }
+        drop(lock);
+        // Unlocked again, so the next launch is primary.
+        assert!(matches!(
+            DesktopInstanceLock::acquire(&path, &DesktopLaunchEvent::default()).unwrap(),
+            DesktopInstance::Primary(_)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn simultaneous_launches_elect_one_primary() {
+        let dir = std::env::temp_dir().join(format!("glory-desktop-racing-instance-{}", std::process::id()));
+        let path = dir.join("instance.lock");
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(8));
+        let launches: Vec<_> = (0..8)
+            .map(|_| {
+                let (path, barrier) = (path.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    match DesktopInstanceLock::acquire(&path, &DesktopLaunchEvent::default()) {
+                        Ok(DesktopInstance::Primary(lock)) => {
+                            lock.listen(|_| {});
+                            true
+                        }
+                        Ok(DesktopInstance::Secondary) => false,
+                        Err(err) => panic!("launch failed: {err}"),
+                    }
+                })
+            })
+            .collect();
+        let primaries = launches
+            .into_iter()
+            .map(|launch| launch.join().unwrap())
+            .filter(|primary| *primary)
+            .count();
+        assert_eq!(primaries, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
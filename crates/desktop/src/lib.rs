@@ -10,6 +10,16 @@
 //! - [`launch`] / [`launch_with_config`] (feature `runtime`, on by
 //!   default) — a tao + wry window host wiring webview IPC to a
 //!   [`CommandHolder`] transaction loop.
+//! - [`DesktopDialogProvider`] — native open/save/message dialogs behind a
+//!   trait, so tests script the answers.
+//! - [`DesktopServices`] — clipboard, notifications, open-URL and app data
+//!   directories, enabled on every window's holder.
+//! - [`DesktopStateStore`] — remembers labeled windows' size, position and
+//!   zoom, and the session's open windows, across runs.
+//! - [`DesktopInstanceLock`] — keeps one running instance and hands later
+//!   launches and their [`DesktopDeepLink`]s to it.
+//! - [`DesktopUpdater`] — checks an update host, downloads and verifies
+//!   signed installers, and installs them on the next start.
 //!
 //! ```ignore
 //! // requires features = ["runtime"]
@@ -101,6 +111,36 @@ pub enum IpcMessage {
     GloryWryEval { id: u64, ok: bool, value: String },
 }
 
+// The request plumbing is only driven by the `runtime` host.
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+mod dialog;
+#[cfg(feature = "runtime")]
+pub use dialog::DesktopNativeDialogs;
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+mod instance;
+pub use instance::{DesktopDeepLink, DesktopInstance, DesktopInstanceLock, DesktopLaunchEvent};
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+mod services;
+pub use dialog::{
+    DesktopDialogFuture, DesktopDialogParent, DesktopDialogProvider, DesktopFileDialog, DesktopFileFilter, DesktopMessageButtons,
+    DesktopMessageDialog, DesktopMessageLevel, DesktopMessageResult,
+};
+#[cfg(feature = "runtime")]
+pub use services::DesktopNativeServices;
+pub use services::{
+    DesktopAppDir, DesktopNotification, DesktopNotificationAction, DesktopNotificationEvent, DesktopNotificationResponder, DesktopServiceError,
+    DesktopServiceProvider, DesktopServices, MemoryServices,
+};
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+mod updater;
+pub use updater::{
+    DesktopStagedUpdate, DesktopUpdate, DesktopUpdateArtifact, DesktopUpdateError, DesktopUpdateManifest, DesktopUpdateProgress, DesktopUpdater,
+    current_target, signed_message,
+};
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+mod window_state;
+pub use window_state::{DesktopSavedState, DesktopSavedWindow, DesktopSessionWindow, DesktopStateStore};
+
 #[cfg(feature = "runtime")]
 mod runtime;
 #[cfg(feature = "runtime")]
@@ -15,8 +15,11 @@ use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Wake, Waker};
 
 use futures_channel::oneshot;
 
@@ -29,7 +32,57 @@ use tao::event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy, EventLoopWi
 use tao::window::{Fullscreen, Window, WindowBuilder, WindowId};
 use wry::{WebView, WebViewBuilder, WebViewId};
 
-use crate::IpcMessage;
+use crate::dialog::{DialogAnswer, DialogRequest, PendingDialogs};
+use crate::instance::deep_links;
+use crate::window_state::MonitorArea;
+use crate::{
+    DesktopAppDir, DesktopDeepLink, DesktopDialogParent, DesktopDialogProvider, DesktopFileDialog, DesktopInstance, DesktopInstanceLock,
+    DesktopLaunchEvent, DesktopMessageDialog, DesktopMessageResult, DesktopNativeDialogs, DesktopNativeServices, DesktopNotificationEvent,
+    DesktopSavedState, DesktopSavedWindow, DesktopServiceProvider, DesktopServices, DesktopSessionWindow, DesktopStateStore, DesktopUpdater,
+    IpcMessage,
+};
+
+/// How a launch goes on once the single-instance lock and any staged update
+/// are settled.
+#[derive(Debug)]
+enum InstanceStart {
+    /// Open the windows, holding the lock if one was taken.
+    Run(Option<DesktopInstanceLock>),
+    /// The launch went to the running instance, or an installer took over.
+    Exit,
+}
+
+/// Takes the single-instance lock at `lock_path`, then installs the update
+/// `updater` staged. A secondary launch only forwards itself to the running
+/// instance and never touches the update; the primary lets go of the lock
+/// before the installer starts so the updated app it launches can take it.
+fn start_instance(lock_path: Option<&Path>, launch: &DesktopLaunchEvent, updater: Option<&DesktopUpdater>) -> InstanceStart {
+    let mut lock = match lock_path.map(|path| DesktopInstanceLock::acquire(path, launch)) {
+        Some(Ok(DesktopInstance::Primary(lock))) => Some(lock),
+        Some(Ok(DesktopInstance::Secondary)) => return InstanceStart::Exit,
+        Some(Err(err)) => {
+            tracing::warn!(%err, "glory-desktop: single-instance lock unavailable; running anyway");
+            None
+        }
+        None => None,
+    };
+    let Some(updater) = updater else {
+        return InstanceStart::Run(lock);
+    };
+    let locked = lock.is_some();
+    match updater.install_staged_releasing(|| drop(lock.take())) {
+        Ok(true) => InstanceStart::Exit,
+        Ok(false) => InstanceStart::Run(lock),
+        Err(err) => {
+            tracing::warn!(%err, "glory-desktop: failed to install the staged update");
+            if locked && lock.is_none() {
+                // Released for an installer that didn't start; take it again.
+                return start_instance(lock_path, launch, None);
+            }
+            InstanceStart::Run(lock)
+        }
+    }
+}
 
 /// Stable process-local id for a desktop window.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -278,6 +331,90 @@ fn build_eval_script(id: u64, js: &str) -> String {
     format!("window.__gloryWryEval({id}, {source});")
 }
 
+thread_local! {
+    static DIALOGS: RefCell<PendingDialogs> = RefCell::new(PendingDialogs::default());
+}
+
+fn dialog_resolve(id: u64, answer: DialogAnswer) {
+    DIALOGS.with(|dialogs| dialogs.borrow_mut().resolve(id, answer));
+}
+
+fn dialog_cancel(id: u64) {
+    DIALOGS.with(|dialogs| dialogs.borrow_mut().cancel(id));
+}
+
+/// Futures spawned with [`DesktopWindowHandle::spawn`], keyed by task id.
+///
+/// `glory_core::spawn::spawn_local` blocks on non-wasm targets, which would
+/// stall the event loop while a future waits on it (an `eval` reply, a
+/// dialog answer). Tasks here are polled on the event-loop thread instead:
+/// their waker posts [`HostEvent::Task`], and each poll runs inside the
+/// owning window's holder so signal writes flush like any other callback.
+struct HostTask {
+    window: DesktopWindowId,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+}
+
+thread_local! {
+    static TASK_NEXT_ID: Cell<u64> = const { Cell::new(0) };
+    static TASKS: RefCell<HashMap<u64, HostTask>> = RefCell::new(HashMap::new());
+}
+
+struct TaskWaker {
+    id: u64,
+    proxy: Mutex<EventLoopProxy<HostEvent>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if let Ok(proxy) = self.proxy.lock() {
+            let _ = proxy.send_event(HostEvent::Task(self.id));
+        }
+    }
+}
+
+/// Drops the tasks of a closed window. The futures are dropped after the
+/// registry is released, since their destructors may spawn again.
+fn drop_tasks(window: DesktopWindowId) {
+    let closed: Vec<HostTask> = TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        let ids: Vec<u64> = tasks.iter().filter(|(_, task)| task.window == window).map(|(id, _)| *id).collect();
+        ids.into_iter().filter_map(|id| tasks.remove(&id)).collect()
+    });
+    drop(closed);
+}
+
+/// Polls task `id` once. A task whose window closed is dropped.
+fn poll_task(slots: &mut [(WindowId, WindowSlot)], id: u64, proxy: &EventLoopProxy<HostEvent>) {
+    // Spurious or late wakes find nothing to poll.
+    let Some(mut task) = TASKS.with(|tasks| tasks.borrow_mut().remove(&id)) else {
+        return;
+    };
+    let Some(slot) = slot_by_id(slots, task.window) else {
+        return;
+    };
+    let waker = Waker::from(Arc::new(TaskWaker {
+        id,
+        proxy: Mutex::new(proxy.clone()),
+    }));
+    let mut context = Context::from_waker(&waker);
+    let poll = match &slot.holder {
+        Some(holder) => {
+            let poll = holder.update(|| task.future.as_mut().poll(&mut context));
+            flush(&slot.webview, holder);
+            poll
+        }
+        None => task.future.as_mut().poll(&mut context),
+    };
+    if poll.is_pending() {
+        TASKS.with(|tasks| tasks.borrow_mut().insert(id, task));
+    }
+}
+
 /// Cached window state visible to widget callbacks.
 #[derive(Clone, Debug)]
 pub struct DesktopWindowState {
@@ -337,6 +474,7 @@ pub struct DesktopWindowHandle {
     /// Per-window child webview id allocator + mirror, shared with the host
     /// slot so allocation and `list` answer synchronously on this thread.
     children: Rc<RefCell<ChildWebviewRegistry>>,
+    factories: Rc<HashMap<String, WindowFactory>>,
 }
 
 impl std::fmt::Debug for DesktopWindowHandle {
@@ -356,6 +494,7 @@ impl DesktopWindowHandle {
         window_queue: Rc<RefCell<Vec<PendingWindow>>>,
         next_window_index: Rc<Cell<usize>>,
         children: Rc<RefCell<ChildWebviewRegistry>>,
+        factories: Rc<HashMap<String, WindowFactory>>,
     ) -> Self {
         Self {
             id,
@@ -364,6 +503,7 @@ impl DesktopWindowHandle {
             window_queue,
             next_window_index,
             children,
+            factories,
         }
     }
 
@@ -468,6 +608,63 @@ impl DesktopWindowHandle {
         }
     }
 
+    /// Runs `future` on the event-loop thread, polling it inside this
+    /// window's holder so the signals it writes flush to the webview. Use it
+    /// to await [`eval`](Self::eval) and the dialogs from widget callbacks.
+    /// The task is dropped when the window closes.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        let id = TASK_NEXT_ID.with(|next| {
+            let id = next.get();
+            next.set(id.wrapping_add(1));
+            id
+        });
+        TASKS.with(|tasks| {
+            tasks.borrow_mut().insert(
+                id,
+                HostTask {
+                    window: self.id,
+                    future: Box::pin(future),
+                },
+            )
+        });
+        // First poll from the event loop, outside the calling callback.
+        if self.proxy.send_event(HostEvent::Task(id)).is_err() {
+            TASKS.with(|tasks| tasks.borrow_mut().remove(&id));
+        }
+    }
+
+    /// Shows a native open dialog parented to this window and resolves with
+    /// the picked files, or none when the user cancels.
+    pub fn open_file_dialog(&self, dialog: DesktopFileDialog) -> impl Future<Output = Vec<PathBuf>> + 'static {
+        let answer = self.dialog(DialogRequest::Open(dialog));
+        async move { DialogAnswer::into_files(answer.await) }
+    }
+
+    /// Shows a native save dialog parented to this window and resolves with
+    /// the chosen path, or `None` when the user cancels.
+    pub fn save_file_dialog(&self, dialog: DesktopFileDialog) -> impl Future<Output = Option<PathBuf>> + 'static {
+        let answer = self.dialog(DialogRequest::Save(dialog));
+        async move { DialogAnswer::into_file(answer.await) }
+    }
+
+    /// Shows a native message dialog parented to this window and resolves
+    /// with the button pressed.
+    pub fn message_dialog(&self, dialog: DesktopMessageDialog) -> impl Future<Output = DesktopMessageResult> + 'static {
+        let answer = self.dialog(DialogRequest::Message(dialog));
+        async move { DialogAnswer::into_message(answer.await) }
+    }
+
+    /// Queues `request` for the event loop. The returned future resolves on
+    /// this thread once the answer comes back, and as cancelled when the
+    /// window or the event loop is gone.
+    fn dialog(&self, request: DialogRequest) -> impl Future<Output = Option<DialogAnswer>> + 'static {
+        let (id, answer) = DIALOGS.with(|dialogs| dialogs.borrow_mut().register());
+        if !self.send(WindowCommand::Dialog { id, request }) {
+            dialog_cancel(id);
+        }
+        answer
+    }
+
     /// Adds a child webview inside this window and returns its id
     /// synchronously. The webview is actually built on the event-loop thread
     /// shortly after (same deferred pattern as [`open_window`]); the returned
@@ -533,6 +730,20 @@ impl DesktopWindowHandle {
         id
     }
 
+    /// Opens a window from the factory registered as `kind` with
+    /// [`Desktop::with_window_factory`]. `label` should be unique among open
+    /// windows; the window's state is saved under it and, with
+    /// [`Desktop::with_session_restore`], the window reopens next run.
+    /// Returns `None` for an unknown `kind`.
+    pub fn open_factory_window(&self, kind: &str, label: impl Into<String>) -> Option<DesktopWindowId> {
+        let factory = self.factories.get(kind)?;
+        let id = DesktopWindowId(self.next_window_index.get());
+        self.next_window_index.set(id.0 + 1);
+        self.window_queue.borrow_mut().push(factory(id, label.into()));
+        let _ = self.proxy.send_event(HostEvent::OpenQueuedWindows);
+        Some(id)
+    }
+
     fn send(&self, command: WindowCommand) -> bool {
         self.proxy.send_event(HostEvent::WindowCommand { id: self.id, command }).is_ok()
     }
@@ -764,6 +975,11 @@ pub fn desktop_protocol_response(status: u16, mime: &str, body: impl Into<Cow<'s
 #[derive(Clone)]
 pub struct DesktopConfig {
     pub title: String,
+    /// Stable key under which the host saves this window's size, position,
+    /// maximized/fullscreen state and zoom level, and restores them when a
+    /// window with the same label opens again. `None` (the default) opens at
+    /// `inner_size` every time.
+    pub label: Option<String>,
     pub inner_size: (f64, f64),
     pub resizable: bool,
     /// Webview devtools (defaults to on in debug builds).
@@ -805,6 +1021,26 @@ pub struct DesktopConfig {
     /// What to do when the user requests the native window be closed (clicks
     /// the title-bar close button). Defaults to [`DesktopCloseBehavior::Close`].
     pub close_behavior: DesktopCloseBehavior,
+    /// Runs on the window's fresh holder before the widget tree mounts, to
+    /// enable extras such as a glory-routing `MemoryAviator`.
+    pub setup_holder: Option<Rc<dyn Fn(CommandHolder) -> CommandHolder>>,
+    /// Shows this window's dialogs. `None` uses [`DesktopNativeDialogs`].
+    pub dialog_provider: Option<Rc<dyn DesktopDialogProvider>>,
+    /// Backs the [`DesktopServices`] enabled on this window's holder. `None`
+    /// uses [`DesktopNativeServices`], shared by all such windows.
+    pub services: Option<Rc<dyn DesktopServiceProvider>>,
+    /// Invoked on the event-loop thread when the user acts on a notification
+    /// this window's [`DesktopServices`] showed.
+    pub on_notification: Option<Rc<dyn Fn(&CommandHolder, DesktopNotificationEvent)>>,
+    /// Invoked on the event-loop thread when the app is launched again while
+    /// [`Desktop::with_single_instance`] keeps this instance running. Only
+    /// the first open window with a handler receives it.
+    pub on_second_instance: Option<Rc<dyn Fn(&CommandHolder, DesktopLaunchEvent)>>,
+    /// Invoked on the event-loop thread with each URL of a
+    /// [`Desktop::with_deep_link_scheme`] scheme the app is opened with. Only
+    /// the first open window with a handler receives them; links arriving
+    /// before it is mounted wait for it.
+    pub on_deep_link: Option<Rc<dyn Fn(&CommandHolder, DesktopDeepLink)>>,
 }
 
 /// How a window reacts to a user-initiated close request.
@@ -825,6 +1061,7 @@ impl std::fmt::Debug for DesktopConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DesktopConfig")
             .field("title", &self.title)
+            .field("label", &self.label)
             .field("inner_size", &self.inner_size)
             .field("resizable", &self.resizable)
             .field("devtools", &self.devtools)
@@ -839,6 +1076,12 @@ impl std::fmt::Debug for DesktopConfig {
             .field("hotkeys", &self.hotkeys)
             .field("on_hotkey", &self.on_hotkey.is_some())
             .field("on_file_drop", &self.on_file_drop.is_some())
+            .field("setup_holder", &self.setup_holder.is_some())
+            .field("dialog_provider", &self.dialog_provider.is_some())
+            .field("services", &self.services.is_some())
+            .field("on_notification", &self.on_notification.is_some())
+            .field("on_second_instance", &self.on_second_instance.is_some())
+            .field("on_deep_link", &self.on_deep_link.is_some())
             .finish()
     }
 }
@@ -847,6 +1090,7 @@ impl Default for DesktopConfig {
     fn default() -> Self {
         Self {
             title: "Glory".to_owned(),
+            label: None,
             inner_size: (900.0, 640.0),
             resizable: true,
             devtools: cfg!(debug_assertions),
@@ -862,11 +1106,23 @@ impl Default for DesktopConfig {
             on_hotkey: None,
             on_file_drop: None,
             close_behavior: DesktopCloseBehavior::default(),
+            setup_holder: None,
+            dialog_provider: None,
+            services: None,
+            on_notification: None,
+            on_second_instance: None,
+            on_deep_link: None,
         }
     }
 }
 
 impl DesktopConfig {
+    /// Persists this window's state under `label`; see [`DesktopConfig::label`].
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     /// Registers an asynchronous custom protocol on this window.
     pub fn with_custom_protocol(mut self, protocol: DesktopProtocol) -> Self {
         self.custom_protocols.push(protocol);
@@ -893,6 +1149,50 @@ impl DesktopConfig {
         self.on_file_drop = Some(Rc::new(handler));
         self
     }
+
+    /// Enables extras on the window's holder before mounting:
+    ///
+    /// ```ignore
+    /// config.with_holder_setup(|holder| holder.enable(MemoryAviator::new(route(), catch())))
+    /// ```
+    pub fn with_holder_setup(mut self, setup: impl Fn(CommandHolder) -> CommandHolder + 'static) -> Self {
+        self.setup_holder = Some(Rc::new(setup));
+        self
+    }
+
+    /// Replaces the native dialogs of this window, e.g. with scripted answers
+    /// in tests.
+    pub fn with_dialog_provider(mut self, provider: impl DesktopDialogProvider + 'static) -> Self {
+        self.dialog_provider = Some(Rc::new(provider));
+        self
+    }
+
+    /// Backs this window's [`DesktopServices`] with `provider`, e.g. a
+    /// [`MemoryServices`](crate::MemoryServices) in tests.
+    pub fn with_services(mut self, provider: impl DesktopServiceProvider + 'static) -> Self {
+        self.services = Some(Rc::new(provider));
+        self
+    }
+
+    pub fn with_notification_handler(mut self, handler: impl Fn(&CommandHolder, DesktopNotificationEvent) + 'static) -> Self {
+        self.on_notification = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn with_second_instance_handler(mut self, handler: impl Fn(&CommandHolder, DesktopLaunchEvent) + 'static) -> Self {
+        self.on_second_instance = Some(Rc::new(handler));
+        self
+    }
+
+    /// Receives deep links, e.g. to drive the window's router:
+    ///
+    /// ```ignore
+    /// config.with_deep_link_handler(move |_, link| aviator.goto(&link.path()))
+    /// ```
+    pub fn with_deep_link_handler(mut self, handler: impl Fn(&CommandHolder, DesktopDeepLink) + 'static) -> Self {
+        self.on_deep_link = Some(Rc::new(handler));
+        self
+    }
 }
 
 const BOOTSTRAP_HTML: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head><body></body></html>";
@@ -902,6 +1202,10 @@ enum HostEvent {
     Dom(DesktopWindowId, Box<EventData>),
     Query(DesktopWindowId, glory_core::renderer::QueryResponse),
     Eval { id: u64, result: Result<serde_json::Value, EvalError> },
+    Dialog { id: u64, answer: DialogAnswer },
+    Task(u64),
+    Notification(DesktopWindowId, DesktopNotificationEvent),
+    Launch(DesktopLaunchEvent),
     Reload(ReloadMessage),
     Menu(String),
     Tray(tray_icon::TrayIconEvent),
@@ -925,6 +1229,10 @@ enum WindowCommand {
         id: u64,
         js: String,
     },
+    Dialog {
+        id: u64,
+        request: DialogRequest,
+    },
     AddChildWebview {
         child_id: ChildWebviewId,
         spec: Box<DesktopChildWebview>,
@@ -981,6 +1289,14 @@ fn spawn_reload_client(proxy: EventLoopProxy<HostEvent>) {
     });
 }
 
+/// Names the app's directories and notifications: the executable's name.
+fn app_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "glory".to_owned())
+}
+
 /// URL for a static asset served through the `glory://` custom protocol.
 ///
 /// Pass an [`Asset::public_path`](glory_core::assets::Asset::public_path)
@@ -1082,12 +1398,16 @@ fn serve_asset(root: &std::path::Path, request: DesktopProtocolRequest) -> Deskt
 
 type MountFn = Box<dyn FnOnce(CommandHolder) -> CommandHolder>;
 type MountFactory = Box<dyn FnOnce(DesktopWindowHandle) -> MountFn>;
+/// Builds a labeled window of one kind; see [`Desktop::with_window_factory`].
+type WindowFactory = Rc<dyn Fn(DesktopWindowId, String) -> PendingWindow>;
 
 struct PendingWindow {
     id: DesktopWindowId,
     config: DesktopConfig,
     state: Rc<RefCell<DesktopWindowState>>,
     mount: MountFactory,
+    /// The factory kind that made this window, recorded in the session.
+    kind: Option<String>,
 }
 
 impl PendingWindow {
@@ -1100,6 +1420,7 @@ impl PendingWindow {
             config,
             state: Rc::new(RefCell::new(DesktopWindowState::new(id))),
             mount: Box::new(move |window| Box::new(move |holder| holder.mount(widget(window)))),
+            kind: None,
         }
     }
 }
@@ -1128,6 +1449,9 @@ struct WindowSlot {
     /// Host-side mirror of the child id allocator, shared with the window's
     /// [`DesktopWindowHandle`] so id allocation stays consistent across both.
     children: Rc<RefCell<ChildWebviewRegistry>>,
+    /// Enabled on the holder at mount; the host refreshes its clipboard.
+    services: Option<DesktopServices>,
+    kind: Option<String>,
 }
 
 /// Multi-window host builder.
@@ -1141,10 +1465,27 @@ struct WindowSlot {
 ///
 /// Every window runs an isolated widget tree (own `CommandHolder`, own
 /// command queue). The process exits when the last window closes.
+///
+/// Windows opened from a registered factory can be restored on the next
+/// run:
+///
+/// ```ignore
+/// glory_desktop::Desktop::new()
+///     .window(DesktopConfig::default().with_label("main"), || MainApp)
+///     .with_window_factory("document", DesktopConfig::default(), |label, handle| DocumentApp::new(label, handle))
+///     .with_session_restore(true)
+///     .run();
+/// ```
 #[derive(Default)]
 pub struct Desktop {
     windows: Vec<PendingWindow>,
     next_window_index: usize,
+    factories: HashMap<String, WindowFactory>,
+    state_store: Option<DesktopStateStore>,
+    restore_session: bool,
+    single_instance: bool,
+    deep_link_schemes: Vec<String>,
+    updater: Option<DesktopUpdater>,
 }
 
 impl Desktop {
@@ -1170,28 +1511,164 @@ impl Desktop {
         self
     }
 
+    /// Registers a factory for windows of `kind`. Each window it makes uses
+    /// `config` with its own label, and `widget` receives that label. Open
+    /// them with [`Desktop::factory_window`] at startup or
+    /// [`DesktopWindowHandle::open_factory_window`] later.
+    pub fn with_window_factory<W>(
+        mut self,
+        kind: impl Into<String>,
+        config: DesktopConfig,
+        widget: impl Fn(&str, DesktopWindowHandle) -> W + 'static,
+    ) -> Self
+    where
+        W: Widget + 'static,
+    {
+        let kind = kind.into();
+        let widget = Rc::new(widget);
+        let factory_kind = kind.clone();
+        let factory: WindowFactory = Rc::new(move |id, label: String| {
+            let widget = widget.clone();
+            let config = config.clone().with_label(label.clone());
+            let mut pending = PendingWindow::new(id, config, move |handle| widget(&label, handle));
+            pending.kind = Some(factory_kind.clone());
+            pending
+        });
+        self.factories.insert(kind, factory);
+        self
+    }
+
+    /// Opens a window labeled `label` from the `kind` factory at startup.
+    ///
+    /// # Panics
+    ///
+    /// If no factory is registered as `kind`.
+    pub fn factory_window(mut self, kind: &str, label: impl Into<String>) -> Self {
+        let Some(factory) = self.factories.get(kind) else {
+            panic!("Desktop::factory_window: no window factory registered as {kind:?}");
+        };
+        let id = DesktopWindowId(self.next_window_index);
+        self.next_window_index += 1;
+        self.windows.push(factory(id, label.into()));
+        self
+    }
+
+    /// Saves labeled windows' state through `store`. Defaults to
+    /// `window-state.json` in the app's data directory.
+    pub fn with_state_store(mut self, store: DesktopStateStore) -> Self {
+        self.state_store = Some(store);
+        self
+    }
+
+    /// Also opens the factory-made windows that were open when the app last
+    /// quit, after the registered ones. Labels already registered are
+    /// skipped, as are kinds without a factory.
+    pub fn with_session_restore(mut self, restore: bool) -> Self {
+        self.restore_session = restore;
+        self
+    }
+
+    /// Keeps one instance of the app running. A later launch hands its
+    /// arguments to the running instance, which focuses its first window and
+    /// calls [`DesktopConfig::on_second_instance`], then exits.
+    pub fn with_single_instance(mut self, single_instance: bool) -> Self {
+        self.single_instance = single_instance;
+        self
+    }
+
+    /// Treats `scheme://...` URLs the app is opened with as deep links for
+    /// [`DesktopConfig::on_deep_link`]. `glory bundle` registers the schemes
+    /// listed in the project's `deep_link_schemes` with the OS.
+    pub fn with_deep_link_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.deep_link_schemes.push(scheme.into());
+        self
+    }
+
+    /// Installs the update `updater` staged in an earlier run before any
+    /// window opens, exiting so the installer can replace the app, and
+    /// enables `updater` on every window's holder. With
+    /// [`with_single_instance`](Self::with_single_instance), only the
+    /// primary instance installs; a later launch still just hands its
+    /// arguments over. Its staging directory
+    /// defaults to `updates` in the app's data directory.
+    pub fn with_updater(mut self, updater: DesktopUpdater) -> Self {
+        self.updater = Some(updater);
+        self
+    }
+
     /// Opens all registered windows and runs the event loop. Never returns.
     pub fn run(self) -> ! {
-        assert!(!self.windows.is_empty(), "Desktop::run called with no windows registered");
+        let Desktop {
+            mut windows,
+            mut next_window_index,
+            factories,
+            state_store,
+            restore_session,
+            single_instance,
+            deep_link_schemes,
+            updater,
+        } = self;
+
+        let native_services: Rc<dyn DesktopServiceProvider> = Rc::new(DesktopNativeServices::new(app_name()));
+        let updater = updater.map(|updater| updater.or_staging_dir(|| native_services.app_dir(DesktopAppDir::Data).map(|dir| dir.join("updates"))));
+        let launch = DesktopLaunchEvent::current();
+        let lock_path = if single_instance {
+            // Only a per-user directory keeps the lock's token private.
+            let dir = native_services.app_dir(DesktopAppDir::Data);
+            if dir.is_none() {
+                tracing::warn!("glory-desktop: no app data directory for the single-instance lock; running anyway");
+            }
+            dir.map(|dir| dir.join("instance.lock"))
+        } else {
+            None
+        };
+        let instance_lock = match start_instance(lock_path.as_deref(), &launch, updater.as_ref()) {
+            InstanceStart::Run(lock) => lock,
+            InstanceStart::Exit => std::process::exit(0),
+        };
+        let mut pending_links = deep_links(&launch.args, &deep_link_schemes);
+        let state_store = state_store.or_else(|| {
+            native_services
+                .app_dir(DesktopAppDir::Data)
+                .map(|dir| DesktopStateStore::new(dir.join("window-state.json")))
+        });
+        let mut persistence = WindowPersistence::new(state_store);
+        if restore_session {
+            for entry in persistence.saved.session.clone() {
+                if windows.iter().any(|window| window.config.label.as_deref() == Some(entry.label.as_str())) {
+                    continue;
+                }
+                let Some(factory) = factories.get(&entry.kind) else {
+                    tracing::warn!(kind = %entry.kind, label = %entry.label, "glory-desktop: no window factory for restored window");
+                    continue;
+                };
+                windows.push(factory(DesktopWindowId(next_window_index), entry.label));
+                next_window_index += 1;
+            }
+        }
+        assert!(!windows.is_empty(), "Desktop::run called with no windows registered");
 
         let event_loop = EventLoopBuilder::<HostEvent>::with_user_event().build();
         let mut slots: Vec<(WindowId, WindowSlot)> = Vec::new();
         let mut menu_routes: HashMap<String, DesktopWindowId> = HashMap::new();
         let mut tray_routes: HashMap<String, DesktopWindowId> = HashMap::new();
         let mut hotkey_routes: HashMap<u32, (DesktopWindowId, DesktopHotKeySpec)> = HashMap::new();
-        let needs_hotkey_manager = self.windows.iter().any(|window| !window.config.hotkeys.is_empty());
+        let needs_hotkey_manager = windows.iter().any(|window| !window.config.hotkeys.is_empty());
         let mut hotkey_manager = needs_hotkey_manager.then(create_hotkey_manager).flatten();
         let proxy = event_loop.create_proxy();
         let window_queue: Rc<RefCell<Vec<PendingWindow>>> = Rc::new(RefCell::new(Vec::new()));
-        let next_window_index = Rc::new(Cell::new(self.next_window_index));
+        let next_window_index = Rc::new(Cell::new(next_window_index));
+        let factories = Rc::new(factories);
 
-        for pending in self.windows {
+        for pending in windows {
             create_window(
                 &event_loop,
                 pending,
                 proxy.clone(),
                 window_queue.clone(),
                 next_window_index.clone(),
+                factories.clone(),
+                &persistence,
                 &mut slots,
                 &mut menu_routes,
                 &mut tray_routes,
@@ -1215,6 +1692,13 @@ impl Desktop {
 
         spawn_reload_client(proxy.clone());
 
+        if let Some(lock) = instance_lock {
+            let launch_proxy = proxy.clone();
+            lock.listen(move |launch| {
+                let _ = launch_proxy.send_event(HostEvent::Launch(launch));
+            });
+        }
+
         event_loop.run(move |event, target, control_flow| {
             *control_flow = ControlFlow::Wait;
             match event {
@@ -1233,7 +1717,7 @@ impl Desktop {
                             slot.window.set_visible(false);
                         }
                     } else {
-                        close_slot_by_window_id(&mut slots, window_id, hotkey_manager.as_ref(), &mut hotkey_routes);
+                        close_slot_by_window_id(&mut slots, window_id, hotkey_manager.as_ref(), &mut hotkey_routes, &mut persistence);
                         if slots.is_empty() {
                             *control_flow = ControlFlow::Exit;
                         }
@@ -1246,6 +1730,11 @@ impl Desktop {
                 } => {
                     if let Some(slot) = slot_by_window_id(&mut slots, window_id) {
                         slot.state.borrow_mut().focused = focused;
+                        // Another app may have copied something meanwhile.
+                        if focused && let (Some(services), Some(holder)) = (&slot.services, &slot.holder) {
+                            holder.update(|| services.refresh_clipboard());
+                            flush(&slot.webview, holder);
+                        }
                     }
                 }
                 Event::WindowEvent {
@@ -1275,9 +1764,21 @@ impl Desktop {
                     ..
                 } => {
                     if let Some(slot) = slot_by_window_id(&mut slots, window_id) {
-                        let mut state = slot.state.borrow_mut();
-                        state.maximized = slot.window.is_maximized();
-                        state.fullscreen = slot.window.fullscreen().is_some();
+                        {
+                            let mut state = slot.state.borrow_mut();
+                            state.maximized = slot.window.is_maximized();
+                            state.fullscreen = slot.window.fullscreen().is_some();
+                        }
+                        persistence.record(slot);
+                    }
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::Moved(_),
+                    window_id,
+                    ..
+                } => {
+                    if let Some(slot) = slot_by_window_id(&mut slots, window_id) {
+                        persistence.record(slot);
                     }
                 }
                 Event::UserEvent(HostEvent::Ready(id)) => {
@@ -1290,10 +1791,26 @@ impl Desktop {
                     };
                     let holder = CommandHolder::new();
                     holder.set_coalesce(slot.config.coalesce);
+                    let provider = slot.config.services.clone().unwrap_or_else(|| native_services.clone());
+                    let notifications = Mutex::new(proxy.clone());
+                    let services = DesktopServices::new(provider).with_notification_handler(move |event| {
+                        if let Ok(proxy) = notifications.lock() {
+                            let _ = proxy.send_event(HostEvent::Notification(id, event));
+                        }
+                    });
+                    slot.services = Some(services.clone());
+                    let mut holder = holder.enable(services);
+                    if let Some(updater) = &updater {
+                        holder = holder.enable(updater.clone());
+                    }
+                    if let Some(setup) = &slot.config.setup_holder {
+                        holder = setup(holder);
+                    }
                     let holder = mount(holder);
                     flush(&slot.webview, &holder);
                     slot.holder = Some(holder);
                     let _ = &slot.window;
+                    deliver_deep_links(&mut slots, &mut pending_links);
                 }
                 Event::UserEvent(HostEvent::Dom(id, data)) => {
                     if let Some(slot) = slot_by_id(&mut slots, id)
@@ -1314,6 +1831,41 @@ impl Desktop {
                 Event::UserEvent(HostEvent::Eval { id, result }) => {
                     eval_resolve(id, result);
                 }
+                Event::UserEvent(HostEvent::Dialog { id, answer }) => {
+                    dialog_resolve(id, answer);
+                }
+                Event::UserEvent(HostEvent::Task(id)) => {
+                    poll_task(&mut slots, id, &proxy);
+                }
+                Event::UserEvent(HostEvent::Notification(id, event)) => {
+                    if let Some(slot) = slot_by_id(&mut slots, id)
+                        && let (Some(callback), Some(holder)) = (&slot.config.on_notification, &slot.holder)
+                    {
+                        holder.update(|| callback(holder, event));
+                        flush(&slot.webview, holder);
+                    }
+                }
+                Event::UserEvent(HostEvent::Launch(launch)) => {
+                    if let Some((_, slot)) = slots.first() {
+                        slot.window.set_visible(true);
+                        slot.window.set_minimized(false);
+                        slot.window.set_focus();
+                    }
+                    pending_links.extend(deep_links(&launch.args, &deep_link_schemes));
+                    if let Some(slot) = slots.iter_mut().map(|(_, slot)| slot).find(|slot| slot.config.on_second_instance.is_some())
+                        && let (Some(callback), Some(holder)) = (&slot.config.on_second_instance, &slot.holder)
+                    {
+                        holder.update(|| callback(holder, launch));
+                        flush(&slot.webview, holder);
+                    }
+                    deliver_deep_links(&mut slots, &mut pending_links);
+                }
+                Event::Opened { urls } => {
+                    // macOS hands URL launches to the running app instead of argv.
+                    let urls = urls.iter().map(|url| url.as_str().to_owned()).collect::<Vec<_>>();
+                    pending_links.extend(deep_links(&urls, &deep_link_schemes));
+                    deliver_deep_links(&mut slots, &mut pending_links);
+                }
                 Event::UserEvent(HostEvent::Menu(menu_id)) => {
                     let Some(id) = menu_routes.get(&menu_id).copied() else { return };
                     if let Some(slot) = slot_by_id(&mut slots, id)
@@ -1347,7 +1899,15 @@ impl Desktop {
                     }
                 }
                 Event::UserEvent(HostEvent::WindowCommand { id, command }) => {
-                    apply_window_command(&mut slots, id, command, hotkey_manager.as_ref(), &mut hotkey_routes);
+                    apply_window_command(
+                        &mut slots,
+                        id,
+                        command,
+                        &proxy,
+                        hotkey_manager.as_ref(),
+                        &mut hotkey_routes,
+                        &mut persistence,
+                    );
                     if slots.is_empty() {
                         *control_flow = ControlFlow::Exit;
                     }
@@ -1364,6 +1924,8 @@ impl Desktop {
                             proxy.clone(),
                             window_queue.clone(),
                             next_window_index.clone(),
+                            factories.clone(),
+                            &persistence,
                             &mut slots,
                             &mut menu_routes,
                             &mut tray_routes,
@@ -1427,25 +1989,58 @@ fn dispatch_file_drop_event(slots: &mut [(WindowId, WindowSlot)], window_id: Win
     }
 }
 
+/// Hands queued deep links to the first window with a deep-link handler,
+/// once that window is mounted.
+fn deliver_deep_links(slots: &mut [(WindowId, WindowSlot)], pending: &mut Vec<DesktopDeepLink>) {
+    if pending.is_empty() {
+        return;
+    }
+    if let Some(slot) = slots.iter_mut().map(|(_, slot)| slot).find(|slot| slot.config.on_deep_link.is_some())
+        && let (Some(callback), Some(holder)) = (&slot.config.on_deep_link, &slot.holder)
+    {
+        for link in pending.drain(..) {
+            holder.update(|| callback(holder, link));
+        }
+        flush(&slot.webview, holder);
+    }
+}
+
 fn create_window(
     target: &EventLoopWindowTarget<HostEvent>,
     pending: PendingWindow,
     proxy: EventLoopProxy<HostEvent>,
     window_queue: Rc<RefCell<Vec<PendingWindow>>>,
     next_window_index: Rc<Cell<usize>>,
+    factories: Rc<HashMap<String, WindowFactory>>,
+    persistence: &WindowPersistence,
     slots: &mut Vec<(WindowId, WindowSlot)>,
     menu_routes: &mut HashMap<String, DesktopWindowId>,
     tray_routes: &mut HashMap<String, DesktopWindowId>,
     hotkey_manager: Option<&global_hotkey::GlobalHotKeyManager>,
     hotkey_routes: &mut HashMap<u32, (DesktopWindowId, DesktopHotKeySpec)>,
 ) {
-    let PendingWindow { id, config, state, mount } = pending;
-    let window = WindowBuilder::new()
+    let PendingWindow {
+        id,
+        config,
+        state,
+        mount,
+        kind,
+    } = pending;
+    let saved = config.label.as_deref().and_then(|label| persistence.restore(label, target));
+    let mut builder = WindowBuilder::new()
         .with_title(&config.title)
         .with_inner_size(tao::dpi::LogicalSize::new(config.inner_size.0, config.inner_size.1))
-        .with_resizable(config.resizable)
-        .build(target)
-        .expect("glory-desktop: failed to create window");
+        .with_resizable(config.resizable);
+    if let Some(saved) = &saved {
+        builder = builder
+            .with_inner_size(tao::dpi::PhysicalSize::new(saved.width, saved.height))
+            .with_position(tao::dpi::PhysicalPosition::new(saved.x, saved.y))
+            .with_maximized(saved.maximized);
+        if saved.fullscreen {
+            builder = builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+    }
+    let window = builder.build(target).expect("glory-desktop: failed to create window");
 
     {
         let mut state = state.borrow_mut();
@@ -1458,7 +2053,15 @@ fn create_window(
     install_bundle_asset_manifest(&assets_root_dir);
 
     let children = Rc::new(RefCell::new(ChildWebviewRegistry::new()));
-    let handle = DesktopWindowHandle::new(id, proxy.clone(), state.clone(), window_queue, next_window_index, children.clone());
+    let handle = DesktopWindowHandle::new(
+        id,
+        proxy.clone(),
+        state.clone(),
+        window_queue,
+        next_window_index,
+        children.clone(),
+        factories,
+    );
     let mount = mount(handle);
     let ipc_proxy = proxy.clone();
     let mut webview = WebViewBuilder::new()
@@ -1498,6 +2101,12 @@ fn create_window(
     }
 
     let webview = webview.build(&window).expect("glory-desktop: failed to create webview");
+    if let Some(zoom_level) = saved.map(|saved| saved.zoom_level).filter(|zoom_level| *zoom_level != 1.0) {
+        match webview.zoom(zoom_level) {
+            Ok(()) => state.borrow_mut().zoom_level = zoom_level,
+            Err(err) => tracing::warn!(%err, window_id = id.as_usize(), "glory-desktop: restoring zoom level failed"),
+        }
+    }
 
     let menu = config.menu.as_ref().map(|spec| {
         let menu = build_menu(spec, id, menu_routes);
@@ -1531,6 +2140,8 @@ fn create_window(
             registered_hotkeys,
             extra_webviews: Vec::new(),
             children,
+            services: None,
+            kind,
         },
     ));
 }
@@ -1543,17 +2154,120 @@ fn slot_by_window_id<'s>(slots: &'s mut [(WindowId, WindowSlot)], window_id: Win
     slots.iter_mut().find(|(id, _)| *id == window_id).map(|(_, slot)| slot)
 }
 
+/// The saved state of labeled windows. Kept current as windows move and
+/// resize, and written through the store whenever a window closes.
+struct WindowPersistence {
+    store: Option<DesktopStateStore>,
+    saved: DesktopSavedState,
+    /// Something was recorded since the last write.
+    dirty: bool,
+}
+
+impl WindowPersistence {
+    fn new(store: Option<DesktopStateStore>) -> Self {
+        let saved = store.as_ref().map(DesktopStateStore::load).unwrap_or_default();
+        Self { store, saved, dirty: false }
+    }
+
+    /// The saved state for `label`, fitted to the current monitors.
+    fn restore(&self, label: &str, target: &EventLoopWindowTarget<HostEvent>) -> Option<DesktopSavedWindow> {
+        let monitors = target
+            .available_monitors()
+            .map(|monitor| {
+                let position = monitor.position();
+                let size = monitor.size();
+                MonitorArea {
+                    x: position.x,
+                    y: position.y,
+                    width: size.width,
+                    height: size.height,
+                }
+            })
+            .collect::<Vec<_>>();
+        self.saved.windows.get(label).cloned().map(|saved| saved.clamp_to(&monitors))
+    }
+
+    /// Copies `slot`'s state into the saved state. A maximized, fullscreen
+    /// or minimized window keeps its last normal bounds.
+    fn record(&mut self, slot: &WindowSlot) {
+        let Some(label) = &slot.config.label else {
+            return;
+        };
+        let state = slot.state.borrow();
+        let bounds = match self.saved.windows.get(label) {
+            Some(previous) if state.maximized || state.fullscreen || slot.window.is_minimized() => {
+                (previous.x, previous.y, previous.width, previous.height)
+            }
+            _ => {
+                let (x, y) = slot.window.outer_position().map(|position| (position.x, position.y)).unwrap_or((0, 0));
+                let size = slot.window.inner_size();
+                (x, y, size.width, size.height)
+            }
+        };
+        let (x, y, width, height) = bounds;
+        self.saved.windows.insert(
+            label.clone(),
+            DesktopSavedWindow {
+                x,
+                y,
+                width,
+                height,
+                maximized: state.maximized,
+                fullscreen: state.fullscreen,
+                zoom_level: state.zoom_level,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Records the just-closed window and writes the state. The session
+    /// lists the windows still open, or the closed one if it was the last:
+    /// closing the last window quits the app.
+    fn close(&mut self, open: &[(WindowId, WindowSlot)], closed: &WindowSlot) {
+        self.record(closed);
+        for (_, slot) in open {
+            self.record(slot);
+        }
+        if !self.dirty {
+            return;
+        }
+        let session: Vec<&WindowSlot> = if open.is_empty() {
+            vec![closed]
+        } else {
+            open.iter().map(|(_, slot)| slot).collect()
+        };
+        self.saved.session = session
+            .into_iter()
+            .filter_map(|slot| {
+                Some(DesktopSessionWindow {
+                    label: slot.config.label.clone()?,
+                    kind: slot.kind.clone()?,
+                })
+            })
+            .collect();
+        if let Some(store) = &self.store
+            && let Err(err) = store.save(&self.saved)
+        {
+            tracing::warn!(%err, path = %store.path().display(), "glory-desktop: failed to save window state");
+        }
+        self.dirty = false;
+    }
+}
+
 fn close_slot_by_window_id(
     slots: &mut Vec<(WindowId, WindowSlot)>,
     window_id: WindowId,
     hotkey_manager: Option<&global_hotkey::GlobalHotKeyManager>,
     hotkey_routes: &mut HashMap<u32, (DesktopWindowId, DesktopHotKeySpec)>,
+    persistence: &mut WindowPersistence,
 ) -> bool {
     let Some(position) = slots.iter().position(|(id, _)| *id == window_id) else {
         return false;
     };
     let (_, slot) = slots.remove(position);
+    persistence.close(slots, &slot);
     unregister_hotkeys(&slot, hotkey_manager, hotkey_routes);
+    drop_tasks(slot.id);
     slot.state.borrow_mut().closed = true;
     true
 }
@@ -1563,12 +2277,15 @@ fn close_slot_by_id(
     id: DesktopWindowId,
     hotkey_manager: Option<&global_hotkey::GlobalHotKeyManager>,
     hotkey_routes: &mut HashMap<u32, (DesktopWindowId, DesktopHotKeySpec)>,
+    persistence: &mut WindowPersistence,
 ) -> bool {
     let Some(position) = slots.iter().position(|(_, slot)| slot.id == id) else {
         return false;
     };
     let (_, slot) = slots.remove(position);
+    persistence.close(slots, &slot);
     unregister_hotkeys(&slot, hotkey_manager, hotkey_routes);
+    drop_tasks(slot.id);
     slot.state.borrow_mut().closed = true;
     true
 }
@@ -1577,19 +2294,23 @@ fn apply_window_command(
     slots: &mut Vec<(WindowId, WindowSlot)>,
     id: DesktopWindowId,
     command: WindowCommand,
+    proxy: &EventLoopProxy<HostEvent>,
     hotkey_manager: Option<&global_hotkey::GlobalHotKeyManager>,
     hotkey_routes: &mut HashMap<u32, (DesktopWindowId, DesktopHotKeySpec)>,
+    persistence: &mut WindowPersistence,
 ) {
     if matches!(command, WindowCommand::Close) {
-        close_slot_by_id(slots, id, hotkey_manager, hotkey_routes);
+        close_slot_by_id(slots, id, hotkey_manager, hotkey_routes, persistence);
         return;
     }
 
     let Some(slot) = slot_by_id(slots, id) else {
-        // Resolve a dangling eval so the caller stops awaiting; other
-        // commands are simply dropped.
-        if let WindowCommand::Eval { id: eval_id, .. } = command {
-            eval_resolve(eval_id, Err(EvalError::Disconnected));
+        // Resolve a dangling eval or dialog so the caller stops awaiting;
+        // other commands are simply dropped.
+        match command {
+            WindowCommand::Eval { id: eval_id, .. } => eval_resolve(eval_id, Err(EvalError::Disconnected)),
+            WindowCommand::Dialog { id: dialog_id, .. } => dialog_cancel(dialog_id),
+            _ => {}
         }
         tracing::warn!(window_id = id.as_usize(), "glory-desktop: window command target no longer exists");
         return;
@@ -1643,6 +2364,19 @@ fn apply_window_command(
                 eval_resolve(eval_id, Err(EvalError::Js(err.to_string())));
             }
         }
+        WindowCommand::Dialog { id: dialog_id, request } => {
+            let provider: &dyn DesktopDialogProvider = match &slot.config.dialog_provider {
+                Some(provider) => provider.as_ref(),
+                None => &DesktopNativeDialogs,
+            };
+            let answer = request.show(provider, DesktopDialogParent::new(&slot.window));
+            // The dialog is up; wait for it off the event loop.
+            let proxy = proxy.clone();
+            std::thread::spawn(move || {
+                let answer = futures::executor::block_on(answer);
+                let _ = proxy.send_event(HostEvent::Dialog { id: dialog_id, answer });
+            });
+        }
         WindowCommand::AddChildWebview { child_id, spec } => {
             add_child_webview(slot, child_id, *spec);
         }
@@ -1900,8 +2634,51 @@ fn flush(webview: &wry::WebView, holder: &CommandHolder) {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
     use super::*;
 
+    #[test]
+    fn secondary_launches_forward_themselves_and_leave_the_staged_update() {
+        let dir = std::env::temp_dir().join(format!("glory-desktop-secondary-start-{}", std::process::id()));
+        let lock_path = dir.join("instance.lock");
+        let staging = dir.join("updates");
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::write(staging.join("app_2.0.0.AppImage"), b"installer").unwrap();
+        let staged = crate::DesktopStagedUpdate {
+            version: "2.0.0".to_owned(),
+            target: "linux-x86_64".to_owned(),
+            signature: "unchecked".to_owned(),
+            path: staging.join("app_2.0.0.AppImage"),
+        };
+        std::fs::write(staging.join("staged.json"), serde_json::to_vec(&staged).unwrap()).unwrap();
+        let updater = DesktopUpdater::new(
+            "http://127.0.0.1:9/manifest.json",
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+            "1.0.0",
+        )
+        .with_target("linux-x86_64")
+        .with_staging_dir(&staging);
+
+        let InstanceStart::Run(Some(lock)) = start_instance(Some(&lock_path), &DesktopLaunchEvent::default(), None) else {
+            panic!("the first launch must take the lock");
+        };
+        let (sender, receiver) = mpsc::channel();
+        lock.listen(move |launch| sender.send(launch).unwrap());
+
+        let second = DesktopLaunchEvent {
+            args: vec!["myapp://project/42".to_owned()],
+            cwd: None,
+        };
+        assert!(matches!(start_instance(Some(&lock_path), &second, Some(&updater)), InstanceStart::Exit));
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), second);
+        // Installing is the primary's job; the secondary leaves it staged.
+        assert_eq!(updater.staged().map(|update| update.version), Some("2.0.0".to_owned()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn asset_path_resolution_rejects_traversal() {
         let root = std::env::temp_dir().join("glory-asset-test");
@@ -1974,6 +2751,82 @@ mod tests {
         assert!(format!("{config:?}").contains("on_file_drop: true"));
     }
 
+    #[test]
+    fn desktop_config_records_dialog_provider() {
+        struct Cancelling;
+        impl DesktopDialogProvider for Cancelling {
+            fn open_file(&self, _: DesktopDialogParent<'_>, _: DesktopFileDialog) -> crate::DesktopDialogFuture<Vec<PathBuf>> {
+                Box::pin(std::future::ready(Vec::new()))
+            }
+            fn save_file(&self, _: DesktopDialogParent<'_>, _: DesktopFileDialog) -> crate::DesktopDialogFuture<Option<PathBuf>> {
+                Box::pin(std::future::ready(None))
+            }
+            fn message(&self, _: DesktopDialogParent<'_>, _: DesktopMessageDialog) -> crate::DesktopDialogFuture<DesktopMessageResult> {
+                Box::pin(std::future::ready(DesktopMessageResult::Cancel))
+            }
+        }
+
+        let config = DesktopConfig::default().with_dialog_provider(Cancelling);
+
+        assert!(config.dialog_provider.is_some());
+        assert!(format!("{config:?}").contains("dialog_provider: true"));
+    }
+
+    #[test]
+    fn desktop_config_records_services_and_notification_handler() {
+        let config = DesktopConfig::default()
+            .with_services(crate::MemoryServices::new())
+            .with_notification_handler(|_, _| {});
+
+        assert!(config.services.is_some());
+        assert!(format!("{config:?}").contains("services: true"));
+        assert!(format!("{config:?}").contains("on_notification: true"));
+    }
+
+    #[test]
+    fn desktop_records_single_instance_and_deep_link_routing() {
+        let desktop = Desktop::new().with_single_instance(true).with_deep_link_scheme("myapp");
+        assert!(desktop.single_instance);
+        assert_eq!(desktop.deep_link_schemes, vec!["myapp".to_owned()]);
+
+        let config = DesktopConfig::default()
+            .with_holder_setup(|holder| holder)
+            .with_second_instance_handler(|_, _| {})
+            .with_deep_link_handler(|_, _| {});
+        assert!(format!("{config:?}").contains("setup_holder: true"));
+        assert!(format!("{config:?}").contains("on_second_instance: true"));
+        assert!(format!("{config:?}").contains("on_deep_link: true"));
+    }
+
+    #[test]
+    fn window_factories_label_the_windows_they_make() {
+        #[derive(Debug)]
+        struct Document;
+        impl Widget for Document {
+            fn build(&mut self, _ctx: &mut glory_core::Scope) {}
+        }
+
+        let desktop = Desktop::new()
+            .window(DesktopConfig::default().with_label("main"), || Document)
+            .with_window_factory("document", DesktopConfig::default(), |_, _| Document)
+            .factory_window("document", "doc-1")
+            .with_session_restore(true);
+
+        let windows = desktop
+            .windows
+            .iter()
+            .map(|window| (window.id, window.config.label.as_deref(), window.kind.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            windows,
+            vec![
+                (DesktopWindowId(0), Some("main"), None),
+                (DesktopWindowId(1), Some("doc-1"), Some("document")),
+            ]
+        );
+        assert!(desktop.restore_session);
+    }
+
     #[test]
     fn tray_and_hotkey_event_mapping_is_stable() {
         assert_eq!(map_tray_button(tray_icon::MouseButton::Right), DesktopTrayMouseButton::Right);
@@ -0,0 +1,540 @@
+//! Platform services for desktop widgets: clipboard, notifications, opening
+//! URLs and files, and app data directories.
+//!
+//! The host enables a [`DesktopServices`] on every window's holder, so
+//! widgets take it from the [`Truck`]:
+//!
+//! ```ignore
+//! let services = ctx.truck().obtain::<DesktopServices>().ok().cloned();
+//! ```
+//!
+//! [`DesktopServices`] wraps a [`DesktopServiceProvider`]. The host uses
+//! [`DesktopNativeServices`] unless
+//! [`DesktopConfig::with_services`](crate::DesktopConfig::with_services)
+//! installs another, and tests use [`MemoryServices`] the way they use
+//! [`RecordingSink`](crate::RecordingSink).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use glory_core::holder::Enabler;
+use glory_core::{Cage, Truck};
+
+/// Error from a [`DesktopServiceProvider`], with the platform's message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopServiceError(pub String);
+
+impl std::fmt::Display for DesktopServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "desktop service failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for DesktopServiceError {}
+
+/// Called once with the id of the action the user picked on a notification,
+/// from any thread.
+pub type DesktopNotificationResponder = Box<dyn FnOnce(String) + Send>;
+
+/// The platform side of [`DesktopServices`].
+pub trait DesktopServiceProvider {
+    /// The clipboard text, `None` when it holds no text.
+    fn read_clipboard(&self) -> Option<String>;
+    fn write_clipboard(&self, text: &str) -> Result<(), DesktopServiceError>;
+    /// Shows `notification`. When the user picks one of its actions, or
+    /// clicks the notification itself (`"default"`), the provider calls
+    /// `respond` with the action id. Dismissing it calls nothing.
+    fn notify(&self, notification: DesktopNotification, respond: DesktopNotificationResponder) -> Result<(), DesktopServiceError>;
+    /// Opens `url` in the default browser or handler.
+    fn open_url(&self, url: &str) -> Result<(), DesktopServiceError>;
+    /// Shows `path` in the platform file manager.
+    fn reveal_in_file_manager(&self, path: &Path) -> Result<(), DesktopServiceError>;
+    /// The app's directory of kind `dir`. It may not exist yet.
+    fn app_dir(&self, dir: DesktopAppDir) -> Option<PathBuf>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DesktopAppDir {
+    /// Settings the user may edit or back up.
+    Config,
+    /// Data the app keeps, like databases and downloads.
+    Data,
+    /// Data the app can rebuild.
+    Cache,
+}
+
+/// An OS notification.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DesktopNotification {
+    /// Names the notification in the [`DesktopNotificationEvent`]s it sends.
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub actions: Vec<DesktopNotificationAction>,
+}
+
+impl DesktopNotification {
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Adds a button. Platforms without notification buttons only report
+    /// clicks on the notification itself.
+    pub fn with_action(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.actions.push(DesktopNotificationAction {
+            id: id.into(),
+            label: label.into(),
+        });
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopNotificationAction {
+    pub id: String,
+    pub label: String,
+}
+
+/// The user acted on a notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopNotificationEvent {
+    /// The [`DesktopNotification::id`].
+    pub notification: String,
+    /// The action id, or `"default"` for a click on the notification.
+    pub action: String,
+}
+
+type NotificationRoute = Arc<dyn Fn(DesktopNotificationEvent) + Send + Sync>;
+
+/// Platform services of a window, enabled on its holder.
+///
+/// The clipboard is a [`Cage`]: it is read when the services are created,
+/// again whenever the window gains focus, and after each
+/// [`write_clipboard`](Self::write_clipboard), so widgets render it like any
+/// other state.
+#[derive(Clone)]
+pub struct DesktopServices {
+    provider: Rc<dyn DesktopServiceProvider>,
+    clipboard: Cage<Option<String>>,
+    on_notification: NotificationRoute,
+}
+
+impl std::fmt::Debug for DesktopServices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DesktopServices")
+            .field("clipboard", &self.clipboard)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DesktopServices {
+    pub fn new(provider: Rc<dyn DesktopServiceProvider>) -> Self {
+        let clipboard = Cage::new(provider.read_clipboard());
+        Self {
+            provider,
+            clipboard,
+            on_notification: Arc::new(|event| tracing::debug!(?event, "glory-desktop: notification event without a handler")),
+        }
+    }
+
+    /// Routes notification events to `handler`, which may run on any thread.
+    /// The host sends them to [`DesktopConfig::on_notification`](crate::DesktopConfig::on_notification).
+    pub fn with_notification_handler(mut self, handler: impl Fn(DesktopNotificationEvent) + Send + Sync + 'static) -> Self {
+        self.on_notification = Arc::new(handler);
+        self
+    }
+
+    pub fn clipboard(&self) -> Cage<Option<String>> {
+        self.clipboard
+    }
+
+    /// Reads the clipboard again and updates [`clipboard`](Self::clipboard)
+    /// if it changed.
+    pub fn refresh_clipboard(&self) {
+        let text = self.provider.read_clipboard();
+        if *self.clipboard.get_untracked() != text {
+            self.clipboard.revise(|mut clipboard| *clipboard = text);
+        }
+    }
+
+    pub fn write_clipboard(&self, text: impl Into<String>) -> Result<(), DesktopServiceError> {
+        let text = text.into();
+        self.provider.write_clipboard(&text)?;
+        if self.clipboard.get_untracked().as_deref() != Some(text.as_str()) {
+            self.clipboard.revise(|mut clipboard| *clipboard = Some(text));
+        }
+        Ok(())
+    }
+
+    pub fn notify(&self, notification: DesktopNotification) -> Result<(), DesktopServiceError> {
+        let route = self.on_notification.clone();
+        let id = notification.id.clone();
+        self.provider.notify(
+            notification,
+            Box::new(move |action| route(DesktopNotificationEvent { notification: id, action })),
+        )
+    }
+
+    pub fn open_url(&self, url: &str) -> Result<(), DesktopServiceError> {
+        self.provider.open_url(url)
+    }
+
+    pub fn reveal_in_file_manager(&self, path: impl AsRef<Path>) -> Result<(), DesktopServiceError> {
+        self.provider.reveal_in_file_manager(path.as_ref())
+    }
+
+    pub fn app_dir(&self, dir: DesktopAppDir) -> Option<PathBuf> {
+        self.provider.app_dir(dir)
+    }
+}
+
+impl Enabler for DesktopServices {
+    fn enable(self, truck: Rc<RefCell<Truck>>) {
+        truck.borrow_mut().inject(self);
+    }
+}
+
+/// In-memory [`DesktopServiceProvider`] for tests. Clones share their state.
+#[derive(Clone, Default)]
+pub struct MemoryServices {
+    state: Rc<RefCell<MemoryServicesState>>,
+}
+
+#[derive(Default)]
+struct MemoryServicesState {
+    clipboard: Option<String>,
+    notifications: Vec<DesktopNotification>,
+    responders: HashMap<String, DesktopNotificationResponder>,
+    opened_urls: Vec<String>,
+    revealed_paths: Vec<PathBuf>,
+    app_root: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for MemoryServices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("MemoryServices")
+            .field("clipboard", &state.clipboard)
+            .field("notifications", &state.notifications)
+            .field("opened_urls", &state.opened_urls)
+            .field("revealed_paths", &state.revealed_paths)
+            .field("app_root", &state.app_root)
+            .finish()
+    }
+}
+
+impl MemoryServices {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers [`DesktopAppDir`]s with `config`, `data` and `cache` under
+    /// `root`. Without it there are none.
+    pub fn with_app_root(self, root: impl Into<PathBuf>) -> Self {
+        self.state.borrow_mut().app_root = Some(root.into());
+        self
+    }
+
+    /// Replaces the clipboard, as another app copying would.
+    pub fn set_clipboard(&self, text: impl Into<String>) {
+        self.state.borrow_mut().clipboard = Some(text.into());
+    }
+
+    pub fn clipboard(&self) -> Option<String> {
+        self.state.borrow().clipboard.clone()
+    }
+
+    /// Shown notifications, in order.
+    pub fn notifications(&self) -> Vec<DesktopNotification> {
+        self.state.borrow().notifications.clone()
+    }
+
+    /// Acts on the latest notification named `notification` as the user
+    /// would. Returns `false` when there is none or it was already acted on.
+    pub fn activate(&self, notification: &str, action: &str) -> bool {
+        let respond = self.state.borrow_mut().responders.remove(notification);
+        match respond {
+            Some(respond) => {
+                respond(action.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn opened_urls(&self) -> Vec<String> {
+        self.state.borrow().opened_urls.clone()
+    }
+
+    pub fn revealed_paths(&self) -> Vec<PathBuf> {
+        self.state.borrow().revealed_paths.clone()
+    }
+}
+
+impl DesktopServiceProvider for MemoryServices {
+    fn read_clipboard(&self) -> Option<String> {
+        self.clipboard()
+    }
+
+    fn write_clipboard(&self, text: &str) -> Result<(), DesktopServiceError> {
+        self.set_clipboard(text);
+        Ok(())
+    }
+
+    fn notify(&self, notification: DesktopNotification, respond: DesktopNotificationResponder) -> Result<(), DesktopServiceError> {
+        let mut state = self.state.borrow_mut();
+        state.responders.insert(notification.id.clone(), respond);
+        state.notifications.push(notification);
+        Ok(())
+    }
+
+    fn open_url(&self, url: &str) -> Result<(), DesktopServiceError> {
+        self.state.borrow_mut().opened_urls.push(url.to_owned());
+        Ok(())
+    }
+
+    fn reveal_in_file_manager(&self, path: &Path) -> Result<(), DesktopServiceError> {
+        self.state.borrow_mut().revealed_paths.push(path.to_owned());
+        Ok(())
+    }
+
+    fn app_dir(&self, dir: DesktopAppDir) -> Option<PathBuf> {
+        let name = match dir {
+            DesktopAppDir::Config => "config",
+            DesktopAppDir::Data => "data",
+            DesktopAppDir::Cache => "cache",
+        };
+        self.state.borrow().app_root.as_ref().map(|root| root.join(name))
+    }
+}
+
+/// Where the platform keeps app directories of kind `dir`, read from the
+/// environment through `var`.
+fn platform_app_dir(dir: DesktopAppDir, app_name: &str, var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let absolute = |key: &str| var(key).map(PathBuf::from).filter(|path| path.is_absolute());
+    let base = if cfg!(target_os = "windows") {
+        match dir {
+            DesktopAppDir::Config | DesktopAppDir::Data => absolute("APPDATA"),
+            DesktopAppDir::Cache => absolute("LOCALAPPDATA"),
+        }
+    } else if cfg!(target_os = "macos") {
+        absolute("HOME").map(|home| match dir {
+            DesktopAppDir::Config | DesktopAppDir::Data => home.join("Library/Application Support"),
+            DesktopAppDir::Cache => home.join("Library/Caches"),
+        })
+    } else {
+        let (key, fallback) = match dir {
+            DesktopAppDir::Config => ("XDG_CONFIG_HOME", ".config"),
+            DesktopAppDir::Data => ("XDG_DATA_HOME", ".local/share"),
+            DesktopAppDir::Cache => ("XDG_CACHE_HOME", ".cache"),
+        };
+        absolute(key).or_else(|| absolute("HOME").map(|home| home.join(fallback)))
+    };
+    base.map(|base| base.join(app_name))
+}
+
+/// The platform services: `arboard` for the clipboard, `notify-rust` for
+/// notifications and `open` for URLs.
+#[cfg(feature = "runtime")]
+pub struct DesktopNativeServices {
+    app_name: String,
+    /// Opened on first use; some platforms need a display connection.
+    clipboard: RefCell<Option<arboard::Clipboard>>,
+}
+
+#[cfg(feature = "runtime")]
+impl std::fmt::Debug for DesktopNativeServices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DesktopNativeServices")
+            .field("app_name", &self.app_name)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl DesktopNativeServices {
+    /// `app_name` names the app's directories and its notifications.
+    pub fn new(app_name: impl Into<String>) -> Self {
+        Self {
+            app_name: app_name.into(),
+            clipboard: RefCell::new(None),
+        }
+    }
+
+    fn with_clipboard<R>(&self, f: impl FnOnce(&mut arboard::Clipboard) -> Result<R, arboard::Error>) -> Result<R, DesktopServiceError> {
+        let mut clipboard = self.clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().map_err(|err| DesktopServiceError(err.to_string()))?);
+        }
+        f(clipboard.as_mut().expect("clipboard was just opened")).map_err(|err| DesktopServiceError(err.to_string()))
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl DesktopServiceProvider for DesktopNativeServices {
+    fn read_clipboard(&self) -> Option<String> {
+        self.with_clipboard(|clipboard| clipboard.get_text()).ok()
+    }
+
+    fn write_clipboard(&self, text: &str) -> Result<(), DesktopServiceError> {
+        self.with_clipboard(|clipboard| clipboard.set_text(text))
+    }
+
+    fn notify(&self, notification: DesktopNotification, respond: DesktopNotificationResponder) -> Result<(), DesktopServiceError> {
+        let mut builder = notify_rust::Notification::new();
+        builder.appname(&self.app_name).summary(&notification.title).body(&notification.body);
+        for action in &notification.actions {
+            builder.action(&action.id, &action.label);
+        }
+        let handle = builder.show().map_err(|err| DesktopServiceError(err.to_string()))?;
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                if action != "__closed" {
+                    respond(action.to_owned());
+                }
+            });
+        });
+        Ok(())
+    }
+
+    fn open_url(&self, url: &str) -> Result<(), DesktopServiceError> {
+        open::that_detached(url).map_err(|err| DesktopServiceError(err.to_string()))
+    }
+
+    fn reveal_in_file_manager(&self, path: &Path) -> Result<(), DesktopServiceError> {
+        let spawned = if cfg!(target_os = "macos") {
+            std::process::Command::new("open").arg("-R").arg(path).spawn().map(drop)
+        } else if cfg!(target_os = "windows") {
+            let mut select = OsString::from("/select,");
+            select.push(path);
+            std::process::Command::new("explorer").arg(select).spawn().map(drop)
+        } else {
+            // No portable way to select a file; open the folder holding it.
+            let folder = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+            open::that_detached(folder)
+        };
+        spawned.map_err(|err| DesktopServiceError(err.to_string()))
+    }
+
+    fn app_dir(&self, dir: DesktopAppDir) -> Option<PathBuf> {
+        platform_app_dir(dir, &self.app_name, |key| std::env::var_os(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn memory_services_back_the_clipboard_cage() {
+        let memory = MemoryServices::new();
+        memory.set_clipboard("copied elsewhere");
+        let services = DesktopServices::new(Rc::new(memory.clone()));
+        let clipboard = services.clipboard();
+        assert_eq!(clipboard.get_untracked().as_deref(), Some("copied elsewhere"));
+
+        memory.set_clipboard("copied again");
+        assert_eq!(clipboard.get_untracked().as_deref(), Some("copied elsewhere"));
+        services.refresh_clipboard();
+        assert_eq!(clipboard.get_untracked().as_deref(), Some("copied again"));
+
+        services.write_clipboard("from the app").unwrap();
+        assert_eq!(memory.clipboard().as_deref(), Some("from the app"));
+        assert_eq!(clipboard.get_untracked().as_deref(), Some("from the app"));
+    }
+
+    #[test]
+    fn notification_actions_come_back_as_events() {
+        let memory = MemoryServices::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let services = DesktopServices::new(Rc::new(memory.clone())).with_notification_handler({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        });
+
+        services
+            .notify(
+                DesktopNotification::new("build", "Build finished")
+                    .with_body("3 warnings")
+                    .with_action("open", "Open log"),
+            )
+            .unwrap();
+        assert_eq!(memory.notifications()[0].actions[0].label, "Open log");
+
+        assert!(memory.activate("build", "open"));
+        assert!(!memory.activate("build", "open"), "a notification answers once");
+        assert!(!memory.activate("missing", "default"));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![DesktopNotificationEvent {
+                notification: "build".to_owned(),
+                action: "open".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn services_are_reachable_through_the_truck() {
+        let memory = MemoryServices::new().with_app_root("/tmp/app");
+        let truck = Rc::new(RefCell::new(Truck::new()));
+        DesktopServices::new(Rc::new(memory.clone())).enable(truck.clone());
+
+        let services = truck.borrow().obtain::<DesktopServices>().unwrap().clone();
+        services.open_url("https://glory.rs").unwrap();
+        services.reveal_in_file_manager("/tmp/app/data/export.csv").unwrap();
+
+        assert_eq!(memory.opened_urls(), vec!["https://glory.rs".to_owned()]);
+        assert_eq!(memory.revealed_paths(), vec![PathBuf::from("/tmp/app/data/export.csv")]);
+        assert_eq!(services.app_dir(DesktopAppDir::Cache), Some(PathBuf::from("/tmp/app/cache")));
+        assert_eq!(MemoryServices::new().app_dir(DesktopAppDir::Config), None);
+    }
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn app_dirs_follow_the_xdg_layout() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| vars.iter().find(|(name, _)| *name == key).map(|(_, value)| OsString::from(value))
+        };
+
+        let home = env(&[("HOME", "/home/me")]);
+        assert_eq!(
+            platform_app_dir(DesktopAppDir::Config, "notes", home),
+            Some(PathBuf::from("/home/me/.config/notes"))
+        );
+        assert_eq!(
+            platform_app_dir(DesktopAppDir::Data, "notes", home),
+            Some(PathBuf::from("/home/me/.local/share/notes"))
+        );
+        assert_eq!(
+            platform_app_dir(DesktopAppDir::Cache, "notes", home),
+            Some(PathBuf::from("/home/me/.cache/notes"))
+        );
+
+        let xdg = env(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/etc/me"), ("XDG_CACHE_HOME", "relative")]);
+        assert_eq!(
+            platform_app_dir(DesktopAppDir::Config, "notes", xdg),
+            Some(PathBuf::from("/etc/me/notes"))
+        );
+        assert_eq!(
+            platform_app_dir(DesktopAppDir::Cache, "notes", xdg),
+            Some(PathBuf::from("/home/me/.cache/notes"))
+        );
+
+        assert_eq!(platform_app_dir(DesktopAppDir::Data, "notes", env(&[])), None);
+    }
+}
@@ -0,0 +1,838 @@
+//! Signed auto-update for bundled desktop apps.
+//!
+//! An update host serves a [`DesktopUpdateManifest`]: the latest version and,
+//! per target (`{os}-{arch}`, e.g. `windows-x86_64`), the installer to fetch
+//! and its ed25519 signature. `glory bundle` writes the manifest and the
+//! signatures when given the app's private key; the app embeds the public key
+//! in its [`DesktopUpdater`].
+//!
+//! [`check`](DesktopUpdater::check) compares the manifest's version with the
+//! running one, [`download`](DesktopUpdater::download) fetches the installer
+//! while reporting progress through a [`Cage`], verifies the signature and
+//! stages it. The staged installer runs on the next start, through
+//! [`Desktop::with_updater`](crate::Desktop::with_updater), and the updated
+//! app is launched in place of the old one.
+//!
+//! The signature covers the installer's SHA-256 digest together with the
+//! target and version (see [`signed_message`]), so a validly signed old
+//! installer can't be served as a newer version.
+//!
+//! ```ignore
+//! let updater = ctx.truck().obtain::<DesktopUpdater>().ok().cloned();
+//! handle.spawn(async move {
+//!     let Some(updater) = updater else { return };
+//!     if let Ok(Some(update)) = updater.check().await {
+//!         let staged = updater.download(&update, progress).await;
+//!     }
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures::StreamExt;
+use futures_channel::{mpsc, oneshot};
+use glory_core::holder::Enabler;
+use glory_core::{Cage, Truck};
+use ring::digest::{Context as DigestContext, SHA256};
+use ring::signature::{ED25519, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// How long a manifest or installer request may take to start answering.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The marker recording the staged installer, in the staging directory.
+const STAGED_FILE: &str = "staged.json";
+
+/// The JSON document an update host serves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopUpdateManifest {
+    /// The latest version, as semver.
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Release date, RFC 3339.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pub_date: Option<String>,
+    /// The installer per target, keyed like [`current_target`].
+    #[serde(default)]
+    pub platforms: BTreeMap<String, DesktopUpdateArtifact>,
+}
+
+/// The installer of one target.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopUpdateArtifact {
+    /// Where to download it, absolute or relative to the manifest's URL.
+    pub url: String,
+    /// Base64 ed25519 signature of [`signed_message`].
+    pub signature: String,
+    /// Size in bytes, for progress when the host sends no length.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// A newer version found by [`DesktopUpdater::check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopUpdate {
+    pub version: semver::Version,
+    pub current_version: semver::Version,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    pub target: String,
+    /// The installer, with its URL resolved against the manifest's.
+    pub artifact: DesktopUpdateArtifact,
+}
+
+/// Bytes of an installer downloaded so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DesktopUpdateProgress {
+    pub downloaded: u64,
+    /// The installer's size, when the host or manifest gives it.
+    pub total: Option<u64>,
+}
+
+impl DesktopUpdateProgress {
+    /// Between 0.0 and 1.0, when the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0))
+    }
+}
+
+/// A verified installer waiting for the next start.
+///
+/// The marker keeps the signature, so the installer is verified again right
+/// before it runs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopStagedUpdate {
+    pub version: String,
+    pub target: String,
+    /// Base64 ed25519 signature of [`signed_message`].
+    pub signature: String,
+    /// The installer, inside the updater's staging directory.
+    pub path: PathBuf,
+}
+
+/// Error from a [`DesktopUpdater`].
+#[derive(Debug)]
+pub enum DesktopUpdateError {
+    /// The host could not be reached or answered with an error status.
+    Http(String),
+    /// The manifest is not valid JSON, or its version or URLs don't parse.
+    Manifest(String),
+    /// The installer's signature doesn't match the updater's public key.
+    Signature,
+    Io(std::io::Error),
+    /// The updater has no staging directory; see
+    /// [`DesktopUpdater::with_staging_dir`].
+    NoStagingDir,
+    /// The worker thread went away before answering.
+    Disconnected,
+}
+
+impl std::fmt::Display for DesktopUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DesktopUpdateError::Http(message) => write!(f, "update request failed: {message}"),
+            DesktopUpdateError::Manifest(message) => write!(f, "invalid update manifest: {message}"),
+            DesktopUpdateError::Signature => f.write_str("update signature does not match the public key"),
+            DesktopUpdateError::Io(err) => write!(f, "update staging failed: {err}"),
+            DesktopUpdateError::NoStagingDir => f.write_str("update staging failed: no staging directory"),
+            DesktopUpdateError::Disconnected => f.write_str("update worker disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for DesktopUpdateError {}
+
+impl From<std::io::Error> for DesktopUpdateError {
+    fn from(err: std::io::Error) -> Self {
+        DesktopUpdateError::Io(err)
+    }
+}
+
+fn http_error(err: reqwest::Error) -> DesktopUpdateError {
+    DesktopUpdateError::Http(err.to_string())
+}
+
+/// The running build's target key, `{os}-{arch}` as in
+/// [`std::env::consts`], e.g. `macos-aarch64`.
+pub fn current_target() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// What an artifact's signature covers. `glory bundle` signs the same bytes.
+pub fn signed_message(target: &str, version: &str, sha256: &[u8]) -> Vec<u8> {
+    let mut message = format!("glory-update-v1\n{target}\n{version}\n").into_bytes();
+    message.extend(sha256.iter().flat_map(|byte| format!("{byte:02x}").into_bytes()));
+    message
+}
+
+/// Checks an update host for newer versions, downloads and stages them, and
+/// installs the staged one on the next start.
+///
+/// Enabled on every window's holder by
+/// [`Desktop::with_updater`](crate::Desktop::with_updater), so widgets take
+/// it from the [`Truck`].
+#[derive(Clone, Debug)]
+pub struct DesktopUpdater {
+    manifest_url: String,
+    public_key: Arc<[u8]>,
+    current_version: String,
+    target: String,
+    staging_dir: Option<PathBuf>,
+}
+
+impl DesktopUpdater {
+    /// Checks `manifest_url` for versions newer than `current_version`
+    /// (usually `env!("CARGO_PKG_VERSION")`), trusting installers signed by
+    /// `public_key`, the base64 ed25519 key `glory bundle` prints.
+    ///
+    /// # Panics
+    ///
+    /// If `public_key` is not a base64 32-byte key.
+    pub fn new(manifest_url: impl Into<String>, public_key: &str, current_version: impl Into<String>) -> Self {
+        let public_key = BASE64
+            .decode(public_key.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .expect("DesktopUpdater::new: the public key must be 32 base64-encoded bytes");
+        Self {
+            manifest_url: manifest_url.into(),
+            public_key: public_key.into(),
+            current_version: current_version.into(),
+            target: current_target(),
+            staging_dir: None,
+        }
+    }
+
+    /// Looks up installers for `target` instead of [`current_target`].
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Stages downloads in `dir`. [`Desktop::with_updater`](crate::Desktop::with_updater)
+    /// defaults it to `updates` in the app's data directory.
+    pub fn with_staging_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.staging_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the staging directory unless one is set already.
+    pub(crate) fn or_staging_dir(mut self, dir: impl FnOnce() -> Option<PathBuf>) -> Self {
+        if self.staging_dir.is_none() {
+            self.staging_dir = dir();
+        }
+        self
+    }
+
+    pub fn manifest_url(&self) -> &str {
+        &self.manifest_url
+    }
+
+    pub fn current_version(&self) -> &str {
+        &self.current_version
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Where downloads are staged. An updater without one can check for
+    /// updates but not download or install them.
+    pub fn staging_dir(&self) -> Option<&Path> {
+        self.staging_dir.as_deref()
+    }
+
+    /// Fetches the manifest and resolves with the update for this target
+    /// when its version is newer than the running one. A manifest without
+    /// an installer for this target has no update.
+    ///
+    /// The request runs on a worker thread.
+    pub fn check(&self) -> impl Future<Output = Result<Option<DesktopUpdate>, DesktopUpdateError>> + Send + 'static {
+        let updater = self.clone();
+        let answer = on_worker(move || {
+            let manifest = fetch_manifest(&updater.manifest_url)?;
+            updater.update_from(manifest)
+        });
+        async move { answer.await.unwrap_or(Err(DesktopUpdateError::Disconnected)) }
+    }
+
+    /// The update `manifest` offers this build, if any.
+    fn update_from(&self, manifest: DesktopUpdateManifest) -> Result<Option<DesktopUpdate>, DesktopUpdateError> {
+        let version = parse_version(&manifest.version)?;
+        let current_version = parse_version(&self.current_version)?;
+        if version <= current_version {
+            return Ok(None);
+        }
+        let Some(mut artifact) = manifest.platforms.get(&self.target).cloned() else {
+            return Ok(None);
+        };
+        artifact.url = reqwest::Url::parse(&self.manifest_url)
+            .and_then(|base| base.join(&artifact.url))
+            .map_err(|err| DesktopUpdateError::Manifest(format!("{}: {err}", artifact.url)))?
+            .to_string();
+        Ok(Some(DesktopUpdate {
+            version,
+            current_version,
+            notes: manifest.notes,
+            pub_date: manifest.pub_date,
+            target: self.target.clone(),
+            artifact,
+        }))
+    }
+
+    /// Downloads `update`'s installer on a worker thread, verifies its
+    /// signature and stages it for the next start, replacing any staged one.
+    ///
+    /// `progress` is written as bytes arrive. Poll the returned future on the
+    /// thread that owns the cage, e.g. with
+    /// [`DesktopWindowHandle::spawn`](crate::DesktopWindowHandle::spawn), so
+    /// the writes flush to the webview.
+    pub fn download(
+        &self,
+        update: &DesktopUpdate,
+        progress: Cage<DesktopUpdateProgress>,
+    ) -> impl Future<Output = Result<DesktopStagedUpdate, DesktopUpdateError>> + 'static {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let updater = self.clone();
+        let update = update.clone();
+        let finished = on_worker(move || {
+            updater.stage(&update, |downloaded, total| {
+                let _ = sender.unbounded_send(DesktopUpdateProgress { downloaded, total });
+            })
+        });
+        async move {
+            while let Some(next) = receiver.next().await {
+                if *progress.get_untracked() != next {
+                    progress.revise(|mut progress| *progress = next);
+                }
+            }
+            finished.await.unwrap_or(Err(DesktopUpdateError::Disconnected))
+        }
+    }
+
+    fn stage(&self, update: &DesktopUpdate, on_progress: impl Fn(u64, Option<u64>)) -> Result<DesktopStagedUpdate, DesktopUpdateError> {
+        let dir = self.staging_dir().ok_or(DesktopUpdateError::NoStagingDir)?;
+        create_private_dir(dir)?;
+        let path = dir.join(artifact_file_name(&update.artifact.url));
+        let partial = path.with_file_name(format!("{}.part", path.file_name().and_then(|name| name.to_str()).unwrap_or("update")));
+        let digest = match download_to(&update.artifact, &partial, on_progress) {
+            Ok(digest) => digest,
+            Err(err) => {
+                let _ = std::fs::remove_file(&partial);
+                return Err(err);
+            }
+        };
+        let version = update.version.to_string();
+        if !self.verify(&update.target, &version, &digest, &update.artifact.signature) {
+            let _ = std::fs::remove_file(&partial);
+            return Err(DesktopUpdateError::Signature);
+        }
+        // Earlier installers, staged or already installed, go.
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?.path();
+            if entry != partial && entry.is_file() {
+                std::fs::remove_file(entry)?;
+            }
+        }
+        std::fs::rename(&partial, &path)?;
+        let staged = DesktopStagedUpdate {
+            version,
+            target: update.target.clone(),
+            signature: update.artifact.signature.clone(),
+            path,
+        };
+        let marker = serde_json::to_vec_pretty(&staged).map_err(std::io::Error::other)?;
+        std::fs::write(dir.join(STAGED_FILE), marker)?;
+        Ok(staged)
+    }
+
+    fn verify(&self, target: &str, version: &str, digest: &[u8], signature: &str) -> bool {
+        let Ok(signature) = BASE64.decode(signature.trim()) else {
+            return false;
+        };
+        UnparsedPublicKey::new(&ED25519, &*self.public_key)
+            .verify(&signed_message(target, version, digest), &signature)
+            .is_ok()
+    }
+
+    /// The installer staged by [`download`](Self::download) for this
+    /// target, if it is still there. A marker naming a file outside the
+    /// staging directory is ignored.
+    pub fn staged(&self) -> Option<DesktopStagedUpdate> {
+        let dir = self.staging_dir()?;
+        let marker = std::fs::read(dir.join(STAGED_FILE)).ok()?;
+        let staged: DesktopStagedUpdate = serde_json::from_slice(&marker).ok()?;
+        let inside = std::fs::canonicalize(&staged.path)
+            .ok()
+            .filter(|path| path.is_file())
+            .and_then(|path| Some(path.parent()? == std::fs::canonicalize(dir).ok()?))
+            .unwrap_or(false);
+        (inside && staged.target == self.target).then_some(staged)
+    }
+
+    /// Removes the staged installer, if any.
+    pub fn discard_staged(&self) -> std::io::Result<()> {
+        let Some(dir) = self.staging_dir() else {
+            return Ok(());
+        };
+        let marker = dir.join(STAGED_FILE);
+        if let Some(staged) = self.staged() {
+            std::fs::remove_file(&staged.path)?;
+        }
+        match std::fs::remove_file(marker) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts the staged installer when it is newer than the running build.
+    /// Returns `true` when it did: the caller should exit so the installer
+    /// can replace the app, after which the updated app is launched.
+    ///
+    /// The installer's signature is checked again first, so a staged file
+    /// replaced since the download is refused. The staged update is consumed
+    /// either way, so a failing installer is not retried on every start.
+    pub fn install_staged(&self) -> Result<bool, DesktopUpdateError> {
+        self.install_staged_releasing(|| {})
+    }
+
+    /// [`install_staged`](Self::install_staged), calling `release` right
+    /// before the installer starts, so the single-instance lock is free for
+    /// the updated app it launches.
+    pub(crate) fn install_staged_releasing(&self, release: impl FnOnce()) -> Result<bool, DesktopUpdateError> {
+        let (Some(dir), Some(staged)) = (self.staging_dir(), self.staged()) else {
+            return Ok(false);
+        };
+        let newer = parse_version(&staged.version)? > parse_version(&self.current_version)?;
+        if !newer {
+            self.discard_staged()?;
+            return Ok(false);
+        }
+        std::fs::remove_file(dir.join(STAGED_FILE))?;
+        let digest = file_digest(&staged.path)?;
+        if !self.verify(&staged.target, &staged.version, &digest, &staged.signature) {
+            let _ = std::fs::remove_file(&staged.path);
+            return Err(DesktopUpdateError::Signature);
+        }
+        release();
+        if let Err(err) = install(&staged.path) {
+            let _ = std::fs::remove_file(&staged.path);
+            return Err(err.into());
+        }
+        // The Windows installer still reads the file after this process
+        // exits; the next download clears it.
+        Ok(true)
+    }
+}
+
+impl Enabler for DesktopUpdater {
+    fn enable(self, truck: Rc<RefCell<Truck>>) {
+        truck.borrow_mut().inject(self);
+    }
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, DesktopUpdateError> {
+    semver::Version::parse(version.trim_start_matches('v')).map_err(|err| DesktopUpdateError::Manifest(format!("version {version:?}: {err}")))
+}
+
+/// Runs `work` on a new thread and resolves with its result.
+fn on_worker<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> oneshot::Receiver<T> {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    receiver
+}
+
+fn client() -> Result<reqwest::blocking::Client, DesktopUpdateError> {
+    reqwest::blocking::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(None)
+        .build()
+        .map_err(http_error)
+}
+
+fn fetch_manifest(url: &str) -> Result<DesktopUpdateManifest, DesktopUpdateError> {
+    let response = client()?
+        .get(url)
+        .timeout(CONNECT_TIMEOUT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(http_error)?;
+    let body = response.bytes().map_err(http_error)?;
+    serde_json::from_slice(&body).map_err(|err| DesktopUpdateError::Manifest(err.to_string()))
+}
+
+/// Streams the installer into `path` and returns its SHA-256 digest.
+fn download_to(artifact: &DesktopUpdateArtifact, path: &Path, on_progress: impl Fn(u64, Option<u64>)) -> Result<Vec<u8>, DesktopUpdateError> {
+    let mut response = client()?
+        .get(&artifact.url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(http_error)?;
+    let total = response.content_length().or(artifact.size);
+    let mut file = std::fs::File::create(path)?;
+    let mut digest = DigestContext::new(&SHA256);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut downloaded = 0;
+    on_progress(0, total);
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        digest.update(&buffer[..read]);
+        file.write_all(&buffer[..read])?;
+        downloaded += read as u64;
+        on_progress(downloaded, total);
+    }
+    file.sync_all()?;
+    Ok(digest.finish().as_ref().to_vec())
+}
+
+fn file_digest(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut digest = DigestContext::new(&SHA256);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(digest.finish().as_ref().to_vec());
+        }
+        digest.update(&buffer[..read]);
+    }
+}
+
+/// Creates `dir` readable by this user only, where the platform allows.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// The installer's file name from its URL, without query or fragment.
+fn artifact_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.trim_matches('.').is_empty() { "update".to_owned() } else { name }
+}
+
+/// Runs the Windows installer, then starts the installed app.
+#[cfg(target_os = "windows")]
+fn install(path: &Path) -> std::io::Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    let exe = std::env::current_exe()?;
+    // `cmd` waits for msiexec, which waits for this process to exit.
+    std::process::Command::new("cmd")
+        .raw_arg(format!(
+            "/C msiexec /i \"{}\" /passive /norestart && start \"\" \"{}\"",
+            path.display(),
+            exe.display()
+        ))
+        .spawn()?;
+    Ok(())
+}
+
+/// Copies the `.app` out of the staged disk image over the running bundle,
+/// then starts it.
+#[cfg(target_os = "macos")]
+fn install(path: &Path) -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let bundle = exe
+        .ancestors()
+        .find(|dir| dir.extension().is_some_and(|ext| ext == "app"))
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Unsupported, "the app is not running from a .app bundle"))?
+        .to_path_buf();
+    let mount = path.with_extension("mount");
+    run(std::process::Command::new("hdiutil")
+        .args(["attach", "-nobrowse", "-quiet", "-mountpoint"])
+        .arg(&mount)
+        .arg(path))?;
+    let copied = (|| {
+        let app = std::fs::read_dir(&mount)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|entry| entry.extension().is_some_and(|ext| ext == "app"))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "the disk image holds no .app"))?;
+        let replacement = bundle.with_extension("app-update");
+        let _ = std::fs::remove_dir_all(&replacement);
+        run(std::process::Command::new("ditto").arg(&app).arg(&replacement))?;
+        std::fs::remove_dir_all(&bundle)?;
+        std::fs::rename(&replacement, &bundle)
+    })();
+    let _ = run(std::process::Command::new("hdiutil").args(["detach", "-quiet"]).arg(&mount));
+    copied?;
+    std::process::Command::new("open").arg("-n").arg(&bundle).spawn()?;
+    Ok(())
+}
+
+/// Replaces the running AppImage with the staged one, then starts it.
+#[cfg(target_os = "linux")]
+fn install(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Some(appimage) = std::env::var_os("APPIMAGE").map(PathBuf::from) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "only apps running as an AppImage update themselves on Linux",
+        ));
+    };
+    let replacement = appimage.with_extension("AppImage.update");
+    std::fs::copy(path, &replacement)?;
+    std::fs::set_permissions(&replacement, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::rename(&replacement, &appimage)?;
+    std::process::Command::new(&appimage).args(std::env::args_os().skip(1)).spawn()?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn install(_path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "installing updates is not supported on this platform",
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn run(command: &mut std::process::Command) -> std::io::Result<()> {
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("{command:?} exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::{Ipv4Addr, TcpListener};
+
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    const TARGET: &str = "linux-x86_64";
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap()
+    }
+
+    fn sign(version: &str, installer: &[u8]) -> String {
+        let digest = ring::digest::digest(&SHA256, installer);
+        BASE64.encode(key_pair().sign(&signed_message(TARGET, version, digest.as_ref())).as_ref())
+    }
+
+    /// Serves `files` by path over HTTP on a loopback port until the test
+    /// ends, and returns the base URL.
+    fn serve(files: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut request = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request).unwrap();
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap() <= 2 {
+                        break;
+                    }
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let response = match files.iter().find(|(file, _)| *file == path) {
+                    Some((_, body)) => {
+                        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                        response.extend_from_slice(body);
+                        response
+                    }
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        base
+    }
+
+    fn manifest(version: &str, signature: &str) -> Vec<u8> {
+        serde_json::to_vec(&DesktopUpdateManifest {
+            version: version.to_owned(),
+            notes: Some("Faster startup".to_owned()),
+            pub_date: None,
+            platforms: BTreeMap::from([(
+                TARGET.to_owned(),
+                DesktopUpdateArtifact {
+                    url: "installers/app_1.2.0.AppImage".to_owned(),
+                    signature: signature.to_owned(),
+                    size: None,
+                },
+            )]),
+        })
+        .unwrap()
+    }
+
+    fn updater(base: &str, current_version: &str, staging: &Path) -> DesktopUpdater {
+        let public_key = BASE64.encode(key_pair().public_key().as_ref());
+        DesktopUpdater::new(format!("{base}/updates/manifest.json"), &public_key, current_version)
+            .with_target(TARGET)
+            .with_staging_dir(staging)
+    }
+
+    #[test]
+    fn check_offers_only_newer_versions_for_this_target() {
+        let base = serve(vec![("/updates/manifest.json", manifest("1.2.0", "unused"))]);
+        let staging = std::env::temp_dir().join(format!("glory-desktop-update-check-{}", std::process::id()));
+
+        let update = futures::executor::block_on(updater(&base, "1.1.9", &staging).check()).unwrap().unwrap();
+        assert_eq!(update.version, semver::Version::new(1, 2, 0));
+        assert_eq!(update.notes.as_deref(), Some("Faster startup"));
+        // Relative installer URLs resolve against the manifest's.
+        assert_eq!(update.artifact.url, format!("{base}/updates/installers/app_1.2.0.AppImage"));
+
+        assert!(futures::executor::block_on(updater(&base, "1.2.0", &staging).check()).unwrap().is_none());
+        assert!(
+            futures::executor::block_on(updater(&base, "2.0.0-beta.1", &staging).check())
+                .unwrap()
+                .is_none()
+        );
+        let other_target = updater(&base, "1.0.0", &staging).with_target("windows-x86_64");
+        assert!(futures::executor::block_on(other_target.check()).unwrap().is_none());
+
+        let missing = DesktopUpdater::new(format!("{base}/missing.json"), &BASE64.encode([0; 32]), "1.0.0");
+        assert!(matches!(futures::executor::block_on(missing.check()), Err(DesktopUpdateError::Http(_))));
+    }
+
+    #[test]
+    fn downloads_are_verified_staged_and_report_progress() {
+        let installer: Vec<u8> = (0..200_000u32).map(|byte| byte as u8).collect();
+        let base = serve(vec![
+            ("/updates/manifest.json", manifest("1.2.0", &sign("1.2.0", &installer))),
+            ("/updates/installers/app_1.2.0.AppImage", installer.clone()),
+        ]);
+        let staging = std::env::temp_dir().join(format!("glory-desktop-update-download-{}", std::process::id()));
+        let updater = updater(&base, "1.1.0", &staging);
+
+        let update = futures::executor::block_on(updater.check()).unwrap().unwrap();
+        let progress = Cage::new(DesktopUpdateProgress::default());
+        let staged = futures::executor::block_on(updater.download(&update, progress)).unwrap();
+        assert_eq!(
+            *progress.get_untracked(),
+            DesktopUpdateProgress {
+                downloaded: installer.len() as u64,
+                total: Some(installer.len() as u64),
+            }
+        );
+        assert_eq!(progress.get_untracked().fraction(), Some(1.0));
+        assert_eq!(staged.version, "1.2.0");
+        assert_eq!(std::fs::read(&staged.path).unwrap(), installer);
+        assert_eq!(updater.staged(), Some(staged.clone()));
+
+        // A build that already runs the staged version drops it on start.
+        let updated = DesktopUpdater::new(updater.manifest_url(), &BASE64.encode(key_pair().public_key().as_ref()), "1.2.0")
+            .with_target(TARGET)
+            .with_staging_dir(&staging);
+        assert!(!updated.install_staged().unwrap());
+        assert_eq!(updater.staged(), None);
+        assert!(!staged.path.exists());
+
+        std::fs::remove_dir_all(&staging).unwrap();
+    }
+
+    #[test]
+    fn staged_installers_are_verified_again_before_running() {
+        let installer = b"the real installer".to_vec();
+        let base = serve(vec![
+            ("/updates/manifest.json", manifest("1.2.0", &sign("1.2.0", &installer))),
+            ("/updates/installers/app_1.2.0.AppImage", installer),
+        ]);
+        let staging = std::env::temp_dir().join(format!("glory-desktop-update-restaged-{}", std::process::id()));
+        let updater = updater(&base, "1.0.0", &staging);
+        let update = futures::executor::block_on(updater.check()).unwrap().unwrap();
+        let progress = Cage::new(DesktopUpdateProgress::default());
+        let staged = futures::executor::block_on(updater.download(&update, progress)).unwrap();
+        assert_eq!(staged.target, TARGET);
+
+        // A marker pointing outside the staging directory is ignored.
+        let outside = staging.with_extension("outside");
+        std::fs::write(&outside, b"#!/bin/sh").unwrap();
+        let marker = DesktopStagedUpdate {
+            path: outside.clone(),
+            ..staged.clone()
+        };
+        std::fs::write(staging.join(STAGED_FILE), serde_json::to_vec(&marker).unwrap()).unwrap();
+        assert_eq!(updater.staged(), None);
+        assert!(!updater.install_staged().unwrap());
+        std::fs::remove_file(&outside).unwrap();
+
+        // An installer swapped after the download is refused and removed.
+        std::fs::write(staging.join(STAGED_FILE), serde_json::to_vec(&staged).unwrap()).unwrap();
+        std::fs::write(&staged.path, b"something else").unwrap();
+        assert!(matches!(updater.install_staged(), Err(DesktopUpdateError::Signature)));
+        assert!(!staged.path.exists());
+        assert_eq!(updater.staged(), None);
+
+        // Without a staging directory nothing is downloaded or installed.
+        let unstaged = DesktopUpdater::new(updater.manifest_url(), &BASE64.encode(key_pair().public_key().as_ref()), "1.0.0");
+        assert_eq!(unstaged.staging_dir(), None);
+        assert!(!unstaged.install_staged().unwrap());
+        let result = futures::executor::block_on(unstaged.download(&update, Cage::new(DesktopUpdateProgress::default())));
+        assert!(matches!(result, Err(DesktopUpdateError::NoStagingDir)), "{result:?}");
+
+        std::fs::remove_dir_all(&staging).unwrap();
+    }
+
+    #[test]
+    fn tampered_installers_are_rejected() {
+        let installer = b"the real installer".to_vec();
+        // Signed for an older version: replaying it as 1.2.0 must fail too.
+        let base = serve(vec![
+            ("/updates/manifest.json", manifest("1.2.0", &sign("1.1.0", &installer))),
+            ("/updates/installers/app_1.2.0.AppImage", installer),
+        ]);
+        let staging = std::env::temp_dir().join(format!("glory-desktop-update-tampered-{}", std::process::id()));
+        let updater = updater(&base, "1.0.0", &staging);
+
+        let update = futures::executor::block_on(updater.check()).unwrap().unwrap();
+        let progress = Cage::new(DesktopUpdateProgress::default());
+        let result = futures::executor::block_on(updater.download(&update, progress));
+        assert!(matches!(result, Err(DesktopUpdateError::Signature)), "{result:?}");
+        assert_eq!(updater.staged(), None);
+        assert_eq!(std::fs::read_dir(&staging).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&staging).unwrap();
+    }
+
+    #[test]
+    fn installer_file_names_come_from_the_url() {
+        assert_eq!(artifact_file_name("https://h/x/My App_1.2.0_x64.msi?token=1"), "My_App_1.2.0_x64.msi");
+        assert_eq!(artifact_file_name("https://h/download/"), "update");
+        assert_eq!(artifact_file_name("https://h/.."), "update");
+    }
+}
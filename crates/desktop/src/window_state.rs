@@ -0,0 +1,231 @@
+//! Window state persistence and session restore.
+//!
+//! Windows with a [`DesktopConfig::label`](crate::DesktopConfig::label) keep
+//! their size, position, maximized/fullscreen state and zoom level across
+//! runs. The host records them into a [`DesktopSavedState`] and writes it as
+//! JSON through a [`DesktopStateStore`], by default
+//! `window-state.json` in the app's data directory.
+//!
+//! The saved state also lists the labeled windows that were open when the
+//! app quit, and which window factory made each one, so
+//! [`Desktop::with_session_restore`](crate::Desktop::with_session_restore)
+//! can open them again.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a labeled window was and how it looked. Sizes and positions are
+/// physical pixels; `width`/`height` are the inner size and `x`/`y` the
+/// outer position.
+///
+/// While a window is maximized or fullscreen the geometry keeps its last
+/// normal bounds, so un-maximizing a restored window lands where it was.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DesktopSavedWindow {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default = "default_zoom_level")]
+    pub zoom_level: f64,
+}
+
+fn default_zoom_level() -> f64 {
+    1.0
+}
+
+impl DesktopSavedWindow {
+    /// Moves and shrinks the window so it fits on the monitor it overlaps
+    /// most, or on the first monitor when it is off every screen (e.g. the
+    /// monitor it was on is unplugged).
+    pub(crate) fn clamp_to(mut self, monitors: &[MonitorArea]) -> Self {
+        let overlapping = monitors
+            .iter()
+            .map(|monitor| (monitor, monitor.overlap(&self)))
+            .filter(|(_, overlap)| *overlap > 0)
+            .max_by_key(|(_, overlap)| *overlap)
+            .map(|(monitor, _)| monitor);
+        let Some(monitor) = overlapping.or(monitors.first()) else {
+            return self;
+        };
+        self.width = self.width.min(monitor.width);
+        self.height = self.height.min(monitor.height);
+        self.x = self.x.clamp(monitor.x, monitor.x + (monitor.width - self.width) as i32);
+        self.y = self.y.clamp(monitor.y, monitor.y + (monitor.height - self.height) as i32);
+        self
+    }
+}
+
+/// A monitor's bounds in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct MonitorArea {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl MonitorArea {
+    fn overlap(&self, window: &DesktopSavedWindow) -> u64 {
+        let span = |start: i32, length: u32, other_start: i32, other_length: u32| {
+            let end = (i64::from(start) + i64::from(length)).min(i64::from(other_start) + i64::from(other_length));
+            (end - i64::from(start.max(other_start))).max(0) as u64
+        };
+        span(self.x, self.width, window.x, window.width) * span(self.y, self.height, window.y, window.height)
+    }
+}
+
+/// A labeled window that was open when the app quit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopSessionWindow {
+    pub label: String,
+    /// The [`Desktop::with_window_factory`](crate::Desktop::with_window_factory)
+    /// kind that made the window.
+    pub kind: String,
+}
+
+/// Everything a [`DesktopStateStore`] keeps.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopSavedState {
+    /// Last known state per window label.
+    pub windows: BTreeMap<String, DesktopSavedWindow>,
+    /// The factory-made windows open at the end of the last session, in
+    /// opening order.
+    pub session: Vec<DesktopSessionWindow>,
+}
+
+/// Reads and writes a [`DesktopSavedState`] as a JSON file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopStateStore {
+    path: PathBuf,
+}
+
+impl DesktopStateStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved state, or an empty one when the file is missing or
+    /// unreadable. A broken file is logged and then replaced on the next
+    /// save.
+    pub fn load(&self) -> DesktopSavedState {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!(%err, path = %self.path.display(), "glory-desktop: failed to read window state");
+                }
+                return DesktopSavedState::default();
+            }
+        };
+        serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!(%err, path = %self.path.display(), "glory-desktop: ignoring undecodable window state");
+            DesktopSavedState::default()
+        })
+    }
+
+    /// Writes `state`, creating the parent directory. The file is replaced
+    /// in one rename so a crash mid-write keeps the previous state.
+    pub fn save(&self, state: &DesktopSavedState) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(state).map_err(std::io::Error::other)?;
+        let temp = self.path.with_extension("json.tmp");
+        std::fs::write(&temp, json)?;
+        std::fs::rename(&temp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(x: i32, y: i32, width: u32, height: u32) -> DesktopSavedWindow {
+        DesktopSavedWindow {
+            x,
+            y,
+            width,
+            height,
+            maximized: false,
+            fullscreen: false,
+            zoom_level: 1.0,
+        }
+    }
+
+    const PRIMARY: MonitorArea = MonitorArea {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    };
+    const RIGHT: MonitorArea = MonitorArea {
+        x: 1920,
+        y: 0,
+        width: 1280,
+        height: 1024,
+    };
+
+    #[test]
+    fn clamping_keeps_windows_on_the_monitor_they_overlap_most() {
+        // Fully visible windows stay put.
+        assert_eq!(window(100, 80, 900, 640).clamp_to(&[PRIMARY, RIGHT]), window(100, 80, 900, 640));
+        // Mostly on the right monitor: pulled fully onto it.
+        assert_eq!(window(1800, 500, 900, 640).clamp_to(&[PRIMARY, RIGHT]), window(1920, 384, 900, 640));
+        // Too big for its monitor: shrunk to fit.
+        assert_eq!(window(2000, -50, 1600, 1200).clamp_to(&[PRIMARY, RIGHT]), window(1920, 0, 1280, 1024));
+    }
+
+    #[test]
+    fn clamping_moves_windows_off_every_monitor_to_the_first() {
+        // The right monitor was unplugged since the window was saved there.
+        assert_eq!(window(2400, 200, 900, 640).clamp_to(&[PRIMARY]), window(1020, 200, 900, 640));
+        assert_eq!(window(-5000, -5000, 900, 640).clamp_to(&[PRIMARY]), window(0, 0, 900, 640));
+        assert_eq!(window(-5000, -5000, 900, 640).clamp_to(&[]), window(-5000, -5000, 900, 640));
+    }
+
+    #[test]
+    fn store_round_trips_state_and_tolerates_broken_files() {
+        let dir = std::env::temp_dir().join(format!("glory-desktop-window-state-{}", std::process::id()));
+        let store = DesktopStateStore::new(dir.join("nested/window-state.json"));
+        assert_eq!(store.load(), DesktopSavedState::default());
+
+        let mut state = DesktopSavedState::default();
+        state.windows.insert(
+            "main".to_owned(),
+            DesktopSavedWindow {
+                maximized: true,
+                zoom_level: 1.25,
+                ..window(10, 20, 800, 600)
+            },
+        );
+        state.session.push(DesktopSessionWindow {
+            label: "doc-1".to_owned(),
+            kind: "document".to_owned(),
+        });
+        store.save(&state).unwrap();
+        assert_eq!(store.load(), state);
+
+        std::fs::write(store.path(), "{ not json").unwrap();
+        assert_eq!(store.load(), DesktopSavedState::default());
+
+        // Fields added later default when older files lack them.
+        std::fs::write(store.path(), r#"{"windows":{"main":{"x":1,"y":2,"width":3,"height":4}}}"#).unwrap();
+        let loaded = store.load();
+        assert_eq!(loaded.windows["main"], window(1, 2, 3, 4));
+        assert!(loaded.session.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -115,7 +115,11 @@
   window.__gloryApplyWryCommand = (cmd) => {
     const [type, data] = decode(cmd);
     if (type === "Create") {
-      nodes.set(data.id, document.createElement(data.name));
+      const element = document.createElement(data.name);
+      if (window.__gloryCspNonce && (data.name === "script" || data.name === "style")) {
+        element.nonce = window.__gloryCspNonce;
+      }
+      nodes.set(data.id, element);
     } else if (type === "SetAttribute") {
       byId(data.id).setAttribute(data.name, data.value);
     } else if (type === "RemoveAttribute") {
@@ -196,6 +200,28 @@
     }
   };
 
+  // The element behind a node id, if any. The LiveView client reads element
+  // directives through it.
+  window.__gloryWryNode = (id) => (id === 0 ? document.body : nodes.get(id));
+
+  // Registers server-rendered elements by their `data-glory-id`, so commands
+  // address them like nodes the interpreter created. A LiveView page calls
+  // this before applying a mount on top of its dead render.
+  window.__gloryAdoptWry = (root = document.body) => {
+    for (const element of root.querySelectorAll("[data-glory-id]")) {
+      nodes.set(Number(element.getAttribute("data-glory-id")), element);
+      element.removeAttribute("data-glory-id");
+    }
+  };
+
+  // Removes every node the interpreter created, e.g. before a LiveView client
+  // applies a fresh mount after its session could not be resumed.
+  window.__gloryResetWry = () => {
+    for (const node of nodes.values()) node.remove();
+    nodes.clear();
+    listeners.clear();
+  };
+
   // Runs host-supplied JavaScript and posts the JSON-serialized result back
   // as `{ GloryWryEval: { id, ok, value } }`. The source is wrapped in an
   // async function body so callers can `await` and return via a trailing
@@ -1 +1,33 @@
 console.log("[HOT RELOADING] Connected to server.");
+
+// Hydration mismatches reported by glory-core (`glory:hydration-mismatch`)
+// are collected into a dismissible overlay while developing.
+window.addEventListener("glory:hydration-mismatch", (ev) => {
+    const detail = ev.detail || {};
+    const id = "__glory_hydration_overlay__";
+    let overlay = document.getElementById(id);
+    if (!overlay) {
+        overlay = document.createElement("div");
+        overlay.id = id;
+        overlay.style.cssText = "position:fixed;right:12px;bottom:12px;max-width:min(640px,90vw);max-height:50vh;overflow:auto;z-index:2147483647;background:rgba(20,20,20,0.94);color:#ffb86b;font:12px/1.5 ui-monospace,SFMono-Regular,Menlo,monospace;border-radius:6px;box-shadow:0 4px 16px rgba(0,0,0,0.4);";
+        const bar = document.createElement("div");
+        bar.style.cssText = "display:flex;justify-content:space-between;align-items:center;padding:8px 12px;background:#1b1b1b;color:#fff;font-weight:600;position:sticky;top:0;";
+        const title = document.createElement("span");
+        title.textContent = "Hydration mismatches";
+        const close = document.createElement("button");
+        close.textContent = "×";
+        close.setAttribute("aria-label", "Dismiss hydration mismatches");
+        close.style.cssText = "background:none;border:none;color:#fff;font-size:18px;line-height:1;cursor:pointer;";
+        close.onclick = () => overlay.remove();
+        bar.appendChild(title);
+        bar.appendChild(close);
+        const list = document.createElement("ol");
+        list.style.cssText = "margin:0;padding:8px 12px 8px 32px;";
+        overlay.appendChild(bar);
+        overlay.appendChild(list);
+        document.body.appendChild(overlay);
+    }
+    const item = document.createElement("li");
+    item.textContent = detail.message || String(detail.viewId);
+    overlay.querySelector("ol").appendChild(item);
+});
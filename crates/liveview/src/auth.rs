@@ -0,0 +1,57 @@
+//! Request context and authorization for LiveView sockets.
+//!
+//! The adapters snapshot the WebSocket upgrade request as glory-serverfn's
+//! [`RequestContext`] and inject it into the truck of the session it mounts,
+//! so widgets read cookies and headers the way server functions do:
+//!
+//! ```ignore
+//! let user = ctx.truck().obtain::<RequestContext>().ok().and_then(|request| request.cookie("user"));
+//! ```
+//!
+//! A route mounted with a [`LiveViewAuthorizer`] asks it about every upgrade
+//! request before reading from the socket. It approves a request for a
+//! principal, such as a user id, and a parked session resumes only on a
+//! socket approved for the principal that mounted it; the session then gets
+//! that socket's context. A `Resume` from anyone else mounts a fresh session
+//! and leaves the parked one alone. Without an authorizer the resume token
+//! alone decides. A refusal sends
+//! [`LiveViewMessage::Close`](crate::LiveViewMessage::Close) with the
+//! authorizer's reason, which also stops the client from reconnecting. While
+//! the socket is open the authorizer is asked again every
+//! [`revalidate_secs`](crate::LiveViewConfig::revalidate_secs), and a session
+//! whose request it refuses or approves for another principal by then is
+//! closed the same way and dropped instead of parked.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub use glory_serverfn::RequestContext;
+
+type AuthorizeFn = dyn Fn(RequestContext) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send>> + Send + Sync;
+
+/// Decides whether the request behind a LiveView socket may hold a session.
+/// `Ok` carries the principal the request acts for, `Err` the reason sent
+/// to the client.
+#[derive(Clone)]
+pub struct LiveViewAuthorizer(Arc<AuthorizeFn>);
+
+impl LiveViewAuthorizer {
+    pub fn new<F, Fut>(authorize: F) -> Self
+    where
+        F: Fn(RequestContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        Self(Arc::new(move |request| Box::pin(authorize(request))))
+    }
+
+    pub async fn authorize(&self, request: &RequestContext) -> Result<String, String> {
+        (self.0)(request.clone()).await
+    }
+}
+
+impl std::fmt::Debug for LiveViewAuthorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveViewAuthorizer").finish_non_exhaustive()
+    }
+}
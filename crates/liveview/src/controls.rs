@@ -0,0 +1,86 @@
+//! Element directives the LiveView client honors.
+//!
+//! [`LiveControls`] adds builder methods to every element that set `data-glory-*`
+//! attributes. The commands carry them to the browser like any other
+//! attribute, and `LIVEVIEW_CLIENT_JS` reads them when the element emits an
+//! event:
+//!
+//! - [`live_debounce`](LiveControls::live_debounce) sends an event only once
+//!   the element has been quiet for a while, with its latest data.
+//! - [`live_throttle`](LiveControls::live_throttle) sends at most one event
+//!   per interval, and the last one when the interval ends.
+//! - [`live_loading_class`](LiveControls::live_loading_class) and
+//!   [`live_disable_with`](LiveControls::live_disable_with) mark the element
+//!   while an event it sent waits for the server's reply.
+//! - [`live_hook`](LiveControls::live_hook) attaches a client hook registered
+//!   under that name with `__gloryLiveViewConnect`.
+//! - [`live_upload`](LiveControls::live_upload) sends the files picked in a
+//!   file input to a [`LiveUpload`].
+//!
+//! A delayed event goes out before any other event the page sends, so the
+//! server sees events in the order they happened.
+
+use std::ops::DerefMut;
+
+use glory_core::web::widgets::Element;
+
+use crate::LiveUpload;
+
+pub const DEBOUNCE_ATTRIBUTE: &str = "data-glory-debounce";
+pub const THROTTLE_ATTRIBUTE: &str = "data-glory-throttle";
+pub const LOADING_CLASS_ATTRIBUTE: &str = "data-glory-loading-class";
+pub const DISABLE_WITH_ATTRIBUTE: &str = "data-glory-disable-with";
+pub const HOOK_ATTRIBUTE: &str = "data-glory-hook";
+pub const UPLOAD_ATTRIBUTE: &str = "data-glory-upload";
+
+/// LiveView directives for element builders. See the [module docs](self).
+pub trait LiveControls: DerefMut<Target = Element> + Sized {
+    /// Holds back the element's events until none followed for `ms`
+    /// milliseconds, then sends the last one.
+    fn live_debounce(mut self, ms: u32) -> Self {
+        self.add_attr(DEBOUNCE_ATTRIBUTE, ms);
+        self
+    }
+
+    /// Sends at most one of the element's events per `ms` milliseconds, plus
+    /// the last one held back when the interval ends.
+    fn live_throttle(mut self, ms: u32) -> Self {
+        self.add_attr(THROTTLE_ATTRIBUTE, ms);
+        self
+    }
+
+    /// Adds `class` to the element until the server answered its events.
+    fn live_loading_class(mut self, class: impl Into<String>) -> Self {
+        self.add_attr(LOADING_CLASS_ATTRIBUTE, class.into());
+        self
+    }
+
+    /// Disables the element and shows `text` in it until the server answered
+    /// its events.
+    fn live_disable_with(mut self, text: impl Into<String>) -> Self {
+        self.add_attr(DISABLE_WITH_ATTRIBUTE, text.into());
+        self
+    }
+
+    /// Runs the client hook registered as `name` for this element.
+    fn live_hook(mut self, name: impl Into<String>) -> Self {
+        self.add_attr(HOOK_ATTRIBUTE, name.into());
+        self
+    }
+
+    /// Uploads the files picked in this file input to `upload`, and limits
+    /// the picker to the types and number of files it takes.
+    fn live_upload(mut self, upload: &LiveUpload) -> Self {
+        self.add_attr(UPLOAD_ATTRIBUTE, upload.name());
+        let accept = upload.accept_attribute();
+        if !accept.is_empty() {
+            self.add_attr("accept", accept);
+        }
+        if upload.allows_multiple() {
+            self.add_attr("multiple", true);
+        }
+        self
+    }
+}
+
+impl<E> LiveControls for E where E: DerefMut<Target = Element> {}
@@ -0,0 +1,103 @@
+//! Serving a LiveView page as plain HTML first and upgrading it in place.
+//!
+//! The host renders the widget through a
+//! [`ServerHolder`](glory_core::web::holders::ServerHolder) with
+//! [`node_ids`](glory_core::web::holders::ServerHolder::node_ids) into a
+//! [`DeadRender`]: full HTML whose elements carry their command-stream ids,
+//! plus a fingerprint of the commands that produced it. The page passes the
+//! fingerprint to `__gloryLiveViewConnect` as `adopt`.
+//!
+//! On connect the session mounts as usual. The worker renders the widget dead
+//! once more for the page's URL. When that render still has the page's
+//! fingerprint, the mount carries only the diff from it to the live tree,
+//! together with the live tree's listeners and queries, and the client adopts
+//! the existing elements. Otherwise the page changed in between, and the
+//! client replaces it with a full mount.
+
+use glory_core::renderer::Command;
+use glory_core::renderer::command_dom::CommandDom;
+use serde::{Deserialize, Serialize};
+
+/// Sent in `hello` by a page showing a dead render.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Adoption {
+    /// Path and query the page was rendered for.
+    pub url: String,
+    /// [`DeadRender::fingerprint`] of that render.
+    pub fingerprint: String,
+}
+
+/// Fingerprint of a dead render's commands, numbered from the host as by
+/// [`ServerHolder::host_relative_commands`](glory_core::web::holders::ServerHolder::host_relative_commands).
+pub fn fingerprint(commands: &[Command]) -> String {
+    // FNV-1a: stable across processes and builds, unlike `DefaultHasher`.
+    let bytes = serde_json::to_vec(commands).expect("commands serialize");
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// The mount for a client showing `dead`: what turns that tree into `live`,
+/// followed by the listeners and queries of `live` in their original order.
+pub(crate) fn adopting_mount(dead: &[Command], live: &[Command]) -> Vec<Command> {
+    let mut from = CommandDom::new();
+    from.apply_batch(dead);
+    let mut to = CommandDom::new();
+    to.apply_batch(live);
+    let mut commands = from.diff(&to);
+    commands.extend(
+        live.iter()
+            .filter(|command| matches!(command, Command::AttachEvent { .. } | Command::DetachEvent { .. } | Command::Query { .. }))
+            .cloned(),
+    );
+    commands
+}
+
+#[cfg(feature = "ssr")]
+pub use render::*;
+
+#[cfg(feature = "ssr")]
+mod render {
+    use glory_core::renderer::Command;
+    use glory_core::web::holders::ServerHolder;
+    use glory_core::{GloryConfig, Holder, Widget};
+
+    use crate::RequestContext;
+
+    /// A widget rendered to HTML for a page that upgrades to LiveView.
+    #[derive(Clone, Debug)]
+    pub struct DeadRender {
+        html: String,
+        fingerprint: String,
+    }
+
+    impl DeadRender {
+        /// Renders a holder mounted with
+        /// [`node_ids`](ServerHolder::node_ids).
+        pub fn new(holder: &ServerHolder) -> Self {
+            Self {
+                html: holder.app_html(),
+                fingerprint: super::fingerprint(&holder.host_relative_commands()),
+            }
+        }
+
+        /// The widget's HTML, to be placed directly in `<body>`.
+        pub fn html(&self) -> &str {
+            &self.html
+        }
+
+        /// Passed to `__gloryLiveViewConnect` as `adopt`.
+        pub fn fingerprint(&self) -> &str {
+            &self.fingerprint
+        }
+    }
+
+    /// The commands of a dead render of `widget` at `url`, for the page
+    /// `request` upgrades.
+    pub(crate) fn dead_commands(widget: impl Widget, url: &str, request: &RequestContext) -> Vec<Command> {
+        let holder = ServerHolder::new(GloryConfig::default(), url);
+        holder.truck().borrow_mut().inject(request.clone());
+        holder.mount(widget).host_relative_commands()
+    }
+}
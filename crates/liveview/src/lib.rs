@@ -8,39 +8,163 @@
 //!    the browser.
 //! 3. The browser applies commands, sends [`EventData`] and [`QueryResponse`]
 //!    messages back, and receives patch batches.
+//! 4. When the socket drops, the adapter parks the session in a
+//!    [`SessionRegistry`]. A client reconnecting with the mount's resume token
+//!    gets the patches it missed replayed from the session's
+//!    [`OutboundBuffer`], or a fresh mount when they are no longer available.
+//!
+//! A page can also be served as HTML first and upgraded when the socket
+//! connects; see [`dead_render`]. Widgets subscribed to a [`LiveTopic`] get
+//! patches pushed without a client message; see [`topic`]. The adapters hand
+//! sessions the upgrade request and can authorize it; see [`auth`]. File
+//! inputs upload over the same socket; see [`upload`].
 
 use glory_core::renderer::{Command, EventData, QueryResponse};
+use glory_core::web::CspNonce;
 use glory_core::web::holders::CommandHolder;
 use glory_core::{Holder, Widget};
 use serde::{Deserialize, Serialize};
 
-pub const LIVEVIEW_PROTOCOL_VERSION: u32 = 1;
+pub mod auth;
+pub mod controls;
+pub mod dead_render;
+pub mod topic;
+pub mod upload;
+pub mod wire;
+
+pub use auth::{LiveViewAuthorizer, RequestContext};
+pub use controls::LiveControls;
+pub use dead_render::Adoption;
+#[cfg(feature = "ssr")]
+pub use dead_render::DeadRender;
+pub use topic::{LiveTopic, LocalBackend, PubSub, PubSubBackend, TopicDeliveries, TopicDelivery};
+pub use upload::{CompletedUpload, LiveUpload, UploadEntry};
+pub use wire::{FrameDecoder, FrameEncoder, FrameError, LiveViewCodec, LiveViewFrame};
+
+pub const LIVEVIEW_PROTOCOL_VERSION: u32 = 2;
 pub const LIVEVIEW_DEFAULT_PATH: &str = "/__glory/liveview";
 pub const LIVEVIEW_CLIENT_JS: &str = include_str!("liveview_client.js");
 
+/// [`LIVEVIEW_CLIENT_JS`] as an inline `<script>` for the host page. Under a
+/// Content Security Policy pass the request's nonce; the client then hands it
+/// to every `<script>`/`<style>` element the command interpreter creates.
+pub fn client_script_tag(nonce: Option<&CspNonce>) -> String {
+    let nonce = nonce.map(CspNonce::html_attr).unwrap_or_default();
+    format!("<script{nonce}>{LIVEVIEW_CLIENT_JS}</script>")
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload", rename_all = "snake_case")]
 pub enum LiveViewMessage {
-    Hello { protocol_version: u32 },
-    Mount { commands: Vec<Command> },
+    /// Opens a connection. A client lists the binary [`LiveViewCodec`]s it
+    /// decodes in `codecs`; without any, the server sends JSON.
+    Hello {
+        protocol_version: u32,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        codecs: Vec<LiveViewCodec>,
+        /// Set by a page showing a dead render it wants to keep.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        adopt: Option<Adoption>,
+    },
+    /// The initial render. `token` resumes the session after a reconnect and
+    /// `seq` is the sequence number the following patches count up from.
+    /// With `adopt`, `commands` apply on top of the client's dead render
+    /// instead of an empty page.
+    Mount {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+        #[serde(default)]
+        seq: u64,
+        commands: Vec<Command>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        adopt: bool,
+    },
+    /// Sent by a reconnecting client in place of `Hello`: the token from its
+    /// mount and the sequence number of the last patch it applied.
+    Resume {
+        token: String,
+        last_seq: u64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        codecs: Vec<LiveViewCodec>,
+    },
     Event(Box<EventData>),
     Query(Box<QueryResponse>),
-    Patch { commands: Vec<Command> },
-    Error { message: String },
+    /// Announces a file picked for the [`LiveUpload`] named `upload`. The
+    /// client picks `entry` to name the file in the messages that follow.
+    UploadStart {
+        upload: String,
+        entry: u64,
+        name: String,
+        #[serde(default)]
+        mime: String,
+        size: u64,
+    },
+    /// Data of an upload, sent as a binary frame; see
+    /// [`wire::encode_client_frame`].
+    UploadChunk {
+        entry: u64,
+        offset: u64,
+        data: Vec<u8>,
+    },
+    /// Answers `UploadStart` and every `UploadChunk` with the bytes received
+    /// so far, or with why the upload stopped.
+    UploadStatus {
+        entry: u64,
+        received: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A command batch. Patches without commands repeat the previous `seq`.
+    /// The server answers every `Event` with exactly one patch, in order,
+    /// and marks it as a `reply`; other patches were pushed or replayed.
+    Patch {
+        #[serde(default)]
+        seq: u64,
+        commands: Vec<Command>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        reply: bool,
+    },
+    /// The client has applied every patch up to `seq`; the server may drop
+    /// them from its replay buffer.
+    Ack {
+        seq: u64,
+    },
+    Error {
+        message: String,
+    },
     Ping,
     Pong,
-    Close { reason: String },
+    Close {
+        reason: String,
+    },
 }
 
 impl LiveViewMessage {
     pub fn hello() -> Self {
         Self::Hello {
             protocol_version: LIVEVIEW_PROTOCOL_VERSION,
+            codecs: Vec::new(),
+            adopt: None,
         }
     }
 
-    pub fn patch(commands: Vec<Command>) -> Self {
-        Self::Patch { commands }
+    pub fn patch(seq: u64, commands: Vec<Command>) -> Self {
+        Self::Patch { seq, commands, reply: false }
+    }
+
+    fn upload_status(entry: u64, received: Result<u64, String>) -> Self {
+        match received {
+            Ok(received) => Self::UploadStatus {
+                entry,
+                received,
+                error: None,
+            },
+            Err(error) => Self::UploadStatus {
+                entry,
+                received: 0,
+                error: Some(error),
+            },
+        }
     }
 
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
@@ -54,38 +178,190 @@ impl LiveViewMessage {
 
 pub struct LiveViewSession {
     holder: CommandHolder,
+    /// Patches sent but not yet acknowledged, kept for replay on resume.
+    sent: std::cell::RefCell<OutboundBuffer>,
+    topics: std::rc::Rc<topic::SessionTopics>,
+    deliveries: std::cell::RefCell<Option<TopicDeliveries>>,
+    uploads: std::rc::Rc<upload::SessionUploads>,
 }
 
 impl LiveViewSession {
     pub fn mount(widget: impl Widget) -> (Self, LiveViewMessage) {
-        let holder = CommandHolder::new().mount(widget);
+        Self::mount_with_config(widget, &LiveViewConfig::default())
+    }
+
+    /// Mounts `widget`, sizing the replay buffer from `config`.
+    pub fn mount_with_config(widget: impl Widget, config: &LiveViewConfig) -> (Self, LiveViewMessage) {
+        Self::mount_inner(widget, None, None, config)
+    }
+
+    /// Mounts `widget` with `request` in its truck, as the adapters do with
+    /// the socket's upgrade request.
+    pub fn mount_for_request(widget: impl Widget, request: RequestContext, config: &LiveViewConfig) -> (Self, LiveViewMessage) {
+        Self::mount_inner(widget, Some(request), None, config)
+    }
+
+    /// Mounts `widget` for a client showing `dead`, the commands of a dead
+    /// render of it. The mount only carries what changed since that render,
+    /// and the client keeps the elements it already has.
+    pub fn mount_adopting(widget: impl Widget, dead: &[Command], config: &LiveViewConfig) -> (Self, LiveViewMessage) {
+        Self::mount_inner(widget, None, Some(dead), config)
+    }
+
+    fn mount_inner(
+        widget: impl Widget,
+        request: Option<RequestContext>,
+        dead: Option<&[Command]>,
+        config: &LiveViewConfig,
+    ) -> (Self, LiveViewMessage) {
+        let (session, commands) = Self::build(widget, request, config);
+        let (commands, adopt) = match dead {
+            Some(dead) => (dead_render::adopting_mount(dead, &commands), true),
+            None => (commands, false),
+        };
+        (
+            session,
+            LiveViewMessage::Mount {
+                token: None,
+                seq: 0,
+                commands,
+                adopt,
+            },
+        )
+    }
+
+    fn build(widget: impl Widget, request: Option<RequestContext>, config: &LiveViewConfig) -> (Self, Vec<Command>) {
+        let holder = CommandHolder::new();
+        if let Some(request) = request {
+            holder.truck().borrow_mut().inject(request);
+        }
+        let (topics, deliveries) = topic::SessionTopics::new(holder.id());
+        let uploads = upload::SessionUploads::new(holder.id());
+        let holder = holder.mount(widget);
         let commands = holder.take_batch();
-        (Self { holder }, LiveViewMessage::Mount { commands })
+        let session = Self {
+            holder,
+            sent: std::cell::RefCell::new(OutboundBuffer::from_config(config)),
+            topics,
+            deliveries: std::cell::RefCell::new(Some(deliveries)),
+            uploads,
+        };
+        (session, commands)
     }
 
     pub fn holder(&self) -> &CommandHolder {
         &self.holder
     }
 
+    /// Replaces the [`RequestContext`] in the session's truck, as when a
+    /// resume moves it to a new socket. Widgets see it the next time they
+    /// read it.
+    pub fn set_request(&self, request: RequestContext) {
+        self.holder.truck().borrow_mut().inject(request);
+    }
+
+    /// Sequence number of the last patch sent.
+    pub fn seq(&self) -> u64 {
+        self.sent.borrow().seq()
+    }
+
+    /// Runs the event's handler and returns the patch answering it.
     pub fn dispatch_event(&self, event: EventData) -> LiveViewMessage {
         self.holder.dispatch_event(event);
-        LiveViewMessage::patch(self.holder.take_batch())
+        match self.patch() {
+            LiveViewMessage::Patch { seq, commands, .. } => LiveViewMessage::Patch { seq, commands, reply: true },
+            message => message,
+        }
     }
 
     pub fn resolve_query(&self, response: QueryResponse) -> LiveViewMessage {
         self.holder.resolve_query(response);
-        LiveViewMessage::patch(self.holder.take_batch())
+        self.patch()
+    }
+
+    /// The messages this session's [`LiveTopic`] subscriptions receive, to
+    /// be passed to [`deliver`](Self::deliver) as they arrive. `None` after
+    /// the first call.
+    pub fn take_deliveries(&self) -> Option<TopicDeliveries> {
+        self.deliveries.borrow_mut().take()
+    }
+
+    /// Runs the subscription handler of `delivery` and returns the patch it
+    /// produced, to be pushed to the client. `None` when nothing changed or
+    /// the subscription has ended.
+    pub fn deliver(&self, delivery: TopicDelivery) -> Option<LiveViewMessage> {
+        let (handler, payload) = self.topics.handler(&delivery)?;
+        self.holder.update(|| (handler.borrow_mut())(&payload));
+        self.flush()
+    }
+
+    /// The patch for what changed outside an event or query, such as upload
+    /// progress, to be pushed to the client. `None` when nothing did.
+    pub fn flush(&self) -> Option<LiveViewMessage> {
+        match self.patch() {
+            LiveViewMessage::Patch { commands, .. } if commands.is_empty() => None,
+            patch => Some(patch),
+        }
+    }
+
+    /// Drops patches up to `seq` from the replay buffer.
+    pub fn acknowledge(&self, seq: u64) {
+        self.sent.borrow_mut().acknowledge(seq);
+    }
+
+    /// The messages bringing a client that applied patches up to `last_seq`
+    /// back in sync: the patches it missed, then anything still pending in
+    /// the holder. `None` when the replay buffer no longer covers `last_seq`
+    /// (it was folded into a coalesced batch, or the client claims patches
+    /// this session never sent), in which case the client needs a remount.
+    pub fn resume(&self, last_seq: u64) -> Option<Vec<LiveViewMessage>> {
+        let mut messages: Vec<_> = self
+            .sent
+            .borrow()
+            .replay_after(last_seq)?
+            .into_iter()
+            .map(|(seq, commands)| LiveViewMessage::patch(seq, commands))
+            .collect();
+        self.acknowledge(last_seq);
+        // Uploads in flight were lost with the client's socket.
+        self.holder.update(|| self.uploads.abort_unfinished());
+        if let LiveViewMessage::Patch { seq, commands, .. } = self.patch()
+            && !commands.is_empty()
+        {
+            messages.push(LiveViewMessage::patch(seq, commands));
+        }
+        Some(messages)
+    }
+
+    fn patch(&self) -> LiveViewMessage {
+        let commands = self.holder.take_batch();
+        let seq = self.sent.borrow_mut().push(commands.clone());
+        LiveViewMessage::patch(seq, commands)
     }
 
     pub fn handle_message(&self, message: LiveViewMessage) -> Option<LiveViewMessage> {
         match message {
             LiveViewMessage::Event(event) => Some(self.dispatch_event(*event)),
             LiveViewMessage::Query(response) => Some(self.resolve_query(*response)),
+            LiveViewMessage::UploadStart {
+                upload,
+                entry,
+                name,
+                mime,
+                size,
+            } => {
+                let started = self.holder.update(|| self.uploads.start(&upload, entry, name, mime, size));
+                Some(LiveViewMessage::upload_status(entry, started.map(|()| 0)))
+            }
+            LiveViewMessage::UploadChunk { entry, offset, data } => {
+                let received = self.holder.update(|| self.uploads.chunk(entry, offset, &data));
+                Some(LiveViewMessage::upload_status(entry, received))
+            }
             LiveViewMessage::Ping => Some(LiveViewMessage::Pong),
             // Protocol negotiation: a client whose protocol version does not
             // match the server's is told so, rather than being silently driven
             // with a possibly-incompatible wire format.
-            LiveViewMessage::Hello { protocol_version } => {
+            LiveViewMessage::Hello { protocol_version, .. } => {
                 if protocol_version != LIVEVIEW_PROTOCOL_VERSION {
                     Some(LiveViewMessage::Error {
                         message: format!("liveview protocol mismatch: client {protocol_version}, server {LIVEVIEW_PROTOCOL_VERSION}"),
@@ -94,8 +370,19 @@ impl LiveViewSession {
                     None
                 }
             }
+            LiveViewMessage::Ack { seq } => {
+                self.acknowledge(seq);
+                None
+            }
             LiveViewMessage::Close { .. } => None,
-            LiveViewMessage::Mount { .. } | LiveViewMessage::Patch { .. } | LiveViewMessage::Error { .. } | LiveViewMessage::Pong => None,
+            // Resumes are routed by token, which only the adapter's registry
+            // knows; it calls `resume` on the session it finds.
+            LiveViewMessage::Resume { .. } => None,
+            LiveViewMessage::Mount { .. }
+            | LiveViewMessage::Patch { .. }
+            | LiveViewMessage::UploadStatus { .. }
+            | LiveViewMessage::Error { .. }
+            | LiveViewMessage::Pong => None,
         }
     }
 
@@ -114,48 +401,132 @@ impl LiveViewSession {
 /// batches are folded into the tail batch (commands are concatenated, which is
 /// a faithful sequential replay) so the queue cannot grow without bound when a
 /// client is slow. `max_pending == 0` disables the cap (unbounded).
+///
+/// Folding bounds the number of batches, not their size, so the buffer also
+/// caps the commands it holds at [`max_commands`](Self::max_commands). Past
+/// it, every pending batch is dropped and the buffer is
+/// [`overflowed`](Self::overflowed): it can no longer replay, and a client
+/// that missed those commands needs a fresh mount.
+///
+/// Every non-empty batch gets the next sequence number, so the buffer also
+/// serves as a session's replay log: [`replay_after`](Self::replay_after)
+/// returns what a client that stopped at a given number missed. A folded
+/// batch covers a range of numbers and can only be replayed whole.
 #[derive(Debug, Default)]
 pub struct OutboundBuffer {
     max_pending: usize,
-    batches: std::collections::VecDeque<Vec<Command>>,
+    max_commands: usize,
+    seq: u64,
+    batches: std::collections::VecDeque<NumberedBatch>,
+    /// Commands across `batches`.
+    commands: usize,
+    overflowed: bool,
+}
+
+#[derive(Debug)]
+struct NumberedBatch {
+    first_seq: u64,
+    last_seq: u64,
+    commands: Vec<Command>,
 }
 
 impl OutboundBuffer {
     pub fn new(max_pending: usize) -> Self {
         Self {
             max_pending,
+            max_commands: 0,
+            seq: 0,
             batches: std::collections::VecDeque::new(),
+            commands: 0,
+            overflowed: false,
         }
     }
 
     pub fn from_config(config: &LiveViewConfig) -> Self {
-        Self::new(config.max_pending_patches)
+        Self::new(config.max_pending_patches).max_commands(config.max_pending_commands)
+    }
+
+    /// Caps the commands held across all batches. `0` = unbounded.
+    pub fn max_commands(mut self, max_commands: usize) -> Self {
+        self.max_commands = max_commands;
+        self
     }
 
     /// Enqueue a patch batch (empty batches are dropped). Folds into the tail
-    /// when over the pending cap.
-    pub fn push(&mut self, batch: Vec<Command>) {
+    /// when over the pending cap, and drops everything pending when over the
+    /// command cap. Returns the batch's sequence number, or the current one
+    /// for an empty batch.
+    pub fn push(&mut self, batch: Vec<Command>) -> u64 {
         if batch.is_empty() {
-            return;
+            return self.seq;
+        }
+        self.seq += 1;
+        self.commands += batch.len();
+        if self.max_commands != 0 && self.commands > self.max_commands {
+            self.batches.clear();
+            self.commands = 0;
+            self.overflowed = true;
+            return self.seq;
         }
         if self.max_pending != 0
             && self.batches.len() >= self.max_pending
             && let Some(tail) = self.batches.back_mut()
         {
-            tail.extend(batch);
-            return;
+            tail.commands.extend(batch);
+            tail.last_seq = self.seq;
+            return self.seq;
+        }
+        self.batches.push_back(NumberedBatch {
+            first_seq: self.seq,
+            last_seq: self.seq,
+            commands: batch,
+        });
+        self.seq
+    }
+
+    /// Sequence number of the most recent non-empty batch.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Drop every batch entirely covered by `seq`.
+    pub fn acknowledge(&mut self, seq: u64) {
+        while self.batches.front().is_some_and(|batch| batch.last_seq <= seq) {
+            self.drain_one();
+        }
+    }
+
+    /// The batches after `seq`, each with the sequence number it ends at.
+    /// `None` if they cannot be replayed exactly: `seq` falls inside a folded
+    /// batch, the batches following it were already dropped, it is ahead of
+    /// this buffer, or the buffer overflowed.
+    pub fn replay_after(&self, seq: u64) -> Option<Vec<(u64, Vec<Command>)>> {
+        if seq > self.seq || self.overflowed {
+            return None;
         }
-        self.batches.push_back(batch);
+        let mut next = seq + 1;
+        let mut replay = Vec::new();
+        for batch in self.batches.iter().filter(|batch| batch.last_seq > seq) {
+            if batch.first_seq != next {
+                return None;
+            }
+            replay.push((batch.last_seq, batch.commands.clone()));
+            next = batch.last_seq + 1;
+        }
+        (next == self.seq + 1).then_some(replay)
     }
 
     /// Pop the oldest pending batch (FIFO).
     pub fn drain_one(&mut self) -> Option<Vec<Command>> {
-        self.batches.pop_front()
+        let batch = self.batches.pop_front()?;
+        self.commands -= batch.commands.len();
+        Some(batch.commands)
     }
 
     /// Drain every pending batch in order.
     pub fn take(&mut self) -> Vec<Vec<Command>> {
-        self.batches.drain(..).collect()
+        self.commands = 0;
+        self.batches.drain(..).map(|batch| batch.commands).collect()
     }
 
     pub fn len(&self) -> usize {
@@ -172,7 +543,14 @@ impl OutboundBuffer {
 
     /// Total number of commands across all pending batches.
     pub fn command_count(&self) -> usize {
-        self.batches.iter().map(Vec::len).sum()
+        self.commands
+    }
+
+    /// True once a push went over [`max_commands`](Self::max_commands) and
+    /// dropped the pending batches. A buffer used as a send queue must then
+    /// remount its client; a session's replay log refuses every resume.
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
     }
 }
 
@@ -187,18 +565,50 @@ pub struct LiveViewConfig {
     /// Hard cap on a session's total lifetime, regardless of activity.
     pub max_lifetime_secs: u64,
     /// Maximum number of pending outbound patch batches before an
-    /// [`OutboundBuffer`] starts coalescing. `0` = unbounded.
+    /// [`OutboundBuffer`] starts coalescing. `0` = unbounded. This also bounds
+    /// each session's replay buffer: a client that falls further behind than
+    /// this is remounted instead of resumed.
     pub max_pending_patches: usize,
+    /// Maximum number of commands an [`OutboundBuffer`] holds across its
+    /// batches. `0` = unbounded. A session that goes over it while its client
+    /// is away or slow to acknowledge can no longer be resumed, and the client
+    /// is remounted.
+    pub max_pending_commands: usize,
+    /// How often a route with a [`LiveViewAuthorizer`] asks it again about
+    /// the request of each open socket. `0` only checks on connect.
+    pub revalidate_secs: u64,
+}
+
+impl LiveViewConfig {
+    /// How often an adapter's reaper sweeps parked sessions: half the
+    /// shortest enabled timeout, kept between one second and one minute.
+    /// `None` when both timeouts are disabled.
+    pub fn reap_interval(&self) -> Option<std::time::Duration> {
+        let shortest = [self.idle_timeout_secs, self.max_lifetime_secs]
+            .into_iter()
+            .filter(|secs| *secs > 0)
+            .min()?;
+        Some(std::time::Duration::from_secs((shortest / 2).clamp(1, 60)))
+    }
+
+    /// [`revalidate_secs`](Self::revalidate_secs) as a period; `None` when
+    /// disabled.
+    pub fn revalidate_interval(&self) -> Option<std::time::Duration> {
+        (self.revalidate_secs > 0).then(|| std::time::Duration::from_secs(self.revalidate_secs))
+    }
 }
 
 impl Default for LiveViewConfig {
     fn default() -> Self {
-        // 5 minutes idle, 1 hour absolute, 64 pending patches — conservative
-        // defaults a host app can override.
+        // 5 minutes idle, 1 hour absolute, 64 pending patches of at most
+        // 10k commands, a login check every minute — conservative defaults a
+        // host app can override.
         Self {
             idle_timeout_secs: 300,
             max_lifetime_secs: 3600,
             max_pending_patches: 64,
+            max_pending_commands: 10_000,
+            revalidate_secs: 60,
         }
     }
 }
@@ -208,6 +618,13 @@ impl Default for LiveViewConfig {
 pub struct ResumeToken(String);
 
 impl ResumeToken {
+    /// 128 random bits, hex encoded. The first-party adapters hand these out.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes).expect("the operating system provides randomness");
+        Self(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
     pub fn as_str(&self) -> &str {
         &self.0
     }
@@ -287,12 +704,19 @@ impl<T> SessionRegistry<T> {
     /// Register a session under a caller-supplied token. Overwrites any existing
     /// entry with the same token.
     pub fn insert_with_token(&mut self, now: u64, token: impl Into<String>, value: T) -> ResumeToken {
+        self.reinsert(now, now, token, value)
+    }
+
+    /// Register a session created at `created_at`, such as one handed back
+    /// after [`take`](Self::take). Its age keeps counting toward
+    /// `max_lifetime_secs`; idle time counts from `now`.
+    pub fn reinsert(&mut self, created_at: u64, now: u64, token: impl Into<String>, value: T) -> ResumeToken {
         let token = token.into();
         self.entries.insert(
             token.clone(),
             RegistryEntry {
                 value,
-                created_at: now,
+                created_at,
                 last_active_at: now,
             },
         );
@@ -316,6 +740,13 @@ impl<T> SessionRegistry<T> {
         }
     }
 
+    /// Remove a session that is still alive at `now`, returning it with its
+    /// creation time. Expired entries are removed and `None` is returned.
+    pub fn take(&mut self, now: u64, token: &str) -> Option<(T, u64)> {
+        let entry = self.entries.remove(token)?;
+        (!entry.expired(now, &self.config)).then_some((entry.value, entry.created_at))
+    }
+
     /// Refresh a session's activity timestamp. Returns `false` if unknown.
     pub fn touch(&mut self, now: u64, token: &str) -> bool {
         match self.entries.get_mut(token) {
@@ -364,6 +795,33 @@ pub trait LiveviewRouter {
     fn create_default_liveview_router() -> Self;
 
     fn with_liveview<W>(self, path: &str, widget: impl Fn() -> W + Send + Sync + 'static) -> Self
+    where
+        Self: Sized,
+        W: Widget + 'static,
+    {
+        self.with_liveview_config(path, LiveViewConfig::default(), widget)
+    }
+
+    /// [`with_liveview`](Self::with_liveview) with the session lifetimes and
+    /// replay buffer size of `config`.
+    fn with_liveview_config<W>(self, path: &str, config: LiveViewConfig, widget: impl Fn() -> W + Send + Sync + 'static) -> Self
+    where
+        Self: Sized,
+        W: Widget + 'static,
+    {
+        self.with_liveview_authorized(path, config, None, widget)
+    }
+
+    /// [`with_liveview_config`](Self::with_liveview_config) that lets only
+    /// the sockets `authorize` accepts hold a session, and closes them once it
+    /// no longer does. `None` accepts every socket.
+    fn with_liveview_authorized<W>(
+        self,
+        path: &str,
+        config: LiveViewConfig,
+        authorize: Option<LiveViewAuthorizer>,
+        widget: impl Fn() -> W + Send + Sync + 'static,
+    ) -> Self
     where
         Self: Sized,
         W: Widget + 'static;
@@ -384,6 +842,9 @@ fn normalize_liveview_path(path: &str) -> String {
 #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
 struct SessionWorker {
     sender: futures::channel::mpsc::Sender<SessionRequest>,
+    /// Whom the route's authorizer approved the mounting request for; only
+    /// a request it approves for the same principal resumes the session.
+    principal: Option<String>,
 }
 
 #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
@@ -392,9 +853,21 @@ enum SessionRequest {
         message: LiveViewMessage,
         reply: futures::channel::oneshot::Sender<Option<LiveViewMessage>>,
     },
+    /// Resumes on a new socket, which takes the session's pushes and whose
+    /// request the session sees from now on.
+    Resume {
+        last_seq: u64,
+        request: RequestContext,
+        pushes: Pushes,
+        reply: futures::channel::oneshot::Sender<Option<Vec<LiveViewMessage>>>,
+    },
     Close,
 }
 
+/// Patches a session sends without a client message asking for them.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+type Pushes = futures::channel::mpsc::UnboundedSender<LiveViewMessage>;
+
 #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
 type LocalTask = Box<dyn FnOnce(futures::executor::LocalSpawner) + Send + 'static>;
 
@@ -464,21 +937,27 @@ fn run_local_worker(mut receiver: futures::channel::mpsc::UnboundedReceiver<Loca
 
 #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
 impl SessionWorker {
-    async fn spawn<W>(factory: std::sync::Arc<dyn Fn() -> W + Send + Sync + 'static>) -> Result<(Self, LiveViewMessage), ()>
+    async fn spawn<W>(
+        factory: std::sync::Arc<dyn Fn() -> W + Send + Sync + 'static>,
+        config: LiveViewConfig,
+        adoption: Option<Adoption>,
+        request: RequestContext,
+    ) -> Result<(Self, LiveViewMessage, PushReceiver), ()>
     where
         W: Widget + 'static,
     {
         let (sender, receiver) = futures::channel::mpsc::channel(32);
         let (mount_sender, mount_receiver) = futures::channel::oneshot::channel();
+        let (pushes, push_receiver) = futures::channel::mpsc::unbounded();
         liveview_worker_pool().spawn(Box::new(move |spawner| {
             use futures::task::LocalSpawnExt;
 
             spawner
-                .spawn_local(run_session(factory, receiver, mount_sender))
+                .spawn_local(run_session(factory, config, adoption, request, receiver, mount_sender, pushes))
                 .expect("glory-liveview: failed to spawn session task");
         }))?;
         let mount = mount_receiver.await.map_err(|_| ())?;
-        Ok((Self { sender }, mount))
+        Ok((Self { sender, principal: None }, mount, push_receiver))
     }
 
     async fn handle_message(&self, message: LiveViewMessage) -> Option<LiveViewMessage> {
@@ -497,26 +976,88 @@ impl SessionWorker {
             })
         })
     }
+
+    /// [`LiveViewSession::resume`] on the worker, with the receiver of the
+    /// pushes that follow the replay; `None` also when the worker is gone.
+    async fn resume(&self, last_seq: u64, request: RequestContext) -> Option<(Vec<LiveViewMessage>, PushReceiver)> {
+        use futures::SinkExt;
+
+        let (reply, receiver) = futures::channel::oneshot::channel();
+        let (pushes, push_receiver) = futures::channel::mpsc::unbounded();
+        let mut sender = self.sender.clone();
+        sender
+            .send(SessionRequest::Resume {
+                last_seq,
+                request,
+                pushes,
+                reply,
+            })
+            .await
+            .ok()?;
+        Some((receiver.await.ok().flatten()?, push_receiver))
+    }
 }
 
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+type PushReceiver = futures::channel::mpsc::UnboundedReceiver<LiveViewMessage>;
+
 #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
 async fn run_session<W>(
     factory: std::sync::Arc<dyn Fn() -> W + Send + Sync + 'static>,
+    config: LiveViewConfig,
+    adoption: Option<Adoption>,
+    request: RequestContext,
     mut receiver: futures::channel::mpsc::Receiver<SessionRequest>,
     mount_sender: futures::channel::oneshot::Sender<LiveViewMessage>,
+    mut pushes: Pushes,
 ) where
     W: Widget + 'static,
 {
     use futures::StreamExt;
-
-    let (session, mount) = LiveViewSession::mount(factory());
+    use futures::future::{Either, select};
+
+    // The page still shows what a dead render produces now only if their
+    // fingerprints match; otherwise it gets a full mount.
+    let dead = adoption
+        .map(|adoption| (dead_render::dead_commands(factory(), &adoption.url, &request), adoption.fingerprint))
+        .and_then(|(dead, fingerprint)| (dead_render::fingerprint(&dead) == fingerprint).then_some(dead));
+    let (session, mount) = LiveViewSession::mount_inner(factory(), Some(request), dead.as_deref(), &config);
     if mount_sender.send(mount).is_err() {
         return;
     }
-    while let Some(request) = receiver.next().await {
+    let mut deliveries = session.take_deliveries().expect("a new session has its deliveries");
+    loop {
+        let request = match select(receiver.next(), deliveries.next()).await {
+            Either::Left((Some(request), _)) => request,
+            Either::Left((None, _)) => break,
+            Either::Right((delivery, _)) => {
+                // While parked nobody receives the push; the patch waits in
+                // the replay buffer.
+                if let Some(patch) = delivery.and_then(|delivery| session.deliver(delivery)) {
+                    let _ = pushes.unbounded_send(patch);
+                }
+                continue;
+            }
+        };
         match request {
             SessionRequest::Message { message, reply } => {
-                let _ = reply.send(session.handle_message(message));
+                let response = session.handle_message(message);
+                // Upload messages change widgets without a patch of their
+                // own; it goes out ahead of the response.
+                if let Some(patch) = session.flush() {
+                    let _ = pushes.unbounded_send(patch);
+                }
+                let _ = reply.send(response);
+            }
+            SessionRequest::Resume {
+                last_seq,
+                request,
+                pushes: next,
+                reply,
+            } => {
+                session.set_request(request);
+                pushes = next;
+                let _ = reply.send(session.resume(last_seq));
             }
             SessionRequest::Close => break,
         }
@@ -530,31 +1071,352 @@ impl Drop for SessionWorker {
     }
 }
 
+/// A session attached to a socket.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+struct AttachedSession {
+    token: ResumeToken,
+    created_at: u64,
+    worker: SessionWorker,
+    pushes: PushReceiver,
+}
+
+/// Resolves with the reason to close a socket once its request is no longer
+/// authorized.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+type Revalidation = std::pin::Pin<Box<dyn std::future::Future<Output = String> + Send>>;
+
+/// One socket: its upgrade request and the principal the authorizer approved
+/// it for, its session once attached, and the encoding its client negotiated.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+#[derive(Default)]
+struct LiveViewConnection {
+    request: RequestContext,
+    principal: Option<String>,
+    session: Option<AttachedSession>,
+    frames: FrameEncoder,
+    revalidation: Option<Revalidation>,
+}
+
+/// What a socket's loop handles next.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+enum SocketEvent<T> {
+    /// The socket's next message.
+    Incoming(T),
+    /// A patch the session pushed.
+    Push(LiveViewFrame),
+    /// The request is no longer authorized: send the `Close` and stop.
+    Close(LiveViewFrame),
+}
+
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+impl LiveViewConnection {
+    /// Waits for `incoming`, the socket's next message, the next patch the
+    /// session pushes, or the socket's authorization to end, whichever comes
+    /// first. After a `Close` the session is dropped rather than parked.
+    async fn next<F: std::future::Future>(&mut self, incoming: F) -> SocketEvent<F::Output> {
+        use futures::future::{Either, select};
+
+        let revalidation = async {
+            match &mut self.revalidation {
+                Some(revalidation) => revalidation.await,
+                None => futures::future::pending().await,
+            }
+        };
+        let push = Self::next_push(&mut self.session, &mut self.frames);
+        let reason = match select(std::pin::pin!(incoming), select(std::pin::pin!(push), std::pin::pin!(revalidation))).await {
+            Either::Left((incoming, _)) => return SocketEvent::Incoming(incoming),
+            Either::Right((Either::Left((push, _)), _)) => return SocketEvent::Push(push),
+            Either::Right((Either::Right((reason, _)), _)) => reason,
+        };
+        self.revalidation = None;
+        self.session = None;
+        SocketEvent::Close(self.frames.encode(&LiveViewMessage::Close { reason }))
+    }
+
+    async fn next_push(session: &mut Option<AttachedSession>, frames: &mut FrameEncoder) -> LiveViewFrame {
+        use futures::StreamExt;
+
+        let Some(session) = session else {
+            return futures::future::pending().await;
+        };
+        match session.pushes.next().await {
+            Some(push) => frames.encode(&push),
+            // The worker is gone; the next client message reports it.
+            None => futures::future::pending().await,
+        }
+    }
+
+    /// The pushes already queued.
+    fn queued_pushes(&mut self) -> Vec<LiveViewMessage> {
+        let Some(session) = &mut self.session else {
+            return Vec::new();
+        };
+        std::iter::from_fn(|| session.pushes.try_recv().ok()).collect()
+    }
+}
+
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+type ParkedSessions = std::sync::Mutex<SessionRegistry<SessionWorker>>;
+
+/// State shared by every socket of one LiveView route: the widget factory,
+/// the authorizer, and the sessions whose socket closed, parked until they
+/// are resumed or reaped.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+struct LiveViewRoute<W> {
+    factory: std::sync::Arc<dyn Fn() -> W + Send + Sync + 'static>,
+    config: LiveViewConfig,
+    authorizer: Option<LiveViewAuthorizer>,
+    parked: std::sync::Arc<ParkedSessions>,
+    reaper: std::sync::Arc<std::sync::Once>,
+}
+
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+impl<W> Clone for LiveViewRoute<W> {
+    fn clone(&self) -> Self {
+        Self {
+            factory: self.factory.clone(),
+            config: self.config,
+            authorizer: self.authorizer.clone(),
+            parked: self.parked.clone(),
+            reaper: self.reaper.clone(),
+        }
+    }
+}
+
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+impl<W> LiveViewRoute<W>
+where
+    W: Widget + 'static,
+{
+    fn new(factory: impl Fn() -> W + Send + Sync + 'static, config: LiveViewConfig, authorizer: Option<LiveViewAuthorizer>) -> Self {
+        Self {
+            factory: std::sync::Arc::new(factory),
+            config,
+            authorizer,
+            parked: std::sync::Arc::new(std::sync::Mutex::new(SessionRegistry::new(config))),
+            reaper: std::sync::Arc::new(std::sync::Once::new()),
+        }
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, SessionRegistry<SessionWorker>> {
+        self.parked.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Starts the route's reaper through `spawn` the first time a socket
+    /// connects, so it runs inside the adapter's runtime. The task gets a weak
+    /// handle and should stop once [`reap_parked`] returns `false`.
+    fn start_reaper(&self, spawn: impl FnOnce(std::sync::Weak<ParkedSessions>, std::time::Duration)) {
+        let Some(period) = self.config.reap_interval() else {
+            return;
+        };
+        self.reaper.call_once(|| spawn(std::sync::Arc::downgrade(&self.parked), period));
+    }
+
+    /// Opens the connection of a socket upgraded by `request`, or returns the
+    /// reason to close it with when the route's authorizer refuses the
+    /// request. The connection asks the authorizer again after every
+    /// `sleep` of the configured revalidation period.
+    async fn connect<S, F>(&self, request: RequestContext, sleep: S) -> Result<LiveViewConnection, String>
+    where
+        S: Fn(std::time::Duration) -> F + Send + 'static,
+        F: std::future::Future<Output = ()> + Send,
+    {
+        let mut connection = LiveViewConnection {
+            request,
+            ..LiveViewConnection::default()
+        };
+        let Some(authorizer) = &self.authorizer else {
+            return Ok(connection);
+        };
+        let principal = authorizer.authorize(&connection.request).await?;
+        if let Some(period) = self.config.revalidate_interval() {
+            let authorizer = authorizer.clone();
+            let request = connection.request.clone();
+            let approved = principal.clone();
+            connection.revalidation = Some(Box::pin(async move {
+                loop {
+                    sleep(period).await;
+                    match authorizer.authorize(&request).await {
+                        Ok(principal) if principal == approved => {}
+                        Ok(_) => return "the login changed".to_owned(),
+                        Err(reason) => return reason,
+                    }
+                }
+            }));
+        }
+        connection.principal = Some(principal);
+        Ok(connection)
+    }
+
+    /// Handles one client message. The first message of a socket attaches a
+    /// session for `request`, approved for `principal`: a `Resume` naming a
+    /// session parked for the same principal replays what the client missed,
+    /// and anything else mounts a fresh session. Another principal's session
+    /// stays parked.
+    async fn handle(
+        &self,
+        attached: &mut Option<AttachedSession>,
+        request: &RequestContext,
+        principal: Option<&str>,
+        message: LiveViewMessage,
+    ) -> Result<Vec<LiveViewMessage>, ()> {
+        if let Some(session) = attached {
+            return Ok(session.worker.handle_message(message).await.into_iter().collect());
+        }
+        let now = current_unix_secs();
+        if let LiveViewMessage::Resume { token, last_seq, .. } = &message {
+            let parked = {
+                let mut sessions = self.sessions();
+                match sessions.take(now, token) {
+                    Some((worker, created_at)) if worker.principal.as_deref() != principal => {
+                        sessions.reinsert(created_at, now, token.clone(), worker);
+                        None
+                    }
+                    parked => parked,
+                }
+            };
+            if let Some((worker, created_at)) = parked
+                && let Some((replay, pushes)) = worker.resume(*last_seq, request.clone()).await
+            {
+                *attached = Some(AttachedSession {
+                    token: ResumeToken(token.clone()),
+                    created_at,
+                    worker,
+                    pushes,
+                });
+                return Ok(replay);
+            }
+        }
+
+        let adoption = match &message {
+            LiveViewMessage::Hello { adopt, .. } => adopt.clone(),
+            _ => None,
+        };
+        let (mut worker, mut mount, pushes) = SessionWorker::spawn(self.factory.clone(), self.config, adoption, request.clone()).await?;
+        worker.principal = principal.map(str::to_owned);
+        let token = ResumeToken::generate();
+        if let LiveViewMessage::Mount { token: slot, .. } = &mut mount {
+            *slot = Some(token.as_str().to_owned());
+        }
+        let mut replies = vec![mount];
+        if !matches!(message, LiveViewMessage::Resume { .. }) {
+            replies.extend(worker.handle_message(message).await);
+        }
+        *attached = Some(AttachedSession {
+            token,
+            created_at: now,
+            worker,
+            pushes,
+        });
+        Ok(replies)
+    }
+
+    /// [`handle`](Self::handle), encoded for the socket. The message that
+    /// attaches a session also picks the connection's codec.
+    ///
+    /// Pushes the worker queued meanwhile go out in the order the session
+    /// numbered them: after a mount or replay, and around a patch reply by
+    /// sequence number.
+    async fn respond(&self, connection: &mut LiveViewConnection, message: LiveViewMessage) -> Result<Vec<LiveViewFrame>, ()> {
+        let attaching = connection.session.is_none();
+        if attaching && let LiveViewMessage::Hello { codecs, .. } | LiveViewMessage::Resume { codecs, .. } = &message {
+            connection.frames = FrameEncoder::negotiate(codecs);
+        }
+        let mut replies = self
+            .handle(&mut connection.session, &connection.request, connection.principal.as_deref(), message)
+            .await?;
+        let mut earlier = connection.queued_pushes();
+        let patch_seq = |message: &LiveViewMessage| match message {
+            LiveViewMessage::Patch { seq, .. } => Some(*seq),
+            _ => None,
+        };
+        let split = match replies.iter().find_map(patch_seq) {
+            _ if attaching => 0,
+            Some(reply_seq) => earlier.partition_point(|push| patch_seq(push).is_some_and(|seq| seq <= reply_seq)),
+            None => earlier.len(),
+        };
+        let later = earlier.split_off(split);
+        replies.splice(0..0, earlier);
+        replies.extend(later);
+        Ok(replies.iter().map(|reply| connection.frames.encode(reply)).collect())
+    }
+
+    /// Parks the session of a closed socket for a later resume.
+    fn park(&self, attached: Option<AttachedSession>) {
+        if let Some(session) = attached {
+            let now = current_unix_secs();
+            self.sessions()
+                .reinsert(session.created_at, now, session.token.into_string(), session.worker);
+        }
+    }
+}
+
+/// One reaper sweep; `false` once the route is gone.
+#[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+fn reap_parked(parked: &std::sync::Weak<ParkedSessions>) -> bool {
+    let Some(parked) = parked.upgrade() else {
+        return false;
+    };
+    let reaped = parked.lock().unwrap_or_else(std::sync::PoisonError::into_inner).reap(current_unix_secs());
+    drop(reaped);
+    true
+}
+
 #[cfg(feature = "salvo")]
 pub mod salvo_mount {
-    use std::sync::Arc;
+    use std::sync::Weak;
+    use std::time::Duration;
 
     use futures::StreamExt;
     use glory_core::Widget;
+    use glory_serverfn::salvo_mount::request_context;
     use salvo::prelude::{Depot, FlowCtrl, Request, Response, Router};
     use salvo::websocket::{Message, WebSocket, WebSocketUpgrade};
     use salvo::{Handler, async_trait};
 
-    use crate::{LIVEVIEW_DEFAULT_PATH, LiveViewMessage, SessionWorker};
+    use crate::wire::decode_client_frame;
+    use crate::{
+        FrameError, LIVEVIEW_DEFAULT_PATH, LiveViewAuthorizer, LiveViewConfig, LiveViewFrame, LiveViewMessage, LiveViewRoute, ParkedSessions,
+        RequestContext, SocketEvent, reap_parked,
+    };
 
     pub fn router<W>(widget: impl Fn() -> W + Send + Sync + 'static) -> Router
     where
         W: Widget + 'static,
     {
-        let factory: Arc<dyn Fn() -> W + Send + Sync + 'static> = Arc::new(widget);
-        Router::with_path(LIVEVIEW_DEFAULT_PATH.trim_start_matches('/')).get(LiveViewHandler { factory })
+        router_with_config(LiveViewConfig::default(), widget)
+    }
+
+    /// [`router`] with the session lifetimes and replay buffer size of
+    /// `config`.
+    pub fn router_with_config<W>(config: LiveViewConfig, widget: impl Fn() -> W + Send + Sync + 'static) -> Router
+    where
+        W: Widget + 'static,
+    {
+        mount(LiveViewRoute::new(widget, config, None))
+    }
+
+    /// [`router_with_config`] that lets only the sockets `authorize` accepts
+    /// hold a session.
+    pub fn router_authorized<W>(config: LiveViewConfig, authorize: LiveViewAuthorizer, widget: impl Fn() -> W + Send + Sync + 'static) -> Router
+    where
+        W: Widget + 'static,
+    {
+        mount(LiveViewRoute::new(widget, config, Some(authorize)))
+    }
+
+    fn mount<W>(route: LiveViewRoute<W>) -> Router
+    where
+        W: Widget + 'static,
+    {
+        Router::with_path(LIVEVIEW_DEFAULT_PATH.trim_start_matches('/')).get(LiveViewHandler { route })
     }
 
     struct LiveViewHandler<W>
     where
         W: Widget + 'static,
     {
-        factory: Arc<dyn Fn() -> W + Send + Sync + 'static>,
+        route: LiveViewRoute<W>,
     }
 
     #[async_trait]
@@ -563,9 +1425,10 @@ pub mod salvo_mount {
         W: Widget + 'static,
     {
         async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
-            let factory = self.factory.clone();
+            let route = self.route.clone();
+            let request = request_context(req);
             if let Err(err) = WebSocketUpgrade::new()
-                .upgrade(req, res, move |socket| handle_socket(socket, factory))
+                .upgrade(req, res, move |socket| handle_socket(socket, route, request))
                 .await
             {
                 res.render(err);
@@ -573,50 +1436,91 @@ pub mod salvo_mount {
         }
     }
 
-    async fn handle_socket<W>(mut socket: WebSocket, factory: Arc<dyn Fn() -> W + Send + Sync + 'static>)
+    fn spawn_reaper(parked: Weak<ParkedSessions>, period: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if !reap_parked(&parked) {
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn handle_socket<W>(mut socket: WebSocket, route: LiveViewRoute<W>, request: RequestContext)
     where
         W: Widget + 'static,
     {
-        let Ok((worker, mount)) = SessionWorker::spawn(factory).await else {
-            let _ = send_error(&mut socket, "liveview session worker failed to mount").await;
-            return;
+        route.start_reaper(spawn_reaper);
+        let mut connection = match route.connect(request, tokio::time::sleep).await {
+            Ok(connection) => connection,
+            Err(reason) => {
+                let _ = send(&mut socket, LiveViewMessage::Close { reason }).await;
+                return;
+            }
         };
-        if send(&mut socket, mount).await.is_err() {
-            return;
-        }
-
-        while let Some(message) = socket.next().await {
-            let Ok(message) = message else {
-                break;
+        loop {
+            let message = match connection.next(socket.next()).await {
+                SocketEvent::Incoming(Some(Ok(message))) => message,
+                SocketEvent::Incoming(_) => break,
+                SocketEvent::Push(push) => {
+                    if send_frames(&mut socket, vec![push]).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                SocketEvent::Close(close) => {
+                    let _ = send_frames(&mut socket, vec![close]).await;
+                    break;
+                }
             };
-            if !message.is_text() {
-                continue;
-            }
-            let Ok(text) = message.as_str() else {
-                let _ = send_error(&mut socket, "invalid UTF-8 websocket message").await;
+            let decoded = if message.is_binary() {
+                decode_client_frame(message.as_bytes())
+            } else if message.is_text() {
+                let Ok(text) = message.as_str() else {
+                    let _ = send_error(&mut socket, "invalid UTF-8 websocket message").await;
+                    continue;
+                };
+                LiveViewMessage::from_json(text).map_err(FrameError::Json)
+            } else {
                 continue;
             };
-            match LiveViewMessage::from_json(text) {
+            match decoded {
                 Ok(message) => {
-                    if let Some(reply) = worker.handle_message(message).await
-                        && send(&mut socket, reply).await.is_err()
-                    {
+                    let Ok(frames) = route.respond(&mut connection, message).await else {
+                        let _ = send_error(&mut socket, "liveview session worker failed to mount").await;
+                        break;
+                    };
+                    if send_frames(&mut socket, frames).await.is_err() {
                         break;
                     }
                 }
                 Err(err) => {
-                    if send_error(&mut socket, format!("invalid liveview message: {err}")).await.is_err() {
+                    if send_error(&mut socket, err.to_string()).await.is_err() {
                         break;
                     }
                 }
             }
         }
+        route.park(connection.session);
     }
 
     async fn send(socket: &mut WebSocket, message: LiveViewMessage) -> Result<(), salvo::Error> {
         socket.send(Message::text(message.to_json().expect("liveview messages serialize"))).await
     }
 
+    async fn send_frames(socket: &mut WebSocket, frames: Vec<LiveViewFrame>) -> Result<(), salvo::Error> {
+        for frame in frames {
+            let message = match frame {
+                LiveViewFrame::Text(text) => Message::text(text),
+                LiveViewFrame::Binary(bytes) => Message::binary(bytes),
+            };
+            socket.send(message).await?;
+        }
+        Ok(())
+    }
+
     async fn send_error(socket: &mut WebSocket, message: impl Into<String>) -> Result<(), salvo::Error> {
         send(socket, LiveViewMessage::Error { message: message.into() }).await
     }
@@ -624,15 +1528,22 @@ pub mod salvo_mount {
 
 #[cfg(feature = "axum")]
 pub mod axum_mount {
-    use std::sync::Arc;
+    use std::sync::Weak;
+    use std::time::Duration;
 
     use axum::Router;
+    use axum::extract::Request;
     use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
     use axum::routing::get;
     use futures::StreamExt;
     use glory_core::Widget;
+    use glory_serverfn::axum_mount::request_context;
 
-    use crate::{LIVEVIEW_DEFAULT_PATH, LiveViewMessage, LiveviewRouter, SessionWorker, normalize_liveview_path};
+    use crate::wire::decode_client_frame;
+    use crate::{
+        FrameError, LIVEVIEW_DEFAULT_PATH, LiveViewAuthorizer, LiveViewConfig, LiveViewFrame, LiveViewMessage, LiveViewRoute, LiveviewRouter,
+        ParkedSessions, RequestContext, SocketEvent, normalize_liveview_path, reap_parked,
+    };
 
     pub fn router<W>(widget: impl Fn() -> W + Send + Sync + 'static) -> Router
     where
@@ -657,61 +1568,107 @@ pub mod axum_mount {
             Router::new()
         }
 
-        fn with_liveview<W>(self, path: &str, widget: impl Fn() -> W + Send + Sync + 'static) -> Self
+        fn with_liveview_authorized<W>(
+            self,
+            path: &str,
+            config: LiveViewConfig,
+            authorize: Option<LiveViewAuthorizer>,
+            widget: impl Fn() -> W + Send + Sync + 'static,
+        ) -> Self
         where
             W: Widget + 'static,
         {
-            let factory: Arc<dyn Fn() -> W + Send + Sync + 'static> = Arc::new(widget);
+            let route = LiveViewRoute::new(widget, config, authorize);
             self.route(
                 &normalize_liveview_path(path),
-                get(move |ws: WebSocketUpgrade| {
-                    let factory = factory.clone();
-                    async move { ws.on_upgrade(move |socket| handle_socket(socket, factory)) }
+                get(move |ws: WebSocketUpgrade, request: Request| {
+                    let route = route.clone();
+                    let request = request_context(&request);
+                    async move { ws.on_upgrade(move |socket| handle_socket(socket, route, request)) }
                 }),
             )
         }
     }
 
-    async fn handle_socket<W>(mut socket: WebSocket, factory: Arc<dyn Fn() -> W + Send + Sync + 'static>)
+    fn spawn_reaper(parked: Weak<ParkedSessions>, period: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if !reap_parked(&parked) {
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn handle_socket<W>(mut socket: WebSocket, route: LiveViewRoute<W>, request: RequestContext)
     where
         W: Widget + 'static,
     {
-        let Ok((worker, mount)) = SessionWorker::spawn(factory).await else {
-            let _ = send_error(&mut socket, "liveview session worker failed to mount").await;
-            return;
+        route.start_reaper(spawn_reaper);
+        let mut connection = match route.connect(request, tokio::time::sleep).await {
+            Ok(connection) => connection,
+            Err(reason) => {
+                let _ = send(&mut socket, LiveViewMessage::Close { reason }).await;
+                return;
+            }
         };
-        if send(&mut socket, mount).await.is_err() {
-            return;
-        }
-
-        while let Some(message) = socket.next().await {
-            let Ok(message) = message else {
-                break;
-            };
-            let Message::Text(text) = message else {
-                continue;
-            };
-            match LiveViewMessage::from_json(text.as_str()) {
+        loop {
+            let message = match connection.next(socket.next()).await {
+                SocketEvent::Incoming(Some(Ok(message))) => message,
+                SocketEvent::Incoming(_) => break,
+                SocketEvent::Push(push) => {
+                    if send_frames(&mut socket, vec![push]).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                SocketEvent::Close(close) => {
+                    let _ = send_frames(&mut socket, vec![close]).await;
+                    break;
+                }
+            };
+            let decoded = match message {
+                Message::Text(text) => LiveViewMessage::from_json(text.as_str()).map_err(FrameError::Json),
+                Message::Binary(bytes) => decode_client_frame(&bytes),
+                _ => continue,
+            };
+            match decoded {
                 Ok(message) => {
-                    if let Some(reply) = worker.handle_message(message).await
-                        && send(&mut socket, reply).await.is_err()
-                    {
+                    let Ok(frames) = route.respond(&mut connection, message).await else {
+                        let _ = send_error(&mut socket, "liveview session worker failed to mount").await;
+                        break;
+                    };
+                    if send_frames(&mut socket, frames).await.is_err() {
                         break;
                     }
                 }
                 Err(err) => {
-                    if send_error(&mut socket, format!("invalid liveview message: {err}")).await.is_err() {
+                    if send_error(&mut socket, err.to_string()).await.is_err() {
                         break;
                     }
                 }
             }
         }
+        route.park(connection.session);
     }
 
     async fn send(socket: &mut WebSocket, message: LiveViewMessage) -> Result<(), axum::Error> {
         socket.send(Message::text(message.to_json().expect("liveview messages serialize"))).await
     }
 
+    async fn send_frames(socket: &mut WebSocket, frames: Vec<LiveViewFrame>) -> Result<(), axum::Error> {
+        for frame in frames {
+            let message = match frame {
+                LiveViewFrame::Text(text) => Message::text(text),
+                LiveViewFrame::Binary(bytes) => Message::Binary(bytes.into()),
+            };
+            socket.send(message).await?;
+        }
+        Ok(())
+    }
+
     async fn send_error(socket: &mut WebSocket, message: impl Into<String>) -> Result<(), axum::Error> {
         send(socket, LiveViewMessage::Error { message: message.into() }).await
     }
@@ -719,12 +1676,18 @@ pub mod axum_mount {
 
 #[cfg(feature = "actix")]
 pub mod actix_mount {
-    use std::sync::Arc;
+    use std::sync::Weak;
+    use std::time::Duration;
 
     use actix_web::{HttpRequest, HttpResponse, Scope, web};
     use glory_core::Widget;
+    use glory_serverfn::actix_mount::request_context;
 
-    use crate::{LIVEVIEW_DEFAULT_PATH, LiveViewMessage, LiveviewRouter, SessionWorker, normalize_liveview_path};
+    use crate::wire::decode_client_frame;
+    use crate::{
+        FrameError, LIVEVIEW_DEFAULT_PATH, LiveViewAuthorizer, LiveViewConfig, LiveViewFrame, LiveViewMessage, LiveViewRoute, LiveviewRouter,
+        ParkedSessions, RequestContext, SocketEvent, normalize_liveview_path, reap_parked,
+    };
 
     pub fn scope<W>(widget: impl Fn() -> W + Send + Sync + 'static) -> Scope
     where
@@ -737,12 +1700,12 @@ pub mod actix_mount {
     where
         W: Widget + 'static,
     {
-        let factory: Arc<dyn Fn() -> W + Send + Sync + 'static> = Arc::new(widget);
+        let route = LiveViewRoute::new(widget, LiveViewConfig::default(), None);
         cfg.route(
             LIVEVIEW_DEFAULT_PATH,
             web::get().to(move |req: HttpRequest, body: web::Payload| {
-                let factory = factory.clone();
-                async move { handler(req, body, factory).await }
+                let route = route.clone();
+                async move { handler(req, body, route).await }
             }),
         );
     }
@@ -752,74 +1715,107 @@ pub mod actix_mount {
             web::scope("")
         }
 
-        fn with_liveview<W>(self, path: &str, widget: impl Fn() -> W + Send + Sync + 'static) -> Self
+        fn with_liveview_authorized<W>(
+            self,
+            path: &str,
+            config: LiveViewConfig,
+            authorize: Option<LiveViewAuthorizer>,
+            widget: impl Fn() -> W + Send + Sync + 'static,
+        ) -> Self
         where
             W: Widget + 'static,
         {
-            let factory: Arc<dyn Fn() -> W + Send + Sync + 'static> = Arc::new(widget);
+            let route = LiveViewRoute::new(widget, config, authorize);
             self.route(
                 &normalize_liveview_path(path),
                 web::get().to(move |req: HttpRequest, body: web::Payload| {
-                    let factory = factory.clone();
-                    async move { handler(req, body, factory).await }
+                    let route = route.clone();
+                    async move { handler(req, body, route).await }
                 }),
             )
         }
     }
 
-    async fn handler<W>(req: HttpRequest, body: web::Payload, factory: Arc<dyn Fn() -> W + Send + Sync + 'static>) -> actix_web::Result<HttpResponse>
+    async fn handler<W>(req: HttpRequest, body: web::Payload, route: LiveViewRoute<W>) -> actix_web::Result<HttpResponse>
     where
         W: Widget + 'static,
     {
         let (response, session, stream) = actix_ws::handle(&req, body)?;
-        actix_web::rt::spawn(handle_socket(session, stream, factory));
+        actix_web::rt::spawn(handle_socket(session, stream, route, request_context(&req)));
         Ok(response)
     }
 
-    async fn handle_socket<W>(
-        mut session: actix_ws::Session,
-        mut stream: actix_ws::MessageStream,
-        factory: Arc<dyn Fn() -> W + Send + Sync + 'static>,
-    ) where
+    fn spawn_reaper(parked: Weak<ParkedSessions>, period: Duration) {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(period);
+            loop {
+                interval.tick().await;
+                if !reap_parked(&parked) {
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn handle_socket<W>(mut session: actix_ws::Session, mut stream: actix_ws::MessageStream, route: LiveViewRoute<W>, request: RequestContext)
+    where
         W: Widget + 'static,
     {
-        let Ok((worker, mount)) = SessionWorker::spawn(factory).await else {
-            let _ = send_error(&mut session, "liveview session worker failed to mount").await;
-            return;
+        route.start_reaper(spawn_reaper);
+        let mut connection = match route.connect(request, actix_web::rt::time::sleep).await {
+            Ok(connection) => connection,
+            Err(reason) => {
+                let _ = send(&mut session, LiveViewMessage::Close { reason }).await;
+                let _ = session.close(None).await;
+                return;
+            }
         };
-        if send(&mut session, mount).await.is_err() {
-            return;
-        }
-
-        while let Some(message) = stream.recv().await {
-            let Ok(message) = message else {
-                break;
-            };
-            match message {
-                actix_ws::Message::Text(text) => match LiveViewMessage::from_json(text.as_ref()) {
-                    Ok(message) => {
-                        if let Some(reply) = worker.handle_message(message).await
-                            && send(&mut session, reply).await.is_err()
-                        {
-                            break;
-                        }
-                    }
-                    Err(err) => {
-                        if send_error(&mut session, format!("invalid liveview message: {err}")).await.is_err() {
-                            break;
-                        }
+        loop {
+            let message = match connection.next(stream.recv()).await {
+                SocketEvent::Incoming(Some(Ok(message))) => message,
+                SocketEvent::Incoming(_) => break,
+                SocketEvent::Push(push) => {
+                    if send_frames(&mut session, vec![push]).await.is_err() {
+                        break;
                     }
-                },
+                    continue;
+                }
+                SocketEvent::Close(close) => {
+                    let _ = send_frames(&mut session, vec![close]).await;
+                    break;
+                }
+            };
+            let decoded = match message {
+                actix_ws::Message::Text(text) => LiveViewMessage::from_json(text.as_ref()).map_err(FrameError::Json),
+                actix_ws::Message::Binary(bytes) => decode_client_frame(&bytes),
                 actix_ws::Message::Ping(bytes) => {
                     if session.pong(&bytes).await.is_err() {
                         break;
                     }
+                    continue;
                 }
                 actix_ws::Message::Close(_) => break,
-                _ => {}
+                _ => continue,
+            };
+            match decoded {
+                Ok(message) => {
+                    let Ok(frames) = route.respond(&mut connection, message).await else {
+                        let _ = send_error(&mut session, "liveview session worker failed to mount").await;
+                        break;
+                    };
+                    if send_frames(&mut session, frames).await.is_err() {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    if send_error(&mut session, err.to_string()).await.is_err() {
+                        break;
+                    }
+                }
             }
         }
 
+        route.park(connection.session);
         let _ = session.close(None).await;
     }
 
@@ -827,6 +1823,16 @@ pub mod actix_mount {
         session.text(message.to_json().expect("liveview messages serialize")).await
     }
 
+    async fn send_frames(session: &mut actix_ws::Session, frames: Vec<LiveViewFrame>) -> Result<(), actix_ws::Closed> {
+        for frame in frames {
+            match frame {
+                LiveViewFrame::Text(text) => session.text(text).await?,
+                LiveViewFrame::Binary(bytes) => session.binary(bytes).await?,
+            }
+        }
+        Ok(())
+    }
+
     async fn send_error(session: &mut actix_ws::Session, message: impl Into<String>) -> Result<(), actix_ws::Closed> {
         send(session, LiveViewMessage::Error { message: message.into() }).await
     }
@@ -837,7 +1843,7 @@ mod tests {
     use glory_core::reflow::Cage;
     use glory_core::renderer::{Command, NodeQuery, QueryResponse, QueryValue};
     use glory_core::web::events;
-    use glory_core::web::widgets::{button, div};
+    use glory_core::web::widgets::{button, div, input};
     use glory_core::{Scope, Widget};
 
     use super::*;
@@ -863,7 +1869,11 @@ mod tests {
     fn hello_version_negotiation() {
         let (session, _mount) = LiveViewSession::mount(Counter { value: Cage::new(0) });
         assert!(session.handle_message(LiveViewMessage::hello()).is_none());
-        let reply = session.handle_message(LiveViewMessage::Hello { protocol_version: 999 });
+        let reply = session.handle_message(LiveViewMessage::Hello {
+            protocol_version: 999,
+            codecs: Vec::new(),
+            adopt: None,
+        });
         assert!(matches!(reply, Some(LiveViewMessage::Error { .. })));
     }
 
@@ -910,6 +1920,56 @@ mod tests {
         assert!(registry.is_empty());
     }
 
+    #[test]
+    fn registry_take_and_reinsert_keep_the_creation_time() {
+        let mut registry: SessionRegistry<u8> = SessionRegistry::new(LiveViewConfig {
+            idle_timeout_secs: 100,
+            max_lifetime_secs: 150,
+            ..LiveViewConfig::default()
+        });
+        let token = registry.insert(10, 1);
+        let (value, created_at) = registry.take(50, token.as_str()).expect("alive");
+        assert_eq!((value, created_at), (1, 10));
+        assert!(registry.is_empty());
+
+        registry.reinsert(created_at, 120, token.as_str(), value);
+        assert!(registry.reap(159).is_empty());
+        assert_eq!(registry.reap(160).len(), 1, "lifetime counts from the original creation");
+
+        let token = registry.insert(0, 2);
+        assert!(registry.take(100, token.as_str()).is_none());
+        assert!(registry.is_empty(), "expired entry removed on failed take");
+    }
+
+    #[test]
+    fn config_reap_interval_follows_the_shortest_timeout() {
+        assert_eq!(LiveViewConfig::default().reap_interval(), Some(std::time::Duration::from_secs(60)));
+        let config = LiveViewConfig {
+            idle_timeout_secs: 0,
+            max_lifetime_secs: 30,
+            ..LiveViewConfig::default()
+        };
+        assert_eq!(config.reap_interval(), Some(std::time::Duration::from_secs(15)));
+        let config = LiveViewConfig {
+            idle_timeout_secs: 1,
+            ..config
+        };
+        assert_eq!(config.reap_interval(), Some(std::time::Duration::from_secs(1)));
+        let disabled = LiveViewConfig {
+            idle_timeout_secs: 0,
+            max_lifetime_secs: 0,
+            ..LiveViewConfig::default()
+        };
+        assert_eq!(disabled.reap_interval(), None);
+    }
+
+    #[test]
+    fn resume_tokens_are_unguessable() {
+        let token = ResumeToken::generate();
+        assert_eq!(token.as_str().len(), 32);
+        assert_ne!(token, ResumeToken::generate());
+    }
+
     #[test]
     fn registry_resume_drops_expired_entry() {
         let mut registry: SessionRegistry<u8> = SessionRegistry::new(LiveViewConfig {
@@ -947,6 +2007,63 @@ mod tests {
         assert_eq!(batches[1].len(), 3, "tail absorbed the overflow batch");
     }
 
+    #[test]
+    fn outbound_buffer_numbers_batches_for_replay() {
+        let mut buf = OutboundBuffer::new(4);
+        assert_eq!(buf.push(vec![Command::Remove { parent: 0, child: 1 }]), 1);
+        assert_eq!(buf.push(Vec::new()), 1, "empty batches take no number");
+        assert_eq!(buf.push(vec![Command::Remove { parent: 0, child: 2 }]), 2);
+        assert_eq!(buf.push(vec![Command::Remove { parent: 0, child: 3 }]), 3);
+
+        let replay = buf.replay_after(1).expect("replayable");
+        assert_eq!(replay.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(buf.replay_after(3), Some(Vec::new()));
+        assert_eq!(buf.replay_after(4), None, "ahead of the buffer");
+
+        buf.acknowledge(2);
+        assert_eq!(buf.len(), 1);
+        assert_eq!(buf.replay_after(1), None, "batch 2 was dropped");
+        assert_eq!(buf.replay_after(2).map(|replay| replay.len()), Some(1));
+    }
+
+    #[test]
+    fn outbound_buffer_cannot_replay_into_a_folded_batch() {
+        let mut buf = OutboundBuffer::new(1);
+        buf.push(vec![Command::Remove { parent: 0, child: 1 }]);
+        buf.push(vec![Command::Remove { parent: 0, child: 2 }]);
+        assert_eq!(buf.push(vec![Command::Remove { parent: 0, child: 3 }]), 3);
+        assert_eq!(buf.len(), 1);
+
+        assert_eq!(buf.replay_after(0).map(|replay| replay[0].0), Some(3));
+        assert_eq!(buf.replay_after(1), None, "patch 1 is folded together with 2 and 3");
+        assert_eq!(buf.replay_after(3), Some(Vec::new()));
+    }
+
+    #[test]
+    fn outbound_buffer_over_its_command_cap_stops_replaying() {
+        let mut buf = OutboundBuffer::new(2).max_commands(4);
+        buf.push(vec![Command::Remove { parent: 0, child: 1 }]);
+        buf.push(vec![Command::Remove { parent: 0, child: 2 }]);
+        // Folded batches keep counting towards the cap.
+        buf.push(vec![Command::Remove { parent: 0, child: 3 }, Command::Remove { parent: 0, child: 4 }]);
+        assert_eq!((buf.len(), buf.command_count()), (2, 4));
+        assert!(!buf.overflowed());
+        buf.acknowledge(1);
+        assert_eq!(buf.command_count(), 3);
+
+        assert_eq!(
+            buf.push(vec![Command::Remove { parent: 0, child: 5 }, Command::Remove { parent: 0, child: 6 }]),
+            4
+        );
+        assert!(buf.overflowed());
+        assert!(buf.is_empty());
+        assert_eq!(buf.command_count(), 0);
+        assert_eq!(buf.replay_after(1), None, "the commands after 1 were dropped");
+        assert_eq!(buf.replay_after(4), None, "an overflowed buffer never replays");
+        assert_eq!(buf.push(vec![Command::Remove { parent: 0, child: 7 }]), 5);
+        assert_eq!(buf.replay_after(4), None);
+    }
+
     #[test]
     fn outbound_buffer_unbounded_and_empty_behavior() {
         let mut buf = OutboundBuffer::new(0);
@@ -966,6 +2083,17 @@ mod tests {
         let message = LiveViewMessage::hello();
         let json = message.to_json().unwrap();
         assert_eq!(LiveViewMessage::from_json(&json).unwrap(), message);
+
+        let resume = LiveViewMessage::Resume {
+            token: "abc".to_owned(),
+            last_seq: 4,
+            codecs: Vec::new(),
+        };
+        assert_eq!(resume.to_json().unwrap(), r#"{"type":"resume","payload":{"token":"abc","last_seq":4}}"#);
+        assert_eq!(
+            LiveViewMessage::from_json(r#"{"type":"patch","payload":{"commands":[]}}"#).unwrap(),
+            LiveViewMessage::patch(0, Vec::new())
+        );
     }
 
     #[test]
@@ -974,21 +2102,88 @@ mod tests {
         assert!(LIVEVIEW_CLIENT_JS.contains("__gloryWryEvent"));
         assert!(LIVEVIEW_CLIENT_JS.contains("__gloryWryQuery"));
         assert!(LIVEVIEW_CLIENT_JS.contains("setTimeout(connect"));
+        assert!(LIVEVIEW_CLIENT_JS.contains(r#"type: "resume""#));
+        assert!(LIVEVIEW_CLIENT_JS.contains(r#"type: "ack""#));
+        assert!(LIVEVIEW_CLIENT_JS.contains("__gloryResetWry"));
+        assert!(LIVEVIEW_CLIENT_JS.contains("__gloryAdoptWry"));
     }
 
     #[test]
-    fn session_mount_and_event_emit_command_patches() {
-        let (session, mount) = LiveViewSession::mount(Counter { value: Cage::new(0) });
-        let LiveViewMessage::Mount { commands } = mount else {
+    fn element_directives_reach_the_client_as_attributes() {
+        use glory_core::web::widgets::input;
+
+        #[derive(Debug)]
+        struct Search;
+
+        impl Widget for Search {
+            fn build(&mut self, ctx: &mut Scope) {
+                div()
+                    .fill(input().live_debounce(300).live_hook("Autocomplete"))
+                    .fill(
+                        button()
+                            .text("Save")
+                            .live_throttle(1000)
+                            .live_loading_class("busy")
+                            .live_disable_with("Saving…"),
+                    )
+                    .show_in(ctx);
+            }
+        }
+
+        let (_session, mount) = LiveViewSession::mount(Search);
+        let LiveViewMessage::Mount { commands, .. } = mount else {
             panic!("expected mount message");
         };
-        let button_id = commands
+        let attributes: Vec<_> = commands
             .iter()
-            .find_map(|command| match command {
-                Command::Create { id, name, .. } if name == "button" => Some(*id),
+            .filter_map(|command| match command {
+                Command::SetAttribute { name, value, .. } if name.starts_with("data-glory-") => Some((name.as_str(), value.as_str())),
                 _ => None,
             })
-            .expect("button command");
+            .collect();
+        assert_eq!(
+            attributes,
+            [
+                (controls::DEBOUNCE_ATTRIBUTE, "300"),
+                (controls::HOOK_ATTRIBUTE, "Autocomplete"),
+                (controls::DISABLE_WITH_ATTRIBUTE, "Saving…"),
+                (controls::LOADING_CLASS_ATTRIBUTE, "busy"),
+                (controls::THROTTLE_ATTRIBUTE, "1000"),
+            ]
+        );
+        for attribute in [
+            controls::DEBOUNCE_ATTRIBUTE,
+            controls::THROTTLE_ATTRIBUTE,
+            controls::LOADING_CLASS_ATTRIBUTE,
+            controls::DISABLE_WITH_ATTRIBUTE,
+            controls::HOOK_ATTRIBUTE,
+        ] {
+            assert!(LIVEVIEW_CLIENT_JS.contains(attribute), "the client reads {attribute}");
+        }
+    }
+
+    #[test]
+    fn client_script_negotiates_binary_frames() {
+        assert!(LIVEVIEW_CLIENT_JS.contains(r#"binaryType = "arraybuffer""#));
+        assert!(LIVEVIEW_CLIENT_JS.contains(r#""postcard""#));
+        assert!(LIVEVIEW_CLIENT_JS.contains("deflate-raw"));
+    }
+
+    #[test]
+    fn client_script_tag_carries_the_nonce() {
+        let tag = client_script_tag(Some(&CspNonce::new("abc")));
+        assert!(tag.starts_with(r#"<script nonce="abc">"#));
+        assert!(tag.contains("__gloryCspNonce"));
+        assert!(client_script_tag(None).starts_with("<script>"));
+    }
+
+    #[test]
+    fn session_mount_and_event_emit_command_patches() {
+        let (session, mount) = LiveViewSession::mount(Counter { value: Cage::new(0) });
+        let LiveViewMessage::Mount { commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        let button_id = button_id(&commands);
         assert!(
             commands
                 .iter()
@@ -996,8 +2191,8 @@ mod tests {
         );
 
         let patch = session.dispatch_event(EventData::new("click", button_id));
-        let LiveViewMessage::Patch { commands } = patch else {
-            panic!("expected patch message");
+        let LiveViewMessage::Patch { commands, reply: true, .. } = patch else {
+            panic!("expected the reply patch");
         };
         assert!(
             commands
@@ -1006,6 +2201,42 @@ mod tests {
         );
     }
 
+    fn button_id(commands: &[Command]) -> u64 {
+        commands
+            .iter()
+            .find_map(|command| match command {
+                Command::Create { id, name, .. } if name == "button" => Some(*id),
+                _ => None,
+            })
+            .expect("button command")
+    }
+
+    #[test]
+    fn session_resume_replays_missed_patches() {
+        let (session, mount) = LiveViewSession::mount(Counter { value: Cage::new(0) });
+        let LiveViewMessage::Mount { seq: 0, commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        let button = button_id(&commands);
+
+        let first = session.dispatch_event(EventData::new("click", button));
+        assert!(matches!(first, LiveViewMessage::Patch { seq: 1, .. }));
+        let LiveViewMessage::Patch { seq: 2, commands, .. } = session.dispatch_event(EventData::new("click", button)) else {
+            panic!("expected patch 2");
+        };
+
+        // The client saw patch 1 before its socket dropped. Its loading
+        // states went with the socket, so the replay answers no event.
+        let replay = session.resume(1).expect("patch 2 is buffered");
+        assert_eq!(replay, vec![LiveViewMessage::patch(2, commands)]);
+        assert_eq!(session.resume(2), Some(Vec::new()));
+
+        // Resuming acknowledged everything up to the client's position.
+        assert_eq!(session.resume(0), None);
+        assert!(session.handle_message(LiveViewMessage::Ack { seq: 2 }).is_none());
+        assert_eq!(session.resume(3), None, "client claims a patch never sent");
+    }
+
     #[test]
     fn session_handles_ping() {
         let (session, _) = LiveViewSession::mount(Counter { value: Cage::new(0) });
@@ -1034,25 +2265,24 @@ mod tests {
             token,
             result: Ok(QueryValue::Value("live".to_owned())),
         })));
-        assert_eq!(reply, Some(LiveViewMessage::Patch { commands: Vec::new() }));
+        assert_eq!(reply, Some(LiveViewMessage::patch(0, Vec::new())));
         assert_eq!(futures::executor::block_on(query).unwrap(), QueryValue::Value("live".to_owned()));
     }
 
     #[test]
     #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
     fn session_worker_runs_on_local_pool() {
-        let (worker, mount) = futures::executor::block_on(SessionWorker::spawn(std::sync::Arc::new(|| Counter { value: Cage::new(0) })))
-            .expect("session worker mounts");
-        let LiveViewMessage::Mount { commands } = mount else {
+        let (worker, mount, _pushes) = futures::executor::block_on(SessionWorker::spawn(
+            std::sync::Arc::new(|| Counter { value: Cage::new(0) }),
+            LiveViewConfig::default(),
+            None,
+            RequestContext::default(),
+        ))
+        .expect("session worker mounts");
+        let LiveViewMessage::Mount { commands, .. } = mount else {
             panic!("expected mount message");
         };
-        let button_id = commands
-            .iter()
-            .find_map(|command| match command {
-                Command::Create { id, name, .. } if name == "button" => Some(*id),
-                _ => None,
-            })
-            .expect("button command");
+        let button_id = button_id(&commands);
 
         assert_eq!(
             futures::executor::block_on(worker.handle_message(LiveViewMessage::Ping)),
@@ -1060,7 +2290,7 @@ mod tests {
         );
 
         let reply = futures::executor::block_on(worker.handle_message(LiveViewMessage::Event(Box::new(EventData::new("click", button_id)))));
-        let Some(LiveViewMessage::Patch { commands }) = reply else {
+        let Some(LiveViewMessage::Patch { commands, .. }) = reply else {
             panic!("expected patch message");
         };
         assert!(
@@ -1069,4 +2299,590 @@ mod tests {
                 .any(|command| matches!(command, Command::SetText { value, .. } if value == "1"))
         );
     }
+
+    #[test]
+    #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+    fn route_parks_closed_sessions_and_resumes_them_by_token() {
+        use futures::executor::block_on;
+
+        let route = LiveViewRoute::new(|| Counter { value: Cage::new(0) }, LiveViewConfig::default(), None);
+        let mut attached = None;
+        let replies = block_on(route.handle(&mut attached, &RequestContext::default(), None, LiveViewMessage::hello())).expect("mounts");
+        let [
+            LiveViewMessage::Mount {
+                token: Some(token),
+                commands,
+                ..
+            },
+        ] = replies.as_slice()
+        else {
+            panic!("expected a single mount, got {replies:?}");
+        };
+        let token = token.clone();
+        let button = button_id(commands);
+
+        let click = || LiveViewMessage::Event(Box::new(EventData::new("click", button)));
+        let replies = block_on(route.handle(&mut attached, &RequestContext::default(), None, click())).unwrap();
+        assert!(matches!(replies.as_slice(), [LiveViewMessage::Patch { seq: 1, .. }]));
+
+        // The socket drops; the patch for the second click never arrived.
+        let [LiveViewMessage::Patch { seq, commands, .. }] =
+            &block_on(route.handle(&mut attached, &RequestContext::default(), None, click())).unwrap()[..]
+        else {
+            panic!("expected the reply patch");
+        };
+        let missed = vec![LiveViewMessage::patch(*seq, commands.clone())];
+        route.park(attached);
+        assert_eq!(route.sessions().len(), 1);
+
+        let mut attached = None;
+        let replies = block_on(route.handle(
+            &mut attached,
+            &RequestContext::default(),
+            None,
+            LiveViewMessage::Resume {
+                token: token.clone(),
+                last_seq: 1,
+                codecs: Vec::new(),
+            },
+        ))
+        .unwrap();
+        assert_eq!(replies, missed);
+        assert!(route.sessions().is_empty(), "attached sessions are not parked");
+        route.park(attached);
+
+        // An unknown token gets a fresh session under a new token.
+        let mut attached = None;
+        let replies = block_on(route.handle(
+            &mut attached,
+            &RequestContext::default(),
+            None,
+            LiveViewMessage::Resume {
+                token: "stale".to_owned(),
+                last_seq: 7,
+                codecs: Vec::new(),
+            },
+        ))
+        .unwrap();
+        let [LiveViewMessage::Mount { token: Some(fresh), .. }] = replies.as_slice() else {
+            panic!("expected a remount, got {replies:?}");
+        };
+        assert_ne!(fresh, &token);
+        assert_eq!(route.sessions().len(), 1);
+    }
+
+    #[test]
+    #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+    fn connections_negotiate_binary_frames_when_attaching() {
+        use futures::executor::block_on;
+
+        let route = LiveViewRoute::new(|| Counter { value: Cage::new(0) }, LiveViewConfig::default(), None);
+        let mut connection = LiveViewConnection::default();
+        let hello = LiveViewMessage::Hello {
+            protocol_version: LIVEVIEW_PROTOCOL_VERSION,
+            codecs: vec![LiveViewCodec::Postcard],
+            adopt: None,
+        };
+        let frames = block_on(route.respond(&mut connection, hello)).expect("mounts");
+        let mut decoder = FrameDecoder::new();
+        let [frame @ LiveViewFrame::Binary(_)] = frames.as_slice() else {
+            panic!("expected one binary mount, got {frames:?}");
+        };
+        let LiveViewMessage::Mount { commands, .. } = decoder.decode(frame).unwrap() else {
+            panic!("expected mount message");
+        };
+        let button_id = button_id(&commands);
+
+        let frames = block_on(route.respond(&mut connection, LiveViewMessage::Ping)).unwrap();
+        assert_eq!(frames, [LiveViewFrame::Text(r#"{"type":"pong"}"#.into())], "only commands go binary");
+        let click = LiveViewMessage::Event(Box::new(EventData::new("click", button_id)));
+        let frames = block_on(route.respond(&mut connection, click)).unwrap();
+        let [frame @ LiveViewFrame::Binary(_)] = frames.as_slice() else {
+            panic!("expected one binary patch, got {frames:?}");
+        };
+        assert!(matches!(decoder.decode(frame).unwrap(), LiveViewMessage::Patch { seq: 1, .. }));
+        route.park(connection.session);
+
+        let mut connection = LiveViewConnection::default();
+        let frames = block_on(route.respond(&mut connection, LiveViewMessage::hello())).unwrap();
+        assert!(matches!(frames.as_slice(), [LiveViewFrame::Text(_)]), "clients without codecs get JSON");
+    }
+
+    #[test]
+    #[cfg(feature = "ssr")]
+    fn dead_renders_are_adopted_with_only_the_live_difference() {
+        use glory_core::GloryConfig;
+        use glory_core::renderer::command_dom::{CommandDom, ROOT_ID};
+        use glory_core::web::holders::ServerHolder;
+
+        let holder = ServerHolder::new(GloryConfig::default(), "/")
+            .node_ids()
+            .mount(Counter { value: Cage::new(0) });
+        let page = DeadRender::new(&holder);
+        let dead = holder.host_relative_commands();
+        assert_eq!(page.fingerprint(), dead_render::fingerprint(&dead));
+        assert!(page.html().contains(r#"<button data-glory-id="2""#), "{}", page.html());
+
+        // Only the listener is missing from the page.
+        let (_, mount) = LiveViewSession::mount_adopting(Counter { value: Cage::new(0) }, &dead, &LiveViewConfig::default());
+        let LiveViewMessage::Mount { commands, adopt: true, .. } = mount else {
+            panic!("expected an adopting mount, got {mount:?}");
+        };
+        assert_eq!(
+            commands,
+            [Command::AttachEvent {
+                id: 2,
+                name: "click".into(),
+                bubbles: true,
+            }]
+        );
+
+        // A live state that moved on since the dead render is patched in.
+        let (_, mount) = LiveViewSession::mount_adopting(Counter { value: Cage::new(5) }, &dead, &LiveViewConfig::default());
+        let LiveViewMessage::Mount { commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        assert!(!commands.iter().any(|command| matches!(command, Command::Create { .. })), "{commands:?}");
+        let mut adopted = CommandDom::new();
+        adopted.apply_batch(&dead);
+        adopted.apply_batch(&commands);
+        let (_, mount) = LiveViewSession::mount(Counter { value: Cage::new(5) });
+        let LiveViewMessage::Mount { commands: live, .. } = mount else {
+            panic!("expected mount message");
+        };
+        let mut expected = CommandDom::new();
+        expected.apply_batch(&live);
+        assert_eq!(adopted.inner_html(ROOT_ID), expected.inner_html(ROOT_ID));
+    }
+
+    #[test]
+    #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+    fn route_adopts_dead_renders_it_can_reproduce() {
+        use futures::executor::block_on;
+        use glory_core::GloryConfig;
+        use glory_core::web::holders::ServerHolder;
+
+        let route = LiveViewRoute::new(|| Counter { value: Cage::new(0) }, LiveViewConfig::default(), None);
+        let page = DeadRender::new(
+            &ServerHolder::new(GloryConfig::default(), "/")
+                .node_ids()
+                .mount(Counter { value: Cage::new(0) }),
+        );
+        let hello = |fingerprint: &str| LiveViewMessage::Hello {
+            protocol_version: LIVEVIEW_PROTOCOL_VERSION,
+            codecs: Vec::new(),
+            adopt: Some(Adoption {
+                url: "/".to_owned(),
+                fingerprint: fingerprint.to_owned(),
+            }),
+        };
+
+        let replies = block_on(route.handle(&mut None, &RequestContext::default(), None, hello(page.fingerprint()))).unwrap();
+        let [LiveViewMessage::Mount { commands, adopt: true, .. }] = replies.as_slice() else {
+            panic!("expected an adopting mount, got {replies:?}");
+        };
+        assert_eq!(commands.len(), 1);
+
+        // The page was rendered from other data: it is replaced.
+        let replies = block_on(route.handle(&mut None, &RequestContext::default(), None, hello("0000000000000000"))).unwrap();
+        let [LiveViewMessage::Mount { commands, adopt: false, .. }] = replies.as_slice() else {
+            panic!("expected a full mount, got {replies:?}");
+        };
+        assert!(commands.iter().any(|command| matches!(command, Command::Create { .. })));
+    }
+
+    #[derive(Debug)]
+    struct Ticker {
+        topic: LiveTopic<String>,
+        latest: Cage<String>,
+        listening: Cage<bool>,
+    }
+
+    impl Widget for Ticker {
+        fn build(&mut self, ctx: &mut Scope) {
+            let topic = self.topic.clone();
+            let latest = self.latest;
+            div()
+                .fill(glory_core::widgets::Switch::new().case(self.listening, move || Listener {
+                    topic: topic.clone(),
+                    latest,
+                }))
+                .fill(div().text(self.latest))
+                .show_in(ctx);
+        }
+    }
+
+    #[derive(Debug)]
+    struct Listener {
+        topic: LiveTopic<String>,
+        latest: Cage<String>,
+    }
+
+    impl Widget for Listener {
+        fn build(&mut self, ctx: &mut Scope) {
+            let latest = self.latest;
+            self.topic.subscribe(ctx, move |message| latest.revise(|mut latest| *latest = message));
+        }
+    }
+
+    #[test]
+    fn topic_messages_patch_subscribed_sessions() {
+        use futures::StreamExt;
+        use futures::executor::block_on;
+
+        let hub = PubSub::new(LocalBackend);
+        let topic = hub.topic::<String>("news");
+        let listening = Cage::new(true);
+        let (session, _mount) = LiveViewSession::mount(Ticker {
+            topic: topic.clone(),
+            latest: Cage::new(String::new()),
+            listening,
+        });
+        let mut deliveries = session.take_deliveries().unwrap();
+        assert!(session.take_deliveries().is_none());
+        assert_eq!(hub.subscribers("news"), 1);
+
+        topic.publish(&"hello".to_owned()).unwrap();
+        let patch = session.deliver(block_on(deliveries.next()).unwrap());
+        let Some(LiveViewMessage::Patch {
+            seq: 1,
+            commands,
+            reply: false,
+        }) = patch
+        else {
+            panic!("expected a patch, got {patch:?}");
+        };
+        assert!(
+            commands
+                .iter()
+                .any(|command| matches!(command, Command::SetText { value, .. } if value == "hello"))
+        );
+
+        // A message of another type is dropped without a patch.
+        hub.topic::<u32>("news").publish(&7).unwrap();
+        assert_eq!(session.deliver(block_on(deliveries.next()).unwrap()), None);
+
+        // Detaching the subscribing widget unsubscribes it; a delivery
+        // already on its way is ignored.
+        topic.publish(&"late".to_owned()).unwrap();
+        session.holder().update(|| listening.revise(|mut listening| *listening = false));
+        assert_eq!(hub.subscribers("news"), 0);
+        assert_eq!(session.deliver(block_on(deliveries.next()).unwrap()), None);
+
+        session.holder().update(|| listening.revise(|mut listening| *listening = true));
+        assert_eq!(hub.subscribers("news"), 1);
+        drop(session);
+        assert_eq!(hub.subscribers("news"), 0, "closed sessions unsubscribe");
+    }
+
+    #[test]
+    #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+    fn route_pushes_topic_patches_to_the_socket() {
+        use futures::executor::block_on;
+        use futures::future::pending;
+
+        let hub = PubSub::new(LocalBackend);
+        let topic = hub.topic::<String>("news");
+        let factory_topic = topic.clone();
+        let route = LiveViewRoute::new(
+            move || Ticker {
+                topic: factory_topic.clone(),
+                latest: Cage::new(String::new()),
+                listening: Cage::new(true),
+            },
+            LiveViewConfig::default(),
+            None,
+        );
+        let mut connection = LiveViewConnection::default();
+        block_on(route.respond(&mut connection, LiveViewMessage::hello())).unwrap();
+        assert_eq!(hub.subscribers("news"), 1);
+
+        topic.publish(&"breaking".to_owned()).unwrap();
+        let SocketEvent::Push(LiveViewFrame::Text(push)) = block_on(connection.next(pending::<()>())) else {
+            panic!("expected a pushed frame");
+        };
+        let LiveViewMessage::Patch {
+            seq: 1,
+            commands,
+            reply: false,
+        } = LiveViewMessage::from_json(&push).unwrap()
+        else {
+            panic!("expected a patch, got {push}");
+        };
+        assert!(
+            commands
+                .iter()
+                .any(|command| matches!(command, Command::SetText { value, .. } if value == "breaking"))
+        );
+
+        route.park(connection.session.take());
+        assert_eq!(hub.subscribers("news"), 1, "parked sessions keep listening");
+    }
+
+    /// Greets the user named by the request's `user` cookie.
+    #[derive(Debug)]
+    struct Greeting;
+
+    impl Widget for Greeting {
+        fn build(&mut self, ctx: &mut Scope) {
+            let user = ctx
+                .truck()
+                .obtain::<RequestContext>()
+                .ok()
+                .and_then(|request| request.cookie("user"))
+                .unwrap_or_else(|| "guest".to_owned());
+            div().text(format!("hello {user}")).show_in(ctx);
+        }
+    }
+
+    fn request_with_cookie(cookie: &str) -> RequestContext {
+        RequestContext {
+            method: "GET".to_owned(),
+            uri: LIVEVIEW_DEFAULT_PATH.to_owned(),
+            headers: vec![("cookie".to_owned(), cookie.to_owned())],
+        }
+    }
+
+    fn greets(commands: &[Command], user: &str) -> bool {
+        let greeting = format!("hello {user}");
+        commands
+            .iter()
+            .any(|command| matches!(command, Command::SetText { value, .. } if *value == greeting))
+    }
+
+    #[test]
+    fn sessions_see_the_upgrade_request_in_their_truck() {
+        let (session, mount) = LiveViewSession::mount_for_request(Greeting, request_with_cookie("user=ada"), &LiveViewConfig::default());
+        let LiveViewMessage::Mount { commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        assert!(greets(&commands, "ada"), "{commands:?}");
+
+        session.set_request(request_with_cookie("user=grace"));
+        let request = session.holder().truck().borrow().obtain::<RequestContext>().unwrap().clone();
+        assert_eq!(request.cookie("user").as_deref(), Some("grace"));
+
+        let (_, mount) = LiveViewSession::mount(Greeting);
+        let LiveViewMessage::Mount { commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        assert!(greets(&commands, "guest"), "{commands:?}");
+    }
+
+    #[test]
+    #[cfg(any(feature = "salvo", feature = "axum", feature = "actix"))]
+    fn routes_authorize_sockets_and_close_them_once_the_login_expires() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        use futures::executor::block_on;
+        use futures::future::pending;
+
+        let expired = Arc::new(AtomicBool::new(false));
+        let login = expired.clone();
+        let authorizer = LiveViewAuthorizer::new(move |request: RequestContext| {
+            let valid = request.cookie("user").is_some() && !login.load(Ordering::SeqCst);
+            let user = request.cookie("user").unwrap_or_default();
+            async move { if valid { Ok(user) } else { Err("login required".to_owned()) } }
+        });
+        let route = LiveViewRoute::new(|| Greeting, LiveViewConfig::default(), Some(authorizer));
+        // The clock only moves on once the login expired.
+        let clock = expired.clone();
+        let sleep = move |_| {
+            let expired = clock.load(Ordering::SeqCst);
+            async move {
+                if !expired {
+                    pending::<()>().await;
+                }
+            }
+        };
+
+        let refused = block_on(route.connect(RequestContext::default(), sleep.clone()));
+        assert_eq!(refused.err().as_deref(), Some("login required"));
+
+        let mut connection = block_on(route.connect(request_with_cookie("user=ada"), sleep.clone())).expect("authorized");
+        let frames = block_on(route.respond(&mut connection, LiveViewMessage::hello())).unwrap();
+        let [LiveViewFrame::Text(mount)] = frames.as_slice() else {
+            panic!("expected one mount, got {frames:?}");
+        };
+        let LiveViewMessage::Mount { commands, .. } = LiveViewMessage::from_json(mount).unwrap() else {
+            panic!("expected mount message, got {mount}");
+        };
+        assert!(greets(&commands, "ada"), "{commands:?}");
+
+        // Another user's socket holding the token gets a session of its own.
+        let token = match LiveViewMessage::from_json(mount).unwrap() {
+            LiveViewMessage::Mount { token: Some(token), .. } => token,
+            mount => panic!("expected a token, got {mount:?}"),
+        };
+        route.park(connection.session.take());
+        let resume = || LiveViewMessage::Resume {
+            token: token.clone(),
+            last_seq: 0,
+            codecs: Vec::new(),
+        };
+        let mut other = block_on(route.connect(request_with_cookie("user=grace"), sleep.clone())).expect("authorized");
+        let frames = block_on(route.respond(&mut other, resume())).unwrap();
+        let [LiveViewFrame::Text(mount)] = frames.as_slice() else {
+            panic!("expected a fresh mount, got {frames:?}");
+        };
+        let LiveViewMessage::Mount { commands, token: fresh, .. } = LiveViewMessage::from_json(mount).unwrap() else {
+            panic!("expected mount message, got {mount}");
+        };
+        assert!(greets(&commands, "grace"), "{commands:?}");
+        assert_ne!(fresh.as_ref(), Some(&token));
+        assert_eq!(route.sessions().len(), 1, "ada's session stays parked");
+
+        let mut connection = block_on(route.connect(request_with_cookie("user=ada"), sleep)).expect("authorized");
+        let frames = block_on(route.respond(&mut connection, resume())).unwrap();
+        assert!(frames.is_empty(), "nothing to replay, got {frames:?}");
+        assert!(route.sessions().is_empty(), "ada resumed the parked session");
+
+        expired.store(true, Ordering::SeqCst);
+        let SocketEvent::Close(LiveViewFrame::Text(close)) = block_on(connection.next(pending::<()>())) else {
+            panic!("expected the socket to close");
+        };
+        assert_eq!(
+            LiveViewMessage::from_json(&close).unwrap(),
+            LiveViewMessage::Close {
+                reason: "login required".to_owned()
+            }
+        );
+        route.park(connection.session);
+        assert!(route.sessions().is_empty(), "closed sessions are dropped, not parked");
+    }
+
+    #[derive(Debug)]
+    struct Uploader {
+        upload: std::rc::Rc<std::cell::RefCell<Option<LiveUpload>>>,
+    }
+
+    impl Widget for Uploader {
+        fn build(&mut self, ctx: &mut Scope) {
+            let upload = LiveUpload::new(ctx).accept("text/*").max_file_size(8).max_entries(2);
+            let progress = upload.entries().map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| format!("{} {}%", entry.name, entry.progress()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            });
+            input().attr("type", "file").live_upload(&upload).show_in(ctx);
+            div().text(progress).show_in(ctx);
+            *self.upload.borrow_mut() = Some(upload);
+        }
+    }
+
+    #[test]
+    fn uploads_stream_into_temp_files_with_progress() {
+        let slot = std::rc::Rc::new(std::cell::RefCell::new(None));
+        let (session, mount) = LiveViewSession::mount(Uploader { upload: slot.clone() });
+        let upload = slot.borrow().clone().expect("mounted");
+        let LiveViewMessage::Mount { commands, .. } = mount else {
+            panic!("expected mount message");
+        };
+        let attribute = |name: &str| {
+            commands.iter().find_map(|command| match command {
+                Command::SetAttribute { name: set, value, .. } if set == name => Some(value.clone()),
+                _ => None,
+            })
+        };
+        assert_eq!(attribute(controls::UPLOAD_ATTRIBUTE), Some(upload.name()));
+        assert_eq!(attribute("accept").as_deref(), Some("text/*"));
+        assert!(attribute("multiple").is_some());
+
+        let start = |entry: u64, name: &str, mime: &str, size: u64| LiveViewMessage::UploadStart {
+            upload: upload.name(),
+            entry,
+            name: name.to_owned(),
+            mime: mime.to_owned(),
+            size,
+        };
+        let chunk = |entry: u64, offset: u64, data: &[u8]| LiveViewMessage::UploadChunk {
+            entry,
+            offset,
+            data: data.to_vec(),
+        };
+        let status = |entry: u64, received: u64| LiveViewMessage::UploadStatus {
+            entry,
+            received,
+            error: None,
+        };
+        let refused = |message: LiveViewMessage| match session.handle_message(message) {
+            Some(LiveViewMessage::UploadStatus { error: Some(error), .. }) => error,
+            reply => panic!("expected a refusal, got {reply:?}"),
+        };
+
+        assert!(refused(start(1, "a.png", "image/png", 4)).contains("not accepted"));
+        assert!(refused(start(2, "big.txt", "text/plain", 9)).contains("larger than"));
+        assert_eq!(session.handle_message(start(3, "a.txt", "text/plain", 6)), Some(status(3, 0)));
+        assert_eq!(session.handle_message(chunk(3, 0, b"hel")), Some(status(3, 3)));
+        let Some(LiveViewMessage::Patch { commands, .. }) = session.flush() else {
+            panic!("expected the progress patch");
+        };
+        assert!(
+            commands
+                .iter()
+                .any(|command| matches!(command, Command::SetText { value, .. } if value.contains("a.txt 50%")))
+        );
+
+        assert_eq!(session.handle_message(start(4, "b.txt", "text/plain", 6)), Some(status(4, 0)));
+        assert!(refused(start(5, "c.txt", "text/plain", 1)).contains("at most 2"));
+        assert_eq!(session.handle_message(chunk(4, 0, b"abc")), Some(status(4, 3)));
+        assert_eq!(session.handle_message(chunk(4, 3, b"def")), Some(status(4, 6)));
+
+        let completed = upload.take_completed();
+        let [done] = completed.as_slice() else {
+            panic!("expected one completed upload, got {completed:?}");
+        };
+        assert_eq!(done.entry().name, "b.txt");
+        assert_eq!(done.bytes().unwrap(), b"abcdef");
+        let path = done.path().to_owned();
+        drop(completed);
+        assert!(!path.exists(), "dropped uploads delete their file");
+
+        // The client reconnects halfway through a.txt; it fails and its data
+        // is gone.
+        let partial: Vec<_> = upload.entries().get_untracked().iter().map(|entry| entry.id).collect();
+        assert_eq!(partial, [1, 2, 3, 5], "refused and unfinished entries stay listed");
+        session.resume(session.seq()).expect("resumes");
+        let aborted = upload.entries().get_untracked().iter().find(|entry| entry.id == 3).cloned().unwrap();
+        assert_eq!(aborted.error.as_deref(), Some("connection lost"));
+        assert!(refused(chunk(3, 3, b"lo!")).contains("cancelled"));
+
+        assert_eq!(session.handle_message(start(6, "d.txt", "text/plain", 6)), Some(status(6, 0)));
+        assert!(refused(chunk(6, 2, b"lo!")).contains("offset"), "chunks arrive in order");
+
+        upload.cancel(3);
+        assert_eq!(upload.entries().get_untracked().len(), 4);
+
+        // Listed ids can't be announced again.
+        assert!(refused(start(1, "e.txt", "text/plain", 1)).contains("already started"));
+        // Refused entries are capped, oldest first.
+        for entry in 100..120 {
+            refused(start(entry, "x.png", "image/png", 1));
+        }
+        let failed: Vec<_> = upload
+            .entries()
+            .get_untracked()
+            .iter()
+            .filter(|entry| entry.error.is_some())
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(failed, (112..120).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn upload_temp_files_are_private_to_the_server_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("glory-liveview-uploads-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = upload::TempFile::create(&dir).unwrap();
+        assert_eq!(file.path.parent(), Some(dir.as_path()));
+        assert_eq!(std::fs::metadata(&file.path).unwrap().permissions().mode() & 0o777, 0o600);
+        drop(file);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -4,8 +4,46 @@
 // provides window.__gloryApplyWryBatch and calls __gloryWryEvent /
 // __gloryWryQuery when browser events or node query answers need to go back to
 // the server.
+//
+// The mount carries a resume token. After a dropped connection the client
+// reconnects with exponential backoff and sends `resume` with the token and
+// the last patch sequence number it applied; the server replays the patches
+// it missed, or answers with a fresh mount that replaces the page content.
+//
+// A page served as a dead render passes its fingerprint as `options.adopt`.
+// The first mount then adopts the server-rendered elements, and only carries
+// what changed since, unless the server could not reproduce the render.
+//
+// Elements can carry directives set by `glory_liveview::LiveControls`:
+// `data-glory-debounce` and `data-glory-throttle` delay their events,
+// `data-glory-loading-class` and `data-glory-disable-with` mark them until the
+// server's reply to an event arrives, and `data-glory-hook` names one of
+// `options.hooks`, whose `mounted`, `updated` and `destroyed` callbacks run
+// with `this.el` set to the element.
+//
+// Files picked in an input with `data-glory-upload` are announced with
+// `upload_start` and, once the server accepts them, sent as binary chunks. Each
+// chunk waits for the `upload_status` answering the previous one. Uploads in
+// flight are dropped with their socket, and refused or failed ones are passed
+// to `options.onUploadError`.
+//
+// Unless `options.binary` is false the client offers the binary codecs, and
+// the server then sends mounts and patches as binary frames; see
+// crates/liveview/src/wire.rs and crates/core/src/renderer/command_wire.rs for
+// the layout decoded here.
 (() => {
   const DEFAULT_PATH = "/__glory/liveview";
+  const PROTOCOL_VERSION = 2;
+  // Kind byte of a client upload chunk frame; see wire.rs.
+  const UPLOAD_CHUNK = 1;
+  const UPLOAD_CHUNK_SIZE = 64 * 1024;
+  const FRAME_DEFLATED = 1;
+
+  // Under a Content Security Policy the interpreter stamps this nonce on the
+  // <script>/<style> elements it creates.
+  if (document.currentScript && document.currentScript.nonce) {
+    window.__gloryCspNonce = window.__gloryCspNonce || document.currentScript.nonce;
+  }
 
   const wsUrl = (path) => {
     if (/^wss?:\/\//.test(path)) return path;
@@ -13,16 +51,277 @@
     return `${protocol}//${window.location.host}${path}`;
   };
 
+  // Variants of `Command` in declaration order, with their fields in order.
+  // `name` fields are indices into the connection's interned strings.
+  const COMMANDS = [
+    ["Create", [["id", "u64"], ["name", "name"], ["is_void", "bool"]]],
+    ["SetAttribute", [["id", "u64"], ["name", "name"], ["value", "str"]]],
+    ["RemoveAttribute", [["id", "u64"], ["name", "name"]]],
+    ["SetProperty", [["id", "u64"], ["name", "name"], ["value", "str"]]],
+    ["RemoveProperty", [["id", "u64"], ["name", "name"]]],
+    ["AddClass", [["id", "u64"], ["value", "name"]]],
+    ["RemoveClass", [["id", "u64"], ["value", "name"]]],
+    ["SetText", [["id", "u64"], ["value", "str"]]],
+    ["SetHtml", [["id", "u64"], ["value", "str"]]],
+    ["Insert", [["parent", "u64"], ["child", "u64"], ["position", "position"]]],
+    ["Remove", [["parent", "u64"], ["child", "u64"]]],
+    ["AttachEvent", [["id", "u64"], ["name", "name"], ["bubbles", "bool"]]],
+    ["DetachEvent", [["id", "u64"], ["name", "name"]]],
+    ["Query", [["id", "u64"], ["token", "u64"], ["kind", "query"]]],
+  ];
+  const NODE_QUERIES = ["BoundingRect", "Value", "ScrollOffset"];
+  const utf8 = new TextDecoder();
+
+  const reader = (bytes) => {
+    let offset = 0;
+    const r = {
+      byte() {
+        if (offset >= bytes.length) throw new Error("truncated frame");
+        return bytes[offset++];
+      },
+      // LEB128; ids stay below 2^53, so plain arithmetic is exact.
+      varint() {
+        let value = 0;
+        let scale = 1;
+        for (;;) {
+          const byte = r.byte();
+          value += (byte & 0x7f) * scale;
+          if (byte < 0x80) return value;
+          scale *= 128;
+        }
+      },
+      bool() {
+        return r.byte() !== 0;
+      },
+      str() {
+        const len = r.varint();
+        if (offset + len > bytes.length) throw new Error("truncated frame");
+        const value = utf8.decode(bytes.subarray(offset, offset + len));
+        offset += len;
+        return value;
+      },
+    };
+    return r;
+  };
+
+  const FIELDS = {
+    u64: (r) => r.varint(),
+    bool: (r) => r.bool(),
+    str: (r) => r.str(),
+    name: (r, strings) => {
+      const index = r.varint();
+      if (index >= strings.length) throw new Error(`unknown interned string ${index}`);
+      return strings[index];
+    },
+    position: (r) => {
+      const variant = r.varint();
+      if (variant === 0) return "Head";
+      if (variant === 1) return "Tail";
+      if (variant === 2) return { Before: r.varint() };
+      if (variant === 3) return { After: r.varint() };
+      throw new Error(`unknown insert position ${variant}`);
+    },
+    query: (r) => {
+      const kind = NODE_QUERIES[r.varint()];
+      if (!kind) throw new Error("unknown node query");
+      return kind;
+    },
+  };
+
+  // Turns a binary frame body into the JSON shape of the same message.
+  const decodeBody = (body, strings) => {
+    const r = reader(body);
+    const header = r.varint();
+    let message;
+    if (header === 0) {
+      const token = r.bool() ? r.str() : null;
+      const seq = r.varint();
+      message = { type: "mount", payload: { token, seq, adopt: r.bool() } };
+    } else if (header === 1) {
+      const seq = r.varint();
+      message = { type: "patch", payload: { seq, reply: r.bool() } };
+    } else {
+      throw new Error(`unknown frame header ${header}`);
+    }
+    for (let count = r.varint(); count > 0; count--) strings.push(r.str());
+    const commands = [];
+    for (let count = r.varint(); count > 0; count--) {
+      const variant = r.varint();
+      const spec = COMMANDS[variant];
+      if (!spec) throw new Error(`unknown command ${variant}`);
+      const fields = {};
+      for (const [field, kind] of spec[1]) fields[field] = FIELDS[kind](r, strings);
+      commands.push({ [spec[0]]: fields });
+    }
+    message.payload.commands = commands;
+    return message;
+  };
+
+  const inflate = async (bytes) => {
+    const stream = new Blob([bytes]).stream().pipeThrough(new DecompressionStream("deflate-raw"));
+    return new Uint8Array(await new Response(stream).arrayBuffer());
+  };
+
+  const decodeFrame = async (bytes, strings) => {
+    if (bytes.length === 0) throw new Error("empty binary frame");
+    let body = bytes.subarray(1);
+    if (bytes[0] & FRAME_DEFLATED) body = await inflate(body);
+    return decodeBody(body, strings);
+  };
+
   window.__gloryLiveViewConnect = (path = DEFAULT_PATH, options = {}) => {
     let socket = null;
     let closed = false;
     let reconnectMs = options.reconnectMs || 250;
     const maxReconnectMs = options.maxReconnectMs || 5000;
+    const ackMs = options.ackMs || 1000;
+    let token = null;
+    let lastSeq = 0;
+    let mounted = false;
+    let ackTimer = null;
+    // Inflating is asynchronous; messages are still handled in arrival order.
+    let received = Promise.resolve();
+
+    const codecs = () => {
+      if (options.binary === false) return [];
+      return typeof DecompressionStream === "function" ? ["postcard", "deflate"] : ["postcard"];
+    };
 
     const send = (message) => {
       if (socket && socket.readyState === WebSocket.OPEN) {
         socket.send(JSON.stringify(message));
+        return true;
       }
+      return false;
+    };
+
+    const node = (id) => (typeof window.__gloryWryNode === "function" ? window.__gloryWryNode(id) : undefined);
+
+    // Loading states. The server answers every event with one reply patch,
+    // in order, so the sender of each event waits in a queue.
+    let awaiting = [];
+    const busy = new Map();
+
+    const markBusy = (element) => {
+      const loadingClass = element.getAttribute("data-glory-loading-class");
+      const disableWith = element.getAttribute("data-glory-disable-with");
+      if (loadingClass === null && disableWith === null) return null;
+      let state = busy.get(element);
+      if (!state) {
+        const textKey = element instanceof HTMLInputElement ? "value" : "textContent";
+        state = { count: 0, loadingClass, disableWith, textKey, disabled: element.disabled, text: element[textKey] };
+        if (loadingClass) element.classList.add(...loadingClass.split(/\s+/).filter(Boolean));
+        if (disableWith !== null) {
+          element.disabled = true;
+          element[textKey] = disableWith;
+        }
+        busy.set(element, state);
+      }
+      state.count += 1;
+      return element;
+    };
+
+    const unmarkBusy = (element) => {
+      const state = busy.get(element);
+      if (!state) return;
+      state.count -= 1;
+      if (state.count > 0) return;
+      busy.delete(element);
+      if (state.loadingClass) element.classList.remove(...state.loadingClass.split(/\s+/).filter(Boolean));
+      if (state.disableWith !== null) {
+        element.disabled = state.disabled;
+        element[state.textKey] = state.text;
+      }
+    };
+
+    const sendEvent = (payload) => {
+      const element = node(payload.node_id);
+      if (send({ type: "event", payload })) awaiting.push(element ? markBusy(element) : null);
+    };
+
+    // Debounced and throttled events not sent yet, by node and event name,
+    // in the order they were first held back.
+    const held = new Map();
+    const throttledUntil = new Map();
+
+    const release = (key) => {
+      const entry = held.get(key);
+      if (!entry) return;
+      held.delete(key);
+      window.clearTimeout(entry.timer);
+      sendEvent(entry.payload);
+    };
+
+    const onEvent = (payload) => {
+      const key = `${payload.node_id}:${payload.name}`;
+      // Whatever was held back happened first.
+      for (const other of [...held.keys()]) if (other !== key) release(other);
+      const element = node(payload.node_id);
+      const debounce = element ? Number(element.getAttribute("data-glory-debounce")) : 0;
+      const throttle = element ? Number(element.getAttribute("data-glory-throttle")) : 0;
+      const entry = held.get(key);
+      if (debounce > 0) {
+        if (entry) window.clearTimeout(entry.timer);
+        held.set(key, { payload, timer: window.setTimeout(() => release(key), debounce) });
+      } else if (throttle > 0) {
+        const now = Date.now();
+        const until = throttledUntil.get(key) || 0;
+        if (entry) {
+          entry.payload = payload;
+        } else if (now >= until) {
+          throttledUntil.set(key, now + throttle);
+          sendEvent(payload);
+        } else {
+          const timer = window.setTimeout(() => {
+            throttledUntil.set(key, Date.now() + throttle);
+            release(key);
+          }, until - now);
+          held.set(key, { payload, timer });
+        }
+      } else {
+        if (entry) release(key);
+        sendEvent(payload);
+      }
+    };
+
+    // Client hooks, by element.
+    const hooks = options.hooks || {};
+    const hooked = new Map();
+
+    const runHook = (instance, callback) => {
+      if (typeof instance[callback] !== "function") return;
+      try {
+        instance[callback]();
+      } catch (err) {
+        console.error(`Glory LiveView: hook ${callback} failed`, err);
+      }
+    };
+
+    const updateHooks = (commands) => {
+      for (const [element, instance] of hooked) {
+        if (!element.isConnected) {
+          hooked.delete(element);
+          runHook(instance, "destroyed");
+        }
+      }
+      const mounted = new Set();
+      for (const element of document.querySelectorAll("[data-glory-hook]")) {
+        const hook = hooks[element.getAttribute("data-glory-hook")];
+        if (!hook || hooked.has(element)) continue;
+        const instance = Object.create(hook);
+        instance.el = element;
+        hooked.set(element, instance);
+        mounted.add(element);
+        runHook(instance, "mounted");
+      }
+      const updated = new Set();
+      for (const command of commands) {
+        const fields = command.type ? (command[command.type] ?? command) : Object.values(command)[0] || {};
+        const target = node(fields.parent ?? fields.id);
+        const element = target && target.closest && target.closest("[data-glory-hook]");
+        if (element && hooked.has(element) && !mounted.has(element)) updated.add(element);
+      }
+      for (const element of updated) runHook(hooked.get(element), "updated");
     };
 
     const apply = (commands) => {
@@ -31,46 +330,153 @@
         throw new Error("Glory LiveView: command interpreter is not installed");
       }
       window.__gloryApplyWryBatch(commands);
+      updateHooks(commands);
+    };
+
+    // Uploads in flight, by the entry number announcing them.
+    let nextEntry = 1;
+    const uploading = new Map();
+
+    const sendChunk = async (entry, offset) => {
+      const upload = uploading.get(entry);
+      const data = new Uint8Array(await upload.file.slice(offset, offset + UPLOAD_CHUNK_SIZE).arrayBuffer());
+      // The socket may have closed while the file was read.
+      if (uploading.get(entry) !== upload || !socket || socket.readyState !== WebSocket.OPEN) return;
+      const frame = new Uint8Array(17 + data.length);
+      const view = new DataView(frame.buffer);
+      frame[0] = UPLOAD_CHUNK;
+      view.setBigUint64(1, BigInt(entry), true);
+      view.setBigUint64(9, BigInt(offset), true);
+      frame.set(data, 17);
+      socket.send(frame);
+    };
+
+    const onUploadStatus = (payload) => {
+      const upload = uploading.get(payload.entry);
+      if (!upload) return;
+      if (payload.error || payload.received >= upload.file.size) {
+        uploading.delete(payload.entry);
+        if (payload.error && options.onUploadError) options.onUploadError(upload.file, payload.error);
+        return;
+      }
+      sendChunk(payload.entry, payload.received).catch((err) => {
+        uploading.delete(payload.entry);
+        console.error("Glory LiveView: upload failed", err);
+      });
+    };
+
+    document.addEventListener("change", (event) => {
+      const input = event.target;
+      if (!(input instanceof HTMLInputElement) || !input.files) return;
+      const upload = input.getAttribute("data-glory-upload");
+      if (upload === null) return;
+      for (const file of input.files) {
+        const entry = nextEntry++;
+        const start = { upload, entry, name: file.name, mime: file.type, size: file.size };
+        if (send({ type: "upload_start", payload: start })) uploading.set(entry, { file });
+      }
+      // Picking the same file again is a new upload.
+      input.value = "";
+    });
+
+    // Acknowledged patches are dropped from the server's replay buffer.
+    // Acks are batched so a burst of patches costs one message.
+    const scheduleAck = () => {
+      if (ackTimer !== null) return;
+      ackTimer = window.setTimeout(() => {
+        ackTimer = null;
+        send({ type: "ack", payload: { seq: lastSeq } });
+      }, ackMs);
     };
 
     const handle = (message) => {
       const payload = message.payload || {};
       if (message.type === "hello") {
-        send({ type: "hello", payload: { protocol_version: payload.protocol_version || 1 } });
-      } else if (message.type === "mount" || message.type === "patch") {
+        send({ type: "hello", payload: { protocol_version: payload.protocol_version || PROTOCOL_VERSION } });
+      } else if (message.type === "mount") {
+        // The first mount takes over a dead render's elements. Unless the
+        // server diffed against them they are dropped, like everything an
+        // earlier session built when a resume fails.
+        if (!mounted && typeof window.__gloryAdoptWry === "function") window.__gloryAdoptWry();
+        if (!payload.adopt && typeof window.__gloryResetWry === "function") window.__gloryResetWry();
         apply(payload.commands);
+        mounted = true;
+        token = payload.token || null;
+        lastSeq = payload.seq || 0;
+      } else if (message.type === "patch") {
+        // Patches without commands repeat the last number, and a replay can
+        // overlap what arrived before the reconnect; neither is applied.
+        const seq = payload.seq || 0;
+        // The reply clears the loading state before its commands may
+        // change the element.
+        if (payload.reply) {
+          const element = awaiting.shift();
+          if (element) unmarkBusy(element);
+        }
+        if (seq > lastSeq) {
+          apply(payload.commands);
+          lastSeq = seq;
+          scheduleAck();
+        }
+      } else if (message.type === "upload_status") {
+        onUploadStatus(payload);
       } else if (message.type === "ping") {
         send({ type: "pong" });
       } else if (message.type === "close") {
         closed = true;
         if (socket) socket.close();
+        if (options.onClose) options.onClose(payload.reason);
       } else if (message.type === "error") {
         console.error("Glory LiveView:", payload.message || message);
       }
     };
 
     const connect = () => {
-      socket = new WebSocket(wsUrl(path));
+      const current = new WebSocket(wsUrl(path));
+      socket = current;
+      socket.binaryType = "arraybuffer";
+      // Interned strings of binary frames are scoped to one socket.
+      const strings = [];
       socket.addEventListener("open", () => {
         reconnectMs = options.reconnectMs || 250;
-        send({ type: "hello", payload: { protocol_version: 1 } });
+        if (token) {
+          send({ type: "resume", payload: { token, last_seq: lastSeq, codecs: codecs() } });
+        } else {
+          const adopt = !mounted && options.adopt ? { url: window.location.pathname + window.location.search, fingerprint: options.adopt } : undefined;
+          send({ type: "hello", payload: { protocol_version: PROTOCOL_VERSION, codecs: codecs(), adopt } });
+        }
       });
       socket.addEventListener("message", (event) => {
-        try {
-          handle(JSON.parse(event.data));
-        } catch (err) {
-          console.error("Glory LiveView: bad message", err);
-        }
+        const data = event.data;
+        received = received
+          .then(() => (typeof data === "string" ? JSON.parse(data) : decodeFrame(new Uint8Array(data), strings)))
+          .then(handle)
+          .catch((err) => {
+            console.error("Glory LiveView: bad message", err);
+            // A lost binary frame desynchronises the string table; reconnect
+            // and resume with fresh tables.
+            if (typeof data !== "string") current.close();
+          });
       });
       socket.addEventListener("close", () => {
+        if (ackTimer !== null) {
+          window.clearTimeout(ackTimer);
+          ackTimer = null;
+        }
+        // Replies to events sent on this socket will not arrive.
+        for (const element of awaiting) if (element) unmarkBusy(element);
+        awaiting = [];
+        // The server fails uploads in flight when the session resumes.
+        uploading.clear();
         if (closed) return;
-        const delay = reconnectMs;
+        // Jitter spreads out the clients of a restarted server.
+        const delay = reconnectMs * (0.5 + Math.random() / 2);
         reconnectMs = Math.min(reconnectMs * 2, maxReconnectMs);
         window.setTimeout(connect, delay);
       });
     };
 
-    window.__gloryWryEvent = (event) => send({ type: "event", payload: event });
+    window.__gloryWryEvent = onEvent;
     window.__gloryWryQuery = (query) => send({ type: "query", payload: query });
     connect();
 
@@ -0,0 +1,416 @@
+//! Broadcast topics that push updates into live sessions.
+//!
+//! A [`LiveTopic`] names a channel of `T` messages. A widget running in a
+//! LiveView session subscribes with [`LiveTopic::subscribe`]; every
+//! [`publish`](LiveTopic::publish), from any thread or session, then reaches
+//! the handler on the subscribing session's worker thread. The handler runs
+//! inside the holder's `update()`, and the patch it produces is pushed to the
+//! client like the reply to an event. The subscription ends when its widget
+//! detaches or its session closes.
+//!
+//! Messages pass through a [`PubSub`] hub as JSON. The hub hands each publish
+//! to its [`PubSubBackend`]. The default [`LocalBackend`] delivers it straight
+//! to this process's subscribers; a backend for a broker forwards it to every
+//! node instead and calls [`PubSub::deliver`] with what arrives.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use futures::{Stream, StreamExt};
+use glory_core::{HolderId, Scope, ViewId, Widget};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Carries published messages between the nodes of a deployment.
+pub trait PubSubBackend: Send + Sync + 'static {
+    /// Sends `payload` to the subscribers of `topic` on every node, this one
+    /// through `hub.deliver`.
+    fn publish(&self, hub: &PubSub, topic: &str, payload: &[u8]);
+
+    /// `hub` gained its first subscriber to `topic`.
+    fn subscribed(&self, _hub: &PubSub, _topic: &str) {}
+
+    /// `hub` lost its last subscriber to `topic`.
+    fn unsubscribed(&self, _hub: &PubSub, _topic: &str) {}
+}
+
+/// Delivers within this process only.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalBackend;
+
+impl PubSubBackend for LocalBackend {
+    fn publish(&self, hub: &PubSub, topic: &str, payload: &[u8]) {
+        hub.deliver(topic, payload);
+    }
+}
+
+struct Subscriber {
+    id: u64,
+    inbox: UnboundedSender<TopicDelivery>,
+}
+
+struct Hub {
+    backend: RwLock<Arc<dyn PubSubBackend>>,
+    topics: Mutex<HashMap<String, Vec<Subscriber>>>,
+    next_id: AtomicU64,
+}
+
+/// The subscribers of this process, by topic.
+#[derive(Clone)]
+pub struct PubSub {
+    hub: Arc<Hub>,
+}
+
+impl PubSub {
+    pub fn new(backend: impl PubSubBackend) -> Self {
+        Self {
+            hub: Arc::new(Hub {
+                backend: RwLock::new(Arc::new(backend)),
+                topics: Mutex::new(HashMap::new()),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// The hub behind [`LiveTopic::new`], with a [`LocalBackend`] until
+    /// [`set_backend`](Self::set_backend) replaces it.
+    pub fn global() -> &'static PubSub {
+        static GLOBAL: OnceLock<PubSub> = OnceLock::new();
+        GLOBAL.get_or_init(|| PubSub::new(LocalBackend))
+    }
+
+    /// Replaces the backend. Install it before the first subscription: the
+    /// new backend is not told about topics subscribed earlier.
+    pub fn set_backend(&self, backend: impl PubSubBackend) {
+        *self.hub.backend.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(backend);
+    }
+
+    pub fn topic<T>(&self, name: impl Into<String>) -> LiveTopic<T> {
+        LiveTopic {
+            hub: self.clone(),
+            name: name.into().into(),
+            message: PhantomData,
+        }
+    }
+
+    /// Hands a published payload to this process's subscribers of `topic`.
+    /// Backends call this; to publish, use [`LiveTopic::publish`].
+    pub fn deliver(&self, topic: &str, payload: &[u8]) {
+        let payload: Arc<[u8]> = payload.into();
+        let emptied = {
+            let mut topics = self.topics();
+            let Some(subscribers) = topics.get_mut(topic) else {
+                return;
+            };
+            // Closed inboxes belong to sessions that ended without
+            // unsubscribing; this is where they are dropped.
+            subscribers.retain(|subscriber| {
+                subscriber
+                    .inbox
+                    .unbounded_send(TopicDelivery {
+                        subscription: subscriber.id,
+                        payload: payload.clone(),
+                    })
+                    .is_ok()
+            });
+            subscribers.is_empty() && topics.remove(topic).is_some()
+        };
+        if emptied {
+            self.backend().unsubscribed(self, topic);
+        }
+    }
+
+    /// Number of subscriptions to `topic` in this process.
+    pub fn subscribers(&self, topic: &str) -> usize {
+        self.topics().get(topic).map_or(0, Vec::len)
+    }
+
+    fn topics(&self) -> MutexGuard<'_, HashMap<String, Vec<Subscriber>>> {
+        self.hub.topics.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn backend(&self) -> Arc<dyn PubSubBackend> {
+        self.hub.backend.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn subscribe(&self, topic: &str, inbox: UnboundedSender<TopicDelivery>) -> u64 {
+        let id = self.hub.next_id.fetch_add(1, Ordering::Relaxed);
+        let first = {
+            let mut topics = self.topics();
+            let subscribers = topics.entry(topic.to_owned()).or_default();
+            subscribers.push(Subscriber { id, inbox });
+            subscribers.len() == 1
+        };
+        if first {
+            self.backend().subscribed(self, topic);
+        }
+        id
+    }
+
+    fn unsubscribe(&self, topic: &str, id: u64) {
+        let emptied = {
+            let mut topics = self.topics();
+            let Some(subscribers) = topics.get_mut(topic) else {
+                return;
+            };
+            subscribers.retain(|subscriber| subscriber.id != id);
+            subscribers.is_empty() && topics.remove(topic).is_some()
+        };
+        if emptied {
+            self.backend().unsubscribed(self, topic);
+        }
+    }
+}
+
+impl std::fmt::Debug for PubSub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PubSub").field("topics", &self.topics().len()).finish()
+    }
+}
+
+/// A named broadcast channel of `T` messages.
+pub struct LiveTopic<T> {
+    hub: PubSub,
+    name: Arc<str>,
+    message: PhantomData<fn(T) -> T>,
+}
+
+impl<T> Clone for LiveTopic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+            name: self.name.clone(),
+            message: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for LiveTopic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveTopic").field("name", &self.name).finish()
+    }
+}
+
+impl<T> LiveTopic<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    /// The topic `name` on [`PubSub::global`].
+    pub fn new(name: impl Into<String>) -> Self {
+        PubSub::global().topic(name)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sends `message` to every subscriber, including those of the calling
+    /// session. Handlers run later, on their own session's worker.
+    pub fn publish(&self, message: &T) -> Result<(), serde_json::Error> {
+        let payload = serde_json::to_vec(message)?;
+        self.hub.backend().publish(&self.hub, &self.name, &payload);
+        Ok(())
+    }
+
+    /// Runs `handler` with every message published from now on, as long as
+    /// the returned widget stays attached to `ctx`.
+    ///
+    /// Only widgets running in a LiveView session subscribe; elsewhere, e.g.
+    /// in a dead render, the widget does nothing. Messages that do not
+    /// deserialize as `T` are dropped.
+    pub fn subscribe(&self, ctx: &mut Scope, handler: impl FnMut(T) + 'static) -> ViewId {
+        TopicSubscription {
+            topic: self.clone(),
+            handler: Some(Box::new(handler)),
+            active: None,
+        }
+        .show_in(ctx)
+    }
+}
+
+struct TopicSubscription<T> {
+    topic: LiveTopic<T>,
+    handler: Option<Box<dyn FnMut(T)>>,
+    active: Option<(u64, Weak<SessionTopics>)>,
+}
+
+impl<T> std::fmt::Debug for TopicSubscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicSubscription")
+            .field("topic", &self.topic.name)
+            .field("active", &self.active.is_some())
+            .finish()
+    }
+}
+
+impl<T> Widget for TopicSubscription<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    fn build(&mut self, ctx: &mut Scope) {
+        let holder = ctx.holder_id();
+        let Some(session) = SESSIONS.with_borrow(|sessions| sessions.get(&holder).and_then(Weak::upgrade)) else {
+            return;
+        };
+        let Some(mut handler) = self.handler.take() else {
+            return;
+        };
+        let id = self.topic.hub.subscribe(&self.topic.name, session.inbox.clone());
+        let handler = move |payload: &[u8]| {
+            if let Ok(message) = serde_json::from_slice(payload) {
+                handler(message);
+            }
+        };
+        session.handlers.borrow_mut().insert(
+            id,
+            SessionHandler {
+                hub: self.topic.hub.clone(),
+                topic: self.topic.name.clone(),
+                handler: Rc::new(RefCell::new(handler)),
+            },
+        );
+        self.active = Some((id, Rc::downgrade(&session)));
+    }
+
+    fn detach(&mut self, ctx: &mut Scope) {
+        if let Some((id, session)) = self.active.take()
+            && let Some(session) = session.upgrade()
+        {
+            session.remove(id);
+        }
+        self.detach_children(ctx);
+    }
+}
+
+/// A published message on its way to one subscription.
+#[derive(Clone, Debug)]
+pub struct TopicDelivery {
+    subscription: u64,
+    payload: Arc<[u8]>,
+}
+
+/// What a session's subscriptions received, in publish order. See
+/// [`LiveViewSession::take_deliveries`](crate::LiveViewSession::take_deliveries).
+#[derive(Debug)]
+pub struct TopicDeliveries(UnboundedReceiver<TopicDelivery>);
+
+impl Stream for TopicDeliveries {
+    type Item = TopicDelivery;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<TopicDelivery>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
+
+type Handler = Rc<RefCell<dyn FnMut(&[u8])>>;
+
+struct SessionHandler {
+    hub: PubSub,
+    topic: Arc<str>,
+    handler: Handler,
+}
+
+/// The subscriptions of one session.
+pub(crate) struct SessionTopics {
+    holder: HolderId,
+    inbox: UnboundedSender<TopicDelivery>,
+    handlers: RefCell<HashMap<u64, SessionHandler>>,
+}
+
+thread_local! {
+    /// The sessions on this thread, by the holder their widgets run in.
+    static SESSIONS: RefCell<HashMap<HolderId, Weak<SessionTopics>>> = RefCell::new(HashMap::new());
+}
+
+impl SessionTopics {
+    /// Subscriptions made by widgets of `holder` go to the new session.
+    pub(crate) fn new(holder: HolderId) -> (Rc<Self>, TopicDeliveries) {
+        let (inbox, deliveries) = unbounded();
+        let topics = Rc::new(Self {
+            holder,
+            inbox,
+            handlers: RefCell::new(HashMap::new()),
+        });
+        SESSIONS.with_borrow_mut(|sessions| sessions.insert(holder, Rc::downgrade(&topics)));
+        (topics, TopicDeliveries(deliveries))
+    }
+
+    /// The handler of a delivery, unless its subscription ended since.
+    pub(crate) fn handler(&self, delivery: &TopicDelivery) -> Option<(Handler, Arc<[u8]>)> {
+        let handlers = self.handlers.borrow();
+        let handler = handlers.get(&delivery.subscription)?;
+        Some((handler.handler.clone(), delivery.payload.clone()))
+    }
+
+    fn remove(&self, id: u64) {
+        let removed = self.handlers.borrow_mut().remove(&id);
+        if let Some(SessionHandler { hub, topic, .. }) = removed {
+            hub.unsubscribe(&topic, id);
+        }
+    }
+}
+
+impl Drop for SessionTopics {
+    fn drop(&mut self) {
+        SESSIONS.with_borrow_mut(|sessions| sessions.remove(&self.holder));
+        for (id, SessionHandler { hub, topic, .. }) in self.handlers.get_mut().drain() {
+            hub.unsubscribe(&topic, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recording {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl PubSubBackend for Arc<Recording> {
+        fn publish(&self, hub: &PubSub, topic: &str, payload: &[u8]) {
+            self.events.lock().unwrap().push(format!("publish {topic}"));
+            hub.deliver(topic, payload);
+        }
+
+        fn subscribed(&self, _hub: &PubSub, topic: &str) {
+            self.events.lock().unwrap().push(format!("subscribed {topic}"));
+        }
+
+        fn unsubscribed(&self, _hub: &PubSub, topic: &str) {
+            self.events.lock().unwrap().push(format!("unsubscribed {topic}"));
+        }
+    }
+
+    #[test]
+    fn backends_see_publishes_and_the_first_and_last_subscriber() {
+        let recording = Arc::new(Recording::default());
+        let hub = PubSub::new(recording.clone());
+        let topic = hub.topic::<u32>("scores");
+        let (first, mut first_deliveries) = unbounded();
+        let (second, second_deliveries) = unbounded();
+        let first_id = hub.subscribe("scores", first);
+        hub.subscribe("scores", second);
+        drop(second_deliveries);
+
+        topic.publish(&7).unwrap();
+        let delivery = first_deliveries.try_recv().unwrap();
+        assert_eq!((delivery.subscription, &*delivery.payload), (first_id, &b"7"[..]));
+        assert_eq!(hub.subscribers("scores"), 1, "closed inboxes are dropped on delivery");
+
+        hub.unsubscribe("scores", first_id);
+        assert_eq!(hub.subscribers("scores"), 0);
+        assert_eq!(
+            *recording.events.lock().unwrap(),
+            ["subscribed scores", "publish scores", "unsubscribed scores"]
+        );
+    }
+}
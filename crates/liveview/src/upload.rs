@@ -0,0 +1,508 @@
+//! File uploads over the LiveView socket.
+//!
+//! A widget creates a [`LiveUpload`] with the MIME types and sizes it takes
+//! and marks a file input with
+//! [`live_upload`](crate::LiveControls::live_upload):
+//!
+//! ```ignore
+//! let upload = LiveUpload::new(ctx).accept("image/*").max_file_size(4 << 20).max_entries(3);
+//! input().attr("type", "file").live_upload(&upload).show_in(ctx);
+//! ```
+//!
+//! When files are picked, `LIVEVIEW_CLIENT_JS` announces each one with an
+//! `upload_start` message. The session checks it against the upload's limits
+//! and answers with `upload_status`. An accepted file then goes up in binary
+//! chunks (see [`encode_client_frame`](crate::wire::encode_client_frame)),
+//! each answered with the bytes received so far, so a client never has more
+//! than one chunk in flight.
+//!
+//! [`LiveUpload::entries`] is a [`Cage`] the session updates as files are
+//! announced and received, so widgets render progress from it. The data goes
+//! to a temporary file per entry. Once an entry is complete,
+//! [`LiveUpload::take_completed`] hands it out as a [`CompletedUpload`] to
+//! read or persist.
+//!
+//! Temporary files never outlive their entry. [`LiveUpload::cancel`] and a
+//! failed chunk delete them, as does the widget detaching or the session
+//! closing. Entries still receiving when the client reconnects fail, since
+//! the client lost them with the socket.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+
+use glory_core::reflow::Cage;
+use glory_core::{HolderId, Scope, Widget};
+
+/// Bytes the client sends per chunk.
+pub const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Refused and failed entries an upload keeps listed; older ones drop off,
+/// so a client announcing refused files can't grow the list without limit.
+pub const MAX_FAILED_UPLOAD_ENTRIES: usize = 8;
+
+/// One file announced to a [`LiveUpload`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadEntry {
+    /// Chosen by the client, unique within the session.
+    pub id: u64,
+    pub name: String,
+    pub mime: String,
+    pub size: u64,
+    pub received: u64,
+    /// Why the file was refused or its upload failed.
+    pub error: Option<String>,
+}
+
+impl UploadEntry {
+    pub fn is_done(&self) -> bool {
+        self.error.is_none() && self.received == self.size
+    }
+
+    /// Percentage of the file received.
+    pub fn progress(&self) -> u8 {
+        match self.size {
+            0 => 100,
+            size => (self.received.min(size) * 100 / size) as u8,
+        }
+    }
+}
+
+/// A file input's uploads. See the [module docs](self).
+#[derive(Clone)]
+pub struct LiveUpload {
+    inner: Rc<UploadInner>,
+}
+
+struct UploadInner {
+    name: RefCell<String>,
+    accept: RefCell<Vec<String>>,
+    max_file_size: Cell<u64>,
+    max_entries: Cell<usize>,
+    temp_dir: RefCell<Option<PathBuf>>,
+    entries: Cage<Vec<UploadEntry>>,
+    files: RefCell<HashMap<u64, TempFile>>,
+}
+
+impl std::fmt::Debug for LiveUpload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveUpload")
+            .field("name", &self.inner.name.borrow())
+            .field("accept", &self.inner.accept.borrow())
+            .field("max_file_size", &self.inner.max_file_size.get())
+            .field("max_entries", &self.inner.max_entries.get())
+            .field("temp_dir", &self.inner.temp_dir.borrow())
+            .finish()
+    }
+}
+
+impl LiveUpload {
+    /// An upload for one file of at most 8 MiB, of any type, registered
+    /// with the session `ctx` runs in.
+    pub fn new(ctx: &mut Scope) -> Self {
+        let inner = Rc::new(UploadInner {
+            name: RefCell::new(String::new()),
+            accept: RefCell::new(Vec::new()),
+            max_file_size: Cell::new(8 << 20),
+            max_entries: Cell::new(1),
+            temp_dir: RefCell::new(None),
+            entries: Cage::new(Vec::new()),
+            files: RefCell::new(HashMap::new()),
+        });
+        let view_id = UploadRegistration {
+            inner: inner.clone(),
+            session: None,
+        }
+        .show_in(ctx);
+        *inner.name.borrow_mut() = view_id.to_string();
+        Self { inner }
+    }
+
+    /// Also accepts files of `mime`, either a full type or `type/*`. An
+    /// upload without any accepts every type.
+    pub fn accept(self, mime: impl Into<String>) -> Self {
+        self.inner.accept.borrow_mut().push(mime.into().to_ascii_lowercase());
+        self
+    }
+
+    pub fn max_file_size(self, bytes: u64) -> Self {
+        self.inner.max_file_size.set(bytes);
+        self
+    }
+
+    /// How many files the upload holds at once, counting completed ones
+    /// not taken yet.
+    pub fn max_entries(self, entries: usize) -> Self {
+        self.inner.max_entries.set(entries);
+        self
+    }
+
+    /// Receives files into `dir` instead of the system temporary directory.
+    /// The files are readable by the server's user only either way.
+    pub fn temp_dir(self, dir: impl Into<PathBuf>) -> Self {
+        *self.inner.temp_dir.borrow_mut() = Some(dir.into());
+        self
+    }
+
+    /// The value of the input's upload attribute.
+    pub fn name(&self) -> String {
+        self.inner.name.borrow().clone()
+    }
+
+    /// The accepted types as an `accept` attribute value.
+    pub fn accept_attribute(&self) -> String {
+        self.inner.accept.borrow().join(",")
+    }
+
+    pub fn allows_multiple(&self) -> bool {
+        self.inner.max_entries.get() > 1
+    }
+
+    /// The announced files with their progress, for widgets to render.
+    pub fn entries(&self) -> Cage<Vec<UploadEntry>> {
+        self.inner.entries
+    }
+
+    /// Removes the completed entries and hands out their files.
+    pub fn take_completed(&self) -> Vec<CompletedUpload> {
+        let completed: Vec<_> = self.inner.entries.revise(|mut entries| {
+            let (done, pending) = entries.drain(..).partition(UploadEntry::is_done);
+            *entries = pending;
+            done
+        });
+        let mut files = self.inner.files.borrow_mut();
+        completed
+            .into_iter()
+            .filter_map(|entry| {
+                Some(CompletedUpload {
+                    file: files.remove(&entry.id)?,
+                    entry,
+                })
+            })
+            .collect()
+    }
+
+    /// Drops the entry `id` and its data. A client still sending it is told
+    /// the upload was cancelled.
+    pub fn cancel(&self, id: u64) {
+        self.inner.files.borrow_mut().remove(&id);
+        self.inner.entries.revise(|mut entries| entries.retain(|entry| entry.id != id));
+    }
+}
+
+impl UploadInner {
+    fn accepts(&self, mime: &str) -> bool {
+        let accept = self.accept.borrow();
+        let mime = mime.to_ascii_lowercase();
+        accept.is_empty()
+            || accept.iter().any(|allowed| match allowed.strip_suffix("/*") {
+                Some(kind) => mime.split_once('/').is_some_and(|(prefix, _)| prefix == kind),
+                None => *allowed == mime,
+            })
+    }
+
+    fn start(&self, id: u64, name: String, mime: String, size: u64) -> Result<(), String> {
+        // Ids are the client's; one listed already is not announced again.
+        if self.entries.get_untracked().iter().any(|entry| entry.id == id) {
+            return Err(format!("entry {id} already started"));
+        }
+        let held = self.entries.get_untracked().iter().filter(|entry| entry.error.is_none()).count();
+        let refusal = if !self.accepts(&mime) {
+            Some(format!("{name}: type {mime:?} is not accepted"))
+        } else if size > self.max_file_size.get() {
+            Some(format!("{name}: larger than {} bytes", self.max_file_size.get()))
+        } else if held >= self.max_entries.get() {
+            Some(format!("{name}: at most {} files", self.max_entries.get()))
+        } else {
+            None
+        };
+        let file = match &refusal {
+            Some(_) => None,
+            None => {
+                let dir = self.temp_dir.borrow().clone().unwrap_or_else(std::env::temp_dir);
+                Some(TempFile::create(&dir).map_err(|err| format!("{name}: {err}"))?)
+            }
+        };
+        self.entries.revise(|mut entries| {
+            let failed = entries.iter().filter(|entry| entry.error.is_some()).count();
+            let mut excess = (failed + usize::from(refusal.is_some())).saturating_sub(MAX_FAILED_UPLOAD_ENTRIES);
+            entries.retain(|entry| {
+                let drop = excess > 0 && entry.error.is_some();
+                excess -= usize::from(drop);
+                !drop
+            });
+            entries.push(UploadEntry {
+                id,
+                name,
+                mime,
+                size,
+                received: 0,
+                error: refusal.clone(),
+            })
+        });
+        if let Some(refusal) = refusal {
+            return Err(refusal);
+        }
+        self.files.borrow_mut().extend(file.map(|file| (id, file)));
+        Ok(())
+    }
+
+    /// Appends a chunk to entry `id`; the bytes received so far.
+    fn chunk(&self, id: u64, offset: u64, data: &[u8]) -> Result<u64, String> {
+        let result = self.write(id, offset, data);
+        self.entries.revise(|mut entries| {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) {
+                match &result {
+                    Ok(received) => entry.received = *received,
+                    Err(err) => entry.error = Some(err.clone()),
+                }
+            }
+        });
+        if result.is_err() {
+            self.files.borrow_mut().remove(&id);
+        }
+        result
+    }
+
+    fn write(&self, id: u64, offset: u64, data: &[u8]) -> Result<u64, String> {
+        let size = self
+            .entries
+            .get_untracked()
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.size)
+            .ok_or("upload cancelled")?;
+        let mut files = self.files.borrow_mut();
+        let file = files.get_mut(&id).ok_or("upload cancelled")?;
+        if offset != file.written {
+            return Err(format!("expected offset {}, got {offset}", file.written));
+        }
+        let received = file.written + data.len() as u64;
+        if received > size {
+            return Err(format!("more than the announced {size} bytes"));
+        }
+        file.write(data).map_err(|err| err.to_string())?;
+        Ok(received)
+    }
+
+    /// Fails the entries still receiving.
+    fn abort_unfinished(&self) {
+        let aborted = self.entries.revise(|mut entries| {
+            let mut aborted = Vec::new();
+            for entry in entries.iter_mut().filter(|entry| entry.error.is_none() && entry.received < entry.size) {
+                entry.error = Some("connection lost".to_owned());
+                aborted.push(entry.id);
+            }
+            aborted
+        });
+        let mut files = self.files.borrow_mut();
+        for id in aborted {
+            files.remove(&id);
+        }
+    }
+}
+
+/// A fully received file. Its temporary file is deleted on drop unless
+/// [`persist`](Self::persist) moved it.
+#[derive(Debug)]
+pub struct CompletedUpload {
+    entry: UploadEntry,
+    file: TempFile,
+}
+
+impl CompletedUpload {
+    pub fn entry(&self) -> &UploadEntry {
+        &self.entry
+    }
+
+    /// Where the data is until this is dropped.
+    pub fn path(&self) -> &Path {
+        &self.file.path
+    }
+
+    /// The data as a byte stream.
+    pub fn open(&self) -> io::Result<File> {
+        File::open(&self.file.path)
+    }
+
+    pub fn bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.entry.size as usize);
+        self.open()?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Moves the data to `path`.
+    pub fn persist(mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if std::fs::rename(&self.file.path, path).is_err() {
+            // Across file systems a rename fails; copy instead.
+            std::fs::copy(&self.file.path, path)?;
+            return Ok(());
+        }
+        self.file.path = PathBuf::new();
+        Ok(())
+    }
+}
+
+/// A file in the upload's temporary directory, removed on drop.
+#[derive(Debug)]
+pub(crate) struct TempFile {
+    pub(crate) path: PathBuf,
+    file: Option<File>,
+    written: u64,
+}
+
+impl TempFile {
+    /// A new file in `dir`, readable by the current user only where the
+    /// platform allows.
+    pub(crate) fn create(dir: &Path) -> io::Result<Self> {
+        let mut random = [0u8; 16];
+        getrandom::fill(&mut random).expect("the operating system provides randomness");
+        let name: String = random.iter().map(|byte| format!("{byte:02x}")).collect();
+        let path = dir.join(format!("glory-upload-{name}"));
+        let mut options = File::options();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(&path)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            written: 0,
+        })
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        let file = self.file.as_mut().ok_or_else(|| io::Error::other("upload already complete"))?;
+        file.write_all(data)?;
+        self.written += data.len() as u64;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        self.file.take();
+        if !self.path.as_os_str().is_empty() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Makes a [`LiveUpload`] reachable from its session's messages while
+/// attached.
+struct UploadRegistration {
+    inner: Rc<UploadInner>,
+    session: Option<Weak<SessionUploads>>,
+}
+
+impl std::fmt::Debug for UploadRegistration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadRegistration").field("name", &self.inner.name.borrow()).finish()
+    }
+}
+
+impl Widget for UploadRegistration {
+    fn build(&mut self, ctx: &mut Scope) {
+        let name = ctx.view_id().to_string();
+        *self.inner.name.borrow_mut() = name.clone();
+        let holder = ctx.holder_id();
+        let Some(session) = SESSIONS.with_borrow(|sessions| sessions.get(&holder).and_then(Weak::upgrade)) else {
+            return;
+        };
+        session.uploads.borrow_mut().insert(name, self.inner.clone());
+        self.session = Some(Rc::downgrade(&session));
+    }
+
+    fn detach(&mut self, ctx: &mut Scope) {
+        if let Some(session) = self.session.take().and_then(|session| session.upgrade()) {
+            session.remove(&self.inner);
+        }
+        self.inner.files.borrow_mut().clear();
+        self.detach_children(ctx);
+    }
+}
+
+/// The uploads of one session, by name, and the entries its client is
+/// sending.
+pub(crate) struct SessionUploads {
+    holder: HolderId,
+    uploads: RefCell<HashMap<String, Rc<UploadInner>>>,
+    entries: RefCell<HashMap<u64, Rc<UploadInner>>>,
+}
+
+thread_local! {
+    /// The sessions on this thread, by the holder their widgets run in.
+    static SESSIONS: RefCell<HashMap<HolderId, Weak<SessionUploads>>> = RefCell::new(HashMap::new());
+}
+
+impl SessionUploads {
+    /// Uploads created by widgets of `holder` register with the new session.
+    pub(crate) fn new(holder: HolderId) -> Rc<Self> {
+        let uploads = Rc::new(Self {
+            holder,
+            uploads: RefCell::new(HashMap::new()),
+            entries: RefCell::new(HashMap::new()),
+        });
+        SESSIONS.with_borrow_mut(|sessions| sessions.insert(holder, Rc::downgrade(&uploads)));
+        uploads
+    }
+
+    pub(crate) fn start(&self, upload: &str, entry: u64, name: String, mime: String, size: u64) -> Result<(), String> {
+        let Some(inner) = self.uploads.borrow().get(upload).cloned() else {
+            return Err(format!("no upload {upload:?}"));
+        };
+        if self.entries.borrow().contains_key(&entry) {
+            return Err(format!("entry {entry} already started"));
+        }
+        inner.start(entry, name, mime, size)?;
+        // An empty file is complete as soon as it is accepted.
+        if size > 0 {
+            self.entries.borrow_mut().insert(entry, inner);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn chunk(&self, entry: u64, offset: u64, data: &[u8]) -> Result<u64, String> {
+        let Some(inner) = self.entries.borrow().get(&entry).cloned() else {
+            return Err("upload cancelled".to_owned());
+        };
+        let result = inner.chunk(entry, offset, data);
+        let finished = match &result {
+            Ok(received) => inner
+                .entries
+                .get_untracked()
+                .iter()
+                .any(|held| held.id == entry && held.size == *received),
+            Err(_) => true,
+        };
+        if finished {
+            self.entries.borrow_mut().remove(&entry);
+        }
+        result
+    }
+
+    /// Fails every entry still receiving; its client is gone.
+    pub(crate) fn abort_unfinished(&self) {
+        for inner in self.entries.borrow_mut().drain().map(|(_, inner)| inner) {
+            inner.abort_unfinished();
+        }
+    }
+
+    fn remove(&self, inner: &Rc<UploadInner>) {
+        self.uploads.borrow_mut().retain(|_, held| !Rc::ptr_eq(held, inner));
+        self.entries.borrow_mut().retain(|_, held| !Rc::ptr_eq(held, inner));
+    }
+}
+
+impl Drop for SessionUploads {
+    fn drop(&mut self) {
+        SESSIONS.with_borrow_mut(|sessions| sessions.remove(&self.holder));
+        for inner in self.uploads.get_mut().values() {
+            inner.files.borrow_mut().clear();
+        }
+    }
+}
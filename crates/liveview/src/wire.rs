@@ -0,0 +1,357 @@
+//! Socket framing for [`LiveViewMessage`]s.
+//!
+//! Messages travel as JSON text frames unless the client's `hello` (or
+//! `resume`) lists binary [`LiveViewCodec`]s. Then `mount` and `patch`, which
+//! carry nearly all of the bytes, go out as binary frames holding a
+//! [`CommandEncoder`] batch, optionally deflated. Everything else stays
+//! JSON, and so do client messages except for upload chunks; see
+//! [`encode_client_frame`].
+//!
+//! A binary frame is one flags byte followed by the body. Bit 0 of the flags
+//! marks a raw-deflated body. The body is the postcard encoding of a header
+//! (`Mount { token, seq, adopt }` or `Patch { seq, reply }`) followed by the command
+//! batch.
+//! String interning spans the whole connection, so a [`FrameEncoder`] and its
+//! [`FrameDecoder`] are created per socket. A resumed session starts both
+//! afresh, and its replayed patches are re-encoded.
+
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+use glory_core::renderer::command_wire::{CommandDecoder, CommandEncoder, CommandWireError};
+use serde::{Deserialize, Serialize};
+
+use crate::LiveViewMessage;
+
+/// Set in a binary frame's flags byte when the body is raw-deflated.
+pub const FRAME_DEFLATED: u8 = 0b1;
+
+/// Kind byte of a binary frame from the client carrying an upload chunk. The
+/// entry and offset follow as little-endian `u64`s, then the data.
+pub const CLIENT_UPLOAD_CHUNK: u8 = 1;
+
+/// Smallest body worth deflating.
+const DEFLATE_MIN_BYTES: usize = 1024;
+
+/// Encodings a client can decode, listed in its `hello` or `resume`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveViewCodec {
+    Json,
+    /// Binary `mount`/`patch` frames with interned command batches.
+    Postcard,
+    /// Raw deflate of large binary bodies.
+    Deflate,
+    /// A codec this server does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+/// One WebSocket frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LiveViewFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryHeader<'a> {
+    Mount {
+        #[serde(borrow)]
+        token: Option<Cow<'a, str>>,
+        seq: u64,
+        adopt: bool,
+    },
+    Patch {
+        seq: u64,
+        reply: bool,
+    },
+}
+
+/// Encodes the messages of one connection.
+#[derive(Debug, Default)]
+pub struct FrameEncoder {
+    commands: Option<CommandEncoder>,
+    deflate: bool,
+}
+
+impl FrameEncoder {
+    /// JSON text frames only.
+    pub fn json() -> Self {
+        Self::default()
+    }
+
+    /// The best encoding among the client's `codecs`.
+    pub fn negotiate(codecs: &[LiveViewCodec]) -> Self {
+        let binary = codecs.contains(&LiveViewCodec::Postcard);
+        Self {
+            commands: binary.then(CommandEncoder::new),
+            deflate: binary && codecs.contains(&LiveViewCodec::Deflate),
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.commands.is_some()
+    }
+
+    pub fn encode(&mut self, message: &LiveViewMessage) -> LiveViewFrame {
+        let Some(commands) = &mut self.commands else {
+            return LiveViewFrame::Text(message.to_json().expect("liveview messages serialize"));
+        };
+        let (header, batch) = match message {
+            LiveViewMessage::Mount { token, seq, commands, adopt } => (
+                BinaryHeader::Mount {
+                    token: token.as_deref().map(Cow::Borrowed),
+                    seq: *seq,
+                    adopt: *adopt,
+                },
+                commands,
+            ),
+            LiveViewMessage::Patch { seq, commands, reply } => (BinaryHeader::Patch { seq: *seq, reply: *reply }, commands),
+            _ => return LiveViewFrame::Text(message.to_json().expect("liveview messages serialize")),
+        };
+        let mut body = postcard::to_allocvec(&header).expect("frame headers serialize");
+        commands.encode_into(batch, &mut body);
+
+        if self.deflate
+            && body.len() >= DEFLATE_MIN_BYTES
+            && let Some(deflated) = deflate(&body)
+            && deflated.len() < body.len()
+        {
+            let mut frame = Vec::with_capacity(deflated.len() + 1);
+            frame.push(FRAME_DEFLATED);
+            frame.extend(deflated);
+            return LiveViewFrame::Binary(frame);
+        }
+        let mut frame = Vec::with_capacity(body.len() + 1);
+        frame.push(0);
+        frame.extend(body);
+        LiveViewFrame::Binary(frame)
+    }
+}
+
+fn deflate(body: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(body).ok()?;
+    encoder.finish().ok()
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Json(serde_json::Error),
+    Header(postcard::Error),
+    Commands(CommandWireError),
+    Inflate(std::io::Error),
+    Empty,
+    /// A client frame shorter than its kind requires.
+    Truncated,
+    /// A client frame of a kind this server does not know.
+    UnknownKind(u8),
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid liveview message: {e}"),
+            Self::Header(e) => write!(f, "invalid binary frame header: {e}"),
+            Self::Commands(e) => write!(f, "invalid binary frame: {e}"),
+            Self::Inflate(e) => write!(f, "invalid deflated frame: {e}"),
+            Self::Empty => f.write_str("empty binary frame"),
+            Self::Truncated => f.write_str("truncated binary frame"),
+            Self::UnknownKind(kind) => write!(f, "unknown binary frame kind {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Decodes the frames of one connection, for Rust clients and tests. The
+/// browser client does the same in `LIVEVIEW_CLIENT_JS`.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    commands: CommandDecoder,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(&mut self, frame: &LiveViewFrame) -> Result<LiveViewMessage, FrameError> {
+        let bytes = match frame {
+            LiveViewFrame::Text(text) => return LiveViewMessage::from_json(text).map_err(FrameError::Json),
+            LiveViewFrame::Binary(bytes) => bytes,
+        };
+        let (flags, body) = bytes.split_first().ok_or(FrameError::Empty)?;
+        let inflated;
+        let body = if flags & FRAME_DEFLATED != 0 {
+            let mut buffer = Vec::new();
+            flate2::read::DeflateDecoder::new(body)
+                .read_to_end(&mut buffer)
+                .map_err(FrameError::Inflate)?;
+            inflated = buffer;
+            &inflated[..]
+        } else {
+            body
+        };
+        let (header, batch) = postcard::take_from_bytes::<BinaryHeader<'_>>(body).map_err(FrameError::Header)?;
+        let commands = self.commands.decode(batch).map_err(FrameError::Commands)?;
+        Ok(match header {
+            BinaryHeader::Mount { token, seq, adopt } => LiveViewMessage::Mount {
+                token: token.map(Cow::into_owned),
+                seq,
+                commands,
+                adopt,
+            },
+            BinaryHeader::Patch { seq, reply } => LiveViewMessage::Patch { seq, commands, reply },
+        })
+    }
+}
+
+/// Encodes a client message: upload chunks as binary frames, everything
+/// else as JSON. For Rust clients and tests; the browser client does the
+/// same in `LIVEVIEW_CLIENT_JS`.
+pub fn encode_client_frame(message: &LiveViewMessage) -> LiveViewFrame {
+    let LiveViewMessage::UploadChunk { entry, offset, data } = message else {
+        return LiveViewFrame::Text(message.to_json().expect("liveview messages serialize"));
+    };
+    let mut frame = Vec::with_capacity(17 + data.len());
+    frame.push(CLIENT_UPLOAD_CHUNK);
+    frame.extend(entry.to_le_bytes());
+    frame.extend(offset.to_le_bytes());
+    frame.extend(data);
+    LiveViewFrame::Binary(frame)
+}
+
+/// Decodes a binary frame a client sent.
+pub fn decode_client_frame(bytes: &[u8]) -> Result<LiveViewMessage, FrameError> {
+    let (kind, body) = bytes.split_first().ok_or(FrameError::Empty)?;
+    if *kind != CLIENT_UPLOAD_CHUNK {
+        return Err(FrameError::UnknownKind(*kind));
+    }
+    let (entry, body) = body.split_first_chunk::<8>().ok_or(FrameError::Truncated)?;
+    let (offset, data) = body.split_first_chunk::<8>().ok_or(FrameError::Truncated)?;
+    Ok(LiveViewMessage::UploadChunk {
+        entry: u64::from_le_bytes(*entry),
+        offset: u64::from_le_bytes(*offset),
+        data: data.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glory_core::renderer::{Command, CommandInsertPosition};
+
+    use super::*;
+
+    fn rows(from: u64, to: u64) -> Vec<Command> {
+        (from..to)
+            .flat_map(|id| {
+                [
+                    Command::Create {
+                        id,
+                        name: "td".into(),
+                        is_void: false,
+                    },
+                    Command::SetText {
+                        id,
+                        value: format!("cell {id}"),
+                    },
+                    Command::Insert {
+                        parent: 0,
+                        child: id,
+                        position: CommandInsertPosition::Tail,
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn negotiation_picks_the_best_common_codec() {
+        assert!(!FrameEncoder::negotiate(&[]).is_binary());
+        assert!(!FrameEncoder::negotiate(&[LiveViewCodec::Json, LiveViewCodec::Deflate]).is_binary());
+        let binary = FrameEncoder::negotiate(&[LiveViewCodec::Unknown, LiveViewCodec::Postcard]);
+        assert!(binary.is_binary() && !binary.deflate);
+        assert!(FrameEncoder::negotiate(&[LiveViewCodec::Postcard, LiveViewCodec::Deflate]).deflate);
+
+        let codecs: Vec<LiveViewCodec> = serde_json::from_str(r#"["postcard","brotli","deflate"]"#).unwrap();
+        assert_eq!(codecs, [LiveViewCodec::Postcard, LiveViewCodec::Unknown, LiveViewCodec::Deflate]);
+    }
+
+    #[test]
+    fn binary_frames_round_trip_and_only_carry_commands() {
+        let mut encoder = FrameEncoder::negotiate(&[LiveViewCodec::Postcard]);
+        let mut decoder = FrameDecoder::new();
+        let mount = LiveViewMessage::Mount {
+            token: Some("abc".into()),
+            seq: 3,
+            commands: rows(1, 20),
+            adopt: true,
+        };
+        let frame = encoder.encode(&mount);
+        let LiveViewFrame::Binary(bytes) = &frame else {
+            panic!("expected a binary frame");
+        };
+        assert_eq!(bytes[0], 0);
+        assert!(bytes.len() * 2 < mount.to_json().unwrap().len());
+        assert_eq!(decoder.decode(&frame).unwrap(), mount);
+
+        let patch = LiveViewMessage::Patch {
+            seq: 4,
+            commands: rows(20, 21),
+            reply: true,
+        };
+        assert_eq!(decoder.decode(&encoder.encode(&patch)).unwrap(), patch);
+
+        let pong = encoder.encode(&LiveViewMessage::Pong);
+        assert_eq!(pong, LiveViewFrame::Text(r#"{"type":"pong"}"#.into()));
+        assert_eq!(decoder.decode(&pong).unwrap(), LiveViewMessage::Pong);
+    }
+
+    #[test]
+    fn large_bodies_are_deflated() {
+        let mut encoder = FrameEncoder::negotiate(&[LiveViewCodec::Postcard, LiveViewCodec::Deflate]);
+        let mut decoder = FrameDecoder::new();
+
+        let small = LiveViewMessage::patch(1, rows(1, 2));
+        let LiveViewFrame::Binary(bytes) = encoder.encode(&small) else {
+            panic!("expected a binary frame");
+        };
+        assert_eq!(bytes[0], 0, "small bodies are sent as is");
+        decoder.decode(&LiveViewFrame::Binary(bytes)).unwrap();
+
+        let large = LiveViewMessage::patch(2, rows(2, 500));
+        let frame = encoder.encode(&large);
+        let LiveViewFrame::Binary(bytes) = &frame else {
+            panic!("expected a binary frame");
+        };
+        assert_eq!(bytes[0], FRAME_DEFLATED);
+        assert_eq!(decoder.decode(&frame).unwrap(), large);
+    }
+
+    #[test]
+    fn json_encoder_sends_text() {
+        let mut encoder = FrameEncoder::json();
+        let patch = LiveViewMessage::patch(1, rows(1, 2));
+        assert_eq!(encoder.encode(&patch), LiveViewFrame::Text(patch.to_json().unwrap()));
+    }
+
+    #[test]
+    fn upload_chunks_go_up_as_binary_frames() {
+        let chunk = LiveViewMessage::UploadChunk {
+            entry: 7,
+            offset: 65536,
+            data: b"abc".to_vec(),
+        };
+        let LiveViewFrame::Binary(bytes) = encode_client_frame(&chunk) else {
+            panic!("expected a binary frame");
+        };
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(decode_client_frame(&bytes).unwrap(), chunk);
+
+        assert!(matches!(decode_client_frame(&bytes[..12]), Err(FrameError::Truncated)));
+        assert!(matches!(decode_client_frame(&[9, 0]), Err(FrameError::UnknownKind(9))));
+        assert!(matches!(encode_client_frame(&LiveViewMessage::Ping), LiveViewFrame::Text(_)));
+    }
+}
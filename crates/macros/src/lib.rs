@@ -400,6 +400,40 @@ fn stable_hash(bytes: &[u8]) -> u64 {
 /// binary download chunks). The server build keeps the original function so an
 /// adapter/resource route can pipe it through `into_streaming_response()`; the
 /// wasm client stub fetches the body and decodes it back into a stream.
+/// `#[server(version = 2)]` serves the function at `/__glory/fn/<name>@v2`.
+/// Clients built against a version the server no longer registers get
+/// an HTTP 410 whose `requires_reload()` tells them to reload. To keep
+/// an old version working, declare a server-only shim for it:
+///
+/// ```ignore
+/// #[server(version = 2)]
+/// async fn add_todo(title: String, tags: Vec<String>) -> Result<Todo, ServerFnError> { .. }
+///
+/// #[server(endpoint = "add_todo", version = 1, shim)]
+/// async fn add_todo_v1(title: String) -> Result<Todo, ServerFnError> {
+///     add_todo(title, Vec::new()).await
+/// }
+/// ```
+///
+/// Arguments travel as a struct named after the parameters, so with JSON or
+/// CBOR adding an `Option` parameter keeps older clients (which omit it)
+/// working without a version bump. Postcard encodes the struct positionally,
+/// so an `encoding = "postcard"` function needs a version bump for any
+/// parameter change.
+/// `#[server(websocket)]` declares a typed bidirectional channel instead:
+///
+/// ```ignore
+/// #[server(websocket)]
+/// async fn chat(rx: impl Stream<Item = ChatIn>) -> impl Stream<Item = ChatOut> {
+///     rx.map(|message| ChatOut::echo(message))
+/// }
+/// ```
+///
+/// The server build registers a `WebSocketFnEntry` at `/__glory/ws/<name>`,
+/// which the adapter mounts serve next to the request/response endpoints. The
+/// wasm build replaces the function with a synchronous
+/// `fn chat() -> (ReactiveWebSocket<ChatOut>, ChannelSender<ChatIn>)` that
+/// connects and transparently resumes the session after a reconnect.
 #[proc_macro_attribute]
 pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut item_fn = parse_macro_input!(item as ItemFn);
@@ -409,11 +443,27 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut encoding = "json".to_owned();
     let mut middlewares = Vec::<Expr>::new();
     let mut stream = false;
+    let mut websocket = false;
+    let mut version = 1u32;
+    let mut shim = false;
     if !attr.is_empty() {
         let parser = syn::meta::parser(|meta| {
             if meta.path.is_ident("stream") {
                 stream = true;
                 Ok(())
+            } else if meta.path.is_ident("websocket") {
+                websocket = true;
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                version = value.base10_parse()?;
+                if version == 0 {
+                    return Err(meta.error("#[server] versions start at 1"));
+                }
+                Ok(())
+            } else if meta.path.is_ident("shim") {
+                shim = true;
+                Ok(())
             } else if meta.path.is_ident("endpoint") {
                 let value: LitStr = meta.value()?.parse()?;
                 endpoint = Some(value.value());
@@ -444,7 +494,7 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
                 Ok(())
             } else {
                 Err(meta.error(
-                    "unsupported #[server] option; expected `stream`, `endpoint = \"...\"`, `method = \"GET\"`, `encoding = \"cbor\"`, `encoding = \"postcard\"`, or `middleware = path`",
+                    "unsupported #[server] option; expected `stream`, `websocket`, `shim`, `version = N`, `endpoint = \"...\"`, `method = \"GET\"`, `encoding = \"cbor\"`, `encoding = \"postcard\"`, or `middleware = path`",
                 ))
             }
         });
@@ -478,6 +528,22 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
             .into();
     }
 
+    if websocket {
+        if stream || shim || version != 1 || method != "POST" || encoding != "json" {
+            return syn::Error::new(
+                item_fn.sig.span(),
+                "#[server(websocket)] cannot be combined with `stream`, `shim`, `version`, `method`, or `encoding`",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let endpoint = endpoint.unwrap_or_else(|| item_fn.sig.ident.to_string());
+        return match expand_server_channel(&item_fn, &endpoint, &middlewares) {
+            Ok(tokens) => tokens.into(),
+            Err(err) => err.to_compile_error().into(),
+        };
+    }
+
     let mut arg_idents = Vec::new();
     let mut arg_types = Vec::new();
     for input in &item_fn.sig.inputs {
@@ -505,13 +571,22 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
     let sig = &item_fn.sig;
     let name = &sig.ident;
     let endpoint = endpoint.unwrap_or_else(|| name.to_string());
-    let url = format!("/__glory/fn/{endpoint}");
+    let url = if version == 1 {
+        format!("/__glory/fn/{endpoint}")
+    } else {
+        format!("/__glory/fn/{endpoint}@v{version}")
+    };
     let encoding = match encoding.as_str() {
         "json" => quote! { glory_serverfn::ServerFnEncoding::Json },
         "cbor" => quote! { glory_serverfn::ServerFnEncoding::Cbor },
         "postcard" => quote! { glory_serverfn::ServerFnEncoding::Postcard },
         _ => unreachable!("validated server fn encoding"),
     };
+    if stream && (shim || version != 1) {
+        return syn::Error::new(sig.span(), "#[server(stream)] functions are not versioned")
+            .to_compile_error()
+            .into();
+    }
     if stream {
         if method == "GET" {
             return syn::Error::new(sig.span(), "#[server(stream)] currently supports only POST requests")
@@ -541,27 +616,72 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
         return expanded.into();
     }
 
-    let decode_args = if arg_idents.len() == 1 {
-        let arg_ident = &arg_idents[0];
-        let arg_type = &arg_types[0];
+    // Arguments cross the wire as a struct named after the parameters. Serde
+    // structs also deserialize from a sequence, so clients still sending the
+    // old positional tuple keep working; `Option` parameters default to
+    // `None` when a client omits them, for self-describing encodings only:
+    // postcard is positional and fails on a missing field.
+    let args_struct = format_ident!("__GloryServerFnArgs_{}", name);
+    let field_attrs = arg_types.iter().map(|ty| is_option_type(ty).then(|| quote! { #[serde(default)] }));
+    let (args_def, decode_args, client_args) = if arg_idents.is_empty() {
+        (quote! {}, quote! {}, quote! { &() })
+    } else {
+        let decode_struct = quote! {
+            let #args_struct { #(#arg_idents),* } = glory_serverfn::decode_args_with(__input_encoding, &__body)?;
+        };
+        let decode_args = if arg_idents.len() == 1 {
+            let arg_ident = &arg_idents[0];
+            let arg_type = &arg_types[0];
+            quote! {
+                let #arg_ident: #arg_type = if glory_serverfn::is_form_request() {
+                    glory_serverfn::decode_form(&__body)?
+                } else {
+                    #decode_struct
+                    #arg_ident
+                };
+            }
+        } else {
+            decode_struct
+        };
+        let args_def = quote! {
+            #[allow(non_camel_case_types)]
+            #[cfg_attr(not(target_arch = "wasm32"), derive(glory_serverfn::serde::Deserialize))]
+            #[cfg_attr(target_arch = "wasm32", derive(glory_serverfn::serde::Serialize))]
+            #[serde(crate = "glory_serverfn::serde")]
+            struct #args_struct {
+                #( #field_attrs #arg_idents: #arg_types, )*
+            }
+        };
+        (args_def, decode_args, quote! { &#args_struct { #(#arg_idents),* } })
+    };
+
+    let client_stub = if shim {
+        // Shims only keep an old wire version dispatchable; clients are
+        // generated from the current version.
+        quote! {}
+    } else {
         quote! {
-            if glory_serverfn::is_form_request() {
-                let #arg_ident: #arg_type = glory_serverfn::decode_form(&__body)?;
-                ( #arg_ident, )
-            } else {
-                glory_serverfn::decode_args_with(__input_encoding, &__body)?
+            #[cfg(target_arch = "wasm32")]
+            #vis #sig {
+                glory_serverfn::call_remote_with_method_and_encoding(#method, #url, #client_args, #encoding).await
             }
         }
-    } else {
+    };
+    let args_def = if shim {
         quote! {
-            glory_serverfn::decode_args_with(__input_encoding, &__body)?
+            #[cfg(not(target_arch = "wasm32"))]
+            #args_def
         }
+    } else {
+        args_def
     };
 
     let expanded = quote! {
         #[cfg(not(target_arch = "wasm32"))]
         #item_fn
 
+        #args_def
+
         #[cfg(not(target_arch = "wasm32"))]
         glory_serverfn::inventory::submit! {
             glory_serverfn::ServerFnEntry {
@@ -569,17 +689,14 @@ pub fn server(attr: TokenStream, item: TokenStream) -> TokenStream {
                 method: #method,
                 middlewares: &[ #(#middlewares),* ],
                 handler: |__body: ::std::vec::Vec<u8>, __input_encoding: glory_serverfn::ServerFnEncoding, __output_encoding: glory_serverfn::ServerFnEncoding| ::std::boxed::Box::pin(async move {
-                    let ( #(#arg_idents,)* ): ( #(#arg_types,)* ) = #decode_args;
+                    #decode_args
                     let __output = #name( #(#arg_idents),* ).await?;
                     glory_serverfn::encode_ok_with(__output_encoding, &__output)
                 }),
             }
         }
 
-        #[cfg(target_arch = "wasm32")]
-        #vis #sig {
-            glory_serverfn::call_remote_with_method_and_encoding(#method, #url, &( #(#arg_idents,)* ), #encoding).await
-        }
+        #client_stub
     };
     expanded.into()
 }
@@ -1415,6 +1532,71 @@ fn query_parse_expr(query: &QueryParam, ty: &Type, krate: &TokenStream2) -> Toke
     }
 }
 
+/// Expands `#[server(websocket)] async fn name(rx: impl Stream<Item = In>) ->
+/// impl Stream<Item = Out>`: the server build registers a channel entry, the
+/// wasm build becomes a connect stub returning the socket and a typed sender.
+fn expand_server_channel(item_fn: &ItemFn, endpoint: &str, middlewares: &[Expr]) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &item_fn.sig;
+    let mut inputs = sig.inputs.iter();
+    let (Some(FnArg::Typed(input)), None) = (inputs.next(), inputs.next()) else {
+        return Err(syn::Error::new(
+            sig.inputs.span(),
+            "#[server(websocket)] functions take exactly one `impl Stream<Item = In>` argument",
+        ));
+    };
+    let in_ty = stream_item_type(&input.ty)
+        .ok_or_else(|| syn::Error::new(input.ty.span(), "#[server(websocket)] argument must be `impl Stream<Item = In>`"))?;
+    let out_ty = match &sig.output {
+        syn::ReturnType::Type(_, ty) => stream_item_type(ty),
+        syn::ReturnType::Default => None,
+    }
+    .ok_or_else(|| syn::Error::new(sig.output.span(), "#[server(websocket)] functions must return `impl Stream<Item = Out>`"))?;
+
+    let vis = &item_fn.vis;
+    let name = &sig.ident;
+    let url = format!("/__glory/ws/{endpoint}");
+    Ok(quote! {
+        #[cfg(not(target_arch = "wasm32"))]
+        #item_fn
+
+        #[cfg(not(target_arch = "wasm32"))]
+        glory_serverfn::inventory::submit! {
+            glory_serverfn::WebSocketFnEntry {
+                path: #url,
+                middlewares: &[ #(#middlewares),* ],
+                handler: |__frames: glory_serverfn::BoxedChannelStream| {
+                    glory_serverfn::serve_channel::<#in_ty, #out_ty, _, _, _>(__frames, |__rx| #name(__rx))
+                },
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        #vis fn #name() -> (glory_serverfn::ReactiveWebSocket<#out_ty>, glory_serverfn::ChannelSender<#in_ty>) {
+            glory_serverfn::use_server_channel::<#in_ty, #out_ty>(#url)
+        }
+    })
+}
+
+/// `Item` of an `impl Stream<Item = T>` type (any path ending in `Stream`).
+fn stream_item_type(ty: &Type) -> Option<&Type> {
+    let Type::ImplTrait(impl_trait) = ty else {
+        return None;
+    };
+    impl_trait.bounds.iter().find_map(|bound| {
+        let syn::TypeParamBound::Trait(bound) = bound else {
+            return None;
+        };
+        let segment = bound.path.segments.last().filter(|segment| segment.ident == "Stream")?;
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        args.args.iter().find_map(|arg| match arg {
+            GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
+}
+
 /// True when a `#[server(stream)]` function's `Ok` type is a binary chunk
 /// stream (`StreamingBytes` or its `ByteStream` alias), as opposed to an NDJSON
 /// item stream. Recognizes the bare type name regardless of module path.
@@ -119,5 +119,8 @@ impl Enabler for MemoryAviator {
             Ok(()) => self.push(url),
             Err(err) => glory_core::warn!("MemoryAviator failed to locate initial URL: {err}"),
         }
+        // Like `BrowserAviator`, stay reachable from the truck so host
+        // callbacks (e.g. desktop deep links) can navigate.
+        truck.borrow_mut().inject(self);
     }
 }
@@ -3,15 +3,28 @@ use std::rc::Rc;
 
 use educe::Educe;
 use glory_core::holder::Enabler;
+use glory_core::web::ServerResponseOptions;
 use glory_core::Truck;
 
 use crate::{Aviator, Handler, Locator, NavigationError, Routable, Router};
 
-/// Rewrites an incoming URL before route matching, applying declarative
-/// redirects such as those declared by `#[redirect]` on a [`Routable`] type.
+/// Decides how an incoming URL is handled before route matching, applying
+/// declarative redirects such as those declared by `#[redirect]` on a
+/// [`Routable`] type.
 ///
-/// Returns the rewritten URL, or `None` to leave the URL untouched.
-type RedirectResolver = Rc<dyn Fn(&str) -> Option<String>>;
+/// Returns `None` to leave the URL untouched.
+type RedirectResolver = Rc<dyn Fn(&str) -> Option<Resolution>>;
+
+/// Outcome of a [`RedirectResolver`]; every variant carries the URL route
+/// matching runs against.
+enum Resolution {
+    /// Answer with a `302` to the target (and render it as the body).
+    Redirect(String),
+    /// Render the `#[not_found]` fallback with a `404` status.
+    NotFound(String),
+    /// Canonical form of a matched route; rewritten silently.
+    Rewrite(String),
+}
 
 #[derive(Educe, Clone)]
 #[educe(Debug)]
@@ -39,17 +52,25 @@ impl ServerAviator {
     /// Install a [`Routable`] type as the server-side declarative redirect
     /// resolver.
     ///
-    /// Before each `locate`, the incoming URL is resolved with
-    /// [`Routable::resolve_url`] (which applies `#[redirect]` rules first and a
-    /// `#[not_found]` fallback last). When that yields a route whose
-    /// [`Routable::to_url`] differs from the requested URL, navigation proceeds
-    /// against the rewritten target. This makes routes that configure
-    /// `#[redirect]` behave the same during SSR as they do in the browser.
+    /// Before each `locate`, the incoming URL is checked against
+    /// [`Routable::redirect`] first and the `#[not_found]` fallback last. A
+    /// redirect answers with `302 Found` and a `Location` header through the
+    /// render's [`ServerResponseOptions`], a not-found fallback with `404`;
+    /// in both cases navigation proceeds against the target so the response
+    /// body matches what the browser would render.
     pub fn with_redirects<R>(mut self) -> Self
     where
         R: Routable,
     {
-        self.redirect_resolver = Some(Rc::new(|url: &str| R::resolve_url(url).map(|route| route.to_url())));
+        self.redirect_resolver = Some(Rc::new(|url: &str| {
+            if let Some(route) = R::redirect(url) {
+                return Some(Resolution::Redirect(route.to_url()));
+            }
+            match R::from_url(url) {
+                Some(route) => Some(Resolution::Rewrite(route.to_url())),
+                None => R::not_found(url).map(|route| Resolution::NotFound(route.to_url())),
+            }
+        }));
         self
     }
 
@@ -59,22 +80,46 @@ impl ServerAviator {
     where
         F: Fn(&str) -> Option<String> + 'static,
     {
-        self.redirect_resolver = Some(Rc::new(resolver));
+        self.redirect_resolver = Some(Rc::new(move |url: &str| resolver(url).map(Resolution::Rewrite)));
         self
     }
 
-    /// Apply the configured redirect resolver (if any) to `raw_url`, returning
-    /// the URL that route matching should run against.
+    /// Install a custom redirect hook. A returned URL different from the
+    /// incoming one answers with `302 Found` and is navigated to; returning
+    /// `None` keeps the original URL. Use
+    /// [`with_redirect_resolver`](Self::with_redirect_resolver) to rewrite
+    /// silently instead.
+    pub fn with_redirect_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + 'static,
+    {
+        self.redirect_resolver = Some(Rc::new(move |url: &str| hook(url).map(Resolution::Redirect)));
+        self
+    }
+
+    /// Apply the configured redirect resolver (if any) to `raw_url`, recording
+    /// redirects and not-found statuses on the response, and return the URL
+    /// that route matching should run against.
     fn resolve_redirect(&self, raw_url: String) -> String {
-        match &self.redirect_resolver {
-            Some(resolver) => match resolver(&raw_url) {
-                Some(target) if target != raw_url => {
-                    glory_core::info!("[redirect]: {raw_url} -> {target}");
-                    target
-                }
-                _ => raw_url,
-            },
-            None => raw_url,
+        let Some(resolution) = self.redirect_resolver.as_ref().and_then(|resolver| resolver(&raw_url)) else {
+            return raw_url;
+        };
+        let response = ServerResponseOptions::from_truck(&self.truck.borrow()).unwrap_or_default();
+        match resolution {
+            Resolution::Redirect(target) if target != raw_url => {
+                glory_core::info!("[redirect]: {raw_url} -> {target}");
+                let _ = response.redirect(target.clone());
+                target
+            }
+            Resolution::NotFound(target) => {
+                let _ = response.set_status(404);
+                target
+            }
+            Resolution::Rewrite(target) if target != raw_url => {
+                glory_core::info!("[rewrite]: {raw_url} -> {target}");
+                target
+            }
+            Resolution::Redirect(target) | Resolution::Rewrite(target) => target,
         }
     }
 
@@ -171,6 +216,50 @@ mod tests {
         assert!(aviator.truck.contains_stuff_key("page", "user"), "redirect target route should match the user goal");
     }
 
+    #[test]
+    fn ssr_redirect_answers_found_and_not_found_statuses() {
+        let aviator = build_aviator().with_redirects::<AppRoute>();
+        let response = ServerResponseOptions::default();
+        aviator.truck.borrow_mut().inject(response.clone());
+        aviator.locate("/u/42".to_owned()).unwrap();
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.redirect_location().as_deref(), Some("/users/42"));
+
+        let aviator = build_aviator().with_redirects::<AppRoute>();
+        let response = ServerResponseOptions::default();
+        aviator.truck.borrow_mut().inject(response.clone());
+        aviator.locate("/missing".to_owned()).unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(response.redirect_location(), None);
+
+        let aviator = build_aviator().with_redirects::<AppRoute>();
+        let response = ServerResponseOptions::default();
+        aviator.truck.borrow_mut().inject(response.clone());
+        aviator.locate("/users/7".to_owned()).unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[test]
+    fn ssr_custom_resolver_rewrites_and_hook_redirects() {
+        let legacy = |url: &str| url.strip_prefix("/u/").map(|id| format!("/users/{id}"));
+
+        let aviator = build_aviator().with_redirect_resolver(legacy);
+        let response = ServerResponseOptions::default();
+        aviator.truck.borrow_mut().inject(response.clone());
+        aviator.locate("/u/42".to_owned()).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.redirect_location(), None);
+        assert!(aviator.truck.contains_stuff_key("page", "user"));
+
+        let aviator = build_aviator().with_redirect_hook(legacy);
+        let response = ServerResponseOptions::default();
+        aviator.truck.borrow_mut().inject(response.clone());
+        aviator.locate("/u/42".to_owned()).unwrap();
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.redirect_location().as_deref(), Some("/users/42"));
+        assert!(aviator.truck.contains_stuff_key("page", "user"));
+    }
+
     #[test]
     fn ssr_redirect_resolver_leaves_normal_url_untouched() {
         let aviator = build_aviator().with_redirects::<AppRoute>();
@@ -237,3 +237,21 @@ fn memory_aviator_goto_truncates_forward_history() {
     assert!(av.back().unwrap());
     assert_eq!(av.current().as_deref(), Some("/a"));
 }
+
+#[test]
+fn enabled_memory_aviator_is_reachable_from_the_truck() {
+    use glory_core::holder::Enabler;
+
+    let router = Router::new().push(Router::with_path("users/<id>").outlet("page@user", || UserPage));
+    let catcher: Rc<dyn Handler> = Rc::new(|truck: Rc<RefCell<Truck>>| truck.insert_stuff("page@404", NotFound));
+    let truck = Rc::new(RefCell::new(Truck::new()));
+    MemoryAviator::new(router, move |truck: Rc<RefCell<Truck>>| catcher.handle(truck)).enable(truck.clone());
+
+    // What a host callback (e.g. a desktop deep link) does to navigate.
+    let av = truck.borrow().obtain::<MemoryAviator>().unwrap().clone();
+    av.goto("/users/7").unwrap();
+
+    assert!(truck.contains_stuff_key("page", "user"));
+    assert_eq!(current_path(&av), "/users/7");
+    assert_eq!(av.current().as_deref(), Some("/users/7"));
+}
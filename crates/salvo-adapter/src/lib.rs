@@ -1,40 +1,124 @@
 //! Salvo adapter for Glory SSR.
 
 use std::convert::Infallible;
+use std::sync::Arc;
 
 use futures::StreamExt;
-pub use glory_core::web::holders::{HtmlChunk, SalvoHandler, ServerHolder};
-use salvo::prelude::{Response, StatusCode};
+pub use glory_core::web::holders::{HtmlChunk, RenderPool, RenderStream, SalvoHandler, ServerHolder, apply_response_head};
+pub use glory_core::web::{CSP_HEADER, ContentSecurityPolicy, CspNonce, ResponseHead, ServerResponseOptions};
+pub use glory_serverfn::salvo_mount::request_context;
+pub use glory_serverfn::{IsrCache, IsrConfig, IsrRenderer, IsrResponse, IsrRoute, IsrStatus, RequestContext, revalidate_path, revalidate_tag};
+use salvo::async_trait;
+use salvo::http::header::{HeaderName, HeaderValue};
+use salvo::prelude::{Depot, FlowCtrl, Handler, Request, Response, StatusCode};
 
 pub fn render_to_string(holder: &ServerHolder) -> String {
     holder.render_string()
 }
 
+/// Renders the holder into a `text/html` response carrying the status,
+/// headers and redirect set through its [`ServerResponseOptions`].
 pub fn into_response(holder: ServerHolder) -> Response {
     let mut response = Response::new();
-    response.status_code(StatusCode::OK);
     let _ = response.add_header("content-type", "text/html", true);
     let _ = response.write_body(holder.render_string());
+    apply_response_head(&mut response, holder.response_options().head());
     response
 }
 
-/// Streams the holder's [`HtmlChunk`] sequence as a Salvo chunked stream body
-/// (via [`Response::stream`]) with a `text/html` content type. This mirrors the
-/// `Scribe for ServerHolder` impl in core under an `into_*` name aligned with
-/// the axum/actix adapters.
+/// Streams a holder's [`HtmlChunk`] sequence as a Salvo chunked stream body
+/// (via [`Response::stream`]) with a `text/html` content type, the same way
+/// [`SalvoHandler`] serves its pages.
 ///
-/// `ServerHolder` is `!Send`, but `render_stream()` materializes the whole
-/// [`HtmlChunk`] sequence eagerly (the `!Send` render work runs on this thread
-/// before the stream is constructed); the stream then only yields already-owned
-/// `String` chunks, so it is safe to hand to Salvo's stream body.
-///
-/// This wires up the streaming-body transport (the prerequisite pipeline for
-/// first-byte flush); true incremental parse-and-flush over the wire is bounded
-/// by the `!Send` runtime and is tracked separately as FS4.
-pub fn into_streaming_response(holder: ServerHolder) -> Response {
+/// `ServerHolder` is `!Send`, so the holder is built by `factory` and
+/// rendered on the global [`RenderPool`]. The document head and shell reach
+/// the client before any Suspense resource resolves, each boundary's patch
+/// follows as soon as it is ready, and a client disconnect cancels the render.
+/// The response status and headers are whatever the tree set before the
+/// shell was flushed.
+pub async fn into_streaming_response<F>(factory: F) -> Response
+where
+    F: FnOnce() -> ServerHolder + Send + 'static,
+{
+    into_render_stream_response(RenderPool::global().render(factory)).await
+}
+
+/// Wraps an already started [`RenderStream`] (for example from a dedicated
+/// [`RenderPool`]) into a streaming `text/html` Salvo response.
+pub async fn into_render_stream_response(mut stream: RenderStream) -> Response {
     let mut response = Response::new();
-    response.status_code(StatusCode::OK);
     let _ = response.add_header("content-type", "text/html", true);
-    response.stream(holder.render_stream().map(|chunk| Result::<_, Infallible>::Ok(chunk.into_string())));
+    apply_response_head(&mut response, stream.head().await);
+    response.stream(stream.html().map(Result::<_, Infallible>::Ok));
     response
 }
+
+/// Writes an [`IsrResponse`] as a `text/html` Salvo response carrying the
+/// page's status and headers plus its `Cache-Control`, `Age` and cache-status
+/// headers.
+pub fn write_isr_response(res: &mut Response, isr: IsrResponse) {
+    let _ = res.add_header("content-type", "text/html; charset=utf-8", true);
+    apply_response_head(res, isr.head);
+    for (name, value) in isr.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            res.headers_mut().insert(name, value);
+        }
+    }
+    let _ = res.write_body(isr.body);
+}
+
+/// Salvo handler serving a route through incremental static regeneration.
+///
+/// ```ignore
+/// let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/blog/{slug}").ttl_secs(60)));
+/// Router::with_path("blog/{slug}").get(IsrHandler::new(cache, |ctx| {
+///     ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(App)
+/// }));
+/// ```
+#[derive(Clone)]
+pub struct IsrHandler {
+    cache: IsrCache,
+    render: IsrRenderer,
+}
+
+impl std::fmt::Debug for IsrHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IsrHandler").field("cache", &self.cache).finish_non_exhaustive()
+    }
+}
+
+impl IsrHandler {
+    /// `holder_factory` builds a fresh holder per render; it runs on a
+    /// blocking thread for misses and on a background thread for stale
+    /// regenerations.
+    pub fn new<H>(cache: IsrCache, holder_factory: H) -> Self
+    where
+        H: Fn(&RequestContext) -> ServerHolder + Send + Sync + 'static,
+    {
+        Self {
+            cache,
+            render: Arc::new(move |context| {
+                let holder = holder_factory(context);
+                let html = holder.render_string();
+                (holder.response_options().head(), html)
+            }),
+        }
+    }
+
+    pub fn cache(&self) -> &IsrCache {
+        &self.cache
+    }
+}
+
+#[async_trait]
+impl Handler for IsrHandler {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        match self.cache.serve(&request_context(req), &self.render).await {
+            Ok(isr) => write_isr_response(res, isr),
+            Err(err) => {
+                res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                let _ = res.write_body(err.to_string());
+            }
+        }
+    }
+}
@@ -36,10 +36,33 @@ fn into_response_sets_ok_status_and_html_content_type() {
     assert!(content_type.contains("text/html"), "content-type was {content_type:?}");
 }
 
+#[derive(Debug)]
+struct NotFound;
+
+impl Widget for NotFound {
+    fn build(&mut self, ctx: &mut Scope) {
+        let response = ctx.response_options();
+        response.set_status(404).unwrap();
+        response.set_cookie_header("a=1").unwrap();
+        response.set_cookie_header("b=2").unwrap();
+        div().text("missing").show_in(ctx);
+    }
+}
+
+#[test]
+fn responses_honor_response_options() {
+    let response = into_response(ServerHolder::new(GloryConfig::default(), "/").mount(NotFound));
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+    assert_eq!(response.headers().get_all("set-cookie").iter().count(), 2);
+
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(NotFound)));
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+    assert!(response.headers().get("content-type").unwrap().to_str().unwrap().contains("text/html"));
+}
+
 #[test]
 fn into_streaming_response_sets_ok_status_and_html_content_type() {
-    let holder = ServerHolder::new(GloryConfig::default(), "/").mount(Hello);
-    let response = into_streaming_response(holder);
+    let response = futures::executor::block_on(into_streaming_response(|| ServerHolder::new(GloryConfig::default(), "/").mount(Hello)));
     assert_eq!(response.status_code, Some(StatusCode::OK));
     let content_type = response
         .headers()
@@ -48,3 +71,33 @@ fn into_streaming_response_sets_ok_status_and_html_content_type() {
         .unwrap_or_default();
     assert!(content_type.contains("text/html"), "content-type was {content_type:?}");
 }
+
+#[test]
+fn isr_handler_caches_rendered_page_with_cache_headers() {
+    use glory_salvo::{IsrCache, IsrConfig, IsrHandler, IsrRoute};
+    use salvo::prelude::{Router, Service};
+    use salvo::test::{ResponseExt, TestClient};
+
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/salvo-isr").ttl_secs(60)));
+    let handler = IsrHandler::new(cache, |ctx| ServerHolder::new(GloryConfig::default(), ctx.uri.clone()).mount(Hello));
+    let service = Service::new(Router::with_path("salvo-isr").get(handler));
+
+    let statuses = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let mut statuses = Vec::new();
+            for _ in 0..2 {
+                let mut response = TestClient::get("http://127.0.0.1/salvo-isr").send(&service).await;
+                let cache_control = response.headers().get("cache-control").unwrap().to_str().unwrap().to_owned();
+                assert!(cache_control.contains("s-maxage=60"), "{cache_control}");
+                assert!(response.headers().contains_key("age"));
+                let status = response.headers().get("x-glory-cache").unwrap().to_str().unwrap().to_owned();
+                assert!(response.take_string().await.unwrap().contains("hello-salvo"));
+                statuses.push(status);
+            }
+            statuses
+        });
+    assert_eq!(statuses, ["MISS", "HIT"]);
+}
@@ -15,11 +15,14 @@
 //!
 //! # Wire format
 //!
-//! JSON remains the default wire format: arguments serialize as a tuple
-//! (`(a, b, c)`), responses as the `Ok` value, and errors as
-//! [`ServerFnError`]. When the `cbor` feature is enabled, adapter mounts can
-//! decode `Content-Type: application/cbor` / `application/postcard` request
-//! bodies and encode matching `Accept` responses. The client leg decodes the
+//! JSON remains the default wire format: arguments serialize as a struct
+//! named after the parameters (`{"a": .., "b": ..}`), responses as the `Ok`
+//! value, and errors as [`ServerFnError`]. Servers still accept the older
+//! positional tuple (`[a, b]`), and `Option` parameters may be omitted, so
+//! new optional parameters do not break clients built before them. When the
+//! `cbor` feature is enabled, adapter mounts can decode `Content-Type:
+//! application/cbor` / `application/postcard` request bodies and encode
+//! matching `Accept` responses. The client leg decodes the
 //! same enum, so `?` propagation works symmetrically on both sides.
 
 use serde::Serialize;
@@ -27,6 +30,8 @@ use serde::de::DeserializeOwned;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use inventory;
+/// Re-exported for the argument structs generated by `#[server]`.
+pub use serde;
 
 pub const JSON_CONTENT_TYPE: &str = "application/json";
 #[cfg(feature = "cbor")]
@@ -303,6 +308,25 @@ impl ServerFnError {
         Self::Validation(vec![FormFieldError::field(field, message)])
     }
 
+    /// HTTP 410 for a call to a server-fn version the server no longer
+    /// serves. Carries [`VERSION_RETIRED_HEADER`] with the current version.
+    pub fn version_retired(path: &str, version: u32, current: u32) -> Self {
+        Self::http(
+            410,
+            format!("server fn {path} version {version} was retired (current version {current}); reload the app"),
+        )
+        .with_header(VERSION_RETIRED_HEADER, current.to_string())
+    }
+
+    /// True when the client build is too old for this server and should
+    /// reload (see [`ServerFnError::version_retired`]).
+    pub fn requires_reload(&self) -> bool {
+        match self {
+            Self::Http(err) => err.status == 410 && err.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(VERSION_RETIRED_HEADER)),
+            _ => false,
+        }
+    }
+
     pub fn status_code(&self) -> u16 {
         match self {
             Self::NotFound(_) => 404,
@@ -390,6 +414,28 @@ pub fn server_fn_error_response_parts_with_encoding(err: &ServerFnError, encodin
 /// URL prefix every generated endpoint lives under.
 pub const PREFIX: &str = "/__glory/fn";
 
+/// Response header marking a 410 as a retired server-fn version; its value is
+/// the version the server currently serves.
+pub const VERSION_RETIRED_HEADER: &str = "x-glory-version-retired";
+
+/// Endpoint path for `version` of a server function: version 1 is the bare
+/// `/__glory/fn/<endpoint>`, later versions append `@v<N>`.
+pub fn versioned_path(endpoint: &str, version: u32) -> String {
+    if version <= 1 {
+        format!("{PREFIX}/{endpoint}")
+    } else {
+        format!("{PREFIX}/{endpoint}@v{version}")
+    }
+}
+
+/// Splits an endpoint path into its unversioned path and version; the
+/// inverse of [`versioned_path`].
+pub fn split_versioned_path(path: &str) -> (&str, u32) {
+    path.rsplit_once("@v")
+        .and_then(|(base, version)| version.parse().ok().map(|version| (base, version)))
+        .unwrap_or((path, 1))
+}
+
 pub fn decode_args_with<T: DeserializeOwned>(encoding: ServerFnEncoding, bytes: &[u8]) -> Result<T, ServerFnError> {
     encoding.decode(bytes)
 }
@@ -794,6 +840,7 @@ where
             socket: std::cell::RefCell::new(None),
             callbacks: std::cell::RefCell::new(Vec::new()),
             manual_close: std::cell::Cell::new(false),
+            resume: None,
         }),
         _marker: std::marker::PhantomData,
     };
@@ -824,6 +871,17 @@ struct ReactiveWebSocketInner {
     socket: std::cell::RefCell<Option<web_sys::WebSocket>>,
     callbacks: std::cell::RefCell<Vec<wasm_bindgen::JsValue>>,
     manual_close: std::cell::Cell<bool>,
+    /// Set for `#[server(websocket)]` channels: frames are [`ChannelFrame`]s
+    /// and reconnects resume the session.
+    resume: Option<ChannelResume>,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct ChannelResume {
+    /// The session id and secret.
+    session: std::cell::RefCell<Option<(String, String)>>,
+    last_seen: std::cell::Cell<Option<u64>>,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -908,7 +966,11 @@ fn connect_reactive_websocket<T>(
     state.revise(|mut state| *state = WebSocketConnectionState::Connecting);
     error.revise(|mut error| *error = None);
 
-    let socket = match web_sys::WebSocket::new(&inner.url) {
+    let url = match &inner.resume {
+        Some(resume) => channel_resume_url(&inner.url, resume),
+        None => inner.url.clone(),
+    };
+    let socket = match web_sys::WebSocket::new(&url) {
         Ok(socket) => socket,
         Err(err) => {
             let message = format!("websocket open failed: {err:?}");
@@ -932,10 +994,36 @@ fn connect_reactive_websocket<T>(
     let onmessage = wasm_bindgen::closure::Closure::wrap(Box::new({
         let latest = latest;
         let error = error;
+        let inner = inner.clone();
         move |event: web_sys::MessageEvent| {
             if let Some(text) = event.data().as_string() {
-                match decode_transport_json::<T>(&text) {
-                    Ok(message) => latest.revise(|mut latest| *latest = Some(message)),
+                let decoded = match &inner.resume {
+                    Some(resume) => serde_json::from_str::<ChannelFrame<T>>(&text)
+                        .map_err(|err| ServerFnError::Deserialization(err.to_string()))
+                        .map(|frame| match frame {
+                            ChannelFrame::Session { id, secret, .. } => {
+                                *resume.session.borrow_mut() = Some((id, secret));
+                                None
+                            }
+                            ChannelFrame::Gap { from, to } => {
+                                resume.last_seen.set(Some(to));
+                                error.revise(|mut error| *error = Some(format!("missed channel messages {from} to {to}")));
+                                None
+                            }
+                            ChannelFrame::Message { seq, message } => {
+                                resume.last_seen.set(Some(seq));
+                                // The server ended the channel; do not resume it.
+                                if matches!(message, TransportMessage::Close { .. }) {
+                                    inner.manual_close.set(true);
+                                }
+                                Some(message)
+                            }
+                        }),
+                    None => decode_transport_json::<T>(&text).map(Some),
+                };
+                match decoded {
+                    Ok(Some(message)) => latest.revise(|mut latest| *latest = Some(message)),
+                    Ok(None) => {}
                     Err(err) => error.revise(|mut error| *error = Some(err.to_string())),
                 }
             } else {
@@ -1010,6 +1098,156 @@ fn schedule_websocket_reconnect<T>(
     }
 }
 
+/// Frame sent by the server on a `#[server(websocket)]` channel socket.
+///
+/// The first frame names the session and gives the secret a reconnect must
+/// present to resume it; every later frame wraps one
+/// [`TransportMessage`] with its sequence number, which the client echoes back
+/// as `last_seen` when it reconnects. A `Gap` tells a resuming client that
+/// messages `from..=to` left the replay buffer before it came back and are
+/// lost.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum ChannelFrame<T> {
+    Session { id: String, secret: String, resumed: bool },
+    Message { seq: u64, message: TransportMessage<T> },
+    Gap { from: u64, to: u64 },
+}
+
+/// Typed sending half of a `#[server(websocket)]` channel; the receiving half
+/// is the [`ReactiveWebSocket`] returned alongside it by [`use_server_channel`].
+pub struct ChannelSender<In> {
+    #[cfg(target_arch = "wasm32")]
+    inner: std::rc::Rc<ReactiveWebSocketInner>,
+    _marker: std::marker::PhantomData<fn(In)>,
+}
+
+impl<In> Clone for ChannelSender<In> {
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(target_arch = "wasm32")]
+            inner: self.inner.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<In: Serialize> ChannelSender<In> {
+    pub fn send(&self, value: In) -> Result<(), ServerFnError> {
+        self.send_transport(&TransportMessage::Data(value))
+    }
+
+    /// Tells the server the client is done, ending the session instead of
+    /// leaving it resumable, and closes the socket.
+    pub fn close(&self) -> Result<(), ServerFnError> {
+        self.send_transport(&TransportMessage::close("client closed"))?;
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.inner.manual_close.set(true);
+            if let Some(socket) = self.inner.socket.borrow().as_ref() {
+                let _ = socket.close();
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn send_transport(&self, message: &TransportMessage<In>) -> Result<(), ServerFnError> {
+        let payload = encode_transport_json(message)?;
+        let socket = self
+            .inner
+            .socket
+            .borrow()
+            .clone()
+            .ok_or_else(|| ServerFnError::Request("websocket is not connected".to_owned()))?;
+        socket
+            .send_with_str(&payload)
+            .map_err(|err| ServerFnError::Request(format!("websocket send failed: {err:?}")))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_transport(&self, _message: &TransportMessage<In>) -> Result<(), ServerFnError> {
+        Err(ServerFnError::Request("browser WebSocket client is only available on wasm32".to_owned()))
+    }
+}
+
+/// Connects to the `#[server(websocket)]` channel at `path` (e.g.
+/// `/__glory/ws/chat`). Generated wasm stubs call this.
+///
+/// Relative paths resolve against the page origin. On reconnect the client
+/// passes its session id and the last sequence number it saw, so the server
+/// replays what was missed and the same handler keeps running. Non-wasm
+/// targets get a `Failed` handle, like [`use_websocket`].
+pub fn use_server_channel<In, Out>(path: impl Into<String>) -> (ReactiveWebSocket<Out>, ChannelSender<In>)
+where
+    In: Serialize + 'static,
+    Out: Serialize + DeserializeOwned + std::fmt::Debug + 'static,
+{
+    #[cfg(target_arch = "wasm32")]
+    {
+        let socket = ReactiveWebSocket {
+            state: glory_core::Cage::new(WebSocketConnectionState::Connecting),
+            latest: glory_core::Cage::new(None),
+            error: glory_core::Cage::new(None),
+            inner: std::rc::Rc::new(ReactiveWebSocketInner {
+                url: absolute_websocket_url(&path.into()),
+                options: WebSocketClientOptions::default(),
+                socket: std::cell::RefCell::new(None),
+                callbacks: std::cell::RefCell::new(Vec::new()),
+                manual_close: std::cell::Cell::new(false),
+                resume: Some(ChannelResume::default()),
+            }),
+            _marker: std::marker::PhantomData,
+        };
+        connect_reactive_websocket::<Out>(&socket.inner, socket.state, socket.latest, socket.error);
+        let sender = ChannelSender {
+            inner: socket.inner.clone(),
+            _marker: std::marker::PhantomData,
+        };
+        (socket, sender)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let socket = use_websocket::<Out>(path);
+        (
+            socket,
+            ChannelSender {
+                _marker: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+/// `ws(s)://host/path` for a same-origin path; absolute URLs pass through.
+#[cfg(target_arch = "wasm32")]
+fn absolute_websocket_url(path: &str) -> String {
+    if path.contains("://") {
+        return path.to_owned();
+    }
+    let location = web_sys::window().map(|window| window.location());
+    let host = location.as_ref().and_then(|location| location.host().ok()).unwrap_or_default();
+    let scheme = match location.and_then(|location| location.protocol().ok()).as_deref() {
+        Some("https:") => "wss",
+        _ => "ws",
+    };
+    format!("{scheme}://{host}{path}")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn channel_resume_url(url: &str, resume: &ChannelResume) -> String {
+    let Some((session, secret)) = resume.session.borrow().clone() else {
+        return url.to_owned();
+    };
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    query.append_pair(CHANNEL_SESSION_PARAM, &session);
+    query.append_pair(CHANNEL_SECRET_PARAM, &secret);
+    if let Some(last_seen) = resume.last_seen.get() {
+        query.append_pair(CHANNEL_LAST_SEEN_PARAM, &last_seen.to_string());
+    }
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}{}", query.finish())
+}
+
 /// One Server-Sent Event frame.
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -1735,6 +1973,422 @@ fn parse_header_params(value: &str) -> Vec<(String, String)> {
         .collect()
 }
 
+// ---------------------------------------------------------------------------
+// Streaming multipart
+// ---------------------------------------------------------------------------
+//
+// [`decode_multipart`] needs the whole body in memory. The streaming decoder
+// below parses the body as it arrives and hands each part out as a
+// [`futures::Stream`] of chunks, so large files can go straight to disk.
+// Part headers are capped at [`MULTIPART_MAX_HEADER_BYTES`]; bodies are capped
+// per part by [`MultipartLimits`] while they stream.
+
+/// Upper bound for one part's header block.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MULTIPART_MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// Headers of one streamed `multipart/form-data` part.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultipartPartHeader {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub headers: Vec<(String, String)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MultipartPartHeader {
+    pub fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+enum MultipartEvent {
+    Start(MultipartPartHeader),
+    Data(Vec<u8>),
+    End,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MultipartParserState {
+    Preamble,
+    Headers,
+    Body,
+    Done,
+}
+
+/// Incremental `multipart/form-data` parser: feed it body chunks with
+/// [`push`](Self::push) and it emits part events as soon as they are known.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct MultipartParser {
+    /// `--boundary`.
+    delimiter: Vec<u8>,
+    /// `\r\n--boundary`, the marker that ends a part body.
+    body_delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    state: MultipartParserState,
+    limits: MultipartLimits,
+    body_bytes: usize,
+    part_count: usize,
+    part_bytes: usize,
+    part_limit: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MultipartParser {
+    fn new(boundary: &str, limits: MultipartLimits) -> Self {
+        let delimiter = [b"--".as_slice(), boundary.as_bytes()].concat();
+        let body_delimiter = [b"\r\n".as_slice(), &delimiter].concat();
+        Self {
+            delimiter,
+            body_delimiter,
+            buffer: Vec::new(),
+            state: MultipartParserState::Preamble,
+            limits,
+            body_bytes: 0,
+            part_count: 0,
+            part_bytes: 0,
+            part_limit: 0,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8], events: &mut Vec<MultipartEvent>) -> Result<(), ServerFnError> {
+        self.body_bytes += chunk.len();
+        if self.body_bytes > self.limits.max_body_bytes {
+            return Err(ServerFnError::http(413, "multipart body exceeds size limit"));
+        }
+        if self.state == MultipartParserState::Done {
+            return Ok(());
+        }
+        self.buffer.extend_from_slice(chunk);
+        while self.step(events)? {}
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), ServerFnError> {
+        if self.state == MultipartParserState::Done {
+            Ok(())
+        } else {
+            Err(ServerFnError::http(400, "multipart body ended before the closing boundary"))
+        }
+    }
+
+    /// Consumes as much of the buffer as possible; returns whether another
+    /// step could make progress.
+    fn step(&mut self, events: &mut Vec<MultipartEvent>) -> Result<bool, ServerFnError> {
+        match self.state {
+            MultipartParserState::Preamble => {
+                let Some(index) = find_bytes(&self.buffer, &self.delimiter) else {
+                    // Keep only what could still be the start of the delimiter.
+                    let keep = self.delimiter.len().saturating_sub(1).min(self.buffer.len());
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    return Ok(false);
+                };
+                self.buffer.drain(..index + self.delimiter.len());
+                self.after_delimiter()
+            }
+            MultipartParserState::Headers => {
+                let Some(index) = find_bytes(&self.buffer, b"\r\n\r\n") else {
+                    if self.buffer.len() > MULTIPART_MAX_HEADER_BYTES {
+                        return Err(ServerFnError::http(431, "multipart part headers exceed size limit"));
+                    }
+                    return Ok(false);
+                };
+                if self.part_count >= self.limits.max_parts {
+                    return Err(ServerFnError::http(413, "multipart part count exceeds limit"));
+                }
+                let header = parse_multipart_part_header(&self.buffer[..index])?;
+                self.buffer.drain(..index + 4);
+                self.part_count += 1;
+                self.part_bytes = 0;
+                self.part_limit = if header.is_file() {
+                    self.limits.max_file_bytes
+                } else {
+                    self.limits.max_field_bytes
+                };
+                events.push(MultipartEvent::Start(header));
+                self.state = MultipartParserState::Body;
+                Ok(true)
+            }
+            MultipartParserState::Body => match find_bytes(&self.buffer, &self.body_delimiter) {
+                Some(index) => {
+                    let data: Vec<u8> = self.buffer.drain(..index).collect();
+                    self.emit_data(data, events)?;
+                    self.buffer.drain(..self.body_delimiter.len());
+                    events.push(MultipartEvent::End);
+                    self.after_delimiter()
+                }
+                None => {
+                    let keep = self.body_delimiter.len().saturating_sub(1).min(self.buffer.len());
+                    let data: Vec<u8> = self.buffer.drain(..self.buffer.len() - keep).collect();
+                    self.emit_data(data, events)?;
+                    Ok(false)
+                }
+            },
+            MultipartParserState::Done => Ok(false),
+        }
+    }
+
+    /// Right after a delimiter: `--` closes the body, CRLF opens a part.
+    fn after_delimiter(&mut self) -> Result<bool, ServerFnError> {
+        if self.buffer.len() < 2 {
+            self.state = MultipartParserState::Preamble;
+            // Re-prepend the delimiter so the next push re-enters this check.
+            let mut restored = self.delimiter.clone();
+            restored.append(&mut self.buffer);
+            self.buffer = restored;
+            return Ok(false);
+        }
+        if self.buffer.starts_with(b"--") {
+            self.state = MultipartParserState::Done;
+            self.buffer.clear();
+            return Ok(false);
+        }
+        if !self.buffer.starts_with(b"\r\n") {
+            return Err(ServerFnError::http(400, "malformed multipart boundary line"));
+        }
+        self.buffer.drain(..2);
+        self.state = MultipartParserState::Headers;
+        Ok(true)
+    }
+
+    fn emit_data(&mut self, data: Vec<u8>, events: &mut Vec<MultipartEvent>) -> Result<(), ServerFnError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.part_bytes += data.len();
+        if self.part_bytes > self.part_limit {
+            return Err(ServerFnError::http(413, "multipart part exceeds size limit"));
+        }
+        events.push(MultipartEvent::Data(data));
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_multipart_part_header(bytes: &[u8]) -> Result<MultipartPartHeader, ServerFnError> {
+    let headers = parse_multipart_headers(bytes)?;
+    let disposition = headers
+        .iter()
+        .find(|(name, _)| name == "content-disposition")
+        .map(|(_, value)| value.as_str())
+        .ok_or_else(|| ServerFnError::http(400, "multipart part missing content-disposition"))?;
+    let params = parse_header_params(disposition);
+    let name = params
+        .iter()
+        .find(|(key, _)| key == "name")
+        .map(|(_, value)| value.clone())
+        .ok_or_else(|| ServerFnError::http(400, "multipart part missing name"))?;
+    let filename = params.iter().find(|(key, _)| key == "filename").map(|(_, value)| value.clone());
+    let content_type = headers.iter().find(|(name, _)| name == "content-type").map(|(_, value)| value.clone());
+    Ok(MultipartPartHeader {
+        name,
+        filename,
+        content_type,
+        headers,
+    })
+}
+
+/// A `multipart/form-data` body decoded while it streams in. Obtain one with
+/// [`decode_multipart_stream`] and pull parts with
+/// [`next_part`](Self::next_part).
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MultipartStream<S> {
+    body: S,
+    parser: MultipartParser,
+    events: std::collections::VecDeque<MultipartEvent>,
+    body_done: bool,
+    /// True while the previous part's data has not been read to its end.
+    in_part: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S> std::fmt::Debug for MultipartStream<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartStream")
+            .field("parser", &self.parser)
+            .field("body_done", &self.body_done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Starts decoding a streamed `multipart/form-data` body. `body` yields the
+/// raw request chunks as they arrive from the adapter.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn decode_multipart_stream<S, B>(content_type: &str, body: S, limits: MultipartLimits) -> Result<MultipartStream<S>, ServerFnError>
+where
+    S: futures::Stream<Item = Result<B, ServerFnError>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    let boundary = multipart_boundary(content_type).ok_or_else(|| ServerFnError::http(400, "multipart boundary missing"))?;
+    Ok(MultipartStream {
+        body,
+        parser: MultipartParser::new(&boundary, limits),
+        events: std::collections::VecDeque::new(),
+        body_done: false,
+        in_part: false,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S, B> MultipartStream<S>
+where
+    S: futures::Stream<Item = Result<B, ServerFnError>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    /// The next part, or `None` after the closing boundary. Any unread data
+    /// of the previous part is skipped.
+    pub async fn next_part(&mut self) -> Result<Option<StreamingPart<'_, S>>, ServerFnError> {
+        loop {
+            match std::future::poll_fn(|cx| self.poll_event(cx)).await? {
+                Some(MultipartEvent::Start(header)) => {
+                    self.in_part = true;
+                    return Ok(Some(StreamingPart { header, stream: self }));
+                }
+                Some(MultipartEvent::Data(_)) => {}
+                Some(MultipartEvent::End) => self.in_part = false,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Reads every remaining part into memory, like [`decode_multipart`].
+    pub async fn collect(mut self) -> Result<MultipartForm, ServerFnError> {
+        let mut parts = Vec::new();
+        while let Some(part) = self.next_part().await? {
+            let header = part.header.clone();
+            let bytes = part.bytes().await?;
+            parts.push(MultipartPart {
+                name: header.name,
+                filename: header.filename,
+                content_type: header.content_type,
+                headers: header.headers,
+                bytes,
+            });
+        }
+        Ok(MultipartForm { parts })
+    }
+
+    fn poll_event(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<Option<MultipartEvent>, ServerFnError>> {
+        use futures::StreamExt;
+
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return std::task::Poll::Ready(Ok(Some(event)));
+            }
+            if self.body_done {
+                return std::task::Poll::Ready(Ok(None));
+            }
+            match std::task::ready!(self.body.poll_next_unpin(cx)) {
+                Some(Ok(chunk)) => {
+                    let mut events = Vec::new();
+                    self.parser.push(chunk.as_ref(), &mut events)?;
+                    self.events.extend(events);
+                }
+                Some(Err(err)) => return std::task::Poll::Ready(Err(err)),
+                None => {
+                    self.body_done = true;
+                    self.parser.finish()?;
+                }
+            }
+        }
+    }
+}
+
+/// One part of a [`MultipartStream`]; a [`futures::Stream`] of its body
+/// chunks. Dropping it early skips the rest of the part.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct StreamingPart<'a, S> {
+    pub header: MultipartPartHeader,
+    stream: &'a mut MultipartStream<S>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S> std::fmt::Debug for StreamingPart<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingPart").field("header", &self.header).finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S, B> StreamingPart<'_, S>
+where
+    S: futures::Stream<Item = Result<B, ServerFnError>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    /// Buffers the rest of this part in memory.
+    pub async fn bytes(mut self) -> Result<Vec<u8>, ServerFnError> {
+        use futures::StreamExt;
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.next().await {
+            bytes.extend_from_slice(&chunk?);
+        }
+        Ok(bytes)
+    }
+
+    pub async fn text(self) -> Result<String, ServerFnError> {
+        String::from_utf8(self.bytes().await?).map_err(|err| ServerFnError::Deserialization(err.to_string()))
+    }
+
+    /// Streams the rest of this part into a new file at `path` without
+    /// buffering it. Returns the number of bytes written.
+    pub async fn save_to(mut self, path: impl AsRef<std::path::Path>) -> Result<u64, ServerFnError> {
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        let mut file = tokio::fs::File::create(path.as_ref())
+            .await
+            .map_err(|err| ServerFnError::ServerError(format!("create upload file failed: {err}")))?;
+        let mut written = 0;
+        while let Some(chunk) = self.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk)
+                .await
+                .map_err(|err| ServerFnError::ServerError(format!("write upload file failed: {err}")))?;
+            written += chunk.len() as u64;
+        }
+        file.flush()
+            .await
+            .map_err(|err| ServerFnError::ServerError(format!("write upload file failed: {err}")))?;
+        Ok(written)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S, B> futures::Stream for StreamingPart<'_, S>
+where
+    S: futures::Stream<Item = Result<B, ServerFnError>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<Vec<u8>, ServerFnError>;
+
+    fn poll_next(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
+        if !self.stream.in_part {
+            return std::task::Poll::Ready(None);
+        }
+        match std::task::ready!(self.stream.poll_event(cx)) {
+            Ok(Some(MultipartEvent::Data(chunk))) => std::task::Poll::Ready(Some(Ok(chunk))),
+            Ok(Some(MultipartEvent::End)) | Ok(None) => {
+                self.stream.in_part = false;
+                std::task::Poll::Ready(None)
+            }
+            Ok(Some(MultipartEvent::Start(_))) => {
+                unreachable!("multipart parser starts a part before ending the previous one")
+            }
+            Err(err) => {
+                self.stream.in_part = false;
+                std::task::Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub type BoxedServerFnFuture = std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>, ServerFnError>> + Send>>;
 
@@ -1782,6 +2436,15 @@ pub struct ServerFnEntry {
     pub handler: fn(Vec<u8>, ServerFnEncoding, ServerFnEncoding) -> BoxedServerFnFuture,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerFnEntry {
+    /// Wire version from `#[server(version = N)]`, as encoded in `path`
+    /// (see [`versioned_path`]).
+    pub fn version(&self) -> u32 {
+        split_versioned_path(self.path).1
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 inventory::collect!(ServerFnEntry);
 
@@ -1847,15 +2510,492 @@ pub async fn dispatch_with_method(
         };
     }
     ServerFnDispatchResult {
-        result: Err(ServerFnError::NotFound(path.to_owned())),
+        result: Err(retired_version_error(path).unwrap_or_else(|| ServerFnError::NotFound(path.to_owned()))),
         encoding: output_encoding,
     }
 }
 
+/// [`ServerFnError::version_retired`] when `path` names an older version of a
+/// function that is still registered under a newer one.
+#[cfg(not(target_arch = "wasm32"))]
+fn retired_version_error(path: &str) -> Option<ServerFnError> {
+    let (base, version) = split_versioned_path(path);
+    let current = inventory::iter::<ServerFnEntry>
+        .into_iter()
+        .filter(|entry| split_versioned_path(entry.path).0 == base)
+        .map(ServerFnEntry::version)
+        .max()?;
+    (version < current).then(|| ServerFnError::version_retired(base, version, current))
+}
+
 // ---------------------------------------------------------------------------
-// Request context (server side)
+// `#[server(websocket)]` channels (server side)
 // ---------------------------------------------------------------------------
-
+//
+// `#[server(websocket)]` functions register a [`WebSocketFnEntry`] next to the
+// request/response [`ServerFnEntry`]s. The entry's handler maps a stream of
+// inbound text frames to a stream of outbound text frames; decoding and
+// encoding of the typed messages is generated by the macro.
+//
+// Every socket is bound to a channel *session* that outlives it: outbound
+// messages are numbered and the most recent ones are kept, so a client that
+// reconnects with `?session=<id>&last_seen=<seq>` within
+// [`CHANNEL_RESUME_GRACE`] gets the messages it missed and keeps talking to
+// the same running handler. Resuming takes the session's secret, which only
+// the socket that opened it was told, and the credentials of the request that
+// opened it; see [`channel_principal`]. The adapter mounts only pump frames
+// between a socket and a [`ChannelConnection`].
+
+/// URL prefix every `#[server(websocket)]` channel lives under.
+pub const WS_PREFIX: &str = "/__glory/ws";
+/// Query parameter carrying the session id on reconnect.
+pub const CHANNEL_SESSION_PARAM: &str = "session";
+/// Query parameter carrying the session secret on reconnect.
+pub const CHANNEL_SECRET_PARAM: &str = "secret";
+/// Query parameter carrying the last message sequence number the client saw.
+pub const CHANNEL_LAST_SEEN_PARAM: &str = "last_seen";
+/// Outbound messages kept per session for replay after a reconnect.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CHANNEL_REPLAY_CAPACITY: usize = 256;
+/// Inbound frames queued per session before
+/// [`ChannelConnection::receive`] waits for the handler to catch up.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CHANNEL_INBOUND_CAPACITY: usize = 64;
+/// How long a detached session waits for its client to come back.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CHANNEL_RESUME_GRACE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Text frames flowing into or out of a channel handler.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxedChannelStream = futures::stream::BoxStream<'static, String>;
+
+/// One registered `#[server(websocket)]` channel.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct WebSocketFnEntry {
+    /// Full URL path, e.g. `/__glory/ws/chat`.
+    pub path: &'static str,
+    /// Adapter-neutral middleware run before every (re)connect.
+    pub middlewares: &'static [ServerFnMiddleware],
+    /// Maps inbound `TransportMessage<In>` JSON frames to outbound
+    /// `TransportMessage<Out>` JSON frames.
+    pub handler: fn(BoxedChannelStream) -> BoxedChannelStream,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+inventory::collect!(WebSocketFnEntry);
+
+/// All registered channel paths (diagnostics / route listing).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn registered_websocket_paths() -> Vec<&'static str> {
+    inventory::iter::<WebSocketFnEntry>.into_iter().map(|entry| entry.path).collect()
+}
+
+/// Glue used by the generated channel handler: decodes inbound frames into
+/// `In`, runs `channel`, and encodes its `Out` items.
+///
+/// Inbound `Close` envelopes end the handler's input stream; malformed frames
+/// are logged and skipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn serve_channel<In, Out, F, Fut, S>(frames: BoxedChannelStream, channel: F) -> BoxedChannelStream
+where
+    In: DeserializeOwned + Send + 'static,
+    Out: Serialize + Send + 'static,
+    F: FnOnce(futures::stream::BoxStream<'static, In>) -> Fut,
+    Fut: std::future::Future<Output = S> + Send + 'static,
+    S: futures::Stream<Item = Out> + Send + 'static,
+{
+    use futures::StreamExt;
+
+    let incoming = frames
+        .map(|frame| decode_transport_json::<In>(&frame))
+        .take_while(|message| std::future::ready(!matches!(message, Ok(TransportMessage::Close { .. }))))
+        .filter_map(|message| {
+            std::future::ready(match message {
+                Ok(TransportMessage::Data(value)) => Some(value),
+                Ok(_) => None,
+                Err(err) => {
+                    tracing::warn!(error = %err, "dropping malformed channel frame");
+                    None
+                }
+            })
+        })
+        .boxed();
+    futures::stream::once(channel(incoming))
+        .flatten()
+        .map(|value| {
+            encode_transport_json(&TransportMessage::Data(value))
+                .unwrap_or_else(|err| encode_transport_json(&TransportMessage::<()>::Error(err)).expect("ServerFnError serializes"))
+        })
+        .boxed()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ChannelSession {
+    id: String,
+    /// Sent to the socket that opened the session; required to resume it.
+    secret: String,
+    path: &'static str,
+    principal: ChannelPrincipal,
+    /// Feeds the handler; taken to end its input.
+    inbound: std::sync::Mutex<Option<tokio::sync::mpsc::Sender<String>>>,
+    /// The handler's output; empty while a socket waits on it.
+    outbound: std::sync::Mutex<Option<BoxedChannelStream>>,
+    replay: std::sync::Mutex<ChannelReplay>,
+    /// Woken when a socket takes the session over or puts the handler's
+    /// output back.
+    changed: tokio::sync::Notify,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct ChannelReplay {
+    frames: std::collections::VecDeque<(u64, String)>,
+    next_seq: u64,
+    /// Bumped per attached socket; stale connections stop pulling.
+    generation: u64,
+    detached_at: Option<std::time::Instant>,
+    finished: bool,
+    /// The handler's output ended.
+    drained: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ChannelReplay {
+    fn push(&mut self, message: &str) -> u64 {
+        self.next_seq += 1;
+        let seq = self.next_seq;
+        if self.frames.len() == CHANNEL_REPLAY_CAPACITY {
+            self.frames.pop_front();
+        }
+        self.frames
+            .push_back((seq, format!(r#"{{"type":"message","payload":{{"seq":{seq},"message":{message}}}}}"#)));
+        seq
+    }
+
+    fn after(&self, cursor: u64) -> Option<(u64, String)> {
+        self.frames.iter().find(|(seq, _)| *seq > cursor).cloned()
+    }
+
+    /// The `Gap` frame for a client whose next message already left the
+    /// buffer, with the last sequence number it covers.
+    fn gap_after(&self, cursor: u64) -> Option<(u64, String)> {
+        let oldest = self.frames.front().map_or(self.next_seq + 1, |(seq, _)| *seq);
+        let (from, to) = (cursor + 1, oldest - 1);
+        (from <= to).then(|| {
+            (
+                to,
+                serde_json::to_string(&ChannelFrame::<()>::Gap { from, to }).expect("channel frames serialize"),
+            )
+        })
+    }
+
+    /// Detached past [`CHANNEL_RESUME_GRACE`], or detached after the channel
+    /// ended.
+    fn expired(&self, now: std::time::Instant) -> bool {
+        self.detached_at
+            .is_some_and(|detached_at| self.finished || now.duration_since(detached_at) >= CHANNEL_RESUME_GRACE)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static CHANNEL_SESSIONS: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<ChannelSession>>>> =
+    std::sync::LazyLock::new(Default::default);
+
+/// The credentials a channel socket's request carries: its `Authorization`
+/// and `Cookie` headers.
+#[cfg(not(target_arch = "wasm32"))]
+type ChannelPrincipal = [Option<String>; 2];
+
+#[cfg(not(target_arch = "wasm32"))]
+fn channel_principal(context: Option<&RequestContext>) -> ChannelPrincipal {
+    ["authorization", "cookie"].map(|name| context.and_then(|context| context.header(name)).map(str::to_owned))
+}
+
+/// Starts the thread that drops expired sessions every half
+/// [`CHANNEL_RESUME_GRACE`], whether or not sockets keep connecting.
+#[cfg(not(target_arch = "wasm32"))]
+fn start_channel_reaper() {
+    static REAPER: std::sync::Once = std::sync::Once::new();
+    REAPER.call_once(|| {
+        std::thread::Builder::new()
+            .name("glory-channel-reaper".to_owned())
+            .spawn(|| {
+                loop {
+                    std::thread::sleep(CHANNEL_RESUME_GRACE / 2);
+                    reap_channel_sessions(std::time::Instant::now());
+                }
+            })
+            .expect("glory-serverfn: failed to spawn the channel reaper");
+    });
+}
+
+/// Removes the expired sessions and stops their handlers.
+#[cfg(not(target_arch = "wasm32"))]
+fn reap_channel_sessions(now: std::time::Instant) {
+    let expired: Vec<_> = {
+        let mut sessions = CHANNEL_SESSIONS.lock().expect("channel session registry lock poisoned");
+        sessions
+            .extract_if(|_, session| session.replay.lock().expect("channel replay lock poisoned").expired(now))
+            .map(|(_, session)| session)
+            .collect()
+    };
+    // The handlers drop outside the registry lock.
+    for session in expired {
+        session.close_inbound();
+    }
+}
+
+/// One socket attached to a channel session. Adapters send
+/// [`hello_frame`](Self::hello_frame) first, then pump
+/// [`outgoing`](Self::outgoing) to the socket and socket text into
+/// [`receive`](Self::receive), and drop it when the socket closes.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ChannelConnection {
+    session: std::sync::Arc<ChannelSession>,
+    generation: u64,
+    cursor: u64,
+    resumed: bool,
+    context: Option<RequestContext>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for ChannelConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelConnection")
+            .field("session", &self.session.id)
+            .field("path", &self.session.path)
+            .field("resumed", &self.resumed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Attaches a socket to the channel registered at `path`, resuming the
+/// session named in `query` when it is still alive, `query` carries its
+/// secret and `context` carries the credentials of the request that opened
+/// it; otherwise the socket gets a new session. Runs the channel's middleware against `context` first.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn connect_channel(path: &str, query: Option<&str>, context: Option<RequestContext>) -> Result<ChannelConnection, ServerFnError> {
+    let entry = inventory::iter::<WebSocketFnEntry>
+        .into_iter()
+        .find(|entry| entry.path == path)
+        .ok_or_else(|| ServerFnError::NotFound(path.to_owned()))?;
+    let middleware_context = ServerFnMiddlewareContext {
+        path: path.to_owned(),
+        method: "GET".to_owned(),
+        request: context.clone(),
+        input_encoding: ServerFnEncoding::Json,
+        output_encoding: ServerFnEncoding::Json,
+    };
+    for middleware in entry.middlewares {
+        middleware(middleware_context.clone()).await?;
+    }
+
+    let params: Vec<(String, String)> = form_urlencoded::parse(query.unwrap_or_default().as_bytes()).into_owned().collect();
+    let param = |name: &str| params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+    start_channel_reaper();
+    reap_channel_sessions(std::time::Instant::now());
+    let principal = channel_principal(context.as_ref());
+    let mut sessions = CHANNEL_SESSIONS.lock().expect("channel session registry lock poisoned");
+
+    let existing = param(CHANNEL_SESSION_PARAM)
+        .and_then(|id| sessions.get(id))
+        .filter(|session| {
+            session.path == entry.path && session.principal == principal && param(CHANNEL_SECRET_PARAM) == Some(session.secret.as_str())
+        })
+        .cloned();
+    if let Some(session) = existing {
+        let cursor = param(CHANNEL_LAST_SEEN_PARAM).and_then(|seq| seq.parse().ok()).unwrap_or(0);
+        let mut replay = session.replay.lock().expect("channel replay lock poisoned");
+        replay.generation += 1;
+        replay.detached_at = None;
+        let generation = replay.generation;
+        drop(replay);
+        // A socket still waiting on the handler hands it over.
+        session.changed.notify_waiters();
+        return Ok(ChannelConnection {
+            session,
+            generation,
+            cursor,
+            resumed: true,
+            context,
+        });
+    }
+
+    let (inbound, receiver) = tokio::sync::mpsc::channel(CHANNEL_INBOUND_CAPACITY);
+    let frames = futures::stream::unfold(
+        receiver,
+        |mut receiver| async move { receiver.recv().await.map(|frame| (frame, receiver)) },
+    );
+    let session = std::sync::Arc::new(ChannelSession {
+        id: next_random_id(),
+        secret: next_random_id(),
+        path: entry.path,
+        principal,
+        inbound: std::sync::Mutex::new(Some(inbound)),
+        outbound: std::sync::Mutex::new(Some((entry.handler)(Box::pin(frames)))),
+        replay: std::sync::Mutex::new(ChannelReplay {
+            generation: 1,
+            ..Default::default()
+        }),
+        changed: tokio::sync::Notify::new(),
+    });
+    sessions.insert(session.id.clone(), session.clone());
+    Ok(ChannelConnection {
+        session,
+        generation: 1,
+        cursor: 0,
+        resumed: false,
+        context,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ChannelConnection {
+    pub fn session_id(&self) -> &str {
+        &self.session.id
+    }
+
+    /// True when this socket picked up an existing session.
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// The first frame to send: tells the client its session id and secret.
+    pub fn hello_frame(&self) -> String {
+        serde_json::to_string(&ChannelFrame::<()>::Session {
+            id: self.session.id.clone(),
+            secret: self.session.secret.clone(),
+            resumed: self.resumed,
+        })
+        .expect("channel frames serialize")
+    }
+
+    /// Forwards one inbound text frame to the running handler, waiting while
+    /// [`CHANNEL_INBOUND_CAPACITY`] frames are queued for it, so a client
+    /// sending faster than the handler reads is held back at its socket. A
+    /// `Close` envelope from the client ends the session for good.
+    pub async fn receive(&self, frame: impl Into<String>) {
+        let frame = frame.into();
+        let closing = matches!(decode_transport_json::<serde_json::Value>(&frame), Ok(TransportMessage::Close { .. }));
+        let inbound = self.session.inbound.lock().expect("channel inbound lock poisoned").clone();
+        if let Some(inbound) = inbound {
+            let _ = inbound.send(frame).await;
+        }
+        if closing {
+            self.session.replay.lock().expect("channel replay lock poisoned").finished = true;
+        }
+    }
+
+    /// Outbound frames for this socket: first the buffered messages after
+    /// the client's `last_seen`, preceded by a [`ChannelFrame::Gap`] when some
+    /// of them already left the buffer, then live handler output. Ends when
+    /// the handler finishes or another socket takes over the session.
+    pub fn outgoing(&self) -> impl futures::Stream<Item = String> + Send + 'static {
+        let state = (self.session.clone(), self.generation, self.cursor, self.context.clone());
+        futures::stream::unfold(state, |(session, generation, cursor, context)| async move {
+            loop {
+                // Created before the checks, so a change in between still
+                // wakes it.
+                let changed = session.changed.notified();
+                let ready = {
+                    let replay = session.replay.lock().expect("channel replay lock poisoned");
+                    match replay.gap_after(cursor).or_else(|| replay.after(cursor)) {
+                        Some(frame) => Some(Some(frame)),
+                        None if replay.generation != generation || replay.drained => Some(None),
+                        None => None,
+                    }
+                };
+                if let Some(frame) = ready {
+                    drop(changed);
+                    return frame.map(|(seq, frame)| (frame, (session, generation, seq, context)));
+                }
+                let taken = session.outbound.lock().expect("channel outbound lock poisoned").take();
+                let Some(mut stream) = taken else {
+                    // The socket this one took over still waits on the handler.
+                    changed.await;
+                    continue;
+                };
+                let next = session.pull(generation, &mut stream, context.as_ref()).await;
+                let mut replay = session.replay.lock().expect("channel replay lock poisoned");
+                match next {
+                    Some(Some(message)) => {
+                        replay.push(&message);
+                        *session.outbound.lock().expect("channel outbound lock poisoned") = Some(stream);
+                    }
+                    Some(None) => {
+                        replay.finished = true;
+                        replay.drained = true;
+                        replay.push(&encode_transport_json(&TransportMessage::<()>::close("channel finished")).expect("close serializes"));
+                    }
+                    None => *session.outbound.lock().expect("channel outbound lock poisoned") = Some(stream),
+                }
+                drop(replay);
+                session.changed.notify_waiters();
+            }
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ChannelSession {
+    /// Ends the handler's input once frames already queued are read.
+    fn close_inbound(&self) {
+        self.inbound.lock().expect("channel inbound lock poisoned").take();
+    }
+
+    /// The handler's next message, pulled with `context` as the request
+    /// context; `None` once another socket takes the session over.
+    async fn pull(&self, generation: u64, stream: &mut BoxedChannelStream, context: Option<&RequestContext>) -> Option<Option<String>> {
+        use futures::StreamExt;
+        use futures::future::{Either, select};
+
+        let next = std::future::poll_fn(|cx| match context {
+            Some(context) => REQUEST_CONTEXT.sync_scope(context.clone(), || stream.poll_next_unpin(cx)),
+            None => stream.poll_next_unpin(cx),
+        });
+        let taken_over = async {
+            loop {
+                let changed = self.changed.notified();
+                if self.replay.lock().expect("channel replay lock poisoned").generation != generation {
+                    return;
+                }
+                changed.await;
+            }
+        };
+        match select(std::pin::pin!(next), std::pin::pin!(taken_over)).await {
+            Either::Left((message, _)) => Some(message),
+            Either::Right(_) => None,
+        }
+    }
+}
+
+/// Dropping a connection marks its socket gone. The session stays resumable
+/// for [`CHANNEL_RESUME_GRACE`] unless the handler or the client ended it;
+/// a reaper thread drops it after that.
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ChannelConnection {
+    fn drop(&mut self) {
+        let mut replay = self.session.replay.lock().expect("channel replay lock poisoned");
+        if replay.generation != self.generation {
+            return;
+        }
+        replay.detached_at = Some(std::time::Instant::now());
+        let finished = replay.finished;
+        drop(replay);
+        if finished {
+            self.session.close_inbound();
+            CHANNEL_SESSIONS
+                .lock()
+                .expect("channel session registry lock poisoned")
+                .remove(&self.session.id);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request context (server side)
+// ---------------------------------------------------------------------------
+
 /// Snapshot of the HTTP request a server function is handling. Populated
 /// by the adapter mounts before dispatch; absent when a server function is
 /// called directly (SSR rendering, tests).
@@ -1864,12 +3004,31 @@ pub async fn dispatch_with_method(
 pub struct RequestContext {
     pub method: String,
     pub uri: String,
-    /// Header pairs, names lowercased.
+    /// Header pairs, names lowercased. The adapters add the connected peer's
+    /// address as the `:peer-ip` pseudo-header; see [`peer_ip`](Self::peer_ip).
     pub headers: Vec<(String, String)>,
 }
 
+/// Pseudo-header carrying the connected peer's IP address. No client can send
+/// it, since header names never contain `:`.
+#[cfg(not(target_arch = "wasm32"))]
+const PEER_IP_HEADER: &str = ":peer-ip";
+
 #[cfg(not(target_arch = "wasm32"))]
 impl RequestContext {
+    /// IP address of the directly connected peer, when the adapter knows it.
+    /// Behind a reverse proxy this is the proxy; see [`RateLimitKey::ClientIp`].
+    pub fn peer_ip(&self) -> Option<&str> {
+        self.header(PEER_IP_HEADER)
+    }
+
+    /// Records the connected peer's IP address, replacing any recorded before.
+    pub fn with_peer_ip(mut self, ip: Option<String>) -> Self {
+        self.headers.retain(|(name, _)| name != PEER_IP_HEADER);
+        self.headers.extend(ip.map(|ip| (PEER_IP_HEADER.to_owned(), ip)));
+        self
+    }
+
     /// First value of `name` (case-insensitive).
     pub fn header(&self, name: &str) -> Option<&str> {
         let name = name.to_ascii_lowercase();
@@ -1903,6 +3062,12 @@ impl RequestContext {
     pub fn response_encoding(&self) -> ServerFnEncoding {
         negotiate_response_encoding(self.header("accept"))
     }
+
+    /// True when adapter mounts hand this request's body to the server
+    /// function as a stream ([`take_request_body`]) rather than buffering it.
+    pub fn is_streamed_body(&self) -> bool {
+        !self.method.eq_ignore_ascii_case("GET") && self.content_type().as_deref() == Some("multipart/form-data")
+    }
 }
 
 /// `SameSite` value for generated `Set-Cookie` headers.
@@ -2011,6 +3176,38 @@ pub fn clear_cookie_header(name: &str, path: &str) -> Result<String, ServerFnErr
     set_cookie_header(name, "", CookieOptions::default().path(path).max_age_seconds(0))
 }
 
+/// Cookie helpers for the SSR [`ServerResponseOptions`] a widget tree writes
+/// its response through, built on [`set_cookie_header`]:
+///
+/// ```ignore
+/// use glory_serverfn::{CookieOptions, ResponseOptionsExt};
+///
+/// ctx.response_options().set_cookie("theme", "dark", CookieOptions::default())?;
+/// ```
+///
+/// [`ServerResponseOptions`]: glory_core::web::ServerResponseOptions
+#[cfg(not(target_arch = "wasm32"))]
+pub trait ResponseOptionsExt {
+    fn set_cookie(&self, name: &str, value: &str, options: CookieOptions) -> Result<(), ServerFnError>;
+
+    fn clear_cookie(&self, name: &str, path: &str) -> Result<(), ServerFnError>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ResponseOptionsExt for glory_core::web::ServerResponseOptions {
+    fn set_cookie(&self, name: &str, value: &str, options: CookieOptions) -> Result<(), ServerFnError> {
+        let header = set_cookie_header(name, value, options)?;
+        self.set_cookie_header(header)
+            .map_err(|error| ServerFnError::http(500, error.to_string()))
+    }
+
+    fn clear_cookie(&self, name: &str, path: &str) -> Result<(), ServerFnError> {
+        let header = clear_cookie_header(name, path)?;
+        self.set_cookie_header(header)
+            .map_err(|error| ServerFnError::http(500, error.to_string()))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn validate_cookie_name(name: &str) -> Result<(), ServerFnError> {
     if name.is_empty()
@@ -2034,6 +3231,26 @@ fn validate_cookie_value(value: &str) -> Result<(), ServerFnError> {
 #[cfg(not(target_arch = "wasm32"))]
 tokio::task_local! {
     static REQUEST_CONTEXT: RequestContext;
+    static REQUEST_BODY: std::sync::Mutex<Option<BoxedByteStream>>;
+}
+
+/// Runs `future` with the unread request `body` installed for
+/// [`take_request_body`]. Adapter mounts do this for `multipart/form-data`
+/// requests instead of buffering the body.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn with_request_body<F: std::future::Future>(body: BoxedByteStream, future: F) -> F::Output {
+    REQUEST_BODY.scope(std::sync::Mutex::new(Some(body)), future).await
+}
+
+/// Takes the streamed request body installed by the adapter mount. `None`
+/// when the body was buffered into the server function's arguments, or was
+/// already taken.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn take_request_body() -> Option<BoxedByteStream> {
+    REQUEST_BODY
+        .try_with(|body| body.lock().expect("request body lock poisoned").take())
+        .ok()
+        .flatten()
 }
 
 /// Runs `future` with `context` installed as the task-local request
@@ -2075,105 +3292,1145 @@ pub fn decode_current_multipart(body: &[u8], limits: MultipartLimits) -> Result<
     decode_multipart(content_type, body, limits)
 }
 
-// ---------------------------------------------------------------------------
-// Server state / cache helpers
-// ---------------------------------------------------------------------------
-
-/// Versioned in-memory server state for small fullstack examples and
-/// adapter-local caches.
-///
-/// This is deliberately process-local. Use a database or distributed cache
-/// when multiple server processes must share state.
+/// Decodes the current `multipart/form-data` request while it streams in.
+/// Adapter mounts hand multipart bodies to server functions unbuffered, so a
+/// `#[server]` function without arguments reads its form through this.
 #[cfg(not(target_arch = "wasm32"))]
-#[derive(Debug)]
-pub struct ServerState<T> {
-    value: std::sync::RwLock<T>,
-    version: std::sync::atomic::AtomicU64,
+pub fn current_multipart_stream(limits: MultipartLimits) -> Result<MultipartStream<BoxedByteStream>, ServerFnError> {
+    let body = take_request_body().ok_or_else(|| ServerFnError::http(400, "multipart request body missing"))?;
+    decode_current_multipart_stream(body, limits)
 }
 
+/// Streaming counterpart of [`decode_current_multipart`].
 #[cfg(not(target_arch = "wasm32"))]
-impl<T> ServerState<T>
+pub fn decode_current_multipart_stream<S, B>(body: S, limits: MultipartLimits) -> Result<MultipartStream<S>, ServerFnError>
 where
-    T: Clone,
+    S: futures::Stream<Item = Result<B, ServerFnError>> + Unpin,
+    B: AsRef<[u8]>,
 {
-    pub fn new(value: T) -> Self {
-        Self {
-            value: std::sync::RwLock::new(value),
-            version: std::sync::atomic::AtomicU64::new(1),
-        }
-    }
+    let context = request_context().ok_or_else(|| ServerFnError::http(400, "multipart request context missing"))?;
+    let content_type = context
+        .header("content-type")
+        .ok_or_else(|| ServerFnError::http(400, "multipart content-type missing"))?;
+    decode_multipart_stream(content_type, body, limits)
+}
 
-    pub fn get(&self) -> T {
-        self.value.read().expect("server state lock poisoned").clone()
-    }
+// ---------------------------------------------------------------------------
+// Resumable uploads (tus-like chunk protocol)
+// ---------------------------------------------------------------------------
+//
+// A small subset of the tus protocol, served from one adapter route:
+//
+// - `POST {base}` with `upload-length` (and optional url-encoded
+//   `upload-metadata`) creates an upload: `201`, `location: {base}/{id}`.
+// - `HEAD {base}/{id}` reports `upload-offset` / `upload-length`, so a client
+//   that lost its connection knows where to resume.
+// - `PATCH {base}/{id}` with `upload-offset` and an
+//   `application/offset+octet-stream` body appends a chunk: `204` with the new
+//   offset, `409` when the offset does not match the server's.
+// - `DELETE {base}/{id}` aborts and removes the upload.
+//
+// Offsets are tracked on disk next to the partial data, so a restarted server
+// resumes where it left off.
 
-    pub fn set(&self, value: T) {
-        *self.value.write().expect("server state lock poisoned") = value;
-        self.bump_version();
-    }
+pub const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+pub const UPLOAD_LENGTH_HEADER: &str = "upload-length";
+pub const UPLOAD_METADATA_HEADER: &str = "upload-metadata";
+/// Content type of a `PATCH` chunk body.
+pub const UPLOAD_CHUNK_CONTENT_TYPE: &str = "application/offset+octet-stream";
 
-    pub fn update<R>(&self, update: impl FnOnce(&mut T) -> R) -> R {
-        let mut value = self.value.write().expect("server state lock poisoned");
-        let result = update(&mut value);
-        drop(value);
-        self.bump_version();
-        result
-    }
+/// Server-side bookkeeping for one resumable upload.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ResumableUpload {
+    pub id: String,
+    pub length: u64,
+    pub offset: u64,
+    pub metadata: Vec<(String, String)>,
+}
 
-    pub fn version(&self) -> u64 {
-        self.version.load(std::sync::atomic::Ordering::Relaxed)
+#[cfg(not(target_arch = "wasm32"))]
+impl ResumableUpload {
+    pub fn is_complete(&self) -> bool {
+        self.offset == self.length
     }
 
-    fn bump_version(&self) {
-        self.version.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
     }
 }
 
+/// Disk-backed store for resumable uploads: `{id}.part` holds the received
+/// bytes and `{id}.json` the [`ResumableUpload`] record.
+///
+/// Requests for one upload are serialized; different uploads proceed in
+/// parallel. Uploads left untouched for [`expire_after`](Self::expire_after)
+/// are deleted. Creation goes through the [`authorize`](Self::authorize) hook
+/// and is refused once the stored uploads reach
+/// [`max_pending_uploads`](Self::max_pending_uploads) or
+/// [`max_pending_bytes`](Self::max_pending_bytes).
 #[cfg(not(target_arch = "wasm32"))]
-#[derive(Clone, Debug)]
-struct CacheEntry<V> {
-    value: V,
-    expires_at: Option<std::time::Instant>,
+#[derive(Clone)]
+pub struct ResumableUploads {
+    root: std::path::PathBuf,
+    base_path: String,
+    max_upload_bytes: u64,
+    max_pending_uploads: usize,
+    max_pending_bytes: u64,
+    expire_after: std::time::Duration,
+    authorize: Option<std::sync::Arc<UploadAuthorizeFn>>,
+    state: std::sync::Arc<std::sync::Mutex<UploadStoreState>>,
+    /// Held while checking the quotas and creating, so concurrent creations
+    /// cannot both take the last slot.
+    create_lock: std::sync::Arc<tokio::sync::Mutex<()>>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl<V> CacheEntry<V> {
-    fn new(value: V, ttl: Option<std::time::Duration>) -> Self {
-        Self {
-            value,
-            expires_at: ttl.map(|ttl| std::time::Instant::now() + ttl),
-        }
-    }
+type UploadAuthorizeFn = dyn Fn(&RequestContext, u64, &[(String, String)]) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ServerFnError>> + Send>>
+    + Send
+    + Sync;
 
-    fn is_expired(&self) -> bool {
-        self.expires_at.is_some_and(|expires_at| std::time::Instant::now() >= expires_at)
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for ResumableUploads {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResumableUploads")
+            .field("root", &self.root)
+            .field("base_path", &self.base_path)
+            .field("max_upload_bytes", &self.max_upload_bytes)
+            .field("max_pending_uploads", &self.max_pending_uploads)
+            .field("max_pending_bytes", &self.max_pending_bytes)
+            .field("expire_after", &self.expire_after)
+            .finish_non_exhaustive()
     }
 }
 
-/// Process-local key/value cache with explicit invalidation and optional TTL.
 #[cfg(not(target_arch = "wasm32"))]
-#[derive(Debug)]
-pub struct ServerCache<K, V> {
-    values: std::sync::RwLock<std::collections::HashMap<K, CacheEntry<V>>>,
-    version: std::sync::atomic::AtomicU64,
+#[derive(Debug, Default)]
+struct UploadStoreState {
+    /// Per-upload locks, dropped once no request holds them.
+    locks: std::collections::HashMap<String, std::sync::Weak<tokio::sync::Mutex<()>>>,
+    swept_at: Option<std::time::SystemTime>,
 }
 
+/// How long an upload may sit without a chunk before it is deleted.
 #[cfg(not(target_arch = "wasm32"))]
-impl<K, V> Default for ServerCache<K, V>
-where
-    K: Eq + std::hash::Hash + Clone,
-    V: Clone,
-{
-    fn default() -> Self {
-        Self::new()
-    }
-}
+pub const DEFAULT_UPLOAD_EXPIRY: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Largest `upload-length` accepted by default (64 MiB).
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 64 * 1024 * 1024;
+/// How many uploads may be stored at once by default.
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_PENDING_UPLOADS: usize = 64;
+/// Declared bytes all stored uploads may add up to by default (1 GiB).
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_PENDING_UPLOAD_BYTES: u64 = 1024 * 1024 * 1024;
 
 #[cfg(not(target_arch = "wasm32"))]
-impl<K, V> ServerCache<K, V>
-where
-    K: Eq + std::hash::Hash + Clone,
-    V: Clone,
+impl ResumableUploads {
+    /// Uploads are stored under `root`; `base_path` is the route prefix used
+    /// to build `location` headers (e.g. `/uploads`).
+    pub fn new(root: impl Into<std::path::PathBuf>, base_path: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            base_path: base_path.into().trim_end_matches('/').to_owned(),
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+            max_pending_uploads: DEFAULT_MAX_PENDING_UPLOADS,
+            max_pending_bytes: DEFAULT_MAX_PENDING_UPLOAD_BYTES,
+            expire_after: DEFAULT_UPLOAD_EXPIRY,
+            authorize: None,
+            state: Default::default(),
+            create_lock: Default::default(),
+        }
+    }
+
+    /// Largest `upload-length` accepted at creation (default
+    /// [`DEFAULT_MAX_UPLOAD_BYTES`]).
+    pub fn max_upload_bytes(mut self, max: u64) -> Self {
+        self.max_upload_bytes = max;
+        self
+    }
+
+    /// Most uploads stored at once, finished or not (default
+    /// [`DEFAULT_MAX_PENDING_UPLOADS`]). Creation beyond it answers `507`.
+    pub fn max_pending_uploads(mut self, max: usize) -> Self {
+        self.max_pending_uploads = max;
+        self
+    }
+
+    /// Most declared bytes all stored uploads may add up to (default
+    /// [`DEFAULT_MAX_PENDING_UPLOAD_BYTES`]). Creation beyond it answers `507`.
+    pub fn max_pending_bytes(mut self, max: u64) -> Self {
+        self.max_pending_bytes = max;
+        self
+    }
+
+    /// Asks `authorize` before every upload created through
+    /// [`handle`](Self::handle), with the request, the declared length and
+    /// the metadata. An `Err` is sent back as the response, e.g.
+    /// `ServerFnError::http(403, ..)` for a caller that may not upload or
+    /// `422` for metadata it rejects.
+    pub fn authorize<F, Fut>(mut self, authorize: F) -> Self
+    where
+        F: Fn(&RequestContext, u64, &[(String, String)]) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<(), ServerFnError>> + Send + 'static,
+    {
+        self.authorize = Some(std::sync::Arc::new(move |request, length, metadata| {
+            Box::pin(authorize(request, length, metadata))
+        }));
+        self
+    }
+
+    /// Deletes uploads that received nothing for `expiry`. Defaults to
+    /// [`DEFAULT_UPLOAD_EXPIRY`]; stale uploads are swept while creating new
+    /// ones, or explicitly with [`expire_stale`](Self::expire_stale).
+    pub fn expire_after(mut self, expiry: std::time::Duration) -> Self {
+        self.expire_after = expiry;
+        self
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    pub fn base_path(&self) -> &str {
+        &self.base_path
+    }
+
+    pub async fn create(&self, length: u64, metadata: Vec<(String, String)>) -> Result<ResumableUpload, ServerFnError> {
+        if length > self.max_upload_bytes {
+            return Err(ServerFnError::http(413, "upload exceeds size limit"));
+        }
+        if self.sweep_due() {
+            self.expire_stale().await?;
+        }
+        let _creating = self.create_lock.lock().await;
+        let (pending, pending_bytes) = self.pending().await?;
+        if pending >= self.max_pending_uploads || pending_bytes.saturating_add(length) > self.max_pending_bytes {
+            return Err(ServerFnError::http(507, "upload storage is full"));
+        }
+        tokio::fs::create_dir_all(&self.root).await.map_err(upload_io_error)?;
+        let upload = ResumableUpload {
+            id: next_random_id(),
+            length,
+            offset: 0,
+            metadata,
+        };
+        tokio::fs::File::create(self.data_path(&upload.id)).await.map_err(upload_io_error)?;
+        self.write_record(&upload).await?;
+        Ok(upload)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<ResumableUpload, ServerFnError> {
+        let lock = self.upload_lock(id);
+        let _guard = lock.lock().await;
+        self.read_record(id).await
+    }
+
+    /// Appends the chunks of `body` at `offset`, which must equal the stored
+    /// offset. The chunks are written as they arrive; when `body` fails part
+    /// way, the bytes already written are kept and the client resumes after
+    /// them.
+    pub async fn append<S, B>(&self, id: &str, offset: u64, body: S) -> Result<ResumableUpload, ServerFnError>
+    where
+        S: futures::Stream<Item = Result<B, ServerFnError>>,
+        B: AsRef<[u8]>,
+    {
+        use futures::StreamExt;
+        use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+        let lock = self.upload_lock(id);
+        let _guard = lock.lock().await;
+        let mut upload = self.read_record(id).await?;
+        if offset != upload.offset {
+            return Err(ServerFnError::http(409, format!("upload offset mismatch: expected {}", upload.offset)));
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(self.data_path(id))
+            .await
+            .map_err(upload_io_error)?;
+        // Drop bytes past the recorded offset left behind by an interrupted write.
+        file.set_len(upload.offset).await.map_err(upload_io_error)?;
+        file.seek(std::io::SeekFrom::Start(upload.offset)).await.map_err(upload_io_error)?;
+
+        let mut body = std::pin::pin!(body);
+        let mut result = Ok(());
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+            let chunk = chunk.as_ref();
+            if upload.offset + chunk.len() as u64 > upload.length {
+                result = Err(ServerFnError::http(413, "chunk exceeds declared upload length"));
+                break;
+            }
+            if let Err(err) = file.write_all(chunk).await {
+                result = Err(upload_io_error(err));
+                break;
+            }
+            upload.offset += chunk.len() as u64;
+        }
+        file.flush().await.map_err(upload_io_error)?;
+        self.write_record(&upload).await?;
+        result.map(|()| upload)
+    }
+
+    /// Moves a completed upload's data to `dest` and forgets the upload.
+    pub async fn finish(&self, id: &str, dest: impl AsRef<std::path::Path>) -> Result<ResumableUpload, ServerFnError> {
+        let lock = self.upload_lock(id);
+        let _guard = lock.lock().await;
+        let upload = self.read_record(id).await?;
+        if !upload.is_complete() {
+            return Err(ServerFnError::http(409, "upload is not complete"));
+        }
+        tokio::fs::rename(self.data_path(id), dest.as_ref()).await.map_err(upload_io_error)?;
+        let _ = tokio::fs::remove_file(self.record_path(id)).await;
+        Ok(upload)
+    }
+
+    pub async fn remove(&self, id: &str) -> Result<(), ServerFnError> {
+        let lock = self.upload_lock(id);
+        let _guard = lock.lock().await;
+        self.read_record(id).await?;
+        self.delete_files(id).await;
+        Ok(())
+    }
+
+    /// How many uploads are stored and the sum of their declared lengths.
+    async fn pending(&self) -> Result<(usize, u64), ServerFnError> {
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+            Err(err) => return Err(upload_io_error(err)),
+        };
+        let (mut count, mut bytes) = (0, 0u64);
+        while let Some(entry) = entries.next_entry().await.map_err(upload_io_error)? {
+            let file_name = entry.file_name();
+            if !file_name.to_str().is_some_and(|name| name.ends_with(".json")) {
+                continue;
+            }
+            count += 1;
+            // An unreadable record still takes a slot; count it as full size.
+            let length = match tokio::fs::read(entry.path()).await {
+                Ok(record) => serde_json::from_slice::<ResumableUpload>(&record).map_or(self.max_upload_bytes, |upload| upload.length),
+                Err(_) => self.max_upload_bytes,
+            };
+            bytes = bytes.saturating_add(length);
+        }
+        Ok((count, bytes))
+    }
+
+    /// Deletes every upload whose record has not changed for
+    /// [`expire_after`](Self::expire_after); returns how many were removed.
+    /// Uploads with a request in flight are left alone.
+    pub async fn expire_stale(&self) -> Result<usize, ServerFnError> {
+        let now = std::time::SystemTime::now();
+        self.state.lock().expect("upload store lock poisoned").swept_at = Some(now);
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(upload_io_error(err)),
+        };
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await.map_err(upload_io_error)? {
+            let file_name = entry.file_name();
+            let Some(id) = file_name.to_str().and_then(|name| name.strip_suffix(".json")) else {
+                continue;
+            };
+            let Ok(modified) = entry.metadata().await.and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if now.duration_since(modified).unwrap_or_default() < self.expire_after {
+                continue;
+            }
+            let lock = self.upload_lock(id);
+            let Ok(_guard) = lock.try_lock() else {
+                continue;
+            };
+            self.delete_files(id).await;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Serves one protocol request. `id` is the path segment after
+    /// `base_path` (`None` for the creation `POST`); `body` yields the
+    /// request body chunks as they arrive from the adapter.
+    pub async fn handle<S, B>(&self, request: &RequestContext, id: Option<&str>, body: S) -> ServerFnHttpResponse
+    where
+        S: futures::Stream<Item = Result<B, ServerFnError>>,
+        B: AsRef<[u8]>,
+    {
+        match self.try_handle(request, id, body).await {
+            Ok(response) => response,
+            Err(err) => server_fn_error_response_parts(&err),
+        }
+    }
+
+    async fn try_handle<S, B>(&self, request: &RequestContext, id: Option<&str>, body: S) -> Result<ServerFnHttpResponse, ServerFnError>
+    where
+        S: futures::Stream<Item = Result<B, ServerFnError>>,
+        B: AsRef<[u8]>,
+    {
+        let method = request.method.to_ascii_uppercase();
+        match (method.as_str(), id) {
+            ("POST", None) => {
+                let length = upload_header_u64(request, UPLOAD_LENGTH_HEADER)?;
+                let metadata: Vec<(String, String)> = request
+                    .header(UPLOAD_METADATA_HEADER)
+                    .map(|value| form_urlencoded::parse(value.as_bytes()).into_owned().collect())
+                    .unwrap_or_default();
+                if length > self.max_upload_bytes {
+                    return Err(ServerFnError::http(413, "upload exceeds size limit"));
+                }
+                if let Some(authorize) = &self.authorize {
+                    authorize(request, length, &metadata).await?;
+                }
+                let upload = self.create(length, metadata).await?;
+                let mut response = upload_response(201, &upload);
+                response
+                    .headers
+                    .push(("location".to_owned(), format!("{}/{}", self.base_path, upload.id)));
+                Ok(response)
+            }
+            ("HEAD", Some(id)) => {
+                let mut response = upload_response(200, &self.get(id).await?);
+                response.headers.push(("cache-control".to_owned(), "no-store".to_owned()));
+                Ok(response)
+            }
+            ("PATCH", Some(id)) => {
+                if request.content_type().as_deref() != Some(UPLOAD_CHUNK_CONTENT_TYPE) {
+                    return Err(ServerFnError::http(
+                        415,
+                        format!("chunk content-type must be {UPLOAD_CHUNK_CONTENT_TYPE}"),
+                    ));
+                }
+                let offset = upload_header_u64(request, UPLOAD_OFFSET_HEADER)?;
+                Ok(upload_response(204, &self.append(id, offset, body).await?))
+            }
+            ("DELETE", Some(id)) => {
+                self.remove(id).await?;
+                Ok(ServerFnHttpResponse {
+                    status: 204,
+                    headers: Vec::new(),
+                    body: Vec::new(),
+                })
+            }
+            _ => Err(ServerFnError::http(405, "unsupported upload request")),
+        }
+    }
+
+    fn upload_lock(&self, id: &str) -> std::sync::Arc<tokio::sync::Mutex<()>> {
+        let mut state = self.state.lock().expect("upload store lock poisoned");
+        state.locks.retain(|_, lock| lock.strong_count() > 0);
+        if let Some(lock) = state.locks.get(id).and_then(std::sync::Weak::upgrade) {
+            return lock;
+        }
+        let lock = std::sync::Arc::new(tokio::sync::Mutex::new(()));
+        state.locks.insert(id.to_owned(), std::sync::Arc::downgrade(&lock));
+        lock
+    }
+
+    fn sweep_due(&self) -> bool {
+        let state = self.state.lock().expect("upload store lock poisoned");
+        state
+            .swept_at
+            .is_none_or(|swept_at| swept_at.elapsed().unwrap_or_default() >= self.expire_after / 2)
+    }
+
+    async fn delete_files(&self, id: &str) {
+        let _ = tokio::fs::remove_file(self.data_path(id)).await;
+        let _ = tokio::fs::remove_file(self.record_path(id)).await;
+    }
+
+    fn data_path(&self, id: &str) -> std::path::PathBuf {
+        self.root.join(format!("{}.part", escape_cache_key(id)))
+    }
+
+    fn record_path(&self, id: &str) -> std::path::PathBuf {
+        self.root.join(format!("{}.json", escape_cache_key(id)))
+    }
+
+    async fn read_record(&self, id: &str) -> Result<ResumableUpload, ServerFnError> {
+        let bytes = match tokio::fs::read(self.record_path(id)).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err(ServerFnError::http(404, "upload not found")),
+            Err(err) => return Err(upload_io_error(err)),
+        };
+        let mut upload: ResumableUpload =
+            serde_json::from_slice(&bytes).map_err(|err| ServerFnError::ServerError(format!("corrupt upload record: {err}")))?;
+        // Chunks are not synced to disk, so after a crash the record may be
+        // ahead of the data; resume from what actually landed.
+        let written = tokio::fs::metadata(self.data_path(id)).await.map_err(upload_io_error)?.len();
+        upload.offset = upload.offset.min(written);
+        Ok(upload)
+    }
+
+    async fn write_record(&self, upload: &ResumableUpload) -> Result<(), ServerFnError> {
+        let bytes = serde_json::to_vec(upload).map_err(|err| ServerFnError::Serialization(err.to_string()))?;
+        let path = self.record_path(&upload.id);
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, bytes).await.map_err(upload_io_error)?;
+        tokio::fs::rename(tmp, path).await.map_err(upload_io_error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_response(status: u16, upload: &ResumableUpload) -> ServerFnHttpResponse {
+    ServerFnHttpResponse {
+        status,
+        headers: vec![
+            (UPLOAD_OFFSET_HEADER.to_owned(), upload.offset.to_string()),
+            (UPLOAD_LENGTH_HEADER.to_owned(), upload.length.to_string()),
+        ],
+        body: Vec::new(),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_header_u64(request: &RequestContext, name: &str) -> Result<u64, ServerFnError> {
+    request
+        .header(name)
+        .ok_or_else(|| ServerFnError::http(400, format!("{name} header missing")))?
+        .trim()
+        .parse()
+        .map_err(|_| ServerFnError::http(400, format!("invalid {name} header")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn upload_io_error(err: std::io::Error) -> ServerFnError {
+    ServerFnError::ServerError(format!("upload storage failed: {err}"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// 128 random bits, hex encoded.
+fn next_random_id() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("the operating system provides randomness");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Rate limiting middleware
+// ---------------------------------------------------------------------------
+//
+// A [`RateLimiter`] lives in a `static` and is wired to a server function
+// through a one-line middleware fn, since `#[server(middleware = ..)]` takes
+// plain function paths:
+//
+// ```ignore
+// static LOGIN_LIMIT: LazyLock<RateLimiter> =
+//     LazyLock::new(|| RateLimiter::token_bucket(5, 0.1).keyed_by(RateLimitKey::ClientIp { trusted_hops: 1 }));
+//
+// fn login_limit(ctx: ServerFnMiddlewareContext) -> BoxedServerFnMiddlewareFuture {
+//     LOGIN_LIMIT.middleware(ctx)
+// }
+//
+// #[server(middleware = login_limit)]
+// async fn login(form: LoginForm) -> Result<(), ServerFnError> { .. }
+// ```
+//
+// Time is injected through [`RateLimiter::check_at`] (milliseconds since the
+// Unix epoch) so the algorithms stay deterministic under test.
+
+/// Which counting algorithm a [`RateLimiter`] applies per key.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RateLimitPolicy {
+    /// Bursts of up to `capacity` requests, refilled at `refill_per_sec`
+    /// tokens per second.
+    TokenBucket { capacity: u32, refill_per_sec: f64 },
+    /// At most `limit` requests in any rolling `window_secs` window
+    /// (approximated from the current and previous fixed windows).
+    SlidingWindow { limit: u32, window_secs: u64 },
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimitPolicy {
+    /// How long a key nobody touches takes to count as new again, after
+    /// which a store can forget it; `None` when it never does.
+    pub fn idle_ttl(&self) -> Option<std::time::Duration> {
+        match *self {
+            Self::TokenBucket { capacity, refill_per_sec } => {
+                (refill_per_sec > 0.0).then(|| std::time::Duration::from_secs_f64((f64::from(capacity) / refill_per_sec).ceil().min(u64::MAX as f64)))
+            }
+            Self::SlidingWindow { window_secs, .. } => Some(std::time::Duration::from_secs(window_secs.max(1).saturating_mul(2))),
+        }
+    }
+}
+
+/// What a [`RateLimiter`] counts requests against.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub enum RateLimitKey {
+    /// The client IP. With `trusted_hops == 0` this is the connected peer;
+    /// otherwise the address `trusted_hops` entries from the right of
+    /// `X-Forwarded-For`, i.e. the one appended by the outermost proxy you run,
+    /// or the connected peer when the header has fewer entries. Requests
+    /// whose address the adapter doesn't know share the
+    /// [`UNKNOWN_CLIENT_KEY`] bucket.
+    ClientIp { trusted_hops: usize },
+    /// The value of the named cookie (e.g. a session id), falling back to the
+    /// peer IP when the cookie is absent so dropping it does not bypass the
+    /// limit.
+    Cookie(String),
+    /// A caller-derived key. Returning `None` exempts the request.
+    Custom(std::sync::Arc<dyn Fn(&ServerFnMiddlewareContext) -> Option<String> + Send + Sync>),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ClientIp { trusted_hops } => f.debug_struct("ClientIp").field("trusted_hops", trusted_hops).finish(),
+            Self::Cookie(name) => f.debug_tuple("Cookie").field(name).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimitKey {
+    pub fn custom(key: impl Fn(&ServerFnMiddlewareContext) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::Custom(std::sync::Arc::new(key))
+    }
+
+    /// The key for `context`, or `None` when the request is not limited
+    /// (no request context, or a custom key declined it).
+    pub fn resolve(&self, context: &ServerFnMiddlewareContext) -> Option<String> {
+        match self {
+            Self::ClientIp { trusted_hops } => Some(client_ip(context.request.as_ref()?, *trusted_hops)),
+            Self::Cookie(name) => {
+                let request = context.request.as_ref()?;
+                Some(
+                    request
+                        .cookie(name)
+                        .map(|value| format!("cookie:{value}"))
+                        .unwrap_or_else(|| client_ip(request, 0)),
+                )
+            }
+            Self::Custom(key) => key(context),
+        }
+    }
+}
+
+/// The [`RateLimitKey::ClientIp`] key of requests whose address is unknown,
+/// e.g. from an adapter that doesn't record the peer. They share one bucket
+/// rather than going unlimited.
+#[cfg(not(target_arch = "wasm32"))]
+pub const UNKNOWN_CLIENT_KEY: &str = "unknown";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn client_ip(request: &RequestContext, trusted_hops: usize) -> String {
+    if trusted_hops > 0
+        && let Some(forwarded) = request.header("x-forwarded-for")
+    {
+        let hops: Vec<&str> = forwarded.split(',').map(str::trim).filter(|hop| !hop.is_empty()).collect();
+        // Fewer hops than trusted proxies: the header did not come through
+        // them, so none of it is trusted.
+        if let Some(hop) = hops.len().checked_sub(trusted_hops).and_then(|index| hops.get(index)) {
+            return (*hop).to_owned();
+        }
+    }
+    request.peer_ip().unwrap_or(UNKNOWN_CLIENT_KEY).to_owned()
+}
+
+/// Per-key counter state persisted by a [`RateLimitStore`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RateLimitState {
+    TokenBucket { tokens: f64, updated_at_ms: u64 },
+    SlidingWindow { window_start_ms: u64, current: u32, previous: u32 },
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimitState {
+    /// When the state was last counted against, as far as it records.
+    fn touched_at_ms(&self) -> u64 {
+        match self {
+            Self::TokenBucket { updated_at_ms, .. } => *updated_at_ms,
+            Self::SlidingWindow { window_start_ms, .. } => *window_start_ms,
+        }
+    }
+}
+
+/// Storage for [`RateLimitState`]. `update` must be atomic per key with
+/// respect to other callers of the same store.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Replaces the state under `key` with `update(previous)` and returns it.
+    fn update(&self, key: &str, update: &mut dyn FnMut(Option<RateLimitState>) -> RateLimitState) -> Result<RateLimitState, ServerFnError>;
+
+    /// Drops the state under `key`.
+    fn reset(&self, key: &str) -> Result<(), ServerFnError>;
+}
+
+/// Process-local [`RateLimitStore`]. Keys left alone longer than its idle TTL
+/// are forgotten, so the map does not grow with every client ever seen.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct MemoryRateLimitStore {
+    states: std::sync::Mutex<MemoryRateLimitStates>,
+    idle_ttl_ms: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+struct MemoryRateLimitStates {
+    states: std::collections::HashMap<String, RateLimitState>,
+    swept_at_ms: u64,
+}
+
+/// How long [`MemoryRateLimitStore::new`] keeps a key nobody touches.
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_RATE_LIMIT_IDLE_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// The longest `Retry-After` a [`RateLimiter`] sends, e.g. for a token bucket
+/// that never refills.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_RETRY_AFTER_SECS: u64 = 24 * 60 * 60;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self {
+            states: Default::default(),
+            idle_ttl_ms: DEFAULT_RATE_LIMIT_IDLE_TTL.as_millis() as u64,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets keys not updated for `ttl`, measured on the timestamps of
+    /// their states. Defaults to [`DEFAULT_RATE_LIMIT_IDLE_TTL`]; a
+    /// [`RateLimiter`]'s own store uses [`RateLimitPolicy::idle_ttl`].
+    pub fn idle_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.idle_ttl_ms = (ttl.as_millis() as u64).max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.lock().expect("rate limit store lock poisoned").states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimitStore for MemoryRateLimitStore {
+    fn update(&self, key: &str, update: &mut dyn FnMut(Option<RateLimitState>) -> RateLimitState) -> Result<RateLimitState, ServerFnError> {
+        let mut states = self.states.lock().expect("rate limit store lock poisoned");
+        let state = update(states.states.remove(key));
+        // The newest state carries the current time; sweep idle keys at most
+        // twice per TTL.
+        let now_ms = state.touched_at_ms();
+        if now_ms.saturating_sub(states.swept_at_ms) >= self.idle_ttl_ms / 2 {
+            let idle_ttl_ms = self.idle_ttl_ms;
+            states
+                .states
+                .retain(|_, state| now_ms.saturating_sub(state.touched_at_ms()) < idle_ttl_ms);
+            states.swept_at_ms = now_ms;
+        }
+        states.states.insert(key.to_owned(), state.clone());
+        Ok(state)
+    }
+
+    fn reset(&self, key: &str) -> Result<(), ServerFnError> {
+        self.states.lock().expect("rate limit store lock poisoned").states.remove(key);
+        Ok(())
+    }
+}
+
+/// [`RateLimitStore`] keeping one JSON file per key under `root`, so limits
+/// survive restarts. File names go through [`escape_cache_key`]. Like
+/// [`MemoryRateLimitStore`], it deletes the files of keys left alone longer
+/// than its idle TTL, so `root` does not fill up with one file per cookie or
+/// address ever seen. Updates are serialized within the process; several
+/// processes sharing `root` may race.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileRateLimitStore {
+    root: std::path::PathBuf,
+    /// Serializes updates; holds when idle files were last swept.
+    swept_at_ms: std::sync::Mutex<u64>,
+    idle_ttl_ms: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileRateLimitStore {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            swept_at_ms: std::sync::Mutex::new(0),
+            idle_ttl_ms: DEFAULT_RATE_LIMIT_IDLE_TTL.as_millis() as u64,
+        }
+    }
+
+    /// Deletes the files of keys not updated for `ttl`, measured on the
+    /// timestamps of their states. Defaults to [`DEFAULT_RATE_LIMIT_IDLE_TTL`].
+    pub fn idle_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.idle_ttl_ms = (ttl.as_millis() as u64).max(1);
+        self
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    fn state_path(&self, key: &str) -> std::path::PathBuf {
+        self.root.join(format!("{}.json", escape_cache_key(key)))
+    }
+
+    /// Deletes the state files idle at `now_ms`, and unreadable ones.
+    fn sweep(&self, now_ms: u64) {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return;
+        };
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let idle = std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<RateLimitState>(&bytes).ok())
+                .is_none_or(|state| now_ms.saturating_sub(state.touched_at_ms()) >= self.idle_ttl_ms);
+            if idle {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimitStore for FileRateLimitStore {
+    fn update(&self, key: &str, update: &mut dyn FnMut(Option<RateLimitState>) -> RateLimitState) -> Result<RateLimitState, ServerFnError> {
+        let mut swept_at_ms = self.swept_at_ms.lock().expect("rate limit store lock poisoned");
+        let path = self.state_path(key);
+        let previous = match std::fs::read(&path) {
+            // A corrupt file resets the key instead of locking the client out.
+            Ok(bytes) => serde_json::from_slice(&bytes).ok(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(ServerFnError::ServerError(format!("read rate limit state failed: {err}"))),
+        };
+        let state = update(previous);
+        // As in the memory store: sweep at most twice per TTL, on the time
+        // the newest state carries.
+        let now_ms = state.touched_at_ms();
+        if now_ms.saturating_sub(*swept_at_ms) >= self.idle_ttl_ms / 2 {
+            self.sweep(now_ms);
+            *swept_at_ms = now_ms;
+        }
+        std::fs::create_dir_all(&self.root).map_err(|err| ServerFnError::ServerError(format!("create rate limit dir failed: {err}")))?;
+        let json = serde_json::to_vec(&state).map_err(|err| ServerFnError::Serialization(err.to_string()))?;
+        std::fs::write(&path, json).map_err(|err| ServerFnError::ServerError(format!("write rate limit state failed: {err}")))?;
+        Ok(state)
+    }
+
+    fn reset(&self, key: &str) -> Result<(), ServerFnError> {
+        let _guard = self.swept_at_ms.lock().expect("rate limit store lock poisoned");
+        match std::fs::remove_file(self.state_path(key)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(ServerFnError::ServerError(format!("remove rate limit state failed: {err}"))),
+        }
+    }
+}
+
+/// Outcome of one [`RateLimiter::check_at`] call.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed,
+    /// Rejected; the client may retry after this many seconds, at most
+    /// [`MAX_RETRY_AFTER_SECS`].
+    Limited {
+        retry_after_secs: u64,
+    },
+}
+
+/// Per-client throttle for server functions. See the section comment above
+/// for wiring it through `#[server(middleware = ..)]`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RateLimiter {
+    policy: RateLimitPolicy,
+    key: RateLimitKey,
+    store: Box<dyn RateLimitStore>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("policy", &self.policy)
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RateLimiter {
+    /// A limiter keyed by peer IP with an in-memory store that forgets keys
+    /// once they count as new again.
+    pub fn new(policy: RateLimitPolicy) -> Self {
+        let store = match policy.idle_ttl() {
+            Some(ttl) => MemoryRateLimitStore::new().idle_ttl(ttl),
+            None => MemoryRateLimitStore::new(),
+        };
+        Self {
+            policy,
+            key: RateLimitKey::ClientIp { trusted_hops: 0 },
+            store: Box::new(store),
+        }
+    }
+
+    pub fn token_bucket(capacity: u32, refill_per_sec: f64) -> Self {
+        Self::new(RateLimitPolicy::TokenBucket { capacity, refill_per_sec })
+    }
+
+    pub fn sliding_window(limit: u32, window_secs: u64) -> Self {
+        Self::new(RateLimitPolicy::SlidingWindow { limit, window_secs })
+    }
+
+    pub fn keyed_by(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_store(mut self, store: impl RateLimitStore) -> Self {
+        self.store = Box::new(store);
+        self
+    }
+
+    pub fn policy(&self) -> RateLimitPolicy {
+        self.policy
+    }
+
+    /// Counts one request for `context` at `now_ms` (milliseconds since the
+    /// Unix epoch). Requests without a resolvable key are always allowed.
+    pub fn check_at(&self, context: &ServerFnMiddlewareContext, now_ms: u64) -> Result<RateLimitDecision, ServerFnError> {
+        let Some(key) = self.key.resolve(context) else {
+            return Ok(RateLimitDecision::Allowed);
+        };
+        self.check_key_at(&key, now_ms)
+    }
+
+    /// Counts one request against an explicit `key` at `now_ms`.
+    pub fn check_key_at(&self, key: &str, now_ms: u64) -> Result<RateLimitDecision, ServerFnError> {
+        let mut decision = RateLimitDecision::Allowed;
+        let policy = self.policy;
+        self.store.update(key, &mut |previous| {
+            let (state, outcome) = apply_rate_limit(policy, previous, now_ms);
+            decision = outcome;
+            state
+        })?;
+        Ok(decision)
+    }
+
+    /// Forgets the counter for `key`, e.g. after a successful login.
+    pub fn reset(&self, key: &str) -> Result<(), ServerFnError> {
+        self.store.reset(key)
+    }
+
+    /// Middleware body: `Ok(())` when allowed, otherwise a 429
+    /// [`ServerFnError`] carrying a `Retry-After` header.
+    pub fn middleware(&'static self, context: ServerFnMiddlewareContext) -> BoxedServerFnMiddlewareFuture {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let result = self.check_at(&context, now_ms).and_then(|decision| match decision {
+            RateLimitDecision::Allowed => Ok(()),
+            RateLimitDecision::Limited { retry_after_secs } => {
+                Err(ServerFnError::http(429, "too many requests").with_header("retry-after", retry_after_secs.to_string()))
+            }
+        });
+        Box::pin(std::future::ready(result))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn apply_rate_limit(policy: RateLimitPolicy, previous: Option<RateLimitState>, now_ms: u64) -> (RateLimitState, RateLimitDecision) {
+    match policy {
+        RateLimitPolicy::TokenBucket { capacity, refill_per_sec } => {
+            let capacity = f64::from(capacity);
+            let tokens = match previous {
+                Some(RateLimitState::TokenBucket { tokens, updated_at_ms }) => {
+                    let elapsed_secs = now_ms.saturating_sub(updated_at_ms) as f64 / 1000.0;
+                    (tokens + elapsed_secs * refill_per_sec).min(capacity)
+                }
+                _ => capacity,
+            };
+            let (tokens, decision) = if tokens >= 1.0 {
+                (tokens - 1.0, RateLimitDecision::Allowed)
+            } else {
+                let wait_secs = if refill_per_sec > 0.0 {
+                    ((1.0 - tokens) / refill_per_sec).ceil() as u64
+                } else {
+                    MAX_RETRY_AFTER_SECS
+                };
+                (
+                    tokens,
+                    RateLimitDecision::Limited {
+                        retry_after_secs: wait_secs.clamp(1, MAX_RETRY_AFTER_SECS),
+                    },
+                )
+            };
+            (
+                RateLimitState::TokenBucket {
+                    tokens,
+                    updated_at_ms: now_ms,
+                },
+                decision,
+            )
+        }
+        RateLimitPolicy::SlidingWindow { limit, window_secs } => {
+            let window_ms = window_secs.max(1) * 1000;
+            let window_start_ms = now_ms - now_ms % window_ms;
+            let (mut current, previous_count) = match previous {
+                Some(RateLimitState::SlidingWindow {
+                    window_start_ms: start,
+                    current,
+                    previous,
+                }) if start == window_start_ms => (current, previous),
+                Some(RateLimitState::SlidingWindow {
+                    window_start_ms: start,
+                    current,
+                    ..
+                }) if start + window_ms == window_start_ms => (0, current),
+                _ => (0, 0),
+            };
+            let previous_weight = (window_ms - (now_ms - window_start_ms)) as f64 / window_ms as f64;
+            let estimated = f64::from(previous_count) * previous_weight + f64::from(current);
+            let decision = if estimated + 1.0 > f64::from(limit) {
+                RateLimitDecision::Limited {
+                    retry_after_secs: (window_start_ms + window_ms - now_ms).div_ceil(1000).clamp(1, MAX_RETRY_AFTER_SECS),
+                }
+            } else {
+                current += 1;
+                RateLimitDecision::Allowed
+            };
+            (
+                RateLimitState::SlidingWindow {
+                    window_start_ms,
+                    current,
+                    previous: previous_count,
+                },
+                decision,
+            )
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server state / cache helpers
+// ---------------------------------------------------------------------------
+
+/// Versioned in-memory server state for small fullstack examples and
+/// adapter-local caches.
+///
+/// This is deliberately process-local. Use a database or distributed cache
+/// when multiple server processes must share state.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct ServerState<T> {
+    value: std::sync::RwLock<T>,
+    version: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> ServerState<T>
+where
+    T: Clone,
+{
+    pub fn new(value: T) -> Self {
+        Self {
+            value: std::sync::RwLock::new(value),
+            version: std::sync::atomic::AtomicU64::new(1),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.read().expect("server state lock poisoned").clone()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.value.write().expect("server state lock poisoned") = value;
+        self.bump_version();
+    }
+
+    pub fn update<R>(&self, update: impl FnOnce(&mut T) -> R) -> R {
+        let mut value = self.value.write().expect("server state lock poisoned");
+        let result = update(&mut value);
+        drop(value);
+        self.bump_version();
+        result
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn bump_version(&self) {
+        self.version.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+struct CacheEntry<V> {
+    value: V,
+    expires_at: Option<std::time::Instant>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<V> CacheEntry<V> {
+    fn new(value: V, ttl: Option<std::time::Duration>) -> Self {
+        Self {
+            value,
+            expires_at: ttl.map(|ttl| std::time::Instant::now() + ttl),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| std::time::Instant::now() >= expires_at)
+    }
+}
+
+/// Process-local key/value cache with explicit invalidation and optional TTL.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct ServerCache<K, V> {
+    values: std::sync::RwLock<std::collections::HashMap<K, CacheEntry<V>>>,
+    version: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<K, V> Default for ServerCache<K, V>
+where
+    K: Eq + std::hash::Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<K, V> ServerCache<K, V>
+where
+    K: Eq + std::hash::Hash + Clone,
+    V: Clone,
 {
     pub fn new() -> Self {
         Self {
@@ -2340,7 +4597,7 @@ impl CachedRender {
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Default)]
 pub struct IncrementalCache {
-    entries: std::sync::RwLock<std::collections::HashMap<String, CachedRender>>,
+    entries: std::sync::RwLock<std::collections::HashMap<String, IsrEntry>>,
 }
 
 /// Outcome of a stale-while-revalidate lookup.
@@ -2361,13 +4618,16 @@ impl IncrementalCache {
     /// Caches `body` under `key`, generated at `now` with an optional TTL.
     pub fn insert(&self, key: impl Into<String>, body: impl Into<Vec<u8>>, ttl_secs: Option<u64>, now: u64) {
         let render = CachedRender::new(body.into(), RenderFreshness::new(now, ttl_secs));
-        self.entries.write().expect("incremental cache lock poisoned").insert(key.into(), render);
+        self.entries
+            .write()
+            .expect("incremental cache lock poisoned")
+            .insert(key.into(), IsrEntry::new(render));
     }
 
     /// Returns the cached render only if it exists and is still fresh at `now`.
     pub fn get(&self, key: &str, now: u64) -> Option<CachedRender> {
         let entries = self.entries.read().expect("incremental cache lock poisoned");
-        let render = entries.get(key)?;
+        let render = &entries.get(key)?.render;
         (!render.freshness.is_stale(now)).then(|| render.clone())
     }
 
@@ -2375,7 +4635,7 @@ impl IncrementalCache {
     /// caller should regenerate it. Returns `None` only on a true miss.
     pub fn get_stale_while_revalidate(&self, key: &str, now: u64) -> Option<StaleRender> {
         let entries = self.entries.read().expect("incremental cache lock poisoned");
-        let render = entries.get(key)?.clone();
+        let render = entries.get(key)?.render.clone();
         let needs_revalidation = render.freshness.is_stale(now);
         Some(StaleRender { render, needs_revalidation })
     }
@@ -2419,6 +4679,17 @@ pub struct FileSystemCache {
 struct FileSystemCacheMeta {
     key: String,
     freshness: RenderFreshness,
+    /// Status and headers of an ISR page; entries written without one
+    /// answer `200` with no extra headers.
+    #[serde(default = "default_cached_status")]
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_cached_status() -> u16 {
+    200
 }
 
 /// Escapes a cache key into a filesystem-safe slug. Every byte outside the
@@ -2474,10 +4745,16 @@ impl FileSystemCache {
 
     /// Persists `body` with an explicit [`RenderFreshness`].
     pub fn write(&self, key: &str, body: &[u8], freshness: RenderFreshness) -> Result<(), ServerFnError> {
+        self.write_entry(key, body, freshness, &glory_core::web::ResponseHead::default())
+    }
+
+    fn write_entry(&self, key: &str, body: &[u8], freshness: RenderFreshness, head: &glory_core::web::ResponseHead) -> Result<(), ServerFnError> {
         std::fs::create_dir_all(&self.root).map_err(|err| ServerFnError::ServerError(format!("create cache dir failed: {err}")))?;
         let meta = FileSystemCacheMeta {
             key: key.to_owned(),
             freshness,
+            status: head.status,
+            headers: head.headers.clone(),
         };
         let meta_json = serde_json::to_vec(&meta).map_err(|err| ServerFnError::Serialization(err.to_string()))?;
         std::fs::write(self.body_path(key), body).map_err(|err| ServerFnError::ServerError(format!("write cache body failed: {err}")))?;
@@ -2488,6 +4765,10 @@ impl FileSystemCache {
     /// Loads the persisted render for `key`, fresh or stale. Returns `None`
     /// when either sidecar file is absent.
     pub fn load(&self, key: &str) -> Result<Option<CachedRender>, ServerFnError> {
+        Ok(self.load_entry(key)?.map(|entry| entry.render))
+    }
+
+    fn load_entry(&self, key: &str) -> Result<Option<IsrEntry>, ServerFnError> {
         let meta_path = self.meta_path(key);
         let body_path = self.body_path(key);
         if !meta_path.exists() || !body_path.exists() {
@@ -2496,7 +4777,13 @@ impl FileSystemCache {
         let meta_bytes = std::fs::read(&meta_path).map_err(|err| ServerFnError::ServerError(format!("read cache meta failed: {err}")))?;
         let meta: FileSystemCacheMeta = serde_json::from_slice(&meta_bytes).map_err(|err| ServerFnError::Deserialization(err.to_string()))?;
         let body = std::fs::read(&body_path).map_err(|err| ServerFnError::ServerError(format!("read cache body failed: {err}")))?;
-        Ok(Some(CachedRender::new(body, meta.freshness)))
+        Ok(Some(IsrEntry {
+            render: CachedRender::new(body, meta.freshness),
+            head: glory_core::web::ResponseHead {
+                status: meta.status,
+                headers: meta.headers,
+            },
+        }))
     }
 
     /// Returns the persisted render only if it exists and is still fresh.
@@ -2523,10 +4810,709 @@ impl FileSystemCache {
                 Err(err) => return Err(ServerFnError::ServerError(format!("remove cache file failed: {err}"))),
             }
         }
-        Ok(removed)
+        Ok(removed)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ISR request handling (adapter-neutral)
+// ---------------------------------------------------------------------------
+//
+// [`IsrCache`] ties the caches above to HTTP: it derives a cache key from the
+// request, decides hit / stale / miss, regenerates stale entries on a
+// background thread while the stale copy is served, and answers on-demand
+// [`revalidate_path`] / [`revalidate_tag`] calls made from server functions.
+// The Salvo/Axum/Actix adapter crates only translate their request type into
+// a [`RequestContext`] and the resulting [`IsrResponse`] back into theirs.
+
+/// Storage backend behind an [`IsrCache`]. Implemented for the in-memory
+/// [`IncrementalCache`] and the on-disk [`FileSystemCache`].
+#[cfg(not(target_arch = "wasm32"))]
+pub trait RenderStore: Send + Sync + 'static {
+    /// Loads the entry for `key`, fresh or stale.
+    fn load(&self, key: &str) -> Result<Option<IsrEntry>, ServerFnError>;
+    /// Stores `entry` under `key`, replacing any previous entry.
+    fn store(&self, key: &str, entry: IsrEntry) -> Result<(), ServerFnError>;
+    /// Drops the render for `key`. Returns whether something was removed.
+    fn remove(&self, key: &str) -> Result<bool, ServerFnError>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RenderStore for IncrementalCache {
+    fn load(&self, key: &str) -> Result<Option<IsrEntry>, ServerFnError> {
+        Ok(self.entries.read().expect("incremental cache lock poisoned").get(key).cloned())
+    }
+
+    fn store(&self, key: &str, entry: IsrEntry) -> Result<(), ServerFnError> {
+        self.entries
+            .write()
+            .expect("incremental cache lock poisoned")
+            .insert(key.to_owned(), entry);
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ServerFnError> {
+        Ok(self.invalidate(key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RenderStore for FileSystemCache {
+    fn load(&self, key: &str) -> Result<Option<IsrEntry>, ServerFnError> {
+        self.load_entry(key)
+    }
+
+    fn store(&self, key: &str, entry: IsrEntry) -> Result<(), ServerFnError> {
+        self.write_entry(key, &entry.render.body, entry.render.freshness, &entry.head)
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ServerFnError> {
+        self.invalidate(key)
+    }
+}
+
+/// A cached page: the render plus the status and headers it answered with.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IsrEntry {
+    pub render: CachedRender,
+    pub head: glory_core::web::ResponseHead,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrEntry {
+    /// An entry answering `200` with no extra headers.
+    pub fn new(render: CachedRender) -> Self {
+        Self {
+            render,
+            head: glory_core::web::ResponseHead::default(),
+        }
+    }
+}
+
+/// One cacheable route declared on an [`IsrConfig`].
+///
+/// Patterns are matched segment by segment: a literal segment must match
+/// exactly, `{name}` or `:name` matches any single segment, and a trailing
+/// `*`, `{*rest}` or `{**rest}` matches the remainder of the path.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IsrRoute {
+    pub pattern: String,
+    /// Seconds a render stays fresh; `None` keeps it until revalidated.
+    pub ttl_secs: Option<u64>,
+    /// Tags for [`revalidate_tag`].
+    pub tags: Vec<String>,
+    /// Query parameters that select a different render. Every other
+    /// parameter is dropped from the cache key and from the URI the page is
+    /// rendered with; by default the whole query is ignored.
+    pub query_params: Vec<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrRoute {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ttl_secs: None,
+            tags: Vec::new(),
+            query_params: Vec::new(),
+        }
+    }
+
+    pub fn ttl_secs(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = Some(ttl_secs);
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn query_param(mut self, name: impl Into<String>) -> Self {
+        self.query_params.push(name.into());
+        self
+    }
+
+    /// `path` followed by the allowed parameters of `query`, sorted so their
+    /// order does not split the cache.
+    fn keyed_uri(&self, path: &str, query: &str) -> String {
+        let mut kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| {
+                let name = pair.split('=').next().unwrap_or_default();
+                !name.is_empty() && self.query_params.iter().any(|allowed| allowed == name)
+            })
+            .collect();
+        if kept.is_empty() {
+            return path.to_owned();
+        }
+        kept.sort_unstable();
+        format!("{path}?{}", kept.join("&"))
+    }
+
+    /// True when `path` (without query string) matches this route's pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        for part in self.pattern.split('/').filter(|part| !part.is_empty()) {
+            if part == "*" || part.starts_with("{*") {
+                return true;
+            }
+            let Some(segment) = segments.next() else {
+                return false;
+            };
+            let dynamic = part.starts_with(':') || (part.starts_with('{') && part.ends_with('}'));
+            if !dynamic && part != segment {
+                return false;
+            }
+        }
+        segments.next().is_none()
+    }
+}
+
+/// Default for [`IsrConfig::max_entries`].
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_ISR_MAX_ENTRIES: usize = 10_000;
+
+/// Which requests an [`IsrCache`] serves and how it keys them.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IsrConfig {
+    /// Cacheable routes, first match wins. Requests matching none of them
+    /// bypass the cache and render fresh.
+    pub routes: Vec<IsrRoute>,
+    /// Request headers folded into the cache key (names lowercased), e.g.
+    /// `accept-language` for per-locale renders.
+    pub vary_headers: Vec<String>,
+    /// Most keys kept in the store; the least recently used one is dropped
+    /// to make room, on disk as well as in memory.
+    pub max_entries: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for IsrConfig {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            vary_headers: Vec::new(),
+            max_entries: DEFAULT_ISR_MAX_ENTRIES,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    pub fn route(mut self, route: IsrRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn vary_header(mut self, name: impl Into<String>) -> Self {
+        self.vary_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// The first declared route matching `path`.
+    pub fn route_for(&self, path: &str) -> Option<&IsrRoute> {
+        self.routes.iter().find(|route| route.matches(path))
+    }
+
+    /// Cache key for a request: its path and the query parameters its route
+    /// allows (see [`IsrRoute::query_param`]), followed by the value of every
+    /// configured vary header (missing headers count as empty).
+    pub fn cache_key(&self, context: &RequestContext) -> String {
+        let uri = request_path_and_query(&context.uri);
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        let mut key = match self.route_for(path) {
+            Some(route) => route.keyed_uri(path, query),
+            None => path.to_owned(),
+        };
+        for name in &self.vary_headers {
+            key.push('\n');
+            key.push_str(name);
+            key.push('=');
+            key.push_str(context.header(name).unwrap_or_default());
+        }
+        key
+    }
+}
+
+/// Strips scheme and authority from an absolute request URI.
+#[cfg(not(target_arch = "wasm32"))]
+fn request_path_and_query(uri: &str) -> &str {
+    match uri.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |index| &rest[index..]),
+        None => uri,
+    }
+}
+
+/// How an [`IsrResponse`] body was produced.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IsrStatus {
+    /// Served from a fresh cached render.
+    Hit,
+    /// Served from a stale render while a regeneration runs in the background.
+    Stale,
+    /// Rendered for this request and cached.
+    Miss,
+    /// The route is not cacheable, or the page answered with a non-2xx status
+    /// or set a cookie; rendered fresh and not stored.
+    Bypass,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hit => "HIT",
+            Self::Stale => "STALE",
+            Self::Miss => "MISS",
+            Self::Bypass => "BYPASS",
+        }
+    }
+}
+
+/// Header carrying [`IsrStatus::as_str`] on every ISR response.
+#[cfg(not(target_arch = "wasm32"))]
+pub const ISR_STATUS_HEADER: &str = "x-glory-cache";
+
+/// Adapter-neutral ISR answer: the HTML body, the status and headers the
+/// page set, plus the caching headers the adapter should attach over them.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IsrResponse {
+    pub status: IsrStatus,
+    /// Status and headers set through the page's `ServerResponseOptions`.
+    pub head: glory_core::web::ResponseHead,
+    pub body: Vec<u8>,
+    /// `Cache-Control`, `Age` and [`ISR_STATUS_HEADER`], names lowercased.
+    /// A bypassed page that set its own `Cache-Control` keeps it.
+    pub headers: Vec<(String, String)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrResponse {
+    fn new(status: IsrStatus, head: glory_core::web::ResponseHead, body: Vec<u8>, freshness: Option<RenderFreshness>, now: u64) -> Self {
+        let mut headers = Vec::with_capacity(3);
+        let cache_control = match freshness {
+            None if head.header("cache-control").is_some() => None,
+            None => Some("no-cache".to_owned()),
+            Some(RenderFreshness { ttl_secs: None, .. }) => Some("public, max-age=0, s-maxage=31536000".to_owned()),
+            Some(RenderFreshness { ttl_secs: Some(ttl), .. }) => Some(format!("public, max-age=0, s-maxage={ttl}, stale-while-revalidate")),
+        };
+        if let Some(cache_control) = cache_control {
+            headers.push(("cache-control".to_owned(), cache_control));
+        }
+        if let Some(freshness) = freshness {
+            headers.push(("age".to_owned(), freshness.age(now).to_string()));
+        }
+        headers.push((ISR_STATUS_HEADER.to_owned(), status.as_str().to_owned()));
+        Self { status, head, body, headers }
+    }
+
+    fn cached(status: IsrStatus, entry: IsrEntry, now: u64) -> Self {
+        Self::new(status, entry.head, entry.render.body, Some(entry.render.freshness), now)
+    }
+
+    fn bypass(head: glory_core::web::ResponseHead, body: String, now: u64) -> Self {
+        Self::new(IsrStatus::Bypass, head, body.into_bytes(), None, now)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Renders one request into the response head its page set and the HTML.
+/// Called on a blocking thread on a miss and on a background thread when
+/// regenerating a stale entry, so it must build its own (`!Send`) holder each
+/// time.
+#[cfg(not(target_arch = "wasm32"))]
+pub type IsrRenderer = std::sync::Arc<dyn Fn(&RequestContext) -> (glory_core::web::ResponseHead, String) + Send + Sync>;
+
+/// Only successful pages without cookies are shared through the cache; a
+/// not-found page, a redirect or a per-visitor `Set-Cookie` is passed through.
+#[cfg(not(target_arch = "wasm32"))]
+fn isr_cacheable(head: &glory_core::web::ResponseHead) -> bool {
+    (200..300).contains(&head.status) && head.header("set-cookie").is_none()
+}
+
+/// Key bookkeeping shared by every clone of an [`IsrCache`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+struct IsrIndex {
+    /// Cached keys, for [`revalidate_path`], [`revalidate_tag`] and eviction.
+    keys: std::collections::HashMap<String, IsrKey>,
+    /// Last use -> key, least recently used first.
+    recency: std::collections::BTreeMap<u64, String>,
+    clock: u64,
+    /// Keys whose background regeneration is running.
+    regenerating: std::collections::HashSet<String>,
+    /// Keys whose miss is being rendered; later requests wait for it.
+    rendering: std::collections::HashMap<String, std::sync::Arc<IsrMiss>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct IsrKey {
+    path: String,
+    tags: Vec<String>,
+    used: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrIndex {
+    /// Marks `key` as just used and returns the keys dropped to keep at most
+    /// `max_entries`.
+    fn touch(&mut self, key: &str, path: &str, tags: &[String], max_entries: usize) -> Vec<String> {
+        self.clock += 1;
+        let entry = IsrKey {
+            path: path.to_owned(),
+            tags: tags.to_vec(),
+            used: self.clock,
+        };
+        if let Some(previous) = self.keys.insert(key.to_owned(), entry) {
+            self.recency.remove(&previous.used);
+        }
+        self.recency.insert(self.clock, key.to_owned());
+        let mut evicted = Vec::new();
+        while self.keys.len() > max_entries {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.keys.remove(&oldest);
+            evicted.push(oldest);
+        }
+        evicted
+    }
+
+    fn forget(&mut self, key: &str) {
+        if let Some(entry) = self.keys.remove(key) {
+            self.recency.remove(&entry.used);
+        }
+    }
+}
+
+/// One in-flight miss render, shared with the requests coalesced onto it.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+struct IsrMiss {
+    /// `Ok(None)` when the page was not cacheable and each waiter renders its own.
+    result: std::sync::Mutex<Option<Result<Option<IsrEntry>, ServerFnError>>>,
+    done: std::sync::Condvar,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrMiss {
+    fn publish(&self, result: Result<Option<IsrEntry>, ServerFnError>) {
+        let mut slot = self.result.lock().expect("isr miss lock poisoned");
+        slot.get_or_insert(result);
+        self.done.notify_all();
+    }
+
+    fn wait(&self) -> Result<Option<IsrEntry>, ServerFnError> {
+        let slot = self.result.lock().expect("isr miss lock poisoned");
+        let slot = self.done.wait_while(slot, |slot| slot.is_none()).expect("isr miss lock poisoned");
+        slot.clone().expect("isr miss published")
+    }
+}
+
+/// Ends a miss render: publishes a failure if the renderer panicked, and lets
+/// the next request for the key render again.
+#[cfg(not(target_arch = "wasm32"))]
+struct IsrMissGuard<'a> {
+    shared: &'a IsrShared,
+    key: &'a str,
+    miss: std::sync::Arc<IsrMiss>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for IsrMissGuard<'_> {
+    fn drop(&mut self) {
+        self.miss.publish(Err(ServerFnError::ServerError("isr render panicked".to_owned())));
+        self.shared.index.lock().expect("isr index lock poisoned").rendering.remove(self.key);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct IsrShared {
+    store: Box<dyn RenderStore>,
+    config: IsrConfig,
+    index: std::sync::Mutex<IsrIndex>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for IsrShared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IsrShared")
+            .field("config", &self.config)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static ISR_CACHES: std::sync::Mutex<Vec<std::sync::Weak<IsrShared>>> = std::sync::Mutex::new(Vec::new());
+
+/// Incremental static regeneration for SSR routes.
+///
+/// Cheap to clone; clones share the store. Every live `IsrCache` is reachable
+/// from the process-wide [`revalidate_path`] / [`revalidate_tag`] functions, so
+/// a server function can invalidate pages without holding a handle.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct IsrCache {
+    shared: std::sync::Arc<IsrShared>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IsrCache {
+    pub fn new(store: impl RenderStore, config: IsrConfig) -> Self {
+        let shared = std::sync::Arc::new(IsrShared {
+            store: Box::new(store),
+            config,
+            index: std::sync::Mutex::new(IsrIndex::default()),
+        });
+        let mut caches = ISR_CACHES.lock().expect("isr registry lock poisoned");
+        caches.retain(|cache| cache.strong_count() > 0);
+        caches.push(std::sync::Arc::downgrade(&shared));
+        drop(caches);
+        Self { shared }
+    }
+
+    /// An ISR cache backed by a process-local [`IncrementalCache`].
+    pub fn in_memory(config: IsrConfig) -> Self {
+        Self::new(IncrementalCache::new(), config)
+    }
+
+    /// An ISR cache persisted under `root` through a [`FileSystemCache`].
+    pub fn on_disk(root: impl Into<std::path::PathBuf>, config: IsrConfig) -> Self {
+        Self::new(FileSystemCache::new(root), config)
+    }
+
+    pub fn config(&self) -> &IsrConfig {
+        &self.shared.config
+    }
+
+    /// Serves `context` at the current wall-clock time on a blocking thread,
+    /// so store access and miss renders do not stall the async runtime. See
+    /// [`serve_at`](Self::serve_at).
+    pub async fn serve(&self, context: &RequestContext, render: &IsrRenderer) -> Result<IsrResponse, ServerFnError> {
+        let cache = self.clone();
+        let context = context.clone();
+        let render = render.clone();
+        tokio::task::spawn_blocking(move || cache.serve_at(&context, &render, now()))
+            .await
+            .map_err(|err| ServerFnError::ServerError(format!("isr render failed: {err}")))?
+    }
+
+    /// Serves `context` as of `now` (seconds since the Unix epoch), blocking
+    /// the calling thread while a miss renders.
+    ///
+    /// Fresh entries are returned as-is. Stale entries are returned while one
+    /// background thread regenerates them; concurrent requests for the same
+    /// key do not start a second regeneration. Misses render inline and are
+    /// stored before returning; concurrent misses for the same key wait for
+    /// that one render instead of starting their own. A page answering with a
+    /// non-2xx status or a `Set-Cookie` is returned as rendered and never
+    /// stored. Cached pages render with only the query parameters their route
+    /// allows, so an ignored parameter cannot leak into the shared copy.
+    pub fn serve_at(&self, context: &RequestContext, render: &IsrRenderer, now: u64) -> Result<IsrResponse, ServerFnError> {
+        let uri = request_path_and_query(&context.uri);
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        let Some(route) = self.shared.config.route_for(path) else {
+            let (head, body) = render(context);
+            return Ok(IsrResponse::bypass(head, body, now));
+        };
+        let key = self.shared.config.cache_key(context);
+        let context = &RequestContext {
+            uri: route.keyed_uri(path, query),
+            ..context.clone()
+        };
+        if let Some(cached) = self.shared.store.load(&key)? {
+            // Entries persisted by an earlier process are indexed on first
+            // use so on-demand revalidation can still find them.
+            self.record(&key, path, &route.tags);
+            if !cached.render.freshness.is_stale(now) {
+                return Ok(IsrResponse::cached(IsrStatus::Hit, cached, now));
+            }
+            self.spawn_regeneration(key, context.clone(), route.ttl_secs, render.clone());
+            return Ok(IsrResponse::cached(IsrStatus::Stale, cached, now));
+        }
+        let (miss, leader) = {
+            let mut index = self.shared.index.lock().expect("isr index lock poisoned");
+            match index.rendering.get(&key) {
+                Some(miss) => (miss.clone(), false),
+                None => {
+                    let miss = std::sync::Arc::new(IsrMiss::default());
+                    index.rendering.insert(key.clone(), miss.clone());
+                    (miss, true)
+                }
+            }
+        };
+        if !leader {
+            return match miss.wait()? {
+                Some(entry) => Ok(IsrResponse::cached(IsrStatus::Miss, entry, now)),
+                None => {
+                    let (head, body) = render(context);
+                    Ok(IsrResponse::bypass(head, body, now))
+                }
+            };
+        }
+        let guard = IsrMissGuard {
+            shared: &self.shared,
+            key: &key,
+            miss,
+        };
+        let (head, body) = render(context);
+        if !isr_cacheable(&head) {
+            guard.miss.publish(Ok(None));
+            return Ok(IsrResponse::bypass(head, body, now));
+        }
+        let entry = IsrEntry {
+            render: CachedRender::new(body.into_bytes(), RenderFreshness::new(now, route.ttl_secs)),
+            head,
+        };
+        let stored = self.shared.store.store(&key, entry.clone());
+        if stored.is_ok() {
+            self.record(&key, path, &route.tags);
+        }
+        guard.miss.publish(stored.clone().map(|()| Some(entry.clone())));
+        drop(guard);
+        stored?;
+        Ok(IsrResponse::cached(IsrStatus::Miss, entry, now))
+    }
+
+    /// True while a background regeneration for the request's key is running.
+    pub fn is_regenerating(&self, context: &RequestContext) -> bool {
+        let key = self.shared.config.cache_key(context);
+        self.shared.index.lock().expect("isr index lock poisoned").regenerating.contains(&key)
+    }
+
+    /// Drops every cached render of `path` (all query/vary variants) from this
+    /// cache. Returns how many entries were removed.
+    pub fn revalidate_path(&self, path: &str) -> usize {
+        self.revalidate_where(|entry| entry.path == path)
+    }
+
+    /// Drops every cached render whose route carries `tag` from this cache.
+    /// Returns how many entries were removed.
+    pub fn revalidate_tag(&self, tag: &str) -> usize {
+        self.revalidate_where(|entry| entry.tags.iter().any(|cached| cached == tag))
+    }
+
+    fn revalidate_where(&self, matches: impl Fn(&IsrKey) -> bool) -> usize {
+        let mut index = self.shared.index.lock().expect("isr index lock poisoned");
+        let keys: Vec<String> = index
+            .keys
+            .iter()
+            .filter(|(_, entry)| matches(entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut removed = 0;
+        for key in keys {
+            index.forget(&key);
+            match self.shared.store.remove(&key) {
+                Ok(true) => removed += 1,
+                Ok(false) => {}
+                Err(err) => tracing::warn!(key, error = %err, "isr revalidation failed to remove entry"),
+            }
+        }
+        removed
+    }
+
+    fn record(&self, key: &str, path: &str, tags: &[String]) {
+        let evicted = self
+            .shared
+            .index
+            .lock()
+            .expect("isr index lock poisoned")
+            .touch(key, path, tags, self.shared.config.max_entries);
+        for key in evicted {
+            if let Err(err) = self.shared.store.remove(&key) {
+                tracing::warn!(key, error = %err, "isr eviction failed to remove entry");
+            }
+        }
+    }
+
+    fn spawn_regeneration(&self, key: String, context: RequestContext, ttl_secs: Option<u64>, render: IsrRenderer) {
+        if !self
+            .shared
+            .index
+            .lock()
+            .expect("isr index lock poisoned")
+            .regenerating
+            .insert(key.clone())
+        {
+            return;
+        }
+        let shared = self.shared.clone();
+        let pending = key.clone();
+        let spawned = std::thread::Builder::new().name("glory-isr".to_owned()).spawn(move || {
+            let (head, body) = render(&context);
+            if isr_cacheable(&head) {
+                let entry = IsrEntry {
+                    render: CachedRender::new(body.into_bytes(), RenderFreshness::new(now(), ttl_secs)),
+                    head,
+                };
+                if let Err(err) = shared.store.store(&key, entry) {
+                    tracing::warn!(key, error = %err, "isr background regeneration failed to store entry");
+                }
+            } else {
+                // The page stopped being cacheable (deleted, now redirects,
+                // ...): drop the stale copy so the next request renders it.
+                shared.index.lock().expect("isr index lock poisoned").forget(&key);
+                if let Err(err) = shared.store.remove(&key) {
+                    tracing::warn!(key, error = %err, "isr background regeneration failed to remove entry");
+                }
+            }
+            shared.index.lock().expect("isr index lock poisoned").regenerating.remove(&key);
+        });
+        if let Err(err) = spawned {
+            tracing::warn!(error = %err, "isr background regeneration could not spawn");
+            self.shared.index.lock().expect("isr index lock poisoned").regenerating.remove(&pending);
+        }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn live_isr_caches() -> Vec<IsrCache> {
+    ISR_CACHES
+        .lock()
+        .expect("isr registry lock poisoned")
+        .iter()
+        .filter_map(std::sync::Weak::upgrade)
+        .map(|shared| IsrCache { shared })
+        .collect()
+}
+
+/// Drops every cached render of `path` from every live [`IsrCache`]. Call it
+/// from a server function after a write so the next request regenerates.
+/// Returns how many entries were removed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn revalidate_path(path: &str) -> usize {
+    live_isr_caches().iter().map(|cache| cache.revalidate_path(path)).sum()
+}
+
+/// Drops every cached render tagged `tag` (see [`IsrRoute::tag`]) from every
+/// live [`IsrCache`]. Returns how many entries were removed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn revalidate_tag(tag: &str) -> usize {
+    live_isr_caches().iter().map(|cache| cache.revalidate_tag(tag)).sum()
+}
+
 /// JSON state bag that can be embedded into SSR HTML and read by a hydrated
 /// client before it calls the network.
 #[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -2933,6 +5919,312 @@ where
     Ok(Box::pin(stream))
 }
 
+// ---------------------------------------------------------------------------
+// Client leg — uploads
+// ---------------------------------------------------------------------------
+//
+// `fetch` cannot report request-body progress, so form uploads go through
+// `XMLHttpRequest` and its `upload.onprogress`. Resumable uploads speak the
+// chunk protocol served by [`ResumableUploads`] and report progress per chunk.
+
+/// Bytes sent so far; `total` is `None` until the browser knows the size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub loaded: u64,
+    pub total: Option<u64>,
+}
+
+impl UploadProgress {
+    /// Completed fraction in `0.0..=1.0`, when the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.loaded as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum UploadState {
+    #[default]
+    Pending,
+    Uploading,
+    /// The server accepted the upload. `location` is the resumable upload URL;
+    /// `body` is the response text of a form upload.
+    Completed {
+        location: Option<String>,
+        body: String,
+    },
+    Failed(String),
+}
+
+/// Reactive handle returned by [`upload_form`] and [`upload_resumable`].
+#[derive(Clone, Debug)]
+pub struct UploadHandle {
+    progress: glory_core::Cage<UploadProgress>,
+    state: glory_core::Cage<UploadState>,
+    /// Request callbacks kept alive until the upload ends.
+    #[cfg(target_arch = "wasm32")]
+    callbacks: std::rc::Rc<std::cell::RefCell<Vec<wasm_bindgen::JsValue>>>,
+}
+
+impl Default for UploadHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UploadHandle {
+    pub fn new() -> Self {
+        Self {
+            progress: glory_core::Cage::new(UploadProgress::default()),
+            state: glory_core::Cage::new(UploadState::Pending),
+            #[cfg(target_arch = "wasm32")]
+            callbacks: Default::default(),
+        }
+    }
+
+    pub fn progress(&self) -> glory_core::Cage<UploadProgress> {
+        self.progress
+    }
+
+    pub fn state(&self) -> glory_core::Cage<UploadState> {
+        self.state
+    }
+
+    /// Records progress; custom transports use this to drive the handle.
+    pub fn report(&self, loaded: u64, total: Option<u64>) {
+        self.progress.revise(|mut progress| *progress = UploadProgress { loaded, total });
+    }
+
+    pub fn set_state(&self, state: UploadState) {
+        self.state.revise(|mut current| *current = state);
+    }
+}
+
+/// Options for [`upload_resumable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResumableUploadOptions {
+    pub chunk_size: u64,
+    /// Consecutive failed chunks tolerated before giving up.
+    pub retries: u32,
+    pub retry_delay_ms: u32,
+    /// Upload URL from an earlier attempt; its offset is queried and the
+    /// upload continues from there instead of starting over.
+    pub resume_url: Option<String>,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl Default for ResumableUploadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1024 * 1024,
+            retries: 5,
+            retry_delay_ms: 1_000,
+            resume_url: None,
+            metadata: Vec::new(),
+        }
+    }
+}
+
+/// POSTs `form` to `url` as `multipart/form-data`, reporting upload progress.
+///
+/// Generated `#[server]` stubs send encoded arguments and report no progress,
+/// so a multipart server function is called through this with its
+/// `/__glory/fn/{name}` URL.
+#[cfg(target_arch = "wasm32")]
+pub fn upload_form(url: &str, form: &web_sys::FormData) -> UploadHandle {
+    use wasm_bindgen::JsCast;
+
+    let handle = UploadHandle::new();
+    let request = match web_sys::XmlHttpRequest::new().and_then(|request| {
+        request.open_with_async("POST", url, true)?;
+        Ok(request)
+    }) {
+        Ok(request) => request,
+        Err(err) => {
+            handle.set_state(UploadState::Failed(format!("{err:?}")));
+            return handle;
+        }
+    };
+
+    let onprogress = wasm_bindgen::closure::Closure::<dyn FnMut(web_sys::ProgressEvent)>::new({
+        let handle = handle.clone();
+        move |event: web_sys::ProgressEvent| {
+            let total = event.length_computable().then(|| event.total() as u64);
+            handle.report(event.loaded() as u64, total);
+        }
+    });
+    if let Ok(upload) = request.upload() {
+        upload.set_onprogress(Some(onprogress.as_ref().unchecked_ref()));
+    }
+    handle.callbacks.borrow_mut().push(onprogress.into_js_value());
+
+    let onloadend = wasm_bindgen::closure::Closure::<dyn FnMut()>::new({
+        let request = request.clone();
+        let handle = handle.clone();
+        move || {
+            if let Ok(upload) = request.upload() {
+                upload.set_onprogress(None);
+            }
+            request.set_onloadend(None);
+            handle.callbacks.borrow_mut().clear();
+            let status = request.status().unwrap_or_default();
+            let body = request.response_text().ok().flatten().unwrap_or_default();
+            if (200..300).contains(&status) {
+                handle.set_state(UploadState::Completed { location: None, body });
+            } else if status == 0 {
+                handle.set_state(UploadState::Failed("upload aborted or network error".to_owned()));
+            } else {
+                let message = decode_error_with(ServerFnEncoding::Json, body.as_bytes())
+                    .map(|err| err.to_string())
+                    .unwrap_or_else(|_| format!("HTTP {status}"));
+                handle.set_state(UploadState::Failed(message));
+            }
+        }
+    });
+    request.set_onloadend(Some(onloadend.as_ref().unchecked_ref()));
+    handle.callbacks.borrow_mut().push(onloadend.into_js_value());
+
+    handle.set_state(UploadState::Uploading);
+    if let Err(err) = request.send_with_opt_form_data(Some(form)) {
+        handle.set_state(UploadState::Failed(format!("{err:?}")));
+    }
+    handle
+}
+
+/// Uploads `file` in chunks through the [`ResumableUploads`] protocol at
+/// `base_url`, resuming from the server's offset after failures.
+#[cfg(target_arch = "wasm32")]
+pub fn upload_resumable(base_url: &str, file: &web_sys::Blob, options: ResumableUploadOptions) -> UploadHandle {
+    let handle = UploadHandle::new();
+    let base_url = base_url.to_owned();
+    let file = file.clone();
+    handle.set_state(UploadState::Uploading);
+    let task = handle.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let state = match run_resumable_upload(&base_url, &file, &options, &task).await {
+            Ok(location) => UploadState::Completed {
+                location: Some(location),
+                body: String::new(),
+            },
+            Err(err) => UploadState::Failed(err.to_string()),
+        };
+        task.set_state(state);
+    });
+    handle
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn run_resumable_upload(
+    base_url: &str,
+    file: &web_sys::Blob,
+    options: &ResumableUploadOptions,
+    handle: &UploadHandle,
+) -> Result<String, ServerFnError> {
+    let total = file.size() as u64;
+    let (location, mut offset) = match &options.resume_url {
+        Some(location) => (location.clone(), upload_request("HEAD", location, &[], None).await?.0),
+        None => {
+            let metadata = form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(options.metadata.iter())
+                .finish();
+            let headers = [(UPLOAD_LENGTH_HEADER, total.to_string()), (UPLOAD_METADATA_HEADER, metadata)];
+            let (offset, location) = upload_request("POST", base_url, &headers, None).await?;
+            let location = location.ok_or_else(|| ServerFnError::Request("upload creation returned no location".to_owned()))?;
+            (location, offset)
+        }
+    };
+    handle.report(offset, Some(total));
+
+    let mut failures = 0;
+    while offset < total {
+        let end = (offset + options.chunk_size.max(1)).min(total);
+        let chunk = file
+            .slice_with_f64_and_f64(offset as f64, end as f64)
+            .map_err(|err| ServerFnError::Request(format!("{err:?}")))?;
+        let headers = [
+            (UPLOAD_OFFSET_HEADER, offset.to_string()),
+            ("content-type", UPLOAD_CHUNK_CONTENT_TYPE.to_owned()),
+        ];
+        match upload_request("PATCH", &location, &headers, Some(&chunk)).await {
+            Ok((next, _)) => {
+                failures = 0;
+                offset = next;
+                handle.report(offset, Some(total));
+            }
+            Err(err) if failures < options.retries && !matches!(err.http_status(), 404 | 413 | 415) => {
+                failures += 1;
+                sleep_ms(options.retry_delay_ms).await;
+                // The chunk may have landed before the connection dropped.
+                if let Ok((next, _)) = upload_request("HEAD", &location, &[], None).await {
+                    offset = next;
+                    handle.report(offset, Some(total));
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(location)
+}
+
+/// Sends one protocol request; returns the `upload-offset` and `location`
+/// response headers.
+#[cfg(target_arch = "wasm32")]
+async fn upload_request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, String)],
+    body: Option<&web_sys::Blob>,
+) -> Result<(u64, Option<String>), ServerFnError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let request_err = |err: wasm_bindgen::JsValue| ServerFnError::Request(format!("{err:?}"));
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(method);
+    if let Some(body) = body {
+        init.set_body(body);
+    }
+    let request = web_sys::Request::new_with_str_and_init(url, &init).map_err(request_err)?;
+    for (name, value) in headers {
+        request.headers().set(name, value).map_err(request_err)?;
+    }
+    let window = web_sys::window().ok_or_else(|| ServerFnError::Request("no window".to_owned()))?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(request_err)?
+        .dyn_into()
+        .map_err(request_err)?;
+    if !response.ok() {
+        let buffer = JsFuture::from(response.array_buffer().map_err(request_err)?).await.map_err(request_err)?;
+        let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+        return Err(
+            decode_error_with(ServerFnEncoding::Json, &bytes).unwrap_or_else(|_| ServerFnError::http(response.status(), "upload request failed"))
+        );
+    }
+    let offset = response
+        .headers()
+        .get(UPLOAD_OFFSET_HEADER)
+        .map_err(request_err)?
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| ServerFnError::Request("upload response missing upload-offset".to_owned()))?;
+    let location = response.headers().get("location").map_err(request_err)?;
+    Ok((offset, location))
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep_ms(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
 // ---------------------------------------------------------------------------
 // Adapter mounts
 // ---------------------------------------------------------------------------
@@ -2940,26 +6232,48 @@ where
 /// Salvo integration: `router.push(glory_serverfn::salvo_mount::router())`.
 #[cfg(all(feature = "salvo", not(target_arch = "wasm32")))]
 pub mod salvo_mount {
-    use futures::StreamExt;
+    use futures::{SinkExt, StreamExt};
     use salvo::http::StatusCode;
     use salvo::http::header::{HeaderName, HeaderValue};
     use salvo::prelude::*;
-
-    #[handler]
-    async fn server_fn_handler(req: &mut Request, res: &mut Response) {
-        let path = req.uri().path().to_owned();
-        let method = req.method().to_string();
-        let context = crate::RequestContext {
-            method: method.clone(),
+    use salvo::websocket::{Message, WebSocket, WebSocketUpgrade};
+
+    /// Snapshot of a Salvo request as a [`crate::RequestContext`]: method,
+    /// URI, lowercased headers and the peer IP. The Salvo SSR and liveview
+    /// integrations build their contexts through this too.
+    pub fn request_context(req: &Request) -> crate::RequestContext {
+        crate::RequestContext {
+            method: req.method().to_string(),
             uri: req.uri().to_string(),
             headers: req
                 .headers()
                 .iter()
                 .map(|(name, value)| (name.as_str().to_ascii_lowercase(), value.to_str().unwrap_or_default().to_owned()))
                 .collect(),
-        };
+        }
+        .with_peer_ip(req.remote_addr().ip().map(|ip| ip.to_string()))
+    }
+
+    #[handler]
+    async fn server_fn_handler(req: &mut Request, res: &mut Response) {
+        let path = req.uri().path().to_owned();
+        let method = req.method().to_string();
+        let context = request_context(req);
         let input_encoding = context.request_encoding();
         let output_encoding = context.response_encoding();
+        if context.is_streamed_body() {
+            let body = body_stream(req.take_body());
+            let dispatch = crate::with_request_context(
+                context,
+                crate::with_request_body(
+                    body,
+                    crate::dispatch_with_method(&method, &path, Vec::new(), input_encoding, output_encoding),
+                ),
+            )
+            .await;
+            write_http_response(res, dispatch.into_http_response());
+            return;
+        }
         let body = if method.eq_ignore_ascii_case("GET") {
             match crate::decode_get_args_from_query(req.uri().query()) {
                 Ok(body) => body,
@@ -2991,6 +6305,16 @@ pub mod salvo_mount {
         write_http_response(res, dispatch.into_http_response());
     }
 
+    fn body_stream(body: salvo::http::ReqBody) -> crate::BoxedByteStream {
+        body.filter_map(|frame| async move {
+            match frame {
+                Ok(frame) => frame.into_data().ok().map(|data| Ok(data.to_vec())),
+                Err(err) => Some(Err(crate::ServerFnError::http(400, format!("invalid body: {err}")))),
+            }
+        })
+        .boxed()
+    }
+
     fn write_http_response(res: &mut Response, response: crate::ServerFnHttpResponse) {
         let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
         res.status_code(status);
@@ -3002,10 +6326,84 @@ pub mod salvo_mount {
         let _ = res.write_body(response.body);
     }
 
+    #[handler]
+    async fn channel_handler(req: &mut Request, res: &mut Response) {
+        let path = req.uri().path().to_owned();
+        let query = req.uri().query().map(str::to_owned);
+        let connection = match crate::connect_channel(&path, query.as_deref(), Some(request_context(req))).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                write_http_response(res, crate::server_fn_error_response_parts(&err));
+                return;
+            }
+        };
+        if let Err(err) = WebSocketUpgrade::new()
+            .upgrade(req, res, move |socket| pump_channel(socket, connection))
+            .await
+        {
+            res.render(err);
+        }
+    }
+
+    async fn pump_channel(socket: WebSocket, connection: crate::ChannelConnection) {
+        let (mut sink, mut stream) = socket.split();
+        if sink.send(Message::text(connection.hello_frame())).await.is_err() {
+            return;
+        }
+        let outgoing = async {
+            let mut frames = std::pin::pin!(connection.outgoing());
+            while let Some(frame) = frames.next().await {
+                if sink.send(Message::text(frame)).await.is_err() {
+                    return;
+                }
+            }
+            let _ = sink.close().await;
+        };
+        let incoming = async {
+            while let Some(Ok(message)) = stream.next().await {
+                if message.is_close() {
+                    break;
+                }
+                if let Ok(text) = message.as_str() {
+                    connection.receive(text).await;
+                }
+            }
+        };
+        futures::future::select(std::pin::pin!(outgoing), std::pin::pin!(incoming)).await;
+    }
+
     /// Router serving every registered server function under
-    /// [`crate::PREFIX`]. Push it into your app router as-is.
+    /// [`crate::PREFIX`] and every `#[server(websocket)]` channel under
+    /// [`crate::WS_PREFIX`]. Push it into your app router as-is.
     pub fn router() -> Router {
-        Router::with_path("__glory/fn/{**rest}").get(server_fn_handler).post(server_fn_handler)
+        Router::new()
+            .push(Router::with_path("__glory/fn/{**rest}").get(server_fn_handler).post(server_fn_handler))
+            .push(Router::with_path("__glory/ws/{**rest}").get(channel_handler))
+    }
+
+    #[derive(Clone)]
+    struct UploadHandler {
+        uploads: crate::ResumableUploads,
+    }
+
+    #[async_trait]
+    impl Handler for UploadHandler {
+        async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+            let context = request_context(req);
+            let id = req.param::<String>("id");
+            let body = body_stream(req.take_body());
+            write_http_response(res, self.uploads.handle(&context, id.as_deref(), body).await);
+        }
+    }
+
+    /// Router serving the [`crate::ResumableUploads`] chunk protocol at the
+    /// store's base path. Chunk bodies stream to disk as they arrive.
+    pub fn upload_router(uploads: crate::ResumableUploads) -> Router {
+        let base_path = uploads.base_path().trim_start_matches('/').to_owned();
+        let handler = UploadHandler { uploads };
+        Router::with_path(base_path)
+            .post(handler.clone())
+            .push(Router::with_path("{id}").head(handler.clone()).patch(handler.clone()).delete(handler))
     }
 
     /// Writes a [`crate::StreamingResponse`] to a Salvo response.
@@ -3043,26 +6441,54 @@ pub mod salvo_mount {
 #[cfg(all(feature = "axum", not(target_arch = "wasm32")))]
 pub mod axum_mount {
     use axum::body::{Body, Bytes};
+    use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
     use axum::http::StatusCode;
     use axum::http::header::{HeaderName, HeaderValue};
     use axum::response::{IntoResponse, Response};
-    use futures::StreamExt;
-
-    async fn server_fn_handler(request: axum::extract::Request) -> Response {
-        let path = request.uri().path().to_owned();
-        let method = request.method().to_string();
-        let query = request.uri().query().map(str::to_owned);
-        let context = crate::RequestContext {
-            method: method.clone(),
+    use futures::{SinkExt, StreamExt};
+
+    /// Snapshot of an Axum request as a [`crate::RequestContext`]: method,
+    /// URI, lowercased headers and the peer IP from `ConnectInfo`. The Axum
+    /// SSR and liveview integrations build their contexts through this too.
+    pub fn request_context<B>(request: &axum::http::Request<B>) -> crate::RequestContext {
+        crate::RequestContext {
+            method: request.method().to_string(),
             uri: request.uri().to_string(),
             headers: request
                 .headers()
                 .iter()
                 .map(|(name, value)| (name.as_str().to_ascii_lowercase(), value.to_str().unwrap_or_default().to_owned()))
                 .collect(),
-        };
+        }
+        .with_peer_ip(
+            request
+                .extensions()
+                .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+                .map(|axum::extract::ConnectInfo(addr)| addr.ip().to_string()),
+        )
+    }
+
+    async fn server_fn_handler(request: axum::extract::Request) -> Response {
+        let path = request.uri().path().to_owned();
+        let method = request.method().to_string();
+        let query = request.uri().query().map(str::to_owned);
+        let context = request_context(&request);
         let input_encoding = context.request_encoding();
         let output_encoding = context.response_encoding();
+        if context.is_streamed_body() {
+            let body = body_stream(request.into_body());
+            return into_response(
+                crate::with_request_context(
+                    context,
+                    crate::with_request_body(
+                        body,
+                        crate::dispatch_with_method(&method, &path, Vec::new(), input_encoding, output_encoding),
+                    ),
+                )
+                .await
+                .into_http_response(),
+            );
+        }
         let body = if method.eq_ignore_ascii_case("GET") {
             match crate::decode_get_args_from_query(query.as_deref()) {
                 Ok(body) => body,
@@ -3089,6 +6515,16 @@ pub mod axum_mount {
         )
     }
 
+    fn body_stream(body: Body) -> crate::BoxedByteStream {
+        body.into_data_stream()
+            .map(|chunk| {
+                chunk
+                    .map(|bytes| bytes.to_vec())
+                    .map_err(|err| crate::ServerFnError::http(400, format!("invalid body: {err}")))
+            })
+            .boxed()
+    }
+
     fn into_response(parts: crate::ServerFnHttpResponse) -> Response {
         let status = StatusCode::from_u16(parts.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
         let mut response = (status, parts.body).into_response();
@@ -3100,12 +6536,72 @@ pub mod axum_mount {
         response
     }
 
+    async fn channel_handler(upgrade: WebSocketUpgrade, request: axum::extract::Request) -> Response {
+        let path = request.uri().path().to_owned();
+        let connection = match crate::connect_channel(&path, request.uri().query(), Some(request_context(&request))).await {
+            Ok(connection) => connection,
+            Err(err) => return into_response(crate::server_fn_error_response_parts(&err)),
+        };
+        upgrade.on_upgrade(move |socket| pump_channel(socket, connection))
+    }
+
+    async fn pump_channel(socket: WebSocket, connection: crate::ChannelConnection) {
+        let (mut sink, mut stream) = socket.split();
+        if sink.send(Message::text(connection.hello_frame())).await.is_err() {
+            return;
+        }
+        let outgoing = async {
+            let mut frames = std::pin::pin!(connection.outgoing());
+            while let Some(frame) = frames.next().await {
+                if sink.send(Message::text(frame)).await.is_err() {
+                    return;
+                }
+            }
+            let _ = sink.close().await;
+        };
+        let incoming = async {
+            while let Some(Ok(message)) = stream.next().await {
+                match message {
+                    Message::Text(text) => connection.receive(text.as_str()).await,
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+        };
+        futures::future::select(std::pin::pin!(outgoing), std::pin::pin!(incoming)).await;
+    }
+
     /// Router serving every registered server function under
-    /// [`crate::PREFIX`]. Merge it into your app router.
+    /// [`crate::PREFIX`] and every `#[server(websocket)]` channel under
+    /// [`crate::WS_PREFIX`]. Merge it into your app router.
     pub fn router<S: Clone + Send + Sync + 'static>() -> axum::Router<S> {
-        axum::Router::new().route(
-            &format!("{}/{{*rest}}", crate::PREFIX),
-            axum::routing::get(server_fn_handler).post(server_fn_handler),
+        axum::Router::new()
+            .route(
+                &format!("{}/{{*rest}}", crate::PREFIX),
+                axum::routing::get(server_fn_handler).post(server_fn_handler),
+            )
+            .route(&format!("{}/{{*rest}}", crate::WS_PREFIX), axum::routing::get(channel_handler))
+    }
+
+    async fn upload_handler(uploads: crate::ResumableUploads, id: Option<String>, request: axum::extract::Request) -> Response {
+        let context = request_context(&request);
+        let body = body_stream(request.into_body());
+        into_response(uploads.handle(&context, id.as_deref(), body).await)
+    }
+
+    /// Router serving the [`crate::ResumableUploads`] chunk protocol at the
+    /// store's base path. Chunk bodies stream to disk as they arrive.
+    pub fn upload_router<S: Clone + Send + Sync + 'static>(uploads: crate::ResumableUploads) -> axum::Router<S> {
+        let base_path = uploads.base_path().to_owned();
+        let create = {
+            let uploads = uploads.clone();
+            move |request: axum::extract::Request| upload_handler(uploads, None, request)
+        };
+        let resume =
+            move |axum::extract::Path(id): axum::extract::Path<String>, request: axum::extract::Request| upload_handler(uploads, Some(id), request);
+        axum::Router::new().route(&base_path, axum::routing::post(create)).route(
+            &format!("{base_path}/{{id}}"),
+            axum::routing::head(resume.clone()).patch(resume.clone()).delete(resume),
         )
     }
 
@@ -3134,27 +6630,58 @@ pub mod actix_mount {
     use actix_web::{HttpRequest, HttpResponse, web};
     use futures::StreamExt;
 
-    async fn server_fn_handler(request: HttpRequest, body: web::Bytes) -> HttpResponse {
-        let path = request.uri().path().to_owned();
-        let method = request.method().to_string();
-        let context = crate::RequestContext {
-            method: method.clone(),
+    /// Snapshot of an Actix request as a [`crate::RequestContext`]: method,
+    /// URI, lowercased headers and the peer IP. The Actix SSR and liveview
+    /// integrations build their contexts through this too.
+    pub fn request_context(request: &HttpRequest) -> crate::RequestContext {
+        crate::RequestContext {
+            method: request.method().to_string(),
             uri: request.uri().to_string(),
             headers: request
                 .headers()
                 .iter()
                 .map(|(name, value)| (name.as_str().to_ascii_lowercase(), value.to_str().unwrap_or_default().to_owned()))
                 .collect(),
-        };
+        }
+        .with_peer_ip(request.peer_addr().map(|addr| addr.ip().to_string()))
+    }
+
+    async fn server_fn_handler(request: HttpRequest, payload: web::Payload) -> HttpResponse {
+        let path = request.uri().path().to_owned();
+        let method = request.method().to_string();
+        let context = request_context(&request);
         let input_encoding = context.request_encoding();
         let output_encoding = context.response_encoding();
+        if context.is_streamed_body() {
+            let body = body_stream(payload);
+            return into_http_response(
+                crate::with_request_context(
+                    context,
+                    crate::with_request_body(
+                        body,
+                        crate::dispatch_with_method(&method, &path, Vec::new(), input_encoding, output_encoding),
+                    ),
+                )
+                .await
+                .into_http_response(),
+            );
+        }
         let body = if method.eq_ignore_ascii_case("GET") {
             match crate::decode_get_args_from_query(Some(request.query_string())) {
                 Ok(body) => body,
                 Err(err) => return into_http_response(crate::server_fn_error_response_parts_with_encoding(&err, output_encoding)),
             }
         } else {
-            body.to_vec()
+            // Buffered like the `web::Bytes` extractor, so `PayloadConfig` limits apply.
+            match <web::Bytes as actix_web::FromRequest>::from_request(&request, &mut payload.into_inner()).await {
+                Ok(bytes) => bytes.to_vec(),
+                Err(err) => {
+                    return into_http_response(crate::server_fn_error_response_parts_with_encoding(
+                        &crate::ServerFnError::http(400, format!("invalid body: {err}")),
+                        output_encoding,
+                    ));
+                }
+            }
         };
         into_http_response(
             crate::with_request_context(
@@ -3166,6 +6693,24 @@ pub mod actix_mount {
         )
     }
 
+    /// `web::Payload` is not `Send`; a local task forwards its chunks.
+    fn body_stream(mut payload: web::Payload) -> crate::BoxedByteStream {
+        use futures::SinkExt;
+
+        let (mut sender, receiver) = futures::channel::mpsc::channel(1);
+        actix_web::rt::spawn(async move {
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk
+                    .map(|bytes| bytes.to_vec())
+                    .map_err(|err| crate::ServerFnError::http(400, format!("invalid body: {err}")));
+                if sender.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+        receiver.boxed()
+    }
+
     fn into_http_response(parts: crate::ServerFnHttpResponse) -> HttpResponse {
         let status = StatusCode::from_u16(parts.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
         let mut builder = HttpResponse::build(status);
@@ -3177,11 +6722,85 @@ pub mod actix_mount {
         builder.body(parts.body)
     }
 
-    /// Registers the server-function dispatch route under [`crate::PREFIX`].
+    async fn channel_handler(request: HttpRequest, body: web::Payload) -> actix_web::Result<HttpResponse> {
+        let connection = match crate::connect_channel(request.path(), Some(request.query_string()), Some(request_context(&request))).await {
+            Ok(connection) => connection,
+            Err(err) => return Ok(into_http_response(crate::server_fn_error_response_parts(&err))),
+        };
+        let (response, session, stream) = actix_ws::handle(&request, body)?;
+        actix_web::rt::spawn(pump_channel(session, stream, connection));
+        Ok(response)
+    }
+
+    async fn pump_channel(mut session: actix_ws::Session, mut stream: actix_ws::MessageStream, connection: crate::ChannelConnection) {
+        if session.text(connection.hello_frame()).await.is_err() {
+            return;
+        }
+        let mut writer = session.clone();
+        let outgoing = async {
+            let mut frames = std::pin::pin!(connection.outgoing());
+            while let Some(frame) = frames.next().await {
+                if writer.text(frame).await.is_err() {
+                    return;
+                }
+            }
+            let _ = writer.close(None).await;
+        };
+        let incoming = async {
+            while let Some(Ok(message)) = stream.recv().await {
+                match message {
+                    actix_ws::Message::Text(text) => connection.receive(text.to_string()).await,
+                    actix_ws::Message::Ping(bytes) if session.pong(&bytes).await.is_err() => break,
+                    actix_ws::Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+        };
+        futures::future::select(std::pin::pin!(outgoing), std::pin::pin!(incoming)).await;
+    }
+
+    /// Registers the server-function dispatch route under [`crate::PREFIX`]
+    /// and the `#[server(websocket)]` channel route under
+    /// [`crate::WS_PREFIX`].
     pub fn configure(config: &mut web::ServiceConfig) {
         config
             .route(&format!("{}/{{rest:.*}}", crate::PREFIX), web::get().to(server_fn_handler))
-            .route(&format!("{}/{{rest:.*}}", crate::PREFIX), web::post().to(server_fn_handler));
+            .route(&format!("{}/{{rest:.*}}", crate::PREFIX), web::post().to(server_fn_handler))
+            .route(&format!("{}/{{rest:.*}}", crate::WS_PREFIX), web::get().to(channel_handler));
+    }
+
+    async fn upload_handler(
+        uploads: web::Data<crate::ResumableUploads>,
+        request: HttpRequest,
+        id: Option<web::Path<String>>,
+        payload: web::Payload,
+    ) -> HttpResponse {
+        let context = request_context(&request);
+        let id = id.map(web::Path::into_inner);
+        into_http_response(uploads.handle(&context, id.as_deref(), body_stream(payload)).await)
+    }
+
+    /// Serves the [`crate::ResumableUploads`] chunk protocol at the store's
+    /// base path; chunk bodies stream to disk as they arrive:
+    /// `App::new().configure(actix_mount::configure_uploads(uploads))`.
+    pub fn configure_uploads(uploads: crate::ResumableUploads) -> impl Fn(&mut web::ServiceConfig) + Clone {
+        let uploads = web::Data::new(uploads);
+        move |config: &mut web::ServiceConfig| {
+            let base_path = uploads.base_path().to_owned();
+            config
+                .service(
+                    web::resource(base_path.clone())
+                        .app_data(uploads.clone())
+                        .route(web::post().to(upload_handler)),
+                )
+                .service(
+                    web::resource(format!("{base_path}/{{id}}"))
+                        .app_data(uploads.clone())
+                        .route(web::head().to(upload_handler))
+                        .route(web::patch().to(upload_handler))
+                        .route(web::delete().to(upload_handler)),
+                );
+        }
     }
 
     /// Converts a [`crate::StreamingResponse`] into an Actix streaming
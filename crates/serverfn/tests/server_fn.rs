@@ -420,6 +420,30 @@ fn cookie_helpers_build_set_and_clear_headers() {
     assert!(glory_serverfn::set_cookie_header("bad;name", "x", Default::default()).is_err());
 }
 
+#[test]
+fn response_options_set_and_clear_cookies() {
+    use glory_serverfn::ResponseOptionsExt;
+
+    let response = glory_core::web::ServerResponseOptions::default();
+    response.set_cookie("theme", "dark", glory_serverfn::CookieOptions::default()).unwrap();
+    response.clear_cookie("glory_session", "/").unwrap();
+    let cookies: Vec<_> = response
+        .head()
+        .headers
+        .into_iter()
+        .filter(|(name, _)| name == "set-cookie")
+        .map(|(_, value)| value)
+        .collect();
+    assert_eq!(
+        cookies,
+        [
+            "theme=dark; Path=/; HttpOnly; SameSite=Lax",
+            "glory_session=; Path=/; Max-Age=0; HttpOnly; SameSite=Lax"
+        ]
+    );
+    assert!(response.set_cookie("bad;name", "x", Default::default()).is_err());
+}
+
 #[test]
 fn request_context_normalizes_content_type() {
     let context = glory_serverfn::RequestContext {
@@ -1126,3 +1150,1175 @@ fn filesystem_cache_key_escaping_prevents_traversal() {
 
     std::fs::remove_dir_all(&dir).ok();
 }
+
+// ---------------------------------------------------------------------------
+// ISR request handling
+// ---------------------------------------------------------------------------
+
+use glory_core::web::ResponseHead;
+use glory_serverfn::{IsrCache, IsrConfig, IsrRenderer, IsrRoute, IsrStatus, RequestContext};
+
+fn isr_request(uri: &str, headers: &[(&str, &str)]) -> RequestContext {
+    RequestContext {
+        method: "GET".to_owned(),
+        uri: uri.to_owned(),
+        headers: headers.iter().map(|(name, value)| ((*name).to_owned(), (*value).to_owned())).collect(),
+    }
+}
+
+/// A renderer that counts its calls and embeds the count in the body.
+fn counting_renderer() -> (IsrRenderer, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    let renders = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = renders.clone();
+    let render: IsrRenderer = std::sync::Arc::new(move |context: &RequestContext| {
+        let count = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        (ResponseHead::default(), format!("<p>{} #{count}</p>", context.uri))
+    });
+    (render, renders)
+}
+
+#[test]
+fn isr_routes_match_patterns_and_key_on_vary_headers() {
+    let route = IsrRoute::new("/blog/{slug}");
+    assert!(route.matches("/blog/hello"));
+    assert!(!route.matches("/blog"));
+    assert!(!route.matches("/blog/hello/comments"));
+    assert!(IsrRoute::new("/docs/*").matches("/docs/a/b/c"));
+    assert!(IsrRoute::new("/users/:id").matches("/users/7"));
+    assert!(IsrRoute::new("/").matches("/"));
+
+    let config = IsrConfig::new()
+        .route(IsrRoute::new("/blog/{slug}").ttl_secs(60))
+        .route(IsrRoute::new("/*"))
+        .vary_header("Accept-Language");
+    assert_eq!(config.route_for("/blog/a").unwrap().ttl_secs, Some(60));
+    assert_eq!(config.route_for("/about").unwrap().ttl_secs, None);
+
+    let en = config.cache_key(&isr_request("http://example.com/blog/a?x=1", &[("accept-language", "en")]));
+    let fr = config.cache_key(&isr_request("/blog/a?x=1", &[("accept-language", "fr")]));
+    assert!(en.starts_with("/blog/a\n"), "the query is ignored by default: {en:?}");
+    assert_ne!(en, fr, "vary header splits the key");
+
+    let config = IsrConfig::new().route(IsrRoute::new("/search").query_param("q").query_param("page"));
+    assert_eq!(
+        config.cache_key(&isr_request("/search?utm=1&page=2&q=rust", &[])),
+        "/search?page=2&q=rust"
+    );
+    assert_eq!(config.cache_key(&isr_request("/search?q=rust&page=2", &[])), "/search?page=2&q=rust");
+}
+
+#[test]
+fn isr_cache_serves_miss_hit_then_stale_with_background_regeneration() {
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/page").ttl_secs(30)));
+    let (render, renders) = counting_renderer();
+    let request = isr_request("/page", &[]);
+
+    let miss = cache.serve_at(&request, &render, 100).unwrap();
+    assert_eq!(miss.status, IsrStatus::Miss);
+    assert_eq!(miss.body, b"<p>/page #1</p>");
+    assert_eq!(miss.header("age"), Some("0"));
+    assert_eq!(miss.header("x-glory-cache"), Some("MISS"));
+    assert!(miss.header("cache-control").unwrap().contains("s-maxage=30"));
+
+    let hit = cache.serve_at(&request, &render, 110).unwrap();
+    assert_eq!(hit.status, IsrStatus::Hit);
+    assert_eq!(hit.body, miss.body);
+    assert_eq!(hit.header("age"), Some("10"));
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 1, "hit does not render");
+
+    // Stale: the old body is served immediately while a regeneration runs.
+    let stale = cache.serve_at(&request, &render, 200).unwrap();
+    assert_eq!(stale.status, IsrStatus::Stale);
+    assert_eq!(stale.body, b"<p>/page #1</p>");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while cache.is_regenerating(&request) {
+        assert!(std::time::Instant::now() < deadline, "background regeneration never finished");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 2);
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let regenerated = runtime.block_on(cache.serve(&request, &render)).unwrap();
+    assert_eq!(regenerated.status, IsrStatus::Hit);
+    assert_eq!(regenerated.body, b"<p>/page #2</p>");
+}
+
+#[test]
+fn isr_cache_renders_concurrent_misses_for_a_key_once() {
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/slow")));
+    let renders = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = renders.clone();
+    let render: IsrRenderer = std::sync::Arc::new(move |_: &RequestContext| {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let body = format!("<p>#{}</p>", counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1);
+        (ResponseHead::default(), body)
+    });
+
+    let responses: Vec<_> = std::thread::scope(|scope| {
+        let requests: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| cache.serve_at(&isr_request("/slow", &[]), &render, 0).unwrap()))
+            .collect();
+        requests.into_iter().map(|request| request.join().unwrap()).collect()
+    });
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 1);
+    for response in responses {
+        assert_eq!(response.status, IsrStatus::Miss);
+        assert_eq!(response.body, b"<p>#1</p>");
+    }
+    assert_eq!(cache.serve_at(&isr_request("/slow", &[]), &render, 1).unwrap().status, IsrStatus::Hit);
+}
+
+#[test]
+fn isr_cache_bypasses_undeclared_routes() {
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/cached")));
+    let (render, renders) = counting_renderer();
+    for _ in 0..2 {
+        let response = cache.serve_at(&isr_request("/live", &[]), &render, 0).unwrap();
+        assert_eq!(response.status, IsrStatus::Bypass);
+        assert_eq!(response.header("cache-control"), Some("no-cache"));
+        assert_eq!(response.header("age"), None);
+    }
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[test]
+fn isr_cache_keeps_page_heads_and_never_stores_errors_or_cookies() {
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/*")));
+    let renders = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = renders.clone();
+    let render: IsrRenderer = std::sync::Arc::new(move |context: &RequestContext| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let head = match context.uri.as_str() {
+            "/missing" => ResponseHead {
+                status: 404,
+                headers: Vec::new(),
+            },
+            "/login" => ResponseHead {
+                status: 200,
+                headers: vec![("set-cookie".to_owned(), "session=abc".to_owned())],
+            },
+            _ => ResponseHead {
+                status: 200,
+                headers: vec![("x-robots-tag".to_owned(), "noindex".to_owned())],
+            },
+        };
+        (head, format!("<p>{}</p>", context.uri))
+    });
+
+    for uri in ["/missing", "/login"] {
+        for _ in 0..2 {
+            let response = cache.serve_at(&isr_request(uri, &[]), &render, 0).unwrap();
+            assert_eq!(response.status, IsrStatus::Bypass, "{uri}");
+            assert_eq!(response.header("cache-control"), Some("no-cache"), "{uri}");
+        }
+    }
+    let missing = cache.serve_at(&isr_request("/missing", &[]), &render, 0).unwrap();
+    assert_eq!(missing.head.status, 404, "not-found pages keep their status");
+    assert_eq!(
+        renders.load(std::sync::atomic::Ordering::SeqCst),
+        5,
+        "uncacheable pages render every time"
+    );
+
+    assert_eq!(cache.serve_at(&isr_request("/page", &[]), &render, 0).unwrap().status, IsrStatus::Miss);
+    let hit = cache.serve_at(&isr_request("/page", &[]), &render, 0).unwrap();
+    assert_eq!(hit.status, IsrStatus::Hit);
+    assert_eq!(hit.head.status, 200);
+    assert_eq!(hit.head.header("x-robots-tag"), Some("noindex"), "hits keep the page's headers");
+}
+
+#[test]
+fn isr_cache_ignores_unlisted_query_params_and_evicts_least_recently_used() {
+    let cache = IsrCache::in_memory(IsrConfig::new().route(IsrRoute::new("/list").query_param("page")).max_entries(2));
+    let (render, renders) = counting_renderer();
+
+    let first = cache.serve_at(&isr_request("/list?x=1", &[]), &render, 0).unwrap();
+    assert_eq!(first.status, IsrStatus::Miss);
+    assert_eq!(first.body, b"<p>/list #1</p>", "ignored parameters do not reach the render");
+    for x in 2..50 {
+        let response = cache.serve_at(&isr_request(&format!("/list?x={x}"), &[]), &render, 0).unwrap();
+        assert_eq!(response.status, IsrStatus::Hit);
+    }
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    for page in ["/list?page=2", "/list?page=3"] {
+        assert_eq!(cache.serve_at(&isr_request(page, &[]), &render, 0).unwrap().status, IsrStatus::Miss);
+    }
+    assert_eq!(
+        cache.serve_at(&isr_request("/list?page=3", &[]), &render, 0).unwrap().status,
+        IsrStatus::Hit
+    );
+    assert_eq!(
+        cache.serve_at(&isr_request("/list", &[]), &render, 0).unwrap().status,
+        IsrStatus::Miss,
+        "the least recently used key was evicted"
+    );
+}
+
+#[test]
+fn isr_revalidate_path_and_tag_reach_every_live_cache() {
+    let dir = unique_cache_dir("isr");
+    let cache = IsrCache::on_disk(
+        &dir,
+        IsrConfig::new()
+            .route(IsrRoute::new("/isr-posts/{id}").tag("isr-posts").query_param("page"))
+            .route(IsrRoute::new("/isr-about")),
+    );
+    let (render, renders) = counting_renderer();
+    for uri in ["/isr-posts/1", "/isr-posts/1?page=2", "/isr-posts/2", "/isr-about"] {
+        assert_eq!(cache.serve_at(&isr_request(uri, &[]), &render, 0).unwrap().status, IsrStatus::Miss);
+    }
+
+    // Every query variant of the path goes; other pages stay cached.
+    assert_eq!(glory_serverfn::revalidate_path("/isr-posts/1"), 2);
+    assert_eq!(
+        cache.serve_at(&isr_request("/isr-posts/1", &[]), &render, 0).unwrap().status,
+        IsrStatus::Miss
+    );
+    assert_eq!(
+        cache.serve_at(&isr_request("/isr-about", &[]), &render, 0).unwrap().status,
+        IsrStatus::Hit
+    );
+
+    assert_eq!(glory_serverfn::revalidate_tag("isr-posts"), 2);
+    assert_eq!(
+        cache.serve_at(&isr_request("/isr-posts/2", &[]), &render, 0).unwrap().status,
+        IsrStatus::Miss
+    );
+    assert_eq!(
+        cache.serve_at(&isr_request("/isr-about", &[]), &render, 0).unwrap().status,
+        IsrStatus::Hit
+    );
+    assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 6);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+// ---------------------------------------------------------------------------
+// Rate limiting
+// ---------------------------------------------------------------------------
+
+use glory_serverfn::{FileRateLimitStore, RateLimitDecision, RateLimitKey, RateLimiter, ServerFnEncoding, ServerFnMiddlewareContext};
+
+fn limit_context(peer_ip: Option<&str>, headers: &[(&str, &str)]) -> ServerFnMiddlewareContext {
+    ServerFnMiddlewareContext {
+        path: "/__glory/fn/limited".to_owned(),
+        method: "POST".to_owned(),
+        request: Some(
+            RequestContext {
+                method: "POST".to_owned(),
+                uri: "/__glory/fn/limited".to_owned(),
+                headers: headers.iter().map(|(name, value)| ((*name).to_owned(), (*value).to_owned())).collect(),
+            }
+            .with_peer_ip(peer_ip.map(str::to_owned)),
+        ),
+        input_encoding: ServerFnEncoding::Json,
+        output_encoding: ServerFnEncoding::Json,
+    }
+}
+
+static LIMITED_FN_LIMIT: std::sync::LazyLock<RateLimiter> = std::sync::LazyLock::new(|| RateLimiter::token_bucket(2, 0.001));
+
+fn limited_fn_limit(ctx: ServerFnMiddlewareContext) -> glory_serverfn::BoxedServerFnMiddlewareFuture {
+    LIMITED_FN_LIMIT.middleware(ctx)
+}
+
+#[server(endpoint = "limited", middleware = limited_fn_limit)]
+async fn limited() -> Result<&'static str, ServerFnError> {
+    Ok("ok")
+}
+
+#[test]
+fn rate_limit_middleware_rejects_with_429_and_retry_after() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let body = serde_json::to_vec(&()).unwrap();
+        let request = limit_context(Some("10.0.0.1"), &[]).request.unwrap();
+        for _ in 0..2 {
+            let bytes = glory_serverfn::with_request_context(request.clone(), handle("/__glory/fn/limited", body.clone()))
+                .await
+                .unwrap();
+            assert_eq!(serde_json::from_slice::<String>(&bytes).unwrap(), "ok");
+        }
+        let err = glory_serverfn::with_request_context(request, handle("/__glory/fn/limited", body.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 429);
+        let retry_after = err.response_headers().iter().find(|(name, _)| name == "retry-after").unwrap();
+        assert_eq!(retry_after.1, "1000");
+
+        // Other clients and direct calls without a request context are unaffected.
+        let other = limit_context(Some("10.0.0.2"), &[]).request.unwrap();
+        assert!(
+            glory_serverfn::with_request_context(other, handle("/__glory/fn/limited", body.clone()))
+                .await
+                .is_ok()
+        );
+        assert!(handle("/__glory/fn/limited", body).await.is_ok());
+    });
+}
+
+#[test]
+fn token_bucket_refills_over_time() {
+    let limiter = RateLimiter::token_bucket(2, 1.0);
+    let context = limit_context(Some("10.0.0.1"), &[]);
+    assert_eq!(limiter.check_at(&context, 0).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(limiter.check_at(&context, 0).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(
+        limiter.check_at(&context, 500).unwrap(),
+        RateLimitDecision::Limited { retry_after_secs: 1 }
+    );
+    assert_eq!(limiter.check_at(&context, 1_000).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(
+        limiter.check_at(&context, 1_000).unwrap(),
+        RateLimitDecision::Limited { retry_after_secs: 1 }
+    );
+
+    limiter.reset("10.0.0.1").unwrap();
+    assert_eq!(limiter.check_at(&context, 1_000).unwrap(), RateLimitDecision::Allowed);
+}
+
+#[test]
+fn token_bucket_without_refill_caps_retry_after() {
+    let limiter = RateLimiter::token_bucket(1, 0.0);
+    let context = limit_context(Some("10.0.0.1"), &[]);
+    assert_eq!(limiter.check_at(&context, 0).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(
+        limiter.check_at(&context, 0).unwrap(),
+        RateLimitDecision::Limited {
+            retry_after_secs: glory_serverfn::MAX_RETRY_AFTER_SECS
+        }
+    );
+}
+
+#[test]
+fn memory_rate_limit_store_forgets_idle_keys() {
+    use glory_serverfn::{MemoryRateLimitStore, RateLimitPolicy, RateLimitStore};
+
+    let policy = RateLimitPolicy::TokenBucket {
+        capacity: 2,
+        refill_per_sec: 1.0,
+    };
+    assert_eq!(policy.idle_ttl(), Some(std::time::Duration::from_secs(2)));
+    assert_eq!(
+        RateLimitPolicy::SlidingWindow { limit: 1, window_secs: 10 }.idle_ttl(),
+        Some(std::time::Duration::from_secs(20))
+    );
+
+    let store = MemoryRateLimitStore::new().idle_ttl(policy.idle_ttl().unwrap());
+    let bucket = |at| glory_serverfn::RateLimitState::TokenBucket {
+        tokens: 0.0,
+        updated_at_ms: at,
+    };
+    store.update("10.0.0.1", &mut |_| bucket(0)).unwrap();
+    store.update("10.0.0.2", &mut |_| bucket(1_500)).unwrap();
+    assert_eq!(store.len(), 2);
+    // Over two seconds on, the first key has refilled and is dropped.
+    store.update("10.0.0.3", &mut |_| bucket(2_600)).unwrap();
+    assert_eq!(store.len(), 2);
+    let mut previous = None;
+    store
+        .update("10.0.0.2", &mut |state| {
+            previous = state;
+            bucket(2_600)
+        })
+        .unwrap();
+    assert_eq!(previous, Some(bucket(1_500)), "keys still counting are kept");
+}
+
+#[test]
+fn sliding_window_weighs_the_previous_window() {
+    let limiter = RateLimiter::sliding_window(4, 10);
+    let context = limit_context(Some("10.0.0.1"), &[]);
+    for _ in 0..4 {
+        assert_eq!(limiter.check_at(&context, 1_000).unwrap(), RateLimitDecision::Allowed);
+    }
+    assert_eq!(
+        limiter.check_at(&context, 9_000).unwrap(),
+        RateLimitDecision::Limited { retry_after_secs: 1 }
+    );
+    // Halfway through the next window half of the previous count still applies.
+    assert_eq!(limiter.check_at(&context, 15_000).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(limiter.check_at(&context, 15_000).unwrap(), RateLimitDecision::Allowed);
+    assert_eq!(
+        limiter.check_at(&context, 15_000).unwrap(),
+        RateLimitDecision::Limited { retry_after_secs: 5 }
+    );
+    // Two windows later the history is gone.
+    assert_eq!(limiter.check_at(&context, 31_000).unwrap(), RateLimitDecision::Allowed);
+}
+
+#[test]
+fn rate_limit_keys_respect_trusted_forwarded_hops_cookies_and_closures() {
+    let forwarded = [("x-forwarded-for", "203.0.113.9, 198.51.100.7, 10.0.0.5")];
+    let context = limit_context(Some("10.0.0.1"), &forwarded);
+    assert_eq!(RateLimitKey::ClientIp { trusted_hops: 0 }.resolve(&context).as_deref(), Some("10.0.0.1"));
+    assert_eq!(RateLimitKey::ClientIp { trusted_hops: 1 }.resolve(&context).as_deref(), Some("10.0.0.5"));
+    assert_eq!(
+        RateLimitKey::ClientIp { trusted_hops: 2 }.resolve(&context).as_deref(),
+        Some("198.51.100.7")
+    );
+    // More trusted hops than entries: the header did not pass through them,
+    // so the connected peer counts.
+    assert_eq!(RateLimitKey::ClientIp { trusted_hops: 9 }.resolve(&context).as_deref(), Some("10.0.0.1"));
+    // Without a known peer, clients share one bucket instead of going unlimited.
+    let unknown = limit_context(None, &[]);
+    assert_eq!(
+        RateLimitKey::ClientIp { trusted_hops: 0 }.resolve(&unknown).as_deref(),
+        Some(glory_serverfn::UNKNOWN_CLIENT_KEY)
+    );
+    let limiter = RateLimiter::sliding_window(1, 60);
+    assert_eq!(limiter.check_at(&unknown, 1_000).unwrap(), RateLimitDecision::Allowed);
+    assert!(matches!(limiter.check_at(&unknown, 1_000).unwrap(), RateLimitDecision::Limited { .. }));
+
+    let cookie = RateLimitKey::Cookie("session".to_owned());
+    let with_cookie = limit_context(Some("10.0.0.1"), &[("cookie", "session=abc")]);
+    assert_eq!(cookie.resolve(&with_cookie).as_deref(), Some("cookie:abc"));
+    assert_eq!(cookie.resolve(&limit_context(Some("10.0.0.1"), &[])).as_deref(), Some("10.0.0.1"));
+    assert_eq!(cookie.resolve(&unknown).as_deref(), Some(glory_serverfn::UNKNOWN_CLIENT_KEY));
+
+    let custom = RateLimitKey::custom(|ctx| ctx.request.as_ref()?.header("x-api-key").map(str::to_owned));
+    assert_eq!(custom.resolve(&limit_context(None, &[("x-api-key", "k1")])).as_deref(), Some("k1"));
+    assert_eq!(custom.resolve(&limit_context(None, &[])), None);
+}
+
+#[test]
+fn file_rate_limit_store_persists_counters_across_instances() {
+    let dir = unique_cache_dir("ratelimit");
+    let context = limit_context(Some("10.0.0.1"), &[]);
+    let limiter = RateLimiter::sliding_window(1, 60).with_store(FileRateLimitStore::new(&dir));
+    assert_eq!(limiter.check_at(&context, 1_000).unwrap(), RateLimitDecision::Allowed);
+
+    let reopened = RateLimiter::sliding_window(1, 60).with_store(FileRateLimitStore::new(&dir));
+    assert!(matches!(reopened.check_at(&context, 2_000).unwrap(), RateLimitDecision::Limited { .. }));
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn file_rate_limit_store_deletes_idle_keys() {
+    use glory_serverfn::RateLimitStore;
+
+    let dir = unique_cache_dir("ratelimit-idle");
+    let store = FileRateLimitStore::new(&dir).idle_ttl(std::time::Duration::from_secs(2));
+    let bucket = |at| glory_serverfn::RateLimitState::TokenBucket {
+        tokens: 0.0,
+        updated_at_ms: at,
+    };
+    let files = || std::fs::read_dir(&dir).unwrap().count();
+    for cookie in ["a", "b", "c"] {
+        store.update(&format!("cookie:{cookie}"), &mut |_| bucket(0)).unwrap();
+    }
+    store.update("10.0.0.2", &mut |_| bucket(1_500)).unwrap();
+    assert_eq!(files(), 4);
+    // Over two seconds on, the cookies nobody sent again are gone.
+    store.update("10.0.0.3", &mut |_| bucket(2_600)).unwrap();
+    assert_eq!(files(), 2);
+    let mut previous = None;
+    store
+        .update("10.0.0.2", &mut |state| {
+            previous = state;
+            bucket(2_600)
+        })
+        .unwrap();
+    assert_eq!(previous, Some(bucket(1_500)), "keys still counting are kept");
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+// ---------------------------------------------------------------------------
+// Streaming multipart and resumable uploads
+// ---------------------------------------------------------------------------
+
+use glory_serverfn::{MultipartLimits, ResumableUploads, decode_multipart_stream};
+
+/// Splits `body` into `size`-byte chunks, as an adapter body stream would.
+fn chunked_body(body: &[u8], size: usize) -> impl futures::Stream<Item = Result<Vec<u8>, ServerFnError>> + Unpin {
+    futures::stream::iter(body.chunks(size).map(|chunk| Ok(chunk.to_vec())).collect::<Vec<_>>())
+}
+
+const STREAMED_MULTIPART: &str = concat!(
+    "preamble\r\n",
+    "--BOUNDARY\r\n",
+    "Content-Disposition: form-data; name=\"title\"\r\n",
+    "\r\n",
+    "Hello\r\n",
+    "--BOUNDARY\r\n",
+    "Content-Disposition: form-data; name=\"avatar\"; filename=\"a.bin\"\r\n",
+    "Content-Type: application/octet-stream\r\n",
+    "\r\n",
+    "bytes with \r\n--BOUND inside\r\n",
+    "--BOUNDARY--\r\n"
+);
+
+#[test]
+fn streaming_multipart_yields_parts_for_any_chunking() {
+    futures::executor::block_on(async {
+        for size in [1, 2, 7, 64, STREAMED_MULTIPART.len()] {
+            let body = chunked_body(STREAMED_MULTIPART.as_bytes(), size);
+            let mut parts = decode_multipart_stream("multipart/form-data; boundary=BOUNDARY", body, Default::default()).unwrap();
+
+            let title = parts.next_part().await.unwrap().unwrap();
+            assert_eq!(title.header.name, "title");
+            assert!(!title.header.is_file());
+            assert_eq!(title.text().await.unwrap(), "Hello");
+
+            let avatar = parts.next_part().await.unwrap().unwrap();
+            assert_eq!(avatar.header.filename.as_deref(), Some("a.bin"));
+            assert_eq!(avatar.header.content_type.as_deref(), Some("application/octet-stream"));
+            assert_eq!(avatar.bytes().await.unwrap(), b"bytes with \r\n--BOUND inside", "chunk size {size}");
+
+            assert!(parts.next_part().await.unwrap().is_none());
+        }
+    });
+}
+
+#[test]
+fn streaming_multipart_skips_unread_parts_and_enforces_limits() {
+    futures::executor::block_on(async {
+        let body = chunked_body(STREAMED_MULTIPART.as_bytes(), 5);
+        let mut parts = decode_multipart_stream("multipart/form-data; boundary=BOUNDARY", body, Default::default()).unwrap();
+        drop(parts.next_part().await.unwrap());
+        assert_eq!(parts.next_part().await.unwrap().unwrap().header.name, "avatar");
+
+        let limits = MultipartLimits {
+            max_file_bytes: 8,
+            ..Default::default()
+        };
+        let body = chunked_body(STREAMED_MULTIPART.as_bytes(), 5);
+        let form = decode_multipart_stream("multipart/form-data; boundary=BOUNDARY", body, limits).unwrap();
+        assert_eq!(form.collect().await.unwrap_err().status_code(), 413);
+
+        let truncated = &STREAMED_MULTIPART.as_bytes()[..60];
+        let body = chunked_body(truncated, 16);
+        let form = decode_multipart_stream("multipart/form-data; boundary=BOUNDARY", body, Default::default()).unwrap();
+        assert_eq!(form.collect().await.unwrap_err().status_code(), 400);
+    });
+}
+
+#[test]
+fn streaming_multipart_part_saves_to_disk() {
+    let dir = unique_cache_dir("multipart-save");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let body = chunked_body(STREAMED_MULTIPART.as_bytes(), 3);
+        let mut parts = decode_multipart_stream("multipart/form-data; boundary=BOUNDARY", body, Default::default()).unwrap();
+        while let Some(part) = parts.next_part().await.unwrap() {
+            if part.header.is_file() {
+                let written = part.save_to(dir.join("avatar.bin")).await.unwrap();
+                assert_eq!(written, 27);
+            }
+        }
+    });
+    assert_eq!(std::fs::read(dir.join("avatar.bin")).unwrap(), b"bytes with \r\n--BOUND inside");
+}
+
+#[server]
+async fn save_avatar() -> Result<String, ServerFnError> {
+    let mut parts = glory_serverfn::current_multipart_stream(MultipartLimits::default())?;
+    let mut summary = Vec::new();
+    while let Some(part) = parts.next_part().await? {
+        let name = part.header.name.clone();
+        summary.push(format!("{name}={}", part.bytes().await?.len()));
+    }
+    Ok(summary.join(","))
+}
+
+#[test]
+fn multipart_server_fns_read_the_body_as_it_streams_in() {
+    use futures::StreamExt;
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let context = upload_request(
+            "POST",
+            "/__glory/fn/save_avatar",
+            &[("content-type", "multipart/form-data; boundary=BOUNDARY")],
+        );
+        let body = chunked_body(STREAMED_MULTIPART.as_bytes(), 4).boxed();
+        let out = glory_serverfn::with_request_context(
+            context,
+            glory_serverfn::with_request_body(body, handle("/__glory/fn/save_avatar", Vec::new())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(serde_json::from_slice::<String>(&out).unwrap(), "title=5,avatar=27");
+
+        // Without a streamed body (a direct call) there is nothing to decode.
+        let err = handle("/__glory/fn/save_avatar", Vec::new()).await.unwrap_err();
+        assert_eq!(err.status_code(), 400);
+    });
+}
+
+fn upload_request(method: &str, uri: &str, headers: &[(&str, &str)]) -> RequestContext {
+    RequestContext {
+        method: method.into(),
+        uri: uri.into(),
+        headers: headers.iter().map(|(name, value)| ((*name).to_owned(), (*value).to_owned())).collect(),
+    }
+}
+
+fn response_header<'a>(response: &'a glory_serverfn::ServerFnHttpResponse, name: &str) -> Option<&'a str> {
+    response.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+fn no_body() -> impl futures::Stream<Item = Result<Vec<u8>, ServerFnError>> + Unpin {
+    chunked_body(&[], 1)
+}
+
+#[test]
+fn resumable_upload_protocol_creates_appends_and_resumes() {
+    let dir = unique_cache_dir("resumable");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let uploads = ResumableUploads::new(dir.join("store"), "/uploads");
+
+        let created = uploads
+            .handle(
+                &upload_request("POST", "/uploads", &[("upload-length", "10"), ("upload-metadata", "filename=notes.txt")]),
+                None,
+                no_body(),
+            )
+            .await;
+        assert_eq!(created.status, 201);
+        let location = response_header(&created, "location").unwrap().to_owned();
+        let id = location.strip_prefix("/uploads/").unwrap();
+        assert_eq!(uploads.get(id).await.unwrap().metadata("filename"), Some("notes.txt"));
+
+        let chunk = |offset: &'static str, content_type: &'static str, body: &'static [u8]| {
+            let request = upload_request("PATCH", &location, &[("upload-offset", offset), ("content-type", content_type)]);
+            let uploads = uploads.clone();
+            async move { uploads.handle(&request, Some(id), chunked_body(body, 2)).await }
+        };
+        let first = chunk("0", "application/offset+octet-stream", b"hello").await;
+        assert_eq!(first.status, 204);
+        assert_eq!(response_header(&first, "upload-offset"), Some("5"));
+        assert_eq!(chunk("0", "application/offset+octet-stream", b"again").await.status, 409);
+        assert_eq!(chunk("5", "text/plain", b"world").await.status, 415);
+
+        // A new store over the same directory (server restart) resumes the offset.
+        let restarted = ResumableUploads::new(dir.join("store"), "/uploads");
+        let head = restarted.handle(&upload_request("HEAD", &location, &[]), Some(id), no_body()).await;
+        assert_eq!(response_header(&head, "upload-offset"), Some("5"));
+        assert_eq!(response_header(&head, "upload-length"), Some("10"));
+        let last = restarted.append(id, 5, chunked_body(b"world", 3)).await.unwrap();
+        assert!(last.is_complete());
+        assert_eq!(restarted.append(id, 10, chunked_body(b"!", 1)).await.unwrap_err().status_code(), 413);
+
+        restarted.finish(id, dir.join("notes.txt")).await.unwrap();
+        assert_eq!(std::fs::read(dir.join("notes.txt")).unwrap(), b"helloworld");
+        let head = restarted.handle(&upload_request("HEAD", &location, &[]), Some(id), no_body()).await;
+        assert_eq!(head.status, 404);
+    });
+}
+
+#[test]
+fn resumable_upload_keeps_the_chunks_of_a_dropped_request() {
+    let dir = unique_cache_dir("resumable-dropped");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let uploads = ResumableUploads::new(&dir, "/uploads");
+        let upload = uploads.create(10, Vec::new()).await.unwrap();
+
+        // The connection drops after two chunks of the request body.
+        let body = futures::stream::iter(vec![
+            Ok(b"hel".to_vec()),
+            Ok(b"lo".to_vec()),
+            Err(ServerFnError::http(400, "connection reset")),
+        ]);
+        assert_eq!(uploads.append(&upload.id, 0, body).await.unwrap_err().status_code(), 400);
+        assert_eq!(uploads.get(&upload.id).await.unwrap().offset, 5);
+
+        // A record ahead of the data on disk (unsynced chunks lost in a crash)
+        // resumes from the bytes that landed.
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(dir.join(format!("{}.part", upload.id)))
+            .unwrap()
+            .set_len(3)
+            .unwrap();
+        assert_eq!(uploads.get(&upload.id).await.unwrap().offset, 3);
+        let upload = uploads.append(&upload.id, 3, chunked_body(b"loworld", 4)).await.unwrap();
+        assert!(upload.is_complete());
+        uploads.finish(&upload.id, dir.join("done.txt")).await.unwrap();
+        assert_eq!(std::fs::read(dir.join("done.txt")).unwrap(), b"helloworld");
+    });
+}
+
+#[test]
+fn resumable_upload_rejects_oversized_and_deletes() {
+    let dir = unique_cache_dir("resumable-limits");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let uploads = ResumableUploads::new(&dir, "/uploads/").max_upload_bytes(4);
+        let too_big = uploads
+            .handle(&upload_request("POST", "/uploads", &[("upload-length", "5")]), None, no_body())
+            .await;
+        assert_eq!(too_big.status, 413);
+
+        let upload = uploads.create(4, Vec::new()).await.unwrap();
+        let deleted = uploads
+            .handle(&upload_request("DELETE", "/uploads/x", &[]), Some(&upload.id), no_body())
+            .await;
+        assert_eq!(deleted.status, 204);
+        assert_eq!(uploads.get(&upload.id).await.unwrap_err().status_code(), 404);
+    });
+}
+
+#[test]
+fn resumable_upload_creation_is_authorized_and_capped() {
+    let dir = unique_cache_dir("resumable-quota");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let uploads = ResumableUploads::new(&dir, "/uploads")
+            .max_pending_uploads(2)
+            .max_pending_bytes(10)
+            .authorize(|request, _length, metadata| {
+                let signed_in = request.header("authorization").is_some();
+                let named = metadata.iter().any(|(key, _)| key == "filename");
+                async move {
+                    match (signed_in, named) {
+                        (false, _) => Err(ServerFnError::http(401, "sign in to upload")),
+                        (true, false) => Err(ServerFnError::http(422, "filename metadata missing")),
+                        (true, true) => Ok(()),
+                    }
+                }
+            });
+        let create = |length: &'static str, headers: &'static [(&'static str, &'static str)]| {
+            let mut all = vec![("upload-length", length)];
+            all.extend_from_slice(headers);
+            let request = upload_request("POST", "/uploads", &all);
+            let uploads = uploads.clone();
+            async move { uploads.handle(&request, None, no_body()).await.status }
+        };
+        const SIGNED: &[(&str, &str)] = &[("authorization", "Bearer t"), ("upload-metadata", "filename=a.txt")];
+
+        assert_eq!(create("4", &[("upload-metadata", "filename=a.txt")]).await, 401);
+        assert_eq!(create("4", &[("authorization", "Bearer t")]).await, 422);
+        assert_eq!(create("4", SIGNED).await, 201);
+        assert_eq!(create("7", SIGNED).await, 507, "declared bytes over the quota");
+        assert_eq!(create("6", SIGNED).await, 201);
+        assert_eq!(create("0", SIGNED).await, 507, "upload count over the quota");
+        // Direct creation is capped too.
+        assert_eq!(uploads.create(0, Vec::new()).await.unwrap_err().status_code(), 507);
+    });
+}
+
+#[test]
+fn resumable_uploads_expire_when_abandoned() {
+    let dir = unique_cache_dir("resumable-expiry");
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let uploads = ResumableUploads::new(&dir, "/uploads").expire_after(std::time::Duration::from_millis(50));
+        let abandoned = uploads.create(4, Vec::new()).await.unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(60));
+        let fresh = uploads.create(4, Vec::new()).await.unwrap();
+
+        // Creating `fresh` swept the abandoned upload.
+        assert_eq!(uploads.get(&abandoned.id).await.unwrap_err().status_code(), 404);
+        assert!(!dir.join(format!("{}.part", abandoned.id)).exists());
+        assert_eq!(uploads.get(&fresh.id).await.unwrap().offset, 0);
+        assert_eq!(uploads.expire_stale().await.unwrap(), 0);
+    });
+}
+
+#[test]
+fn upload_progress_reports_fraction() {
+    let progress = glory_serverfn::UploadProgress {
+        loaded: 25,
+        total: Some(100),
+    };
+    assert_eq!(progress.fraction(), Some(0.25));
+    assert_eq!(glory_serverfn::UploadProgress { loaded: 0, total: None }.fraction(), None);
+
+    let handle = glory_serverfn::UploadHandle::new();
+    handle.report(10, Some(10));
+    assert_eq!(handle.progress().get().fraction(), Some(1.0));
+    assert_eq!(*handle.state().get(), glory_serverfn::UploadState::Pending);
+}
+
+// ---------------------------------------------------------------------------
+// `#[server(websocket)]` channels
+// ---------------------------------------------------------------------------
+
+use glory_serverfn::{ChannelFrame, TransportMessage, connect_channel, registered_websocket_paths};
+
+#[server(websocket)]
+async fn echo_channel(rx: impl futures::Stream<Item = String>) -> impl futures::Stream<Item = String> {
+    use futures::StreamExt;
+
+    rx.map(|message| format!("echo: {message}"))
+}
+
+#[server(websocket, endpoint = "guarded_channel", middleware = require_x_user)]
+async fn guarded_channel(rx: impl futures::Stream<Item = u32> + Send) -> impl futures::Stream<Item = u32> + Send {
+    use futures::StreamExt;
+
+    rx.map(|value| value * 2)
+}
+
+fn data_frame<T: Serialize>(value: T) -> String {
+    glory_serverfn::encode_transport_json(&TransportMessage::Data(value)).unwrap()
+}
+
+/// The query a reconnecting client sends: the session id and secret from
+/// `connection`'s hello frame, and the last message it saw.
+fn resume_query(connection: &glory_serverfn::ChannelConnection, last_seen: u64) -> String {
+    let ChannelFrame::<()>::Session { id, secret, .. } = serde_json::from_str(&connection.hello_frame()).unwrap() else {
+        panic!("the hello frame names the session");
+    };
+    format!("session={id}&secret={secret}&last_seen={last_seen}")
+}
+
+async fn next_channel_frame<T: serde::de::DeserializeOwned>(frames: &mut (impl futures::Stream<Item = String> + Unpin)) -> ChannelFrame<T> {
+    use futures::StreamExt;
+
+    serde_json::from_str(&frames.next().await.expect("channel frame")).unwrap()
+}
+
+#[test]
+fn websocket_channels_register_and_run_middleware() {
+    let paths = registered_websocket_paths();
+    assert!(paths.contains(&"/__glory/ws/echo_channel"), "{paths:?}");
+    assert!(paths.contains(&"/__glory/ws/guarded_channel"), "{paths:?}");
+
+    futures::executor::block_on(async {
+        let err = connect_channel("/__glory/ws/guarded_channel", None, None).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+        let missing = connect_channel("/__glory/ws/missing", None, None).await.unwrap_err();
+        assert_eq!(missing.status_code(), 404);
+
+        let context = isr_request("/__glory/ws/guarded_channel", &[("x-user", "ada")]);
+        let connection = connect_channel("/__glory/ws/guarded_channel", None, Some(context)).await.unwrap();
+        let mut frames = Box::pin(connection.outgoing());
+        connection.receive(data_frame(21u32)).await;
+        assert_eq!(
+            next_channel_frame::<u32>(&mut frames).await,
+            ChannelFrame::Message {
+                seq: 1,
+                message: TransportMessage::Data(42)
+            }
+        );
+    });
+}
+
+#[test]
+fn websocket_channel_resumes_session_with_last_seen_id() {
+    futures::executor::block_on(async {
+        let first = connect_channel("/__glory/ws/echo_channel", None, None).await.unwrap();
+        assert!(!first.resumed());
+        let session = first.session_id().to_owned();
+        let hello: ChannelFrame<()> = serde_json::from_str(&first.hello_frame()).unwrap();
+        let ChannelFrame::Session { id, secret, resumed } = hello else {
+            panic!("the hello frame names the session");
+        };
+        assert_eq!((id, resumed), (session.clone(), false));
+        assert_eq!(secret.len(), 32, "{secret}");
+        let query = resume_query(&first, 1);
+
+        let mut frames = Box::pin(first.outgoing());
+        first.receive(data_frame("one")).await;
+        first.receive(data_frame("two")).await;
+        assert!(matches!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message { seq: 1, .. }
+        ));
+        // The socket drops after the client saw message 1; message 2 is
+        // produced while it is away.
+        drop(frames);
+        drop(first);
+
+        let second = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        assert!(second.resumed());
+        assert_eq!(second.session_id(), session);
+        let mut frames = Box::pin(second.outgoing());
+        assert_eq!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message {
+                seq: 2,
+                message: TransportMessage::Data("echo: two".to_owned())
+            }
+        );
+
+        // A client `Close` ends the handler; the session is not resumable.
+        second
+            .receive(glory_serverfn::encode_transport_json(&TransportMessage::<String>::close("bye")).unwrap())
+            .await;
+        assert!(matches!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message {
+                message: TransportMessage::Close { .. },
+                ..
+            }
+        ));
+        assert!(futures::StreamExt::next(&mut frames).await.is_none());
+        drop(frames);
+        drop(second);
+
+        let third = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        assert!(!third.resumed());
+        assert_ne!(third.session_id(), session);
+    });
+}
+
+#[test]
+fn websocket_channel_resumes_only_for_the_credentials_that_opened_it() {
+    futures::executor::block_on(async {
+        let ada = || isr_request("/__glory/ws/echo_channel", &[("cookie", "user=ada")]);
+        let first = connect_channel("/__glory/ws/echo_channel", None, Some(ada())).await.unwrap();
+        let session = first.session_id().to_owned();
+        assert_eq!(session.len(), 32, "{session}");
+        let query = resume_query(&first, 0);
+        drop(first);
+
+        let mallory = isr_request("/__glory/ws/echo_channel", &[("cookie", "user=mallory")]);
+        let stranger = connect_channel("/__glory/ws/echo_channel", Some(&query), Some(mallory)).await.unwrap();
+        assert!(!stranger.resumed());
+        assert_ne!(stranger.session_id(), session);
+        let anonymous = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        assert!(!anonymous.resumed());
+
+        let without_secret = format!("session={session}&last_seen=0");
+        let guessed = connect_channel("/__glory/ws/echo_channel", Some(&without_secret), Some(ada()))
+            .await
+            .unwrap();
+        assert!(!guessed.resumed());
+
+        let owner = connect_channel("/__glory/ws/echo_channel", Some(&query), Some(ada())).await.unwrap();
+        assert!(owner.resumed());
+        assert_eq!(owner.session_id(), session);
+    });
+}
+
+#[test]
+fn websocket_channel_resumes_anonymous_sessions_only_with_their_secret() {
+    futures::executor::block_on(async {
+        let first = connect_channel("/__glory/ws/echo_channel", None, None).await.unwrap();
+        let session = first.session_id().to_owned();
+        let query = resume_query(&first, 0);
+        drop(first);
+
+        for forged in [
+            format!("session={session}&last_seen=0"),
+            format!("session={session}&secret={session}&last_seen=0"),
+        ] {
+            let stranger = connect_channel("/__glory/ws/echo_channel", Some(&forged), None).await.unwrap();
+            assert!(!stranger.resumed(), "{forged}");
+        }
+        let owner = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        assert!(owner.resumed());
+        assert_eq!(owner.session_id(), session);
+    });
+}
+
+#[test]
+fn websocket_channel_holds_back_clients_that_outpace_the_handler() {
+    use futures::StreamExt;
+
+    futures::executor::block_on(async {
+        let connection = connect_channel("/__glory/ws/echo_channel", None, None).await.unwrap();
+        // Nothing pulls the handler's output, so it reads no input.
+        for index in 0..glory_serverfn::CHANNEL_INBOUND_CAPACITY {
+            connection.receive(data_frame(index.to_string())).await;
+        }
+        let mut blocked = Box::pin(connection.receive(data_frame("one too many")));
+        assert!(futures::poll!(blocked.as_mut()).is_pending());
+
+        let mut frames = Box::pin(connection.outgoing());
+        assert!(matches!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message { seq: 1, .. }
+        ));
+        blocked.await;
+        assert_eq!(
+            frames
+                .by_ref()
+                .skip(glory_serverfn::CHANNEL_INBOUND_CAPACITY - 1)
+                .next()
+                .await
+                .map(|frame| { serde_json::from_str::<ChannelFrame<String>>(&frame).unwrap() }),
+            Some(ChannelFrame::Message {
+                seq: glory_serverfn::CHANNEL_INBOUND_CAPACITY as u64 + 1,
+                message: TransportMessage::Data("echo: one too many".to_owned())
+            })
+        );
+    });
+}
+
+#[test]
+fn websocket_channel_reports_messages_lost_from_the_replay_buffer() {
+    use futures::StreamExt;
+
+    futures::executor::block_on(async {
+        let first = connect_channel("/__glory/ws/echo_channel", None, None).await.unwrap();
+        let total = glory_serverfn::CHANNEL_REPLAY_CAPACITY as u64 + 10;
+        let send = async {
+            for index in 0..total {
+                first.receive(data_frame(index.to_string())).await;
+            }
+        };
+        // The socket pulls every message, but the client only saw the first.
+        let (_, pulled) = futures::join!(send, first.outgoing().take(total as usize).collect::<Vec<String>>());
+        assert_eq!(pulled.len() as u64, total);
+        let query = resume_query(&first, 1);
+        drop(first);
+
+        let second = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        let mut frames = Box::pin(second.outgoing());
+        assert_eq!(next_channel_frame::<String>(&mut frames).await, ChannelFrame::Gap { from: 2, to: 10 });
+        assert!(matches!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message { seq: 11, .. }
+        ));
+    });
+}
+
+#[test]
+fn websocket_channel_takeover_does_not_wait_for_the_handler() {
+    use futures::StreamExt;
+
+    futures::executor::block_on(async {
+        let first = connect_channel("/__glory/ws/echo_channel", None, None).await.unwrap();
+        let mut stale = Box::pin(first.outgoing());
+        // The first socket waits on a handler with nothing to say.
+        assert!(futures::poll!(stale.next()).is_pending());
+
+        let query = resume_query(&first, 0);
+        let second = connect_channel("/__glory/ws/echo_channel", Some(&query), None).await.unwrap();
+        assert!(second.resumed());
+        assert!(stale.next().await.is_none(), "the stale socket lets go of the handler");
+
+        let mut frames = Box::pin(second.outgoing());
+        second.receive(data_frame("hi")).await;
+        assert_eq!(
+            next_channel_frame::<String>(&mut frames).await,
+            ChannelFrame::Message {
+                seq: 1,
+                message: TransportMessage::Data("echo: hi".to_owned())
+            }
+        );
+    });
+}
+
+#[test]
+fn server_channel_client_reports_unsupported_on_non_wasm() {
+    let (socket, sender) = glory_serverfn::use_server_channel::<String, String>("/__glory/ws/echo_channel");
+    assert!(matches!(*socket.state().get(), glory_serverfn::WebSocketConnectionState::Failed(_)));
+    assert!(sender.send("hi".to_owned()).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Versioning and named-struct arguments
+// ---------------------------------------------------------------------------
+
+#[server(endpoint = "rename_todo", version = 2)]
+async fn rename_todo(id: u32, title: String, note: Option<String>) -> Result<String, ServerFnError> {
+    Ok(format!("{id}:{title}:{}", note.unwrap_or_default()))
+}
+
+#[server(endpoint = "rename_todo", version = 1, shim)]
+async fn rename_todo_v1(id: u32, title: String) -> Result<String, ServerFnError> {
+    rename_todo(id, title, Some("v1".to_owned())).await
+}
+
+#[server(endpoint = "archive_todo", version = 3)]
+async fn archive_todo(id: u32) -> Result<u32, ServerFnError> {
+    Ok(id)
+}
+
+#[test]
+fn versioned_paths_dispatch_current_version_and_shims() {
+    assert_eq!(glory_serverfn::versioned_path("rename_todo", 1), "/__glory/fn/rename_todo");
+    assert_eq!(glory_serverfn::versioned_path("rename_todo", 2), "/__glory/fn/rename_todo@v2");
+    assert_eq!(
+        glory_serverfn::split_versioned_path("/__glory/fn/rename_todo@v2"),
+        ("/__glory/fn/rename_todo", 2)
+    );
+    assert_eq!(
+        glory_serverfn::split_versioned_path("/__glory/fn/rename_todo"),
+        ("/__glory/fn/rename_todo", 1)
+    );
+
+    futures::executor::block_on(async {
+        let body = serde_json::to_vec(&serde_json::json!({ "id": 7, "title": "milk", "note": "2%" })).unwrap();
+        let out: String = glory_serverfn::decode_ok_with(
+            glory_serverfn::ServerFnEncoding::Json,
+            &handle("/__glory/fn/rename_todo@v2", body).await.unwrap(),
+        )
+        .unwrap();
+        assert_eq!(out, "7:milk:2%");
+
+        // A v1 client (positional tuple, old path) still goes through the shim.
+        let body = glory_serverfn::encode_args(&(7u32, "milk".to_owned())).unwrap();
+        let out: String = serde_json::from_slice(&handle("/__glory/fn/rename_todo", body).await.unwrap()).unwrap();
+        assert_eq!(out, "7:milk:v1");
+    });
+}
+
+#[test]
+fn named_arguments_allow_omitting_optional_fields() {
+    futures::executor::block_on(async {
+        let named = serde_json::to_vec(&serde_json::json!({ "title": "eggs", "id": 1 })).unwrap();
+        let out: String = serde_json::from_slice(&handle("/__glory/fn/rename_todo@v2", named).await.unwrap()).unwrap();
+        assert_eq!(out, "1:eggs:");
+
+        let short_tuple = glory_serverfn::encode_args(&(1u32, "eggs")).unwrap();
+        let out: String = serde_json::from_slice(&handle("/__glory/fn/rename_todo@v2", short_tuple).await.unwrap()).unwrap();
+        assert_eq!(out, "1:eggs:");
+
+        let err = handle("/__glory/fn/rename_todo@v2", br#"{"id": 1}"#.to_vec()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::Deserialization(_)), "{err:?}");
+    });
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn postcard_arguments_are_positional_so_changing_them_needs_a_version() {
+    use glory_serverfn::{ServerFnEncoding, decode_ok_with, dispatch_with_method, encode_args_with};
+
+    let call = |path: &'static str, body: Vec<u8>| {
+        futures::executor::block_on(dispatch_with_method(
+            "POST",
+            path,
+            body,
+            ServerFnEncoding::Postcard,
+            ServerFnEncoding::Postcard,
+        ))
+        .result
+    };
+    // Every version round-trips with the arguments its own clients send.
+    let body = encode_args_with(ServerFnEncoding::Postcard, &(7u32, "milk".to_owned(), Some("2%".to_owned()))).unwrap();
+    let out: String = decode_ok_with(ServerFnEncoding::Postcard, &call("/__glory/fn/rename_todo@v2", body).unwrap()).unwrap();
+    assert_eq!(out, "7:milk:2%");
+    let body = encode_args_with(ServerFnEncoding::Postcard, &(7u32, "milk".to_owned())).unwrap();
+    let out: String = decode_ok_with(ServerFnEncoding::Postcard, &call("/__glory/fn/rename_todo", body.clone()).unwrap()).unwrap();
+    assert_eq!(out, "7:milk:v1");
+
+    // Unlike JSON, postcard carries no field names: a client that omits the
+    // new `Option` parameter fails instead of sending `None`.
+    let err = call("/__glory/fn/rename_todo@v2", body).unwrap_err();
+    assert!(matches!(err, ServerFnError::Deserialization(_)), "{err:?}");
+}
+
+#[test]
+fn retired_versions_tell_the_client_to_reload() {
+    futures::executor::block_on(async {
+        let body = glory_serverfn::encode_args(&(1u32,)).unwrap();
+        let err = handle("/__glory/fn/archive_todo@v2", body.clone()).await.unwrap_err();
+        assert!(err.requires_reload());
+        assert_eq!(err, ServerFnError::version_retired("/__glory/fn/archive_todo", 2, 3));
+        let parts = glory_serverfn::server_fn_error_response_parts(&err);
+        assert_eq!(parts.status, 410);
+        assert!(
+            parts
+                .headers
+                .contains(&(glory_serverfn::VERSION_RETIRED_HEADER.to_owned(), "3".to_owned()))
+        );
+        assert!(!ServerFnError::http(410, "gone").requires_reload());
+        let decoded = glory_serverfn::decode_error_with(glory_serverfn::ServerFnEncoding::Json, &parts.body).unwrap();
+        assert!(decoded.requires_reload());
+
+        // Versions newer than the server's are unknown, not retired.
+        let err = handle("/__glory/fn/archive_todo@v4", body).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::NotFound(_)));
+    });
+}
@@ -17,6 +17,11 @@ async fn main() {
     let site_addr = handler.config.site_addr.clone();
     let router = Router::new()
         .push(Router::with_path("upload").post(upload::upload_handler))
+        // Resumable chunk uploads (`upload_resumable` in the browser).
+        .push(glory::serverfn::salvo_mount::upload_router(glory::serverfn::ResumableUploads::new(
+            "target/uploads",
+            "/uploads",
+        )))
         .push(glory::serverfn::salvo_mount::router())
         .push(Router::with_path("<**path>").get(StaticDir::new("target/site")));
     let service = salvo::Service::new(router).catcher(Catcher::default().hoop(handler));